@@ -0,0 +1,725 @@
+//! Barcode encoding (Code 128, Code 39, EAN-13, ITF) and module drawing
+//!
+//! Barcodes are encoded to a sequence of modules (narrow units) and drawn as
+//! filled rectangles, so they stay sharp at any print resolution. 2D codes
+//! (e.g. QR) are drawn from a `ModuleMatrix` the same way.
+
+use crate::document::Color;
+use crate::{PdfError, Result};
+
+/// Supported 1D barcode symbologies
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BarcodeSymbology {
+    /// Code 128 (full ASCII, automatic code set A/B/C selection)
+    #[default]
+    Code128,
+    /// Code 39 (digits, uppercase letters and `-. $/+%`)
+    Code39,
+    /// EAN-13 (12 digits + check digit)
+    Ean13,
+    /// Interleaved 2 of 5 (even number of digits)
+    Itf,
+}
+
+/// An encoded barcode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Barcode {
+    /// Modules from left to right (true = bar, false = space)
+    pub modules: Vec<bool>,
+    /// Human-readable text (includes computed check digits)
+    pub text: String,
+}
+
+/// A grid of dark/light modules for 2D codes (e.g. QR)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleMatrix {
+    /// Number of modules per row
+    pub columns: usize,
+    /// Modules row by row from the top-left (true = dark)
+    pub modules: Vec<bool>,
+}
+
+impl ModuleMatrix {
+    /// Create a matrix from row-major modules
+    ///
+    /// # Arguments
+    /// * `columns` - Number of modules per row
+    /// * `modules` - Modules row by row (length must be a multiple of `columns`)
+    pub fn new(columns: usize, modules: Vec<bool>) -> Result<Self> {
+        if columns == 0 || !modules.len().is_multiple_of(columns) {
+            return Err(PdfError::BarcodeError(format!(
+                "{} modules do not form rows of {columns}",
+                modules.len()
+            )));
+        }
+        Ok(Self { columns, modules })
+    }
+
+    /// Number of rows
+    pub fn rows(&self) -> usize {
+        self.modules.len() / self.columns
+    }
+
+    /// Return a copy surrounded by a light border
+    ///
+    /// # Arguments
+    /// * `quiet_zone` - Border width in modules
+    pub fn with_quiet_zone(&self, quiet_zone: usize) -> Self {
+        let columns = self.columns + 2 * quiet_zone;
+        let mut modules = vec![false; columns * quiet_zone];
+        for row in self.modules.chunks(self.columns) {
+            modules.extend(std::iter::repeat_n(false, quiet_zone));
+            modules.extend_from_slice(row);
+            modules.extend(std::iter::repeat_n(false, quiet_zone));
+        }
+        modules.extend(std::iter::repeat_n(false, columns * quiet_zone));
+        Self { columns, modules }
+    }
+}
+
+/// Generate PDF operators to draw a module matrix as filled rectangles
+///
+/// Dark modules in a row are merged into one rectangle. The optional
+/// background fills the whole matrix area first.
+///
+/// # Arguments
+/// * `matrix` - Modules to draw
+/// * `x` - X coordinate of the left edge in points
+/// * `y` - Y coordinate of the bottom edge in points (PDF coordinates)
+/// * `width` - Total width in points
+/// * `height` - Total height in points
+/// * `foreground` - Color of dark modules
+/// * `background` - Color of the matrix area, or None for transparent
+///
+/// # Returns
+/// PDF content stream operators as bytes
+pub fn generate_matrix_operators(
+    matrix: &ModuleMatrix,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    foreground: Color,
+    background: Option<Color>,
+) -> Vec<u8> {
+    let mut ops = String::from("q\n");
+
+    if let Some(bg) = background {
+        ops.push_str(&format!("{} {} {} rg\n", bg.r, bg.g, bg.b));
+        ops.push_str(&format!("{x} {y} {width} {height} re\nf\n"));
+    }
+
+    let module_width = width / matrix.columns as f64;
+    let module_height = height / matrix.rows() as f64;
+    let top = y + height;
+
+    ops.push_str(&format!(
+        "{} {} {} rg\n",
+        foreground.r, foreground.g, foreground.b
+    ));
+    for (row_index, row) in matrix.modules.chunks(matrix.columns).enumerate() {
+        let row_y = top - (row_index + 1) as f64 * module_height;
+        let mut i = 0;
+        while i < row.len() {
+            if !row[i] {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < row.len() && row[i] {
+                i += 1;
+            }
+            let run_x = x + start as f64 * module_width;
+            let run_width = (i - start) as f64 * module_width;
+            ops.push_str(&format!("{run_x} {row_y} {run_width} {module_height} re\n"));
+        }
+    }
+
+    ops.push_str("f\nQ\n");
+    ops.into_bytes()
+}
+
+/// Wide element width in modules for Code 39 and ITF
+const WIDE: usize = 3;
+
+/// Code 128 bar/space widths for values 0-106 (106 = stop)
+const CODE128_PATTERNS: [&str; 107] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212",
+    "221213", "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221",
+    "223211", "221132", "221231", "213212", "223112", "312131", "311222", "321122", "321221",
+    "312212", "322112", "322211", "212123", "212321", "232121", "111323", "131123", "131321",
+    "112313", "132113", "132311", "211313", "231113", "231311", "112133", "112331", "132131",
+    "113123", "113321", "133121", "313121", "211331", "231131", "213113", "213311", "213131",
+    "311123", "311321", "331121", "312113", "312311", "332111", "314111", "221411", "431111",
+    "111224", "111422", "121124", "121421", "141122", "141221", "112214", "112412", "122114",
+    "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111", "111242",
+    "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+    "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311",
+    "113141", "114131", "311141", "411131", "211412", "211214", "211232", "2331112",
+];
+
+const CODE128_START_A: u8 = 103;
+const CODE128_START_B: u8 = 104;
+const CODE128_START_C: u8 = 105;
+const CODE128_STOP: u8 = 106;
+
+/// Code 39 alphabet and narrow/wide patterns (bar, space, bar, ...)
+const CODE39_CHARS: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ-. $/+%*";
+const CODE39_PATTERNS: [&str; 44] = [
+    "nnnwwnwnn",
+    "wnnwnnnnw",
+    "nnwwnnnnw",
+    "wnwwnnnnn",
+    "nnnwwnnnw",
+    "wnnwwnnnn",
+    "nnwwwnnnn",
+    "nnnwnnwnw",
+    "wnnwnnwnn",
+    "nnwwnnwnn",
+    "wnnnnwnnw",
+    "nnwnnwnnw",
+    "wnwnnwnnn",
+    "nnnnwwnnw",
+    "wnnnwwnnn",
+    "nnwnwwnnn",
+    "nnnnnwwnw",
+    "wnnnnwwnn",
+    "nnwnnwwnn",
+    "nnnnwwwnn",
+    "wnnnnnnww",
+    "nnwnnnnww",
+    "wnwnnnnwn",
+    "nnnnwnnww",
+    "wnnnwnnwn",
+    "nnwnwnnwn",
+    "nnnnnnwww",
+    "wnnnnnwwn",
+    "nnwnnnwwn",
+    "nnnnwnwwn",
+    "wwnnnnnnw",
+    "nwwnnnnnw",
+    "wwwnnnnnn",
+    "nwnnwnnnw",
+    "wwnnwnnnn",
+    "nwwnwnnnn",
+    "nwnnnnwnw",
+    "wwnnnnwnn",
+    "nwwnnnwnn",
+    "nwnwnwnnn",
+    "nwnwnnnwn",
+    "nwnnnwnwn",
+    "nnnwnwnwn",
+    "nwnnwnwnn",
+];
+
+/// EAN-13 L-code patterns (G and R codes are derived from these)
+const EAN_L_CODES: [&str; 10] = [
+    "0001101", "0011001", "0010011", "0111101", "0100011", "0110001", "0101111", "0111011",
+    "0110111", "0001011",
+];
+
+/// EAN-13 left-half parity (L or G) selected by the first digit
+const EAN_PARITY: [&str; 10] = [
+    "LLLLLL", "LLGLGG", "LLGGLG", "LLGGGL", "LGLLGG", "LGGLLG", "LGGGLL", "LGLGLG", "LGLGGL",
+    "LGGLGL",
+];
+
+/// ITF narrow/wide patterns per digit
+const ITF_PATTERNS: [&str; 10] = [
+    "nnwwn", "wnnnw", "nwnnw", "wwnnn", "nnwnw", "wnwnn", "nwwnn", "nnnww", "wnnwn", "nwnwn",
+];
+
+impl Barcode {
+    /// Encode data with the given symbology
+    ///
+    /// # Arguments
+    /// * `symbology` - Barcode type
+    /// * `data` - Data to encode
+    ///
+    /// # Example
+    /// ```ignore
+    /// let barcode = Barcode::encode(BarcodeSymbology::Code128, "INV-0001")?;
+    /// ```
+    pub fn encode(symbology: BarcodeSymbology, data: &str) -> Result<Self> {
+        if data.is_empty() {
+            return Err(PdfError::BarcodeError("Empty barcode data".to_string()));
+        }
+
+        match symbology {
+            BarcodeSymbology::Code128 => encode_code128(data),
+            BarcodeSymbology::Code39 => encode_code39(data),
+            BarcodeSymbology::Ean13 => encode_ean13(data),
+            BarcodeSymbology::Itf => encode_itf(data),
+        }
+    }
+
+    /// Total barcode width in points
+    ///
+    /// # Arguments
+    /// * `module_width` - Width of one module in points
+    pub fn width(&self, module_width: f64) -> f64 {
+        self.modules.len() as f64 * module_width
+    }
+}
+
+/// Generate PDF operators to draw barcode bars as filled rectangles
+///
+/// Adjacent bar modules are merged into a single rectangle.
+///
+/// # Arguments
+/// * `modules` - Barcode modules (true = bar)
+/// * `x` - X coordinate of the left edge in points
+/// * `y` - Y coordinate of the bottom edge in points (PDF coordinates)
+/// * `module_width` - Width of one module in points
+/// * `bar_height` - Bar height in points
+///
+/// # Returns
+/// PDF content stream operators as bytes
+pub fn generate_barcode_operators(
+    modules: &[bool],
+    x: f64,
+    y: f64,
+    module_width: f64,
+    bar_height: f64,
+) -> Vec<u8> {
+    let mut ops = String::from("q\n0 g\n");
+
+    let mut i = 0;
+    while i < modules.len() {
+        if !modules[i] {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < modules.len() && modules[i] {
+            i += 1;
+        }
+        let bar_x = x + start as f64 * module_width;
+        let bar_width = (i - start) as f64 * module_width;
+        ops.push_str(&format!("{bar_x} {y} {bar_width} {bar_height} re\n"));
+    }
+
+    ops.push_str("f\nQ\n");
+    ops.into_bytes()
+}
+
+/// Append alternating bar/space elements with the given widths in modules
+fn push_widths(modules: &mut Vec<bool>, widths: impl IntoIterator<Item = usize>) {
+    let mut bar = true;
+    for width in widths {
+        modules.extend(std::iter::repeat_n(bar, width));
+        bar = !bar;
+    }
+}
+
+/// Convert a narrow/wide pattern ("nnwwn") to module widths
+fn narrow_wide(pattern: &str) -> impl Iterator<Item = usize> + '_ {
+    pattern.chars().map(|c| if c == 'w' { WIDE } else { 1 })
+}
+
+/// Code 128 code sets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CodeSet {
+    A,
+    B,
+    C,
+}
+
+/// Number of consecutive ASCII digits starting at `pos`
+fn digit_run(bytes: &[u8], pos: usize) -> usize {
+    bytes[pos..]
+        .iter()
+        .take_while(|b| b.is_ascii_digit())
+        .count()
+}
+
+/// Pick code set A or B for the character at `pos`
+///
+/// A is needed for control characters, B for lowercase letters; otherwise
+/// the first of those two that appears ahead decides.
+fn code128_set_for(bytes: &[u8], pos: usize) -> CodeSet {
+    for &b in &bytes[pos..] {
+        if b < 32 {
+            return CodeSet::A;
+        }
+        if b >= 96 {
+            return CodeSet::B;
+        }
+    }
+    CodeSet::B
+}
+
+/// Code 128 symbol value of a character in code set A or B
+fn code128_value(b: u8, set: CodeSet) -> u8 {
+    match set {
+        CodeSet::A if b < 32 => b + 64,
+        _ => b - 32,
+    }
+}
+
+fn encode_code128(data: &str) -> Result<Barcode> {
+    let bytes = data.as_bytes();
+    if let Some(c) = data.chars().find(|c| !c.is_ascii()) {
+        return Err(PdfError::BarcodeError(format!(
+            "Code 128 cannot encode character '{c}'"
+        )));
+    }
+
+    let mut values: Vec<u8> = Vec::new();
+    let starts_with_digits = digit_run(bytes, 0);
+    let mut set =
+        if starts_with_digits >= 4 || starts_with_digits == bytes.len() && bytes.len() == 2 {
+            values.push(CODE128_START_C);
+            CodeSet::C
+        } else {
+            let set = code128_set_for(bytes, 0);
+            values.push(if set == CodeSet::A {
+                CODE128_START_A
+            } else {
+                CODE128_START_B
+            });
+            set
+        };
+
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let run = digit_run(bytes, pos);
+
+        if set == CodeSet::C {
+            if run >= 2 {
+                values.push((bytes[pos] - b'0') * 10 + (bytes[pos + 1] - b'0'));
+                pos += 2;
+                continue;
+            }
+            // Leave code set C for the next non-pair character
+            set = code128_set_for(bytes, pos);
+            values.push(if set == CodeSet::A { 101 } else { 100 });
+            continue;
+        }
+
+        // Switch to C for runs of 4+ digits (an odd leading digit stays in A/B)
+        if run >= 4 && run.is_multiple_of(2) {
+            values.push(99);
+            set = CodeSet::C;
+            continue;
+        }
+
+        let b = bytes[pos];
+        let needed = if b < 32 {
+            CodeSet::A
+        } else if b >= 96 {
+            CodeSet::B
+        } else {
+            set
+        };
+        if needed != set {
+            values.push(if needed == CodeSet::A { 101 } else { 100 });
+            set = needed;
+        }
+        values.push(code128_value(b, set));
+        pos += 1;
+    }
+
+    let checksum = values
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| v as usize * i.max(1))
+        .sum::<usize>()
+        % 103;
+    values.push(checksum as u8);
+    values.push(CODE128_STOP);
+
+    let mut modules = Vec::new();
+    for value in values {
+        let widths = CODE128_PATTERNS[value as usize]
+            .bytes()
+            .map(|w| (w - b'0') as usize);
+        push_widths(&mut modules, widths);
+    }
+
+    // Control characters are not printable
+    let text = data
+        .chars()
+        .map(|c| if c.is_ascii_control() { ' ' } else { c })
+        .collect();
+
+    Ok(Barcode { modules, text })
+}
+
+fn encode_code39(data: &str) -> Result<Barcode> {
+    let text = data.to_ascii_uppercase();
+
+    let mut modules = Vec::new();
+    for (i, c) in format!("*{text}*").chars().enumerate() {
+        let index = CODE39_CHARS
+            .find(c)
+            .filter(|_| c != '*' || i == 0 || i == text.len() + 1)
+            .ok_or_else(|| {
+                PdfError::BarcodeError(format!("Code 39 cannot encode character '{c}'"))
+            })?;
+        if i > 0 {
+            // Narrow inter-character gap
+            modules.push(false);
+        }
+        push_widths(&mut modules, narrow_wide(CODE39_PATTERNS[index]));
+    }
+
+    Ok(Barcode { modules, text })
+}
+
+/// Parse a string of ASCII digits
+fn parse_digits(data: &str, symbology: &str) -> Result<Vec<u8>> {
+    data.chars()
+        .map(|c| {
+            c.to_digit(10).map(|d| d as u8).ok_or_else(|| {
+                PdfError::BarcodeError(format!("{symbology} accepts digits only, got '{c}'"))
+            })
+        })
+        .collect()
+}
+
+/// Compute the EAN-13 check digit for the first 12 digits
+fn ean13_check_digit(digits: &[u8]) -> u8 {
+    let sum: u32 = digits
+        .iter()
+        .take(12)
+        .enumerate()
+        .map(|(i, &d)| d as u32 * if i % 2 == 0 { 1 } else { 3 })
+        .sum();
+    ((10 - sum % 10) % 10) as u8
+}
+
+fn encode_ean13(data: &str) -> Result<Barcode> {
+    let mut digits = parse_digits(data, "EAN-13")?;
+    match digits.len() {
+        12 => digits.push(ean13_check_digit(&digits)),
+        13 => {
+            let expected = ean13_check_digit(&digits);
+            if digits[12] != expected {
+                return Err(PdfError::BarcodeError(format!(
+                    "Invalid EAN-13 check digit: expected {expected}, got {}",
+                    digits[12]
+                )));
+            }
+        }
+        n => {
+            return Err(PdfError::BarcodeError(format!(
+                "EAN-13 needs 12 or 13 digits, got {n}"
+            )))
+        }
+    }
+
+    let bits = |pattern: &str, invert: bool, reverse: bool| -> Vec<bool> {
+        let mut bits: Vec<bool> = pattern.chars().map(|c| (c == '1') != invert).collect();
+        if reverse {
+            bits.reverse();
+        }
+        bits
+    };
+
+    let mut modules = vec![true, false, true];
+    let parity = EAN_PARITY[digits[0] as usize];
+    for (&d, p) in digits[1..7].iter().zip(parity.chars()) {
+        // G code is the reversed R code
+        let g = p == 'G';
+        modules.extend(bits(EAN_L_CODES[d as usize], g, g));
+    }
+    modules.extend([false, true, false, true, false]);
+    for &d in &digits[7..] {
+        modules.extend(bits(EAN_L_CODES[d as usize], true, false));
+    }
+    modules.extend([true, false, true]);
+
+    let text = digits.iter().map(|d| char::from(b'0' + d)).collect();
+    Ok(Barcode { modules, text })
+}
+
+fn encode_itf(data: &str) -> Result<Barcode> {
+    let digits = parse_digits(data, "ITF")?;
+    if !digits.len().is_multiple_of(2) {
+        return Err(PdfError::BarcodeError(format!(
+            "ITF needs an even number of digits, got {}",
+            digits.len()
+        )));
+    }
+
+    let mut modules = Vec::new();
+    // Start: narrow bar, narrow space, narrow bar, narrow space
+    push_widths(&mut modules, [1, 1, 1, 1]);
+    for pair in digits.chunks(2) {
+        // First digit in the bars, second digit in the spaces
+        let bars = narrow_wide(ITF_PATTERNS[pair[0] as usize]);
+        let spaces = narrow_wide(ITF_PATTERNS[pair[1] as usize]);
+        push_widths(&mut modules, bars.zip(spaces).flat_map(|(b, s)| [b, s]));
+    }
+    // Stop: wide bar, narrow space, narrow bar
+    push_widths(&mut modules, [WIDE, 1, 1]);
+
+    Ok(Barcode {
+        modules,
+        text: data.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Render modules as a "1010" string
+    fn bit_string(modules: &[bool]) -> String {
+        modules.iter().map(|&m| if m { '1' } else { '0' }).collect()
+    }
+
+    #[test]
+    fn test_code128_patterns_width() {
+        for (value, pattern) in CODE128_PATTERNS.iter().enumerate() {
+            let width: u32 = pattern.bytes().map(|w| (w - b'0') as u32).sum();
+            let expected = if value == CODE128_STOP as usize {
+                13
+            } else {
+                11
+            };
+            assert_eq!(width, expected, "pattern {value}");
+        }
+    }
+
+    #[test]
+    fn test_code128_code_set_b() {
+        // Start B (104), "A" (33), "b" (66); checksum (104 + 33 + 2*66) % 103 = 63
+        let barcode = Barcode::encode(BarcodeSymbology::Code128, "Ab").unwrap();
+        // 4 symbols of 11 modules plus the 13-module stop
+        assert_eq!(barcode.modules.len(), 4 * 11 + 13);
+        let expected: String = [104, 33, 66, 63, 106]
+            .iter()
+            .map(|&v| {
+                let mut modules = Vec::new();
+                let widths = CODE128_PATTERNS[v].bytes().map(|w| (w - b'0') as usize);
+                push_widths(&mut modules, widths);
+                bit_string(&modules)
+            })
+            .collect();
+        assert_eq!(bit_string(&barcode.modules), expected);
+        assert_eq!(barcode.text, "Ab");
+    }
+
+    #[test]
+    fn test_code128_code_set_c() {
+        // Start C + 3 digit pairs + checksum + stop
+        let barcode = Barcode::encode(BarcodeSymbology::Code128, "123456").unwrap();
+        assert_eq!(barcode.modules.len(), 5 * 11 + 13);
+    }
+
+    #[test]
+    fn test_code128_control_characters() {
+        // Thai bill payment data separates fields with carriage returns
+        let barcode =
+            Barcode::encode(BarcodeSymbology::Code128, "|010553600001100\r1234\r0").unwrap();
+        assert!(!barcode.modules.is_empty());
+        assert_eq!(barcode.text, "|010553600001100 1234 0");
+    }
+
+    #[test]
+    fn test_code128_rejects_non_ascii() {
+        assert!(Barcode::encode(BarcodeSymbology::Code128, "ทดสอบ").is_err());
+    }
+
+    #[test]
+    fn test_code39() {
+        let barcode = Barcode::encode(BarcodeSymbology::Code39, "ab1").unwrap();
+        assert_eq!(barcode.text, "AB1");
+        // 5 characters of 6 narrow + 3 wide, plus 4 gaps
+        assert_eq!(barcode.modules.len(), 5 * (6 + 3 * WIDE) + 4);
+        assert!(Barcode::encode(BarcodeSymbology::Code39, "A*B").is_err());
+    }
+
+    #[test]
+    fn test_ean13_check_digit() {
+        let barcode = Barcode::encode(BarcodeSymbology::Ean13, "885000000001").unwrap();
+        assert_eq!(barcode.text, "8850000000010");
+        assert_eq!(barcode.modules.len(), 95);
+
+        assert!(Barcode::encode(BarcodeSymbology::Ean13, "8850000000010").is_ok());
+        assert!(Barcode::encode(BarcodeSymbology::Ean13, "8850000000013").is_err());
+        assert!(Barcode::encode(BarcodeSymbology::Ean13, "12345").is_err());
+    }
+
+    #[test]
+    fn test_ean13_modules() {
+        // 4006381333931: first digit 4 selects LGLLGG parity
+        let barcode = Barcode::encode(BarcodeSymbology::Ean13, "4006381333931").unwrap();
+        let bits = bit_string(&barcode.modules);
+        assert!(bits.starts_with("101"));
+        assert!(bits.ends_with("101"));
+        assert_eq!(&bits[45..50], "01010");
+        // Second digit 0 in L code
+        assert_eq!(&bits[3..10], "0001101");
+        // Third digit 0 in G code
+        assert_eq!(&bits[10..17], "0100111");
+        // Last digit 1 in R code
+        assert_eq!(&bits[85..92], "1100110");
+    }
+
+    #[test]
+    fn test_itf() {
+        let barcode = Barcode::encode(BarcodeSymbology::Itf, "1234").unwrap();
+        // Start (4) + 2 pairs of 10 elements (4 wide) + stop (2 + WIDE)
+        let pair_width = 6 + 4 * WIDE;
+        assert_eq!(barcode.modules.len(), 4 + 2 * pair_width + 2 + WIDE);
+        assert!(Barcode::encode(BarcodeSymbology::Itf, "123").is_err());
+        assert!(Barcode::encode(BarcodeSymbology::Itf, "12a4").is_err());
+    }
+
+    #[test]
+    fn test_generate_barcode_operators_merges_bars() {
+        let modules = [true, true, false, true];
+        let ops = generate_barcode_operators(&modules, 10.0, 20.0, 0.5, 30.0);
+        let ops = String::from_utf8(ops).unwrap();
+
+        assert!(ops.contains("10 20 1 30 re"));
+        assert!(ops.contains("11.5 20 0.5 30 re"));
+        assert_eq!(ops.matches(" re").count(), 2);
+        assert!(ops.ends_with("f\nQ\n"));
+    }
+
+    #[test]
+    fn test_module_matrix_quiet_zone() {
+        let matrix = ModuleMatrix::new(2, vec![true, false, false, true]).unwrap();
+        let padded = matrix.with_quiet_zone(1);
+
+        assert_eq!(padded.columns, 4);
+        assert_eq!(padded.rows(), 4);
+        assert_eq!(
+            bit_string(&padded.modules),
+            "0000".to_string() + "0100" + "0010" + "0000"
+        );
+        assert!(ModuleMatrix::new(3, vec![true; 4]).is_err());
+    }
+
+    #[test]
+    fn test_generate_matrix_operators() {
+        let matrix = ModuleMatrix::new(2, vec![true, true, false, true]).unwrap();
+        let ops = generate_matrix_operators(
+            &matrix,
+            10.0,
+            20.0,
+            4.0,
+            4.0,
+            Color::black(),
+            Some(Color::white()),
+        );
+        let ops = String::from_utf8(ops).unwrap();
+
+        // Background, then the merged top row and the single bottom module
+        assert!(ops.contains("1 1 1 rg\n10 20 4 4 re\nf\n"));
+        assert!(ops.contains("0 0 0 rg\n"));
+        assert!(ops.contains("10 22 4 2 re\n"));
+        assert!(ops.contains("12 20 2 2 re\n"));
+        assert_eq!(ops.matches(" re\n").count(), 3);
+    }
+
+    #[test]
+    fn test_barcode_width() {
+        let barcode = Barcode::encode(BarcodeSymbology::Ean13, "885000000001").unwrap();
+        assert_eq!(barcode.width(0.5), 47.5);
+    }
+}
@@ -1,15 +1,31 @@
 //! PDF Document wrapper
 
+use crate::barcode::{
+    generate_barcode_operators, generate_matrix_operators, Barcode, ModuleMatrix,
+};
+use crate::font::{FontObjects, ShapedGlyph};
+use crate::form::{
+    self, FieldDefinition, FieldNode, FieldWidget, FlattenMode, FormField, FormFieldType,
+};
+use crate::graphics::{
+    generate_check_mark_operators, generate_ext_gstate_operators, generate_line_operators,
+    generate_path_operators, generate_rect_operators, generate_transformed_operators, BlendMode,
+    CheckMark, Path as GraphicsPath, ShapeStyle, Transform, Transparency,
+};
 use crate::image::{
-    calculate_scaled_dimensions, generate_image_operators, ImageScaleMode, ImageXObject,
+    calculate_scaled_dimensions, generate_image_operators, get_dimensions, ImageScaleMode,
+    ImageXObject,
 };
-use crate::text::{generate_text_operators, TextRenderContext};
+use crate::page::{Margins, PageSize};
+use crate::text::{generate_shaped_text_operators, simple_word_wrap_width, TextRenderContext};
+use crate::watermark::{watermark_centers, Watermark, WatermarkContent, WatermarkLayer};
 use crate::{Align, FontData, FontFamily, FontFamilyBuilder, PdfError, Result};
 use crate::{FontStyle, FontWeight};
-use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId, Stream};
 use std::collections::hash_map::DefaultHasher;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
+use std::ops::{Bound, RangeBounds};
 use std::path::Path;
 
 /// A segment of text with its associated font
@@ -24,8 +40,8 @@ struct TextSegment {
 /// after fonts have been subsetted and glyph IDs remapped.
 #[derive(Debug, Clone)]
 struct BufferedTextOp {
-    /// The text to render
-    text: String,
+    /// Shaped glyphs to render (original glyph IDs)
+    glyphs: Vec<ShapedGlyph>,
     /// Font name (e.g., "sarabun-bold")
     font_name: String,
     /// Font resource name (e.g., "F1")
@@ -40,6 +56,145 @@ struct BufferedTextOp {
     font_size: f32,
     /// Text color
     color: Color,
+    /// Clipping rectangle (in PDF coordinates, already converted)
+    clip: Option<Rect>,
+    /// Transformation matrix (applied to the clip as well)
+    transform: Option<[f64; 6]>,
+    /// ExtGState resource name for transparency
+    ext_gstate: Option<String>,
+    /// Draw under the existing page content
+    underlay: bool,
+    /// Page view matrix when the text was inserted (see `PageGeometry`)
+    view_matrix: Option<[f64; 6]>,
+}
+
+/// Operators buffered for a page, with the page view matrix they were
+/// placed in (see `PageGeometry`)
+type ContentChunk = (Option<[f64; 6]>, Vec<u8>);
+
+/// A form field appearance stream for deferred encoding
+///
+/// Like `BufferedTextOp`, the glyphs are encoded during save once fonts
+/// have been subsetted and embedded.
+#[derive(Debug, Clone)]
+struct BufferedFieldAppearance {
+    /// Widget annotation receiving the appearance
+    widget_id: ObjectId,
+    /// Widget width in points
+    width: f64,
+    /// Widget height in points
+    height: f64,
+    /// Appearance /Matrix of a rotated widget
+    matrix: Option<[f64; 6]>,
+    /// Shaped text runs (positions relative to the widget's lower-left corner)
+    runs: Vec<AppearanceRun>,
+    /// Font size in points
+    font_size: f32,
+    /// Text color
+    color: Color,
+}
+
+/// Glyphs of one font inside a field appearance
+#[derive(Debug, Clone)]
+struct AppearanceRun {
+    glyphs: Vec<ShapedGlyph>,
+    font_name: String,
+    x: f64,
+    y: f64,
+}
+
+/// Visible area of a page as a viewer shows it
+///
+/// Public APIs take top-left coordinates in this view: the CropBox (within
+/// the MediaBox), turned by the page's /Rotate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PageGeometry {
+    /// Visible width in points, after rotation
+    width: f64,
+    /// Visible height in points, after rotation
+    height: f64,
+    /// Maps view coordinates (origin at the bottom-left corner of the view)
+    /// to PDF user space; `None` for an unrotated box starting at (0,0)
+    matrix: Option<[f64; 6]>,
+}
+
+impl PageGeometry {
+    /// Geometry of a visible box `[x0, y0, x1, y1]` with a /Rotate value
+    fn new(visible: [f64; 4], rotate: i64) -> Self {
+        let [x0, y0, x1, y1] = visible;
+        let (width, height) = (x1 - x0, y1 - y0);
+        // /Rotate turns the page clockwise; the content is turned back so
+        // the view's x axis runs along the page edge shown at the top
+        let (width, height, matrix) = match rotate.rem_euclid(360) {
+            90 => (height, width, [0.0, 1.0, -1.0, 0.0, x1, y0]),
+            180 => (width, height, [-1.0, 0.0, 0.0, -1.0, x1, y1]),
+            270 => (height, width, [0.0, -1.0, 1.0, 0.0, x0, y1]),
+            _ => (width, height, [1.0, 0.0, 0.0, 1.0, x0, y0]),
+        };
+        let matrix = (matrix != [1.0, 0.0, 0.0, 1.0, 0.0, 0.0]).then_some(matrix);
+        Self {
+            width,
+            height,
+            matrix,
+        }
+    }
+
+    /// Geometry of the area inside margins, with its origin at their corner
+    fn inset(self, margins: &Margins) -> Self {
+        let [a, b, c, d, e, f] = self.matrix.unwrap_or([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+        let (x, y) = (margins.left, margins.bottom);
+        let matrix = [a, b, c, d, a * x + c * y + e, b * x + d * y + f];
+        Self {
+            width: self.width - margins.left - margins.right,
+            height: self.height - margins.top - margins.bottom,
+            matrix: (matrix != [1.0, 0.0, 0.0, 1.0, 0.0, 0.0]).then_some(matrix),
+        }
+    }
+
+    /// Convert a view point (bottom-left origin) to PDF user space
+    fn pdf_point(&self, x: f64, y: f64) -> (f64, f64) {
+        match self.matrix {
+            Some([a, b, c, d, e, f]) => (a * x + c * y + e, b * x + d * y + f),
+            None => (x, y),
+        }
+    }
+
+    /// Convert a point in PDF user space to the view (bottom-left origin)
+    fn view_point(&self, x: f64, y: f64) -> (f64, f64) {
+        match self.inverse_matrix() {
+            Some([a, b, c, d, e, f]) => (a * x + c * y + e, b * x + d * y + f),
+            None => (x, y),
+        }
+    }
+
+    /// Maps PDF user space to view coordinates (the matrix is a rotation
+    /// by a multiple of 90 degrees, so its inverse is its transpose)
+    fn inverse_matrix(&self) -> Option<[f64; 6]> {
+        let [a, b, c, d, e, f] = self.matrix?;
+        Some([a, c, b, d, -(a * e + b * f), -(c * e + d * f)])
+    }
+
+    /// PDF rectangle `[llx, lly, urx, ury]` of a rectangle in top-left view
+    /// coordinates
+    fn pdf_rect(&self, rect: Rect) -> [f64; 4] {
+        let bottom = self.height - rect.y - rect.height;
+        let (ax, ay) = self.pdf_point(rect.x, bottom);
+        let (bx, by) = self.pdf_point(rect.x + rect.width, bottom + rect.height);
+        [ax.min(bx), ay.min(by), ax.max(bx), ay.max(by)]
+    }
+
+    /// Rectangle in top-left view coordinates of a PDF rectangle
+    fn view_rect(&self, pdf_rect: [f64; 4]) -> Rect {
+        let [llx, lly, urx, ury] = pdf_rect;
+        let (ax, ay) = self.view_point(llx, lly);
+        let (bx, by) = self.view_point(urx, ury);
+        Rect::new(
+            ax.min(bx),
+            self.height - ay.max(by),
+            (bx - ax).abs(),
+            (by - ay).abs(),
+        )
+    }
 }
 
 /// RGB Color (values 0.0 - 1.0)
@@ -97,7 +252,46 @@ impl Default for Color {
     }
 }
 
+/// Rectangle in points
+///
+/// Public APIs take rectangles in top-left page coordinates, matching
+/// `insert_text` and `insert_image`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    /// Create a new rectangle
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+/// A page of another PDF imported as a Form XObject
+///
+/// Created by `PdfDocument::import_page` and drawn with `place_page`, as
+/// often as needed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImportedPage {
+    /// Form XObject holding the page content
+    pub object_id: ObjectId,
+    /// Page width in points
+    pub width: f64,
+    /// Page height in points
+    pub height: f64,
+}
+
 /// PDF Document wrapper providing high-level operations
+#[derive(Clone)]
 pub struct PdfDocument {
     /// The underlying lopdf document
     inner: Document,
@@ -115,6 +309,12 @@ pub struct PdfDocument {
     current_font_size: f32,
     /// Current text color
     current_text_color: Color,
+    /// Current text clipping rectangle (top-left coordinates)
+    current_text_clip: Option<Rect>,
+    /// Current transform for text, images and shapes
+    current_transform: Option<Transform>,
+    /// Current transparency for text, images and shapes
+    current_transparency: Option<Transparency>,
     /// Embedded fonts (font name -> PDF object ID)
     embedded_fonts: HashMap<String, ObjectId>,
     /// Page font resources (page number -> font name -> resource name)
@@ -127,15 +327,73 @@ pub struct PdfDocument {
     page_image_resources: HashMap<usize, HashMap<String, ObjectId>>,
     /// Next image resource number
     next_image_resource: u32,
+    /// Embedded ExtGState objects (transparency key -> PDF object ID)
+    ext_gstates: HashMap<(u16, u16, BlendMode), ObjectId>,
+    /// Page ExtGState resources (page number -> resource name -> object ID)
+    page_ext_gstate_resources: HashMap<usize, HashMap<String, ObjectId>>,
+    /// Next ExtGState resource number
+    next_ext_gstate_resource: u32,
     /// Font fallback chains (family -> list of fallback families)
     font_fallbacks: HashMap<String, Vec<String>>,
     /// Buffered content operators per page (page number -> operators)
-    page_content_buffer: HashMap<usize, Vec<u8>>,
+    page_content_buffer: HashMap<usize, Vec<ContentChunk>>,
+    /// Buffered operators drawn under the existing page content
+    page_underlay_buffer: HashMap<usize, Vec<ContentChunk>>,
+    /// Whether content is currently buffered as underlay
+    underlay: bool,
     /// Buffered text operations (encoded during save after font subsetting)
     buffered_text_ops: Vec<BufferedTextOp>,
+    /// Buffered form field appearances (encoded during save after font embedding)
+    buffered_field_appearances: Vec<BufferedFieldAppearance>,
+    /// Annotations to flatten into page content during save
+    flatten_mode: Option<FlattenMode>,
+    /// Fonts of created fields (font name -> /DR resource name)
+    form_fonts: HashMap<String, String>,
+    /// Embedded font programs by content hash (built on the first append)
+    font_files: Option<HashMap<u64, ObjectId>>,
+    /// Imported pages (source data hash, page number -> Form XObject)
+    imported_pages: HashMap<(u64, usize), ImportedPage>,
+    /// Pages whose original content is wrapped in q/Q
+    isolated_pages: HashSet<ObjectId>,
+    /// Margins of pages added with `add_page` (page object ID -> margins)
+    page_margins: HashMap<ObjectId, Margins>,
+}
+
+impl Default for PdfDocument {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PdfDocument {
+    /// Create an empty document without a base PDF
+    ///
+    /// Add pages with `add_page`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut doc = PdfDocument::new();
+    /// doc.add_page(PageSize::A4)?;
+    /// doc.add_font("sarabun", &font_bytes)?;
+    /// doc.set_font("sarabun", 16.0)?;
+    /// doc.insert_text("รายงานประจำเดือน", 1, 72.0, 72.0, Align::Left)?;
+    /// ```
+    pub fn new() -> Self {
+        let mut inner = Document::with_version("1.7");
+        let pages_id = inner.add_object(dictionary! {
+            "Type" => "Pages",
+            "Kids" => Vec::<Object>::new(),
+            "Count" => 0,
+        });
+        let catalog_id = inner.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        inner.trailer.set("Root", catalog_id);
+
+        Self::from_document(inner)
+    }
+
     /// Open a PDF document from a file path
     ///
     /// # Arguments
@@ -147,26 +405,7 @@ impl PdfDocument {
     /// ```
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
         let inner = Document::load(path).map_err(|e| PdfError::OpenError(e.to_string()))?;
-
-        Ok(Self {
-            inner,
-            fonts: HashMap::new(),
-            font_families: HashMap::new(),
-            current_family: None,
-            current_weight: FontWeight::default(),
-            current_style: FontStyle::default(),
-            current_font_size: 12.0,
-            current_text_color: Color::default(),
-            embedded_fonts: HashMap::new(),
-            page_font_resources: HashMap::new(),
-            next_font_resource: 1,
-            embedded_images: HashMap::new(),
-            page_image_resources: HashMap::new(),
-            next_image_resource: 1,
-            font_fallbacks: HashMap::new(),
-            page_content_buffer: HashMap::new(),
-            buffered_text_ops: Vec::new(),
-        })
+        Ok(Self::from_document(inner))
     }
 
     /// Open a PDF document from bytes
@@ -175,8 +414,12 @@ impl PdfDocument {
     /// * `data` - PDF file bytes
     pub fn open_from_bytes(data: &[u8]) -> Result<Self> {
         let inner = Document::load_mem(data).map_err(|e| PdfError::OpenError(e.to_string()))?;
+        Ok(Self::from_document(inner))
+    }
 
-        Ok(Self {
+    /// Wrap a lopdf document with default state
+    fn from_document(inner: Document) -> Self {
+        Self {
             inner,
             fonts: HashMap::new(),
             font_families: HashMap::new(),
@@ -185,16 +428,31 @@ impl PdfDocument {
             current_style: FontStyle::default(),
             current_font_size: 12.0,
             current_text_color: Color::default(),
+            current_text_clip: None,
+            current_transform: None,
+            current_transparency: None,
             embedded_fonts: HashMap::new(),
             page_font_resources: HashMap::new(),
             next_font_resource: 1,
             embedded_images: HashMap::new(),
             page_image_resources: HashMap::new(),
             next_image_resource: 1,
+            ext_gstates: HashMap::new(),
+            page_ext_gstate_resources: HashMap::new(),
+            next_ext_gstate_resource: 1,
             font_fallbacks: HashMap::new(),
             page_content_buffer: HashMap::new(),
+            page_underlay_buffer: HashMap::new(),
+            underlay: false,
             buffered_text_ops: Vec::new(),
-        })
+            buffered_field_appearances: Vec::new(),
+            flatten_mode: None,
+            form_fonts: HashMap::new(),
+            font_files: None,
+            imported_pages: HashMap::new(),
+            isolated_pages: HashSet::new(),
+            page_margins: HashMap::new(),
+        }
     }
 
     /// Get the number of pages in the document
@@ -339,6 +597,26 @@ impl PdfDocument {
         Ok(self)
     }
 
+    /// Get the current font size in points
+    pub fn get_font_size(&self) -> f32 {
+        self.current_font_size
+    }
+
+    /// Get the current font family name, if one is set
+    pub fn get_font_family(&self) -> Option<&str> {
+        self.current_family.as_deref()
+    }
+
+    /// Get the current font weight
+    pub fn get_font_weight(&self) -> FontWeight {
+        self.current_weight
+    }
+
+    /// Get the current font style
+    pub fn get_font_style(&self) -> FontStyle {
+        self.current_style
+    }
+
     /// Set the font weight (keeps current family/size/style)
     ///
     /// # Arguments
@@ -381,6 +659,85 @@ impl PdfDocument {
         self
     }
 
+    /// Set the text clipping rectangle
+    ///
+    /// Text inserted while a clip is set is only painted inside the
+    /// rectangle. Pass `None` to stop clipping.
+    ///
+    /// # Arguments
+    /// * `clip` - Rectangle in top-left page coordinates, or None
+    ///
+    /// # Example
+    /// ```ignore
+    /// doc.set_text_clip(Some(Rect::new(100.0, 200.0, 150.0, 40.0)));
+    /// doc.insert_text("Long text...", 1, 100.0, 220.0, Align::Left)?;
+    /// doc.set_text_clip(None);
+    /// ```
+    pub fn set_text_clip(&mut self, clip: Option<Rect>) -> &mut Self {
+        self.current_text_clip = clip;
+        self
+    }
+
+    /// Set the transform for text, images and shapes
+    ///
+    /// Everything drawn while a transform is set is rotated, skewed and
+    /// scaled around the transform's anchor, or around the drawing's own
+    /// origin when it has none: the insertion point of text, the top-left
+    /// corner of images, rectangles, barcodes and check marks, the start of
+    /// lines and paths, and the center of ellipses. A text clip rectangle is
+    /// transformed along with its text. Pass `None` to stop transforming.
+    ///
+    /// # Arguments
+    /// * `transform` - Transform, or None
+    ///
+    /// # Example
+    /// ```ignore
+    /// doc.set_transform(Some(Transform::rotate(30.0).with_anchor(300.0, 400.0)));
+    /// doc.insert_image_scaled(&stamp, 1, 250.0, 375.0, 100.0, 50.0, ImageScaleMode::Fit)?;
+    /// doc.set_transform(None);
+    /// ```
+    pub fn set_transform(&mut self, transform: Option<Transform>) -> &mut Self {
+        self.current_transform = transform;
+        self
+    }
+
+    /// Set the transparency for text, images and shapes
+    ///
+    /// Everything drawn while a transparency is set is painted with its
+    /// fill and stroke opacity and blend mode. Pass `None` to paint opaque
+    /// again.
+    ///
+    /// # Arguments
+    /// * `transparency` - Opacity and blend mode, or None
+    ///
+    /// # Example
+    /// ```ignore
+    /// // Signature scan that lets the form lines show through
+    /// doc.set_transparency(Some(Transparency::blend(BlendMode::Multiply)));
+    /// doc.insert_image(&signature_jpeg, 1, 400.0, 700.0, 120.0, 40.0)?;
+    /// doc.set_transparency(None);
+    /// ```
+    pub fn set_transparency(&mut self, transparency: Option<Transparency>) -> &mut Self {
+        self.current_transparency = transparency;
+        self
+    }
+
+    /// Draw text, images and shapes under the existing page content
+    ///
+    /// While set, everything drawn is painted before the base PDF content
+    /// (and before earlier overlays), e.g. a letterhead behind a scanned form.
+    ///
+    /// # Example
+    /// ```ignore
+    /// doc.set_underlay(true);
+    /// doc.place_page(&letterhead, 1, 0.0, 0.0, 1.0)?;
+    /// doc.set_underlay(false);
+    /// ```
+    pub fn set_underlay(&mut self, underlay: bool) -> &mut Self {
+        self.underlay = underlay;
+        self
+    }
+
     /// Set font fallback chain for a family
     ///
     /// # Arguments
@@ -412,11 +769,13 @@ impl PdfDocument {
             .as_ref()
             .ok_or_else(|| PdfError::FontNotFound("No font family set".to_string()))?;
 
-        // First try font families (new API)
+        // First try font families (new API), falling back to regular
+        // when the requested variant is not registered
         if let Some(family) = self.font_families.get(family_name) {
-            let variant_name =
-                family.get_variant_name(family_name, self.current_weight, self.current_style);
-            return Ok(variant_name);
+            let variant = family
+                .get_variant(self.current_weight, self.current_style)
+                .ok_or_else(|| PdfError::FontNotFound(family_name.clone()))?;
+            return Ok(variant.name.clone());
         }
 
         // Fall back to legacy fonts
@@ -449,41 +808,22 @@ impl PdfDocument {
             return Ok(());
         }
 
-        // Get the current font family name
-        let family_name = self
-            .current_family
-            .as_ref()
-            .ok_or_else(|| PdfError::FontNotFound("No font family set".to_string()))?
-            .clone();
-
-        // Get the actual font name (variant) to use
-        let font_name = self.get_current_font_name()?;
-
-        // Check if fallbacks are configured for this font
-        let has_fallbacks = self.font_fallbacks.contains_key(&family_name);
-
-        // Segment text by font availability if fallbacks are configured
-        let segments = if has_fallbacks {
-            self.segment_text_by_font(text, &family_name, &font_name)
-        } else {
-            // No fallbacks, treat entire text as single segment
-            vec![TextSegment {
-                text: text.to_string(),
-                font_name: font_name.clone(),
-            }]
-        };
+        // Segment text by font availability (fallbacks)
+        let segments = self.segment_current_text(text)?;
 
         // Calculate total text width for alignment
-        let mut total_width = 0.0f64;
-        for segment in &segments {
-            let font_data = self.get_font_data(&segment.font_name)?;
-            total_width +=
-                font_data.text_width_points(&segment.text, self.current_font_size) as f64;
-        }
+        let total_width = self.segments_width(&segments)?;
 
         // Convert Y coordinate from top-origin to PDF bottom-origin
-        let page_height = self.get_page_height(page)?;
+        let geometry = self.content_geometry(page)?;
+        let page_height = geometry.height;
         let pdf_y = page_height - y;
+        let clip = self.current_text_clip.map(|r| Rect {
+            y: page_height - r.y - r.height,
+            ..r
+        });
+        let transform = self.current_matrix(page_height, x, y);
+        let ext_gstate = self.current_ext_gstate_ref(page)?;
 
         // Calculate starting x position based on alignment
         let start_x = match align {
@@ -495,31 +835,33 @@ impl PdfDocument {
         // Render each segment
         let mut current_x = start_x;
         for segment in &segments {
-            // Track characters used in font for subsetting
-            {
+            // Shape the segment and track glyphs used in font for subsetting
+            let font_size = self.current_font_size;
+            let (glyphs, segment_width) = {
                 let font_data = self.get_font_data_mut(&segment.font_name)?;
-                font_data.add_chars(&segment.text);
-            }
+                let glyphs = font_data.add_text(&segment.text);
+                let width = font_data.text_width_points(&segment.text, font_size);
+                (glyphs, width as f64)
+            };
 
             // Get or create font reference for this page
             let font_resource_name = self.get_or_create_font_ref(&segment.font_name, page)?;
 
-            // Get segment text width
-            let segment_width = {
-                let font_data = self.get_font_data(&segment.font_name)?;
-                font_data.text_width_points(&segment.text, self.current_font_size) as f64
-            };
-
             // Buffer text operation for deferred encoding (after font subsetting)
             self.buffered_text_ops.push(BufferedTextOp {
-                text: segment.text.clone(),
+                glyphs,
                 font_name: segment.font_name.clone(),
                 font_resource_name: font_resource_name.clone(),
                 page,
                 x: current_x,
                 y: pdf_y,
-                font_size: self.current_font_size,
+                font_size,
                 color: self.current_text_color,
+                clip,
+                transform,
+                ext_gstate: ext_gstate.clone(),
+                underlay: self.underlay,
+                view_matrix: geometry.matrix,
             });
 
             // Move to next segment position
@@ -529,6 +871,40 @@ impl PdfDocument {
         Ok(())
     }
 
+    /// Segment text for the current font, applying fallbacks if configured
+    fn segment_current_text(&self, text: &str) -> Result<Vec<TextSegment>> {
+        // Get the current font family name
+        let family_name = self
+            .current_family
+            .as_ref()
+            .ok_or_else(|| PdfError::FontNotFound("No font family set".to_string()))?;
+
+        // Get the actual font name (variant) to use
+        let font_name = self.get_current_font_name()?;
+
+        // Segment text by font availability if fallbacks are configured
+        if self.font_fallbacks.contains_key(family_name) {
+            Ok(self.segment_text_by_font(text, family_name, &font_name))
+        } else {
+            // No fallbacks, treat entire text as single segment
+            Ok(vec![TextSegment {
+                text: text.to_string(),
+                font_name,
+            }])
+        }
+    }
+
+    /// Total width in points of text segments at the current font size
+    fn segments_width(&self, segments: &[TextSegment]) -> Result<f64> {
+        let mut total_width = 0.0f64;
+        for segment in segments {
+            let font_data = self.get_font_data(&segment.font_name)?;
+            total_width +=
+                font_data.text_width_points(&segment.text, self.current_font_size) as f64;
+        }
+        Ok(total_width)
+    }
+
     /// Get font data by name (searches both families and legacy fonts)
     fn get_font_data(&self, name: &str) -> Result<&FontData> {
         // First try font families
@@ -672,75 +1048,1346 @@ impl PdfDocument {
         segments
     }
 
-    /// Insert an image at a specific position
+    /// Import a page of another PDF as a Form XObject
+    ///
+    /// The page content is copied with its resources, clipped to its
+    /// CropBox (or MediaBox). Importing the same page of the same data
+    /// again returns the existing XObject. The page's /Rotate is not applied.
     ///
     /// # Arguments
-    /// * `data` - Image file bytes (JPEG or PNG)
-    /// * `page` - Page number (1-indexed)
-    /// * `x` - X coordinate in points
-    /// * `y` - Y coordinate in points (from top)
-    /// * `width` - Image width in points
-    /// * `height` - Image height in points
-    pub fn insert_image(
-        &mut self,
-        data: &[u8],
-        page: usize,
-        x: f64,
-        y: f64,
-        width: f64,
-        height: f64,
-    ) -> Result<()> {
-        self.insert_image_scaled(data, page, x, y, width, height, ImageScaleMode::Stretch)
+    /// * `data` - PDF file bytes
+    /// * `page` - Page number in that PDF (1-indexed)
+    ///
+    /// # Example
+    /// ```ignore
+    /// let letterhead = doc.import_page(&std::fs::read("letterhead.pdf")?, 1)?;
+    /// for page in 1..=doc.page_count() {
+    ///     doc.place_page(&letterhead, page, 0.0, 0.0, 1.0)?;
+    /// }
+    /// ```
+    pub fn import_page(&mut self, data: &[u8], page: usize) -> Result<ImportedPage> {
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+        let key = (hasher.finish(), page);
+        if let Some(imported) = self.imported_pages.get(&key) {
+            return Ok(*imported);
+        }
+
+        let mut source = PdfDocument::open_from_bytes(data)?;
+        let pages = source.inner.get_pages();
+        let page_id = *pages
+            .get(&(page as u32))
+            .ok_or(PdfError::InvalidPage(page, pages.len()))?;
+        source.copy_inherited_attributes(page_id)?;
+        let (visible, geometry) = source.page_geometry(page)?;
+
+        // The form shows the visible area upright, from (0,0)
+        let mut dict = form_xobject_dict(0.0, 0.0);
+        dict.set(
+            "BBox",
+            Object::Array(visible.iter().map(|&v| Object::Real(v as f32)).collect()),
+        );
+        if let Some(matrix) = geometry.inverse_matrix() {
+            dict.set(
+                "Matrix",
+                Object::Array(matrix.iter().map(|&v| Object::Real(v as f32)).collect()),
+            );
+        }
+
+        // Resources and transparency group come along with the content
+        let page_dict = source.inner.get_dictionary(page_id)?;
+        let mut copied: Vec<(&[u8], Object)> = Vec::new();
+        for key in [b"Resources".as_slice(), b"Group"] {
+            if let Ok(value) = page_dict.get(key) {
+                copied.push((key, value.clone()));
+            }
+        }
+        let mut roots = Vec::new();
+        for (_, value) in &copied {
+            object_references(value, &mut roots);
+        }
+        let mut id_map = HashMap::new();
+        self.import_objects(&source.inner, &roots, &mut id_map)?;
+        for (key, mut value) in copied {
+            remap_references(&mut value, &id_map);
+            dict.set(key, value);
+        }
+
+        // One content stream, each part separated so tokens cannot merge
+        let mut content = Vec::new();
+        for id in source.inner.get_page_contents(page_id) {
+            if let Ok(Object::Stream(stream)) = source.inner.get_object(id) {
+                let part = stream
+                    .decompressed_content()
+                    .unwrap_or_else(|_| stream.content.clone());
+                content.extend_from_slice(&part);
+                content.push(b'\n');
+            }
+        }
+        let mut stream = Stream::new(dict, content);
+        // Uncompressed content is still valid
+        let _ = stream.compress();
+        let object_id = self.inner.add_object(stream);
+
+        let imported = ImportedPage {
+            object_id,
+            width: geometry.width,
+            height: geometry.height,
+        };
+        self.imported_pages.insert(key, imported);
+        Ok(imported)
     }
 
-    /// Insert an image with scaling mode
+    /// Draw an imported page
+    ///
+    /// Follows the current transform, transparency and underlay setting
+    /// like images do.
     ///
     /// # Arguments
-    /// * `data` - Image file bytes (JPEG or PNG)
-    /// * `page` - Page number (1-indexed)
-    /// * `x` - X coordinate in points
-    /// * `y` - Y coordinate in points (from top)
-    /// * `width` - Target width in points
-    /// * `height` - Target height in points
-    /// * `mode` - Scaling mode
-    #[allow(clippy::too_many_arguments)]
-    pub fn insert_image_scaled(
+    /// * `imported` - Page from `import_page`
+    /// * `page` - Page number to draw on (1-indexed)
+    /// * `x`, `y` - Top-left corner in points (Y from top)
+    /// * `scale` - Size factor (1.0 = the imported page's size)
+    pub fn place_page(
         &mut self,
-        data: &[u8],
+        imported: &ImportedPage,
         page: usize,
         x: f64,
         y: f64,
-        width: f64,
-        height: f64,
-        mode: ImageScaleMode,
+        scale: f64,
     ) -> Result<()> {
         let page_count = self.page_count();
         if page == 0 || page > page_count {
             return Err(PdfError::InvalidPage(page, page_count));
         }
 
-        // Get or create image resource reference (now returns dimensions too)
-        let (image_resource_name, orig_width, orig_height) =
-            self.get_or_create_image_ref(data, page)?;
-
-        // Calculate actual display dimensions based on mode
-        let (actual_width, actual_height) =
-            calculate_scaled_dimensions(orig_width, orig_height, width, height, mode);
+        // Reuse the page's name for this XObject, skipping names the base
+        // PDF already uses
+        let existing_name = self.page_image_resources.get(&page).and_then(|resources| {
+            resources
+                .iter()
+                .find(|(_, id)| **id == imported.object_id)
+                .map(|(name, _)| name.clone())
+        });
+        let resource_name = match existing_name {
+            Some(name) => name,
+            None => {
+                let existing = self.page_resource_names(page, b"XObject")?;
+                let name = loop {
+                    let name = format!("Pg{}", self.next_image_resource);
+                    self.next_image_resource += 1;
+                    if !existing.iter().any(|used| used == name.as_bytes()) {
+                        break name;
+                    }
+                };
+                self.page_image_resources
+                    .entry(page)
+                    .or_default()
+                    .insert(name.clone(), imported.object_id);
+                self.add_xobjects_to_page_resources(page, &[(name.clone(), imported.object_id)])?;
+                name
+            }
+        };
 
-        // Convert Y coordinate from top-origin to PDF bottom-origin
+        // Map the imported page box onto the target rectangle
         let page_height = self.get_page_height(page)?;
-        let pdf_y = page_height - y - actual_height;
+        let bottom = page_height - y - imported.height * scale;
+        let operators = format!("q\n{scale} 0 0 {scale} {x} {bottom} cm\n/{resource_name} Do\nQ\n");
+
+        self.buffer_graphics(page, page_height, operators.as_bytes(), (x, y))
+    }
+
+    /// Stamp a watermark (text or image) on pages
+    ///
+    /// The watermark is centered on each page (or tiled across it), rotated
+    /// around its center and painted with its opacity, over or under the
+    /// page content. The document's current font, color, transform and
+    /// transparency are left unchanged.
+    ///
+    /// # Arguments
+    /// * `watermark` - Watermark settings
+    ///
+    /// # Example
+    /// ```ignore
+    /// doc.add_watermark(&Watermark::text("VOID").with_font("sarabun", 96.0).with_color(Color::red()))?;
+    /// doc.add_watermark(&Watermark::image(&logo, 200.0, 200.0).with_layer(WatermarkLayer::Underlay))?;
+    /// ```
+    pub fn add_watermark(&mut self, watermark: &Watermark) -> Result<()> {
+        let page_count = self.page_count();
+        let pages = match &watermark.pages {
+            Some(pages) => pages.clone(),
+            None => (1..=page_count).collect(),
+        };
+        if let Some(&page) = pages.iter().find(|&&p| p == 0 || p > page_count) {
+            return Err(PdfError::InvalidPage(page, page_count));
+        }
+
+        // Drawing state restored afterwards
+        let saved = (
+            self.current_family.clone(),
+            self.current_font_size,
+            self.current_text_color,
+            self.current_transform,
+            self.current_transparency,
+            self.underlay,
+        );
+
+        self.underlay = watermark.layer == WatermarkLayer::Underlay;
+        self.current_transparency = Some(Transparency::alpha(watermark.opacity));
+        let result = self.draw_watermark(watermark, &pages);
+
+        (
+            self.current_family,
+            self.current_font_size,
+            self.current_text_color,
+            self.current_transform,
+            self.current_transparency,
+            self.underlay,
+        ) = saved;
+        result
+    }
+
+    /// Draw watermark content centered on watermark positions
+    fn draw_watermark(&mut self, watermark: &Watermark, pages: &[usize]) -> Result<()> {
+        // Size of one watermark, and the top-left offset from its center
+        // to where it is inserted
+        let (width, height, offset_x, offset_y) = match &watermark.content {
+            WatermarkContent::Text(text) => {
+                if let Some(family) = &watermark.font {
+                    self.set_font(family, watermark.font_size)?;
+                } else {
+                    self.set_font_size(watermark.font_size)?;
+                }
+                self.current_text_color = watermark.color;
+
+                // Center the text box between ascent and descent on the baseline
+                let (ascent, descent) = self.get_font_extents()?;
+                let width = self.get_text_width(text)?;
+                (width, ascent + descent, 0.0, (ascent - descent) / 2.0)
+            }
+            WatermarkContent::Image {
+                data,
+                width,
+                height,
+            } => {
+                let dims = get_dimensions(data)?;
+                let (width, height) = calculate_scaled_dimensions(
+                    dims.width,
+                    dims.height,
+                    *width,
+                    *height,
+                    ImageScaleMode::FitBox,
+                );
+                (width, height, -width / 2.0, -height / 2.0)
+            }
+        };
+
+        for &page in pages {
+            // Centered on the whole page, then placed relative to the margins
+            let (_, geometry) = self.page_geometry(page)?;
+            let margins = self
+                .page_margins
+                .get(&self.page_id(page)?)
+                .copied()
+                .unwrap_or_default();
+            let centers = watermark_centers(
+                geometry.width,
+                geometry.height,
+                width,
+                height,
+                watermark.tile,
+            );
+
+            for (cx, cy) in centers {
+                let (cx, cy) = (cx - margins.left, cy - margins.top);
+                self.current_transform =
+                    Some(Transform::rotate(watermark.angle).with_anchor(cx, cy));
+                let (x, y) = (cx + offset_x, cy + offset_y);
+                match &watermark.content {
+                    WatermarkContent::Text(text) => {
+                        self.insert_text(text, page, x, y, Align::Center)?
+                    }
+                    WatermarkContent::Image { data, .. } => self.insert_image_scaled(
+                        data,
+                        page,
+                        x,
+                        y,
+                        width,
+                        height,
+                        ImageScaleMode::Stretch,
+                    )?,
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Insert an image at a specific position
+    ///
+    /// # Arguments
+    /// * `data` - Image file bytes (JPEG or PNG)
+    /// * `page` - Page number (1-indexed)
+    /// * `x` - X coordinate in points
+    /// * `y` - Y coordinate in points (from top)
+    /// * `width` - Image width in points
+    /// * `height` - Image height in points
+    pub fn insert_image(
+        &mut self,
+        data: &[u8],
+        page: usize,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    ) -> Result<()> {
+        self.insert_image_scaled(data, page, x, y, width, height, ImageScaleMode::Stretch)
+    }
+
+    /// Insert an image with scaling mode
+    ///
+    /// # Arguments
+    /// * `data` - Image file bytes (JPEG or PNG)
+    /// * `page` - Page number (1-indexed)
+    /// * `x` - X coordinate in points
+    /// * `y` - Y coordinate in points (from top)
+    /// * `width` - Target width in points
+    /// * `height` - Target height in points
+    /// * `mode` - Scaling mode
+    #[allow(clippy::too_many_arguments)]
+    pub fn insert_image_scaled(
+        &mut self,
+        data: &[u8],
+        page: usize,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        mode: ImageScaleMode,
+    ) -> Result<()> {
+        let page_count = self.page_count();
+        if page == 0 || page > page_count {
+            return Err(PdfError::InvalidPage(page, page_count));
+        }
+
+        // Get or create image resource reference (now returns dimensions too)
+        let (image_resource_name, orig_width, orig_height) =
+            self.get_or_create_image_ref(data, page)?;
+
+        // Calculate actual display dimensions based on mode
+        let (actual_width, actual_height) =
+            calculate_scaled_dimensions(orig_width, orig_height, width, height, mode);
+
+        // Convert Y coordinate from top-origin to PDF bottom-origin
+        let page_height = self.get_page_height(page)?;
+        let pdf_y = page_height - y - actual_height;
 
         // Generate PDF image drawing operators
         let operators =
             generate_image_operators(&image_resource_name, x, pdf_y, actual_width, actual_height);
 
-        // Buffer content operators (will be flushed at save time)
-        self.buffer_content(page, &operators);
+        // Buffer content operators (will be flushed at save time)
+        self.buffer_graphics(page, page_height, &operators, (x, y))?;
+
+        Ok(())
+    }
+
+    /// Draw a straight line
+    ///
+    /// # Arguments
+    /// * `page` - Page number (1-indexed)
+    /// * `x1`, `y1` - Start point in points (Y from top)
+    /// * `x2`, `y2` - End point in points (Y from top)
+    /// * `style` - Stroke color and width
+    ///
+    /// # Example
+    /// ```ignore
+    /// // Strike through a voided amount
+    /// doc.draw_line(1, 400.0, 300.0, 480.0, 300.0, &ShapeStyle::stroke(Color::red(), 1.0))?;
+    /// ```
+    pub fn draw_line(
+        &mut self,
+        page: usize,
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+        style: &ShapeStyle,
+    ) -> Result<()> {
+        let page_count = self.page_count();
+        if page == 0 || page > page_count {
+            return Err(PdfError::InvalidPage(page, page_count));
+        }
+
+        let page_height = self.get_page_height(page)?;
+        let operators = generate_line_operators(x1, page_height - y1, x2, page_height - y2, style);
+        self.buffer_graphics(page, page_height, &operators, (x1, y1))?;
+
+        Ok(())
+    }
+
+    /// Draw a rectangle
+    ///
+    /// # Arguments
+    /// * `page` - Page number (1-indexed)
+    /// * `rect` - Rectangle in top-left page coordinates
+    /// * `style` - Stroke and fill settings
+    ///
+    /// # Example
+    /// ```ignore
+    /// let style = ShapeStyle::stroke(Color::black(), 0.5).with_fill(Color::rgb(0.9, 0.9, 0.9));
+    /// doc.draw_rect(1, Rect::new(350.0, 600.0, 200.0, 24.0), &style)?;
+    /// ```
+    pub fn draw_rect(&mut self, page: usize, rect: Rect, style: &ShapeStyle) -> Result<()> {
+        let page_count = self.page_count();
+        if page == 0 || page > page_count {
+            return Err(PdfError::InvalidPage(page, page_count));
+        }
+
+        // Convert Y coordinate from top-origin to PDF bottom-origin
+        let page_height = self.get_page_height(page)?;
+        let pdf_y = page_height - rect.y - rect.height;
+
+        let operators = generate_rect_operators(rect.x, pdf_y, rect.width, rect.height, style);
+        self.buffer_graphics(page, page_height, &operators, (rect.x, rect.y))?;
+
+        Ok(())
+    }
+
+    /// Draw a rectangle with rounded corners
+    ///
+    /// # Arguments
+    /// * `page` - Page number (1-indexed)
+    /// * `rect` - Rectangle in top-left page coordinates
+    /// * `radius` - Corner radius in points (clamped to half the shorter side)
+    /// * `style` - Stroke and fill settings
+    pub fn draw_rounded_rect(
+        &mut self,
+        page: usize,
+        rect: Rect,
+        radius: f64,
+        style: &ShapeStyle,
+    ) -> Result<()> {
+        let path =
+            GraphicsPath::new().rounded_rect(rect.x, rect.y, rect.width, rect.height, radius);
+        self.draw_path_anchored(page, &path, style, (rect.x, rect.y))
+    }
+
+    /// Draw an ellipse (or circle when both radii are equal)
+    ///
+    /// # Arguments
+    /// * `page` - Page number (1-indexed)
+    /// * `cx`, `cy` - Center in points (Y from top)
+    /// * `rx`, `ry` - Horizontal and vertical radius in points
+    /// * `style` - Stroke and fill settings
+    ///
+    /// # Example
+    /// ```ignore
+    /// // Circle a stamp area
+    /// doc.draw_ellipse(1, 480.0, 720.0, 40.0, 40.0, &ShapeStyle::stroke(Color::blue(), 1.5))?;
+    /// ```
+    pub fn draw_ellipse(
+        &mut self,
+        page: usize,
+        cx: f64,
+        cy: f64,
+        rx: f64,
+        ry: f64,
+        style: &ShapeStyle,
+    ) -> Result<()> {
+        let path = GraphicsPath::new().ellipse(cx, cy, rx, ry);
+        self.draw_path_anchored(page, &path, style, (cx, cy))
+    }
+
+    /// Draw an arbitrary path
+    ///
+    /// # Arguments
+    /// * `page` - Page number (1-indexed)
+    /// * `path` - Path in top-left page coordinates
+    /// * `style` - Stroke and fill settings
+    ///
+    /// # Example
+    /// ```ignore
+    /// let tick = Path::new().move_to(100.0, 200.0).line_to(104.0, 205.0).line_to(112.0, 194.0);
+    /// doc.draw_path(1, &tick, &ShapeStyle::stroke(Color::black(), 1.5).with_line_cap(LineCap::Round))?;
+    /// ```
+    pub fn draw_path(
+        &mut self,
+        page: usize,
+        path: &GraphicsPath,
+        style: &ShapeStyle,
+    ) -> Result<()> {
+        let anchor = path.start_point().unwrap_or_default();
+        self.draw_path_anchored(page, path, style, anchor)
+    }
+
+    /// Draw a path, transformed around `anchor` (top-left coordinates)
+    fn draw_path_anchored(
+        &mut self,
+        page: usize,
+        path: &GraphicsPath,
+        style: &ShapeStyle,
+        anchor: (f64, f64),
+    ) -> Result<()> {
+        let page_count = self.page_count();
+        if page == 0 || page > page_count {
+            return Err(PdfError::InvalidPage(page, page_count));
+        }
+
+        // Convert Y coordinates from top-origin to PDF bottom-origin
+        let page_height = self.get_page_height(page)?;
+        let path = path.clone().map_points(|x, y| (x, page_height - y));
+
+        let operators = generate_path_operators(&path, style);
+        self.buffer_graphics(page, page_height, &operators, anchor)?;
+
+        Ok(())
+    }
+
+    /// Draw a check mark (tick, cross or filled box) without a symbol font
+    ///
+    /// # Arguments
+    /// * `page` - Page number (1-indexed)
+    /// * `x` - X coordinate of the box's left edge in points
+    /// * `y` - Y coordinate of the box's top edge in points (from top)
+    /// * `size` - Box side in points
+    /// * `mark` - Mark shape
+    /// * `color` - Mark color
+    ///
+    /// # Example
+    /// ```ignore
+    /// doc.draw_check_mark(1, 72.0, 300.0, 10.0, CheckMark::Tick, Color::black())?;
+    /// ```
+    pub fn draw_check_mark(
+        &mut self,
+        page: usize,
+        x: f64,
+        y: f64,
+        size: f64,
+        mark: CheckMark,
+        color: Color,
+    ) -> Result<()> {
+        let page_count = self.page_count();
+        if page == 0 || page > page_count {
+            return Err(PdfError::InvalidPage(page, page_count));
+        }
+
+        // Convert the top edge to the PDF bottom edge
+        let page_height = self.get_page_height(page)?;
+        let operators = generate_check_mark_operators(mark, x, page_height - y - size, size, color);
+        self.buffer_graphics(page, page_height, &operators, (x, y))?;
+
+        Ok(())
+    }
+
+    /// Insert a 1D barcode drawn as filled rectangles
+    ///
+    /// # Arguments
+    /// * `barcode` - Encoded barcode (see `Barcode::encode`)
+    /// * `page` - Page number (1-indexed)
+    /// * `x` - X coordinate of the left edge in points
+    /// * `y` - Y coordinate of the top edge in points (from top)
+    /// * `module_width` - Width of one module (narrow bar) in points
+    /// * `bar_height` - Bar height in points
+    ///
+    /// # Example
+    /// ```ignore
+    /// let barcode = Barcode::encode(BarcodeSymbology::Code128, "INV-0001")?;
+    /// doc.insert_barcode(&barcode, 1, 100.0, 700.0, 0.8, 40.0)?;
+    /// ```
+    pub fn insert_barcode(
+        &mut self,
+        barcode: &Barcode,
+        page: usize,
+        x: f64,
+        y: f64,
+        module_width: f64,
+        bar_height: f64,
+    ) -> Result<()> {
+        let page_count = self.page_count();
+        if page == 0 || page > page_count {
+            return Err(PdfError::InvalidPage(page, page_count));
+        }
+
+        // Convert Y coordinate from top-origin to PDF bottom-origin
+        let page_height = self.get_page_height(page)?;
+        let pdf_y = page_height - y - bar_height;
+
+        let operators =
+            generate_barcode_operators(&barcode.modules, x, pdf_y, module_width, bar_height);
+        self.buffer_graphics(page, page_height, &operators, (x, y))?;
+
+        Ok(())
+    }
+
+    /// Insert a 2D module matrix (e.g. a QR code) drawn as filled rectangles
+    ///
+    /// # Arguments
+    /// * `matrix` - Modules to draw (include the quiet zone if needed)
+    /// * `page` - Page number (1-indexed)
+    /// * `rect` - Target area in top-left page coordinates
+    /// * `foreground` - Color of dark modules
+    /// * `background` - Color of the whole area, or None for transparent
+    ///
+    /// # Example
+    /// ```ignore
+    /// let rect = Rect::new(400.0, 100.0, 80.0, 80.0);
+    /// doc.insert_module_matrix(&matrix, 1, rect, Color::black(), Some(Color::white()))?;
+    /// ```
+    pub fn insert_module_matrix(
+        &mut self,
+        matrix: &ModuleMatrix,
+        page: usize,
+        rect: Rect,
+        foreground: Color,
+        background: Option<Color>,
+    ) -> Result<()> {
+        let page_count = self.page_count();
+        if page == 0 || page > page_count {
+            return Err(PdfError::InvalidPage(page, page_count));
+        }
+
+        // Convert Y coordinate from top-origin to PDF bottom-origin
+        let page_height = self.get_page_height(page)?;
+        let pdf_y = page_height - rect.y - rect.height;
+
+        let operators = generate_matrix_operators(
+            matrix,
+            rect.x,
+            pdf_y,
+            rect.width,
+            rect.height,
+            foreground,
+            background,
+        );
+        self.buffer_graphics(page, page_height, &operators, (rect.x, rect.y))?;
+
+        Ok(())
+    }
+
+    /// List the fields of the document's interactive form (AcroForm)
+    ///
+    /// Returns terminal fields only (those holding a value), with fully
+    /// qualified names such as `"applicant.name"`. A document without a
+    /// form returns an empty list.
+    ///
+    /// # Example
+    /// ```ignore
+    /// for field in doc.form_fields()? {
+    ///     println!("{} {:?} page {:?}", field.name, field.field_type, field.page());
+    /// }
+    /// ```
+    pub fn form_fields(&self) -> Result<Vec<FormField>> {
+        let nodes = form::collect_fields(&self.inner)?;
+        let widget_pages = form::widget_pages(&self.inner);
+
+        let mut fields = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            let mut widgets = Vec::new();
+            for &widget_id in &node.widgets {
+                let Some([llx, lly, urx, ury]) = form::widget_rect(&self.inner, widget_id) else {
+                    continue;
+                };
+                let page = widget_pages.get(&widget_id).copied();
+                let (_, geometry) = self.page_geometry(page.unwrap_or(1))?;
+                widgets.push(FieldWidget {
+                    page,
+                    rect: geometry.view_rect([llx, lly, urx, ury]),
+                    on_state: form::widget_on_states(&self.inner, widget_id)
+                        .into_iter()
+                        .next(),
+                });
+            }
+
+            let options = match node.field_type {
+                FormFieldType::ComboBox | FormFieldType::ListBox => {
+                    node.options.iter().map(|o| o.export.clone()).collect()
+                }
+                FormFieldType::Checkbox | FormFieldType::Radio => {
+                    let mut states: Vec<String> = Vec::new();
+                    for state in widgets.iter().filter_map(|w| w.on_state.clone()) {
+                        if !states.contains(&state) {
+                            states.push(state);
+                        }
+                    }
+                    states
+                }
+                _ => Vec::new(),
+            };
+
+            fields.push(FormField {
+                name: node.name,
+                field_type: node.field_type,
+                value: node.value,
+                options,
+                flags: node.flags,
+                widgets,
+            });
+        }
+
+        Ok(fields)
+    }
+
+    /// Set the value of a form field by its fully qualified name
+    ///
+    /// - Text fields take any text. An appearance is generated with the
+    ///   current font (including fallbacks), size and text color, so Thai
+    ///   text shows correctly in every viewer.
+    /// - Checkboxes are turned off by `""`, `"Off"`, `"false"`, `"0"` or
+    ///   `"no"`; any other value (or the name of an on-state) turns them on.
+    /// - Radio groups take the on-state name of one button, or `"Off"`.
+    /// - Combo and list boxes take an export value or display text of one
+    ///   of their options (editable combo boxes take any text).
+    ///
+    /// # Arguments
+    /// * `name` - Fully qualified field name (see `form_fields()`)
+    /// * `value` - Value to set
+    ///
+    /// # Example
+    /// ```ignore
+    /// doc.set_font("sarabun", 14.0)?;
+    /// doc.set_field_value("applicant.name", "นายสมชาย ใจดี")?;
+    /// doc.set_field_value("agree", "true")?;
+    /// doc.set_field_value("province", "กรุงเทพมหานคร")?;
+    /// ```
+    pub fn set_field_value(&mut self, name: &str, value: &str) -> Result<()> {
+        let node = form::collect_fields(&self.inner)?
+            .into_iter()
+            .find(|node| node.name == name)
+            .ok_or_else(|| PdfError::FormError(format!("Field not found: {name}")))?;
+
+        match node.field_type {
+            FormFieldType::Text => {
+                let dict = self.inner.get_dictionary_mut(node.id)?;
+                dict.set("V", form::encode_text_string(value));
+                dict.remove(b"RV");
+                self.buffer_field_text(&node, value)?;
+            }
+            FormFieldType::ComboBox | FormFieldType::ListBox => {
+                self.set_choice_value(&node, value)?;
+            }
+            FormFieldType::Checkbox | FormFieldType::Radio => {
+                self.set_button_value(&node, value)?;
+            }
+            FormFieldType::PushButton | FormFieldType::Signature => {
+                return Err(PdfError::FormError(format!(
+                    "Field {name} ({:?}) does not take a value",
+                    node.field_type
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Create an interactive form field
+    ///
+    /// Text fields take the current font, size and text color as their
+    /// default appearance. The whole font is embedded in the form resources
+    /// (not only the glyphs used on the pages), so the recipient can type
+    /// any character it has into the field. A default value is applied like
+    /// `set_field_value`.
+    ///
+    /// Adding a field whose name was already created with the same type
+    /// adds its widgets to that field; they share one value.
+    ///
+    /// # Arguments
+    /// * `field` - Field name, type, widgets, default value and flags
+    ///
+    /// # Example
+    /// ```ignore
+    /// doc.set_font("sarabun", 14.0)?;
+    /// doc.add_form_field(
+    ///     &FieldDefinition::text("remarks", 1, Rect::new(50.0, 700.0, 300.0, 60.0)).multiline(),
+    /// )?;
+    /// doc.add_form_field(&FieldDefinition::checkbox("agree", 1, Rect::new(50.0, 650.0, 12.0, 12.0)))?;
+    /// ```
+    pub fn add_form_field(&mut self, field: &FieldDefinition) -> Result<()> {
+        if field.name.is_empty() || field.name.contains('.') {
+            return Err(PdfError::FormError(format!(
+                "Invalid field name '{}' (must be non-empty without '.')",
+                field.name
+            )));
+        }
+        if field.widgets.is_empty() {
+            return Err(PdfError::FormError(format!(
+                "Field {} has no widgets",
+                field.name
+            )));
+        }
+
+        let existing = form::collect_fields(&self.inner)?
+            .into_iter()
+            .find(|node| node.name == field.name);
+        let field_id = match &existing {
+            // Only fields with separate widget kids can take more widgets
+            Some(node)
+                if node.field_type == field.field_type && !node.widgets.contains(&node.id) =>
+            {
+                node.id
+            }
+            Some(_) => {
+                return Err(PdfError::FormError(format!(
+                    "Field already exists: {}",
+                    field.name
+                )))
+            }
+            None => {
+                let dict = self.new_field_dictionary(field)?;
+                let field_id = self.inner.add_object(dict);
+                self.add_acroform_field(field_id)?;
+                field_id
+            }
+        };
+
+        for widget in &field.widgets {
+            let widget_id = self.add_field_widget(field, field_id, widget)?;
+            let kids = self.inner.get_dictionary_mut(field_id)?.get_mut(b"Kids");
+            if let Ok(Object::Array(kids)) = kids {
+                kids.push(Object::Reference(widget_id));
+            }
+        }
+
+        let value = field
+            .value
+            .clone()
+            .or_else(|| existing.and_then(|node| node.value));
+        match value {
+            Some(value) => self.set_field_value(&field.name, &value),
+            None => Ok(()),
+        }
+    }
+
+    /// Build the field dictionary (without widgets) for `add_form_field`
+    fn new_field_dictionary(&mut self, field: &FieldDefinition) -> Result<Dictionary> {
+        let mut dict = Dictionary::new();
+        dict.set("T", form::encode_text_string(&field.name));
+        dict.set("Kids", Object::Array(Vec::new()));
+
+        let field_type: &[u8] = match field.field_type {
+            FormFieldType::Text => b"Tx",
+            FormFieldType::Checkbox | FormFieldType::Radio => b"Btn",
+            FormFieldType::Signature => b"Sig",
+            other => {
+                return Err(PdfError::FormError(format!(
+                    "Cannot create {other:?} field {}",
+                    field.name
+                )))
+            }
+        };
+        dict.set("FT", Object::Name(field_type.to_vec()));
+
+        let mut flags = field.flags;
+        if field.field_type == FormFieldType::Radio {
+            flags |= form::flags::RADIO;
+        }
+        if flags != 0 {
+            dict.set("Ff", Object::Integer(flags as i64));
+        }
+
+        match field.field_type {
+            FormFieldType::Text => {
+                let font_name = self.get_current_font_name()?;
+                let resource_name = self.form_font_resource(&font_name);
+                let c = self.current_text_color;
+                dict.set(
+                    "DA",
+                    Object::string_literal(format!(
+                        "/{resource_name} {} Tf {} {} {} rg",
+                        self.current_font_size, c.r, c.g, c.b
+                    )),
+                );
+                let quadding = match field.align {
+                    Align::Left => 0,
+                    Align::Center => 1,
+                    Align::Right => 2,
+                };
+                if quadding != 0 {
+                    dict.set("Q", Object::Integer(quadding));
+                }
+                if let Some(max_len) = field.max_len {
+                    dict.set("MaxLen", Object::Integer(max_len as i64));
+                }
+                if let Some(value) = &field.value {
+                    dict.set("DV", form::encode_text_string(value));
+                }
+            }
+            FormFieldType::Checkbox | FormFieldType::Radio => {
+                dict.set("V", Object::Name(b"Off".to_vec()));
+            }
+            _ => {}
+        }
+
+        Ok(dict)
+    }
+
+    /// Create a widget annotation of `field_id` and list it on its page
+    fn add_field_widget(
+        &mut self,
+        field: &FieldDefinition,
+        field_id: ObjectId,
+        widget: &FieldWidget,
+    ) -> Result<ObjectId> {
+        let page = widget.page.unwrap_or(1);
+        let pages = self.inner.get_pages();
+        let page_id = *pages
+            .get(&(page as u32))
+            .ok_or(PdfError::InvalidPage(page, pages.len()))?;
+
+        // Convert the top-left rectangle to PDF coordinates
+        let (_, geometry) = self.page_geometry(page)?;
+        let rect = widget.rect;
+        let mut dict = Dictionary::new();
+        dict.set("Type", Object::Name(b"Annot".to_vec()));
+        dict.set("Subtype", Object::Name(b"Widget".to_vec()));
+        dict.set(
+            "Rect",
+            Object::Array(
+                geometry
+                    .pdf_rect(rect)
+                    .iter()
+                    .map(|&v| Object::Real(v as f32))
+                    .collect(),
+            ),
+        );
+        dict.set("P", Object::Reference(page_id));
+        dict.set("Parent", Object::Reference(field_id));
+        // Print flag, so the field shows on paper too
+        dict.set("F", Object::Integer(4));
+        // Turn the widget with the page so its content shows upright
+        let rotation = self.page_rotation(page_id);
+        if rotation != 0 {
+            dict.set("MK", dictionary! { "R" => rotation });
+        }
+        if matches!(
+            field.field_type,
+            FormFieldType::Checkbox | FormFieldType::Radio
+        ) {
+            dict.set("AS", Object::Name(b"Off".to_vec()));
+        }
+        let widget_id = self.inner.add_object(dict);
+
+        match field.field_type {
+            FormFieldType::Checkbox => {
+                let on_state = widget.on_state.as_deref().unwrap_or("Yes");
+                let on = form::generate_check_operators(rect.width, rect.height);
+                self.set_button_appearance(widget_id, on_state, on)?;
+            }
+            FormFieldType::Radio => {
+                let on_state = widget.on_state.as_deref().ok_or_else(|| {
+                    PdfError::FormError(format!("Radio button of {} has no state", field.name))
+                })?;
+                let on = form::generate_radio_operators(rect.width, rect.height);
+                self.set_button_appearance(widget_id, on_state, on)?;
+            }
+            _ => {}
+        }
+
+        self.add_page_annotation(page_id, widget_id)?;
+        Ok(widget_id)
+    }
+
+    /// Resource name of a field font in the AcroForm /DR dictionary
+    ///
+    /// Names already used by the base PDF's form resources are skipped.
+    fn form_font_resource(&mut self, font_name: &str) -> String {
+        if let Some(name) = self.form_fonts.get(font_name) {
+            return name.clone();
+        }
+
+        let existing: Vec<Vec<u8>> = form::acroform(&self.inner)
+            .and_then(|acroform| acroform.get_deref(b"DR", &self.inner).ok())
+            .and_then(|dr| dr.as_dict().ok())
+            .and_then(|dr| dr.get_deref(b"Font", &self.inner).ok())
+            .and_then(|fonts| fonts.as_dict().ok())
+            .map(|fonts| fonts.iter().map(|(name, _)| name.clone()).collect())
+            .unwrap_or_default();
+
+        let mut n = self.form_fonts.len() + 1;
+        let name = loop {
+            let candidate = format!("FF{n}");
+            let taken = existing.contains(&candidate.as_bytes().to_vec())
+                || self.form_fonts.values().any(|v| *v == candidate);
+            if !taken {
+                break candidate;
+            }
+            n += 1;
+        };
+        self.form_fonts.insert(font_name.to_string(), name.clone());
+        name
+    }
+
+    /// The AcroForm dictionary as an indirect object, created if missing
+    fn acroform_id(&mut self) -> Result<ObjectId> {
+        let acroform = self.inner.catalog()?.get(b"AcroForm").cloned();
+        let dict = match acroform {
+            Ok(Object::Reference(id)) => return Ok(id),
+            Ok(Object::Dictionary(dict)) => dict,
+            _ => {
+                let mut dict = Dictionary::new();
+                dict.set("Fields", Object::Array(Vec::new()));
+                dict
+            }
+        };
+        let id = self.inner.add_object(dict);
+        self.inner
+            .catalog_mut()?
+            .set("AcroForm", Object::Reference(id));
+        Ok(id)
+    }
+
+    /// Append a field to the AcroForm /Fields array
+    fn add_acroform_field(&mut self, field_id: ObjectId) -> Result<()> {
+        let acroform_id = self.acroform_id()?;
+        let fields = self
+            .inner
+            .get_dictionary(acroform_id)?
+            .get(b"Fields")
+            .cloned();
+        match fields {
+            Ok(Object::Reference(id)) => {
+                if let Ok(Object::Array(fields)) = self.inner.get_object_mut(id) {
+                    fields.push(Object::Reference(field_id));
+                }
+            }
+            Ok(Object::Array(mut fields)) => {
+                fields.push(Object::Reference(field_id));
+                self.inner
+                    .get_dictionary_mut(acroform_id)?
+                    .set("Fields", Object::Array(fields));
+            }
+            _ => {
+                self.inner
+                    .get_dictionary_mut(acroform_id)?
+                    .set("Fields", Object::Array(vec![Object::Reference(field_id)]));
+            }
+        }
+        Ok(())
+    }
+
+    /// Append an annotation to a page's /Annots array
+    fn add_page_annotation(&mut self, page_id: ObjectId, annot_id: ObjectId) -> Result<()> {
+        let annots = self.inner.get_dictionary(page_id)?.get(b"Annots").cloned();
+        match annots {
+            Ok(Object::Reference(id)) => {
+                if let Ok(Object::Array(annots)) = self.inner.get_object_mut(id) {
+                    annots.push(Object::Reference(annot_id));
+                }
+            }
+            Ok(Object::Array(mut annots)) => {
+                annots.push(Object::Reference(annot_id));
+                self.inner
+                    .get_dictionary_mut(page_id)?
+                    .set("Annots", Object::Array(annots));
+            }
+            _ => {
+                self.inner
+                    .get_dictionary_mut(page_id)?
+                    .set("Annots", Object::Array(vec![Object::Reference(annot_id)]));
+            }
+        }
+        Ok(())
+    }
+
+    /// Select an option of a combo or list box
+    fn set_choice_value(&mut self, node: &FieldNode, value: &str) -> Result<()> {
+        let found = node
+            .options
+            .iter()
+            .position(|o| o.export == value)
+            .or_else(|| node.options.iter().position(|o| o.display == value));
+        let editable =
+            node.field_type == FormFieldType::ComboBox && node.has_flag(form::flags::EDIT);
+
+        let (export, display, index) = match found {
+            Some(i) => (
+                node.options[i].export.clone(),
+                node.options[i].display.clone(),
+                Some(i),
+            ),
+            None if value.is_empty() || editable || node.options.is_empty() => {
+                (value.to_string(), value.to_string(), None)
+            }
+            None => {
+                return Err(PdfError::FormError(format!(
+                    "'{value}' is not an option of field {}",
+                    node.name
+                )))
+            }
+        };
+
+        let dict = self.inner.get_dictionary_mut(node.id)?;
+        dict.set("V", form::encode_text_string(&export));
+        match index {
+            Some(i) if node.field_type == FormFieldType::ListBox => {
+                dict.set("I", Object::Array(vec![Object::Integer(i as i64)]));
+            }
+            _ => {
+                dict.remove(b"I");
+            }
+        }
+
+        self.buffer_field_text(node, &display)
+    }
+
+    /// Switch a checkbox or radio group to the state matching `value`
+    fn set_button_value(&mut self, node: &FieldNode, value: &str) -> Result<()> {
+        let widget_states: Vec<(ObjectId, Vec<String>)> = node
+            .widgets
+            .iter()
+            .map(|&w| (w, form::widget_on_states(&self.inner, w)))
+            .collect();
+        let has_state = |state: &str| {
+            widget_states
+                .iter()
+                .any(|(_, s)| s.iter().any(|n| n == state))
+        };
+
+        let state = if node.field_type == FormFieldType::Checkbox {
+            if has_state(value) {
+                value.to_string()
+            } else if form::is_off_value(value) {
+                "Off".to_string()
+            } else {
+                widget_states
+                    .iter()
+                    .find_map(|(_, s)| s.first().cloned())
+                    .unwrap_or_else(|| "Yes".to_string())
+            }
+        } else if value.is_empty() || value == "Off" {
+            "Off".to_string()
+        } else if has_state(value) {
+            value.to_string()
+        } else {
+            return Err(PdfError::FormError(format!(
+                "'{value}' is not an option of radio group {}",
+                node.name
+            )));
+        };
+
+        self.inner
+            .get_dictionary_mut(node.id)?
+            .set("V", Object::Name(state.clone().into_bytes()));
+
+        for (widget_id, states) in widget_states {
+            // Checkboxes without an appearance get a vector tick
+            if states.is_empty() && node.field_type == FormFieldType::Checkbox {
+                self.add_check_appearance(widget_id, &state)?;
+            }
+            let shown = if states.contains(&state) || states.is_empty() {
+                state.as_str()
+            } else {
+                "Off"
+            };
+            self.inner
+                .get_dictionary_mut(widget_id)?
+                .set("AS", Object::Name(shown.as_bytes().to_vec()));
+        }
+
+        Ok(())
+    }
+
+    /// Give a checkbox widget "on" and "Off" appearances
+    fn add_check_appearance(&mut self, widget_id: ObjectId, on_state: &str) -> Result<()> {
+        if on_state == "Off" {
+            return Ok(());
+        }
+        let Some((width, height, _)) = form::widget_appearance_box(&self.inner, widget_id) else {
+            return Ok(());
+        };
+        let on = form::generate_check_operators(width, height);
+        self.set_button_appearance(widget_id, on_state, on)
+    }
+
+    /// Set a button widget's `on_state` appearance to `on` and "Off" to empty
+    fn set_button_appearance(
+        &mut self,
+        widget_id: ObjectId,
+        on_state: &str,
+        on: Vec<u8>,
+    ) -> Result<()> {
+        let Some((width, height, matrix)) = form::widget_appearance_box(&self.inner, widget_id)
+        else {
+            return Ok(());
+        };
+
+        let mut normal = Dictionary::new();
+        for (state, content) in [(on_state, on), ("Off", Vec::new())] {
+            let stream_id = self.inner.add_object(Stream::new(
+                appearance_xobject_dict(width, height, matrix),
+                content,
+            ));
+            normal.set(state.as_bytes(), Object::Reference(stream_id));
+        }
+
+        let mut appearance = Dictionary::new();
+        appearance.set("N", Object::Dictionary(normal));
+        self.inner
+            .get_dictionary_mut(widget_id)?
+            .set("AP", Object::Dictionary(appearance));
+        Ok(())
+    }
+
+    /// Lay out field text in each widget and buffer the appearances
+    fn buffer_field_text(&mut self, node: &FieldNode, text: &str) -> Result<()> {
+        for &widget_id in &node.widgets {
+            let Some((width, height, matrix)) = form::widget_appearance_box(&self.inner, widget_id)
+            else {
+                continue;
+            };
+            let runs = if text.is_empty() {
+                Vec::new()
+            } else {
+                self.layout_field_text(node, text, width, height)?
+            };
+
+            self.buffered_field_appearances
+                .retain(|a| a.widget_id != widget_id);
+            self.buffered_field_appearances
+                .push(BufferedFieldAppearance {
+                    widget_id,
+                    width,
+                    height,
+                    matrix,
+                    runs,
+                    font_size: self.current_font_size,
+                    color: self.current_text_color,
+                });
+        }
+
+        self.set_need_appearances(false)
+    }
+
+    /// Shape field text inside a `width` x `height` box
+    ///
+    /// Single-line text is centered vertically, multiline text flows from
+    /// the top, and comb fields put one character cluster in each cell.
+    fn layout_field_text(
+        &mut self,
+        node: &FieldNode,
+        text: &str,
+        width: f64,
+        height: f64,
+    ) -> Result<Vec<AppearanceRun>> {
+        const PADDING: f64 = 2.0;
+        let (ascent, descent) = self.get_font_extents()?;
+        let single_line_y = (height - ascent - descent) / 2.0 + descent;
+        let mut runs = Vec::new();
+
+        if node.has_flag(form::flags::COMB) && !node.has_flag(form::flags::MULTILINE) {
+            if let Some(cells) = node.max_len.filter(|&n| n > 0) {
+                let cell_width = width / cells as f64;
+                for (i, cluster) in form::char_clusters(text).iter().take(cells).enumerate() {
+                    let cluster_width = self.get_text_width(cluster)?;
+                    let x = cell_width * i as f64 + (cell_width - cluster_width) / 2.0;
+                    runs.extend(self.shape_field_runs(cluster, x, single_line_y)?);
+                }
+                return Ok(runs);
+            }
+        }
+
+        let lines: Vec<String> = if node.has_flag(form::flags::MULTILINE) {
+            let mut lines = Vec::new();
+            for paragraph in text.lines() {
+                lines.extend(simple_word_wrap_width(
+                    paragraph,
+                    width - 2.0 * PADDING,
+                    |line| self.get_text_width(line).unwrap_or(0.0),
+                ));
+            }
+            lines
+        } else {
+            vec![text.replace(['\r', '\n'], " ")]
+        };
+
+        let mut y = if node.has_flag(form::flags::MULTILINE) {
+            height - PADDING - ascent
+        } else {
+            single_line_y
+        };
+        for line in lines {
+            let line_width = self.get_text_width(&line)?;
+            let x = match node.quadding {
+                1 => (width - line_width) / 2.0,
+                2 => width - PADDING - line_width,
+                _ => PADDING,
+            };
+            runs.extend(self.shape_field_runs(&line, x, y)?);
+            y -= ascent + descent;
+        }
+
+        Ok(runs)
+    }
+
+    /// Shape text with the current font (and fallbacks) starting at `x`, `y`
+    fn shape_field_runs(&mut self, text: &str, x: f64, y: f64) -> Result<Vec<AppearanceRun>> {
+        let font_size = self.current_font_size;
+        let mut runs = Vec::new();
+        let mut current_x = x;
+
+        for segment in self.segment_current_text(text)? {
+            let font_data = self.get_font_data_mut(&segment.font_name)?;
+            let glyphs = font_data.add_text(&segment.text);
+            let segment_width = font_data.text_width_points(&segment.text, font_size) as f64;
+
+            runs.push(AppearanceRun {
+                glyphs,
+                font_name: segment.font_name,
+                x: current_x,
+                y,
+            });
+            current_x += segment_width;
+        }
 
+        Ok(runs)
+    }
+
+    /// Set /NeedAppearances on the AcroForm dictionary
+    ///
+    /// Generated appearances must not be replaced by the viewer, whose
+    /// default form font usually has no Thai glyphs.
+    fn set_need_appearances(&mut self, need: bool) -> Result<()> {
+        let acroform = self.inner.catalog()?.get(b"AcroForm").cloned();
+        match acroform {
+            Ok(Object::Reference(id)) => {
+                self.inner
+                    .get_dictionary_mut(id)?
+                    .set("NeedAppearances", Object::Boolean(need));
+            }
+            Ok(Object::Dictionary(_)) => {
+                if let Ok(Object::Dictionary(dict)) = self.inner.catalog_mut()?.get_mut(b"AcroForm")
+                {
+                    dict.set("NeedAppearances", Object::Boolean(need));
+                }
+            }
+            _ => {}
+        }
         Ok(())
     }
 
+    /// Flatten form fields (and optionally other annotations) into page content
+    ///
+    /// At save time, each widget's current appearance is drawn onto its page
+    /// as a Form XObject, the widgets are removed from /Annots and /AcroForm is
+    /// removed from the catalog, so the filled values can no longer be edited.
+    /// Appearances generated by `set_field_value` are included. Hidden
+    /// annotations are removed without being drawn.
+    ///
+    /// # Arguments
+    /// * `mode` - `FlattenMode::Forms` for widgets only, `FlattenMode::All` to
+    ///   also flatten comments, stamps and other annotations with an appearance
+    ///
+    /// # Example
+    /// ```ignore
+    /// doc.set_field_value("applicant.name", "นายสมชาย ใจดี")?;
+    /// doc.flatten(FlattenMode::Forms);
+    /// doc.save("filled.pdf")?;
+    /// ```
+    pub fn flatten(&mut self, mode: FlattenMode) -> &mut Self {
+        self.flatten_mode = Some(mode);
+        self
+    }
+
     /// Save the document to a file
     ///
     /// # Arguments
@@ -758,6 +2405,15 @@ impl PdfDocument {
         // 4. Embed subsetted fonts into PDF
         self.embed_fonts()?;
 
+        // 5. Write form field appearances and fonts referencing the embedded fonts
+        self.write_field_appearances()?;
+        self.write_form_fonts()?;
+
+        // 6. Flatten annotations now that every appearance exists
+        if let Some(mode) = self.flatten_mode {
+            self.flatten_annotations(mode)?;
+        }
+
         self.inner
             .save(path)
             .map_err(|e| PdfError::SaveError(e.to_string()))?;
@@ -778,6 +2434,15 @@ impl PdfDocument {
         // 4. Embed subsetted fonts into PDF
         self.embed_fonts()?;
 
+        // 5. Write form field appearances and fonts referencing the embedded fonts
+        self.write_field_appearances()?;
+        self.write_form_fonts()?;
+
+        // 6. Flatten annotations now that every appearance exists
+        if let Some(mode) = self.flatten_mode {
+            self.flatten_annotations(mode)?;
+        }
+
         let mut buffer = Vec::new();
         self.inner
             .save_to(&mut buffer)
@@ -843,16 +2508,13 @@ impl PdfDocument {
         let text_ops: Vec<BufferedTextOp> = std::mem::take(&mut self.buffered_text_ops);
 
         for op in text_ops {
-            // Get font data and encode text with remapped GIDs
-            let text_hex = {
-                let font_data = self.get_font_data(&op.font_name)?;
-                font_data.encode_text_hex_remapped(&op.text)
-            };
-
-            // Calculate text width for alignment (already calculated as Left in insert_text)
-            let text_width = {
+            // Get font data and position glyphs with remapped GIDs
+            let (glyphs, text_width) = {
                 let font_data = self.get_font_data(&op.font_name)?;
-                font_data.text_width_points(&op.text, op.font_size) as f64
+                let glyphs = font_data.position_glyphs(&op.glyphs);
+                // Width for alignment (already calculated as Left in insert_text)
+                let units: f64 = glyphs.iter().map(|g| g.x_advance).sum();
+                (glyphs, units / 1000.0 * op.font_size as f64)
             };
 
             // Create text rendering context
@@ -864,10 +2526,30 @@ impl PdfDocument {
             };
 
             // Generate PDF text operators (position already calculated, use Left)
-            let operators = generate_text_operators(&text_hex, op.x, op.y, Align::Left, &ctx);
+            let mut operators =
+                generate_shaped_text_operators(&glyphs, op.x, op.y, Align::Left, &ctx);
+
+            // Restrict painting to the clip rectangle
+            if let Some(clip) = op.clip {
+                let mut clipped = format!(
+                    "q\n{} {} {} {} re W n\n",
+                    clip.x, clip.y, clip.width, clip.height
+                )
+                .into_bytes();
+                clipped.append(&mut operators);
+                clipped.extend_from_slice(b"Q\n");
+                operators = clipped;
+            }
+
+            if let Some(matrix) = op.transform {
+                operators = generate_transformed_operators(matrix, &operators);
+            }
+            if let Some(name) = &op.ext_gstate {
+                operators = generate_ext_gstate_operators(name, &operators);
+            }
 
             // Add to page content buffer
-            self.buffer_content(op.page, &operators);
+            self.buffer_layer_content(op.page, op.view_matrix, &operators, op.underlay);
         }
 
         Ok(())
@@ -926,7 +2608,17 @@ impl PdfDocument {
 
         // Generate all PDF objects for the font
         let font_objects = font_data.to_pdf_objects()?;
+        let type0_font_id = self.add_font_objects(font_objects);
+
+        // Store the reference
+        self.embedded_fonts
+            .insert(font_name.to_string(), type0_font_id);
+
+        Ok(type0_font_id)
+    }
 
+    /// Add the objects of an embedded font, returning the Type0 font's ID
+    fn add_font_objects(&mut self, font_objects: FontObjects) -> ObjectId {
         // Add font file stream
         let font_file_id = self.inner.add_object(font_objects.font_file_stream);
 
@@ -951,13 +2643,232 @@ impl PdfDocument {
         let tounicode_id = self.inner.add_object(font_objects.tounicode_stream);
         type0_font.set("ToUnicode", Object::Reference(tounicode_id));
 
-        let type0_font_id = self.inner.add_object(type0_font);
+        self.inner.add_object(type0_font)
+    }
 
-        // Store the reference
-        self.embedded_fonts
-            .insert(font_name.to_string(), type0_font_id);
+    /// Write buffered form field appearances as Form XObjects
+    ///
+    /// This should be called after embed_fonts() so the appearance
+    /// resources can reference the embedded (subsetted) fonts.
+    fn write_field_appearances(&mut self) -> Result<()> {
+        let appearances = std::mem::take(&mut self.buffered_field_appearances);
+
+        for appearance in appearances {
+            let mut content = format!(
+                "/Tx BMC\nq\n1 1 {} {} re W n\n",
+                appearance.width - 2.0,
+                appearance.height - 2.0
+            )
+            .into_bytes();
+            let mut fonts = Dictionary::new();
+            let mut resource_names: HashMap<&str, String> = HashMap::new();
+
+            for run in &appearance.runs {
+                let next_name = format!("F{}", resource_names.len() + 1);
+                let resource_name = resource_names
+                    .entry(run.font_name.as_str())
+                    .or_insert(next_name)
+                    .clone();
+                let font_ref = *self
+                    .embedded_fonts
+                    .get(&run.font_name)
+                    .ok_or_else(|| PdfError::FontNotFound(run.font_name.clone()))?;
+                fonts.set(resource_name.as_bytes(), Object::Reference(font_ref));
+
+                let glyphs = self
+                    .get_font_data(&run.font_name)?
+                    .position_glyphs(&run.glyphs);
+                let ctx = TextRenderContext {
+                    font_name: resource_name,
+                    font_size: appearance.font_size,
+                    text_width: 0.0,
+                    color: appearance.color,
+                };
+                content.extend(generate_shaped_text_operators(
+                    &glyphs,
+                    run.x,
+                    run.y,
+                    Align::Left,
+                    &ctx,
+                ));
+            }
+            content.extend_from_slice(b"Q\nEMC\n");
 
-        Ok(type0_font_id)
+            let mut stream_dict =
+                appearance_xobject_dict(appearance.width, appearance.height, appearance.matrix);
+            let mut resources = Dictionary::new();
+            resources.set("Font", Object::Dictionary(fonts));
+            stream_dict.set("Resources", Object::Dictionary(resources));
+            let stream_id = self.inner.add_object(Stream::new(stream_dict, content));
+
+            let mut ap = Dictionary::new();
+            ap.set("N", Object::Reference(stream_id));
+            self.inner
+                .get_dictionary_mut(appearance.widget_id)?
+                .set("AP", Object::Dictionary(ap));
+        }
+
+        Ok(())
+    }
+
+    /// Add the fonts of created fields to the AcroForm /DR dictionary
+    ///
+    /// Viewers use these fonts (named in each field's /DA) when the
+    /// recipient edits a field, so the whole font program is embedded:
+    /// the content subset has no `cmap` to map typed characters with.
+    fn write_form_fonts(&mut self) -> Result<()> {
+        if self.form_fonts.is_empty() {
+            return Ok(());
+        }
+        let acroform_id = self.acroform_id()?;
+
+        // Resolve /DR and its /Font dictionary, which may be indirect
+        let dr = self.inner.get_dictionary(acroform_id)?.get(b"DR").cloned();
+        let (mut dr_dict, dr_id) = match dr {
+            Ok(Object::Dictionary(dict)) => (dict, None),
+            Ok(Object::Reference(id)) => (self.inner.get_dictionary(id)?.clone(), Some(id)),
+            _ => (Dictionary::new(), None),
+        };
+        let (mut fonts, fonts_id) = match dr_dict.get(b"Font") {
+            Ok(Object::Dictionary(dict)) => (dict.clone(), None),
+            Ok(Object::Reference(id)) => (self.inner.get_dictionary(*id)?.clone(), Some(*id)),
+            _ => (Dictionary::new(), None),
+        };
+
+        let form_fonts: Vec<(String, String)> = self
+            .form_fonts
+            .iter()
+            .map(|(font, resource)| (font.clone(), resource.clone()))
+            .collect();
+        for (font_name, resource_name) in form_fonts {
+            let font_objects = self.get_font_data(&font_name)?.to_full_pdf_objects()?;
+            let font_id = self.add_font_objects(font_objects);
+            fonts.set(resource_name.as_bytes(), Object::Reference(font_id));
+        }
+
+        match fonts_id {
+            Some(id) => {
+                self.inner.objects.insert(id, Object::Dictionary(fonts));
+            }
+            None => dr_dict.set("Font", Object::Dictionary(fonts)),
+        }
+        match dr_id {
+            Some(id) => {
+                self.inner.objects.insert(id, Object::Dictionary(dr_dict));
+            }
+            None => self
+                .inner
+                .get_dictionary_mut(acroform_id)?
+                .set("DR", Object::Dictionary(dr_dict)),
+        }
+        Ok(())
+    }
+
+    /// Draw annotation appearances into page content and remove the annotations
+    ///
+    /// This should be called after write_field_appearances() so generated
+    /// field appearances are flattened too.
+    fn flatten_annotations(&mut self, mode: FlattenMode) -> Result<()> {
+        let pages = self.inner.get_pages();
+
+        for (&page_number, &page_id) in &pages {
+            let annots_obj = match self.inner.get_dictionary(page_id)?.get(b"Annots") {
+                Ok(obj) => obj.clone(),
+                Err(_) => continue,
+            };
+            let (annots, annots_ref) = match &annots_obj {
+                Object::Array(arr) => (arr.clone(), None),
+                Object::Reference(id) => match self.inner.get_object(*id) {
+                    Ok(Object::Array(arr)) => (arr.clone(), Some(*id)),
+                    _ => continue,
+                },
+                _ => continue,
+            };
+
+            let mut kept = Vec::new();
+            let mut content = String::new();
+            let mut xobjects = Vec::new();
+
+            for annot in annots {
+                let dict = match &annot {
+                    Object::Reference(id) => match self.inner.get_dictionary(*id) {
+                        Ok(dict) => dict.clone(),
+                        Err(_) => continue,
+                    },
+                    Object::Dictionary(dict) => dict.clone(),
+                    _ => continue,
+                };
+                let subtype = dict.get(b"Subtype").and_then(Object::as_name).ok();
+                let is_widget = subtype == Some(b"Widget".as_slice());
+
+                match mode {
+                    FlattenMode::Forms if !is_widget => {
+                        kept.push(annot);
+                        continue;
+                    }
+                    FlattenMode::All if subtype == Some(b"Popup".as_slice()) => continue,
+                    _ => {}
+                }
+
+                let flags = dict.get(b"F").ok().and_then(form::number).unwrap_or(0.0) as u32;
+                if flags & (form::annot_flags::HIDDEN | form::annot_flags::NO_VIEW) != 0 {
+                    continue;
+                }
+
+                let appearance = form::normal_appearance(&self.inner, &dict);
+                let rect = form::annotation_rect(&self.inner, &dict);
+                let (Some(stream_id), Some(rect)) = (appearance, rect) else {
+                    // Nothing to draw: an empty widget just disappears, other
+                    // annotations stay so their content is not lost
+                    if !is_widget {
+                        kept.push(annot);
+                    }
+                    continue;
+                };
+
+                let Ok(Object::Stream(stream)) = self.inner.get_object_mut(stream_id) else {
+                    continue;
+                };
+                stream.dict.set("Type", Object::Name(b"XObject".to_vec()));
+                stream.dict.set("Subtype", Object::Name(b"Form".to_vec()));
+                let bbox = stream
+                    .dict
+                    .get(b"BBox")
+                    .ok()
+                    .and_then(form::number_array)
+                    .unwrap_or([0.0, 0.0, rect[2] - rect[0], rect[3] - rect[1]]);
+                let matrix = stream
+                    .dict
+                    .get(b"Matrix")
+                    .ok()
+                    .and_then(form::number_array)
+                    .unwrap_or([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+                let [sx, sy, tx, ty] = form::appearance_transform(bbox, matrix, rect);
+                let name = format!("Flat{}", stream_id.0);
+                content.push_str(&format!("q\n{sx} 0 0 {sy} {tx} {ty} cm\n/{name} Do\nQ\n"));
+                xobjects.push((name, stream_id));
+            }
+
+            if !xobjects.is_empty() {
+                self.add_xobjects_to_page_resources(page_number as usize, &xobjects)?;
+                self.append_to_content_stream(page_number as usize, content.as_bytes())?;
+            }
+
+            // Write back the remaining annotations
+            if let Some(id) = annots_ref {
+                self.inner.objects.insert(id, Object::Array(kept.clone()));
+            }
+            let page_dict = self.inner.get_dictionary_mut(page_id)?;
+            if kept.is_empty() {
+                page_dict.remove(b"Annots");
+            } else if annots_ref.is_none() {
+                page_dict.set("Annots", Object::Array(kept));
+            }
+        }
+
+        self.inner.catalog_mut()?.remove(b"AcroForm");
+        Ok(())
     }
 
     /// Get or create a font reference for a specific page
@@ -968,18 +2879,29 @@ impl PdfDocument {
         // Font will be embedded at save time when all characters are known
 
         // Check if font is already registered for this page
-        let page_resources = self.page_font_resources.entry(page).or_default();
-
-        if let Some(resource_name) = page_resources.get(font_name) {
+        if let Some(resource_name) = self
+            .page_font_resources
+            .get(&page)
+            .and_then(|fonts| fonts.get(font_name))
+        {
             return Ok(resource_name.clone());
         }
 
-        // Create new resource name
-        let resource_name = format!("F{}", self.next_font_resource);
-        self.next_font_resource += 1;
+        // Create new resource name, skipping names the base PDF already uses
+        let existing = self.page_resource_names(page, b"Font")?;
+        let resource_name = loop {
+            let name = format!("F{}", self.next_font_resource);
+            self.next_font_resource += 1;
+            if !existing.iter().any(|used| used == name.as_bytes()) {
+                break name;
+            }
+        };
 
         // Store the mapping (font will be added to page resources at save time)
-        page_resources.insert(font_name.to_string(), resource_name.clone());
+        self.page_font_resources
+            .entry(page)
+            .or_default()
+            .insert(font_name.to_string(), resource_name.clone());
 
         Ok(resource_name)
     }
@@ -1021,6 +2943,8 @@ impl PdfDocument {
         let page_id = *pages
             .get(&(page as u32))
             .ok_or(PdfError::InvalidPage(page, pages.len()))?;
+        // Add to inherited resources rather than hiding them
+        self.copy_inherited_attributes(page_id)?;
 
         // Get the page object
         let page_obj = self.inner.get_object(page_id)?;
@@ -1106,20 +3030,77 @@ impl PdfDocument {
         &mut self.inner
     }
 
-    /// Get page height in points
-    ///
-    /// Extracts the page height from the MediaBox or CropBox.
-    /// Handles inherited MediaBox from parent Pages node.
+    /// Get the height of the area content is placed in (see `content_geometry`)
     fn get_page_height(&self, page: usize) -> Result<f64> {
-        let pages = self.inner.get_pages();
-        let page_id = *pages
-            .get(&(page as u32))
-            .ok_or(PdfError::InvalidPage(page, pages.len()))?;
+        Ok(self.content_geometry(page)?.height)
+    }
+
+    /// Geometry for placing content: the visible page inside its margins
+    ///
+    /// Page boxes, field rectangles and watermarks use the whole visible
+    /// page (`page_geometry`); only text, images, shapes and imported
+    /// pages are measured from the corner of the margins.
+    fn content_geometry(&self, page: usize) -> Result<PageGeometry> {
+        let (_, geometry) = self.page_geometry(page)?;
+        Ok(match self.page_margins.get(&self.page_id(page)?) {
+            Some(margins) => geometry.inset(margins),
+            None => geometry,
+        })
+    }
+
+    /// Visible box of a page in PDF user space and its view geometry
+    ///
+    /// The visible box is the CropBox clipped to the MediaBox (both may be
+    /// inherited), turned by the page's /Rotate.
+    fn page_geometry(&self, page: usize) -> Result<([f64; 4], PageGeometry)> {
+        let page_id = self.page_id(page)?;
+        let normalize =
+            |[x0, y0, x1, y1]: [f64; 4]| [x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1)];
+
+        let media_box = box_numbers(&Object::Array(self.get_inherited_media_box(page_id)?))
+            .map(normalize)
+            .ok_or_else(|| PdfError::ParseError("Invalid MediaBox format".to_string()))?;
+        let visible = self
+            .inherited_attribute(page_id, b"CropBox")
+            .as_ref()
+            .and_then(box_numbers)
+            .map(normalize)
+            .map(|crop| {
+                [
+                    crop[0].max(media_box[0]),
+                    crop[1].max(media_box[1]),
+                    crop[2].min(media_box[2]),
+                    crop[3].min(media_box[3]),
+                ]
+            })
+            .filter(|clipped| clipped[0] < clipped[2] && clipped[1] < clipped[3])
+            .unwrap_or(media_box);
+        let rotate = self.page_rotation(page_id);
 
-        // Try to get MediaBox, following parent chain if needed
-        let media_box = self.get_inherited_media_box(page_id)?;
+        Ok((visible, PageGeometry::new(visible, rotate)))
+    }
+
+    /// The page's /Rotate value (0, 90, 180 or 270)
+    fn page_rotation(&self, page_id: ObjectId) -> i64 {
+        let rotate = self
+            .inherited_attribute(page_id, b"Rotate")
+            .and_then(|rotate| form::number(&rotate))
+            .unwrap_or(0.0) as i64;
+        rotate.rem_euclid(360)
+    }
 
-        self.extract_height_from_media_box(&media_box)
+    /// Look up a page attribute, following the page tree for inherited ones
+    fn inherited_attribute(&self, page_id: ObjectId, key: &[u8]) -> Option<Object> {
+        let mut current_id = page_id;
+        // Bounded in case of a Parent cycle
+        for _ in 0..32 {
+            let dict = self.inner.get_dictionary(current_id).ok()?;
+            if let Ok(value) = dict.get_deref(key, &self.inner) {
+                return Some(value.clone());
+            }
+            current_id = dict.get(b"Parent").and_then(Object::as_reference).ok()?;
+        }
+        None
     }
 
     /// Get MediaBox, following parent inheritance chain if needed
@@ -1173,36 +3154,129 @@ impl PdfDocument {
         ])
     }
 
-    /// Extract height from MediaBox array
-    fn extract_height_from_media_box(&self, media_box_array: &[Object]) -> Result<f64> {
-        if media_box_array.len() >= 4 {
-            let y1 = media_box_array[1]
-                .as_f32()
-                .map(|v| v as f64)
-                .ok()
-                .or_else(|| media_box_array[1].as_i64().ok().map(|v| v as f64))
-                .ok_or_else(|| PdfError::ParseError("Invalid MediaBox y1".to_string()))?;
-            let y2 = media_box_array[3]
-                .as_f32()
-                .map(|v| v as f64)
-                .ok()
-                .or_else(|| media_box_array[3].as_i64().ok().map(|v| v as f64))
-                .ok_or_else(|| PdfError::ParseError("Invalid MediaBox y2".to_string()))?;
-            return Ok(y2 - y1);
+    /// Buffer content operators for a page (written at save time)
+    ///
+    /// Instead of immediately appending to content stream (which creates orphan objects),
+    /// this buffers the operators and flushes them all at once during save.
+    /// The operators keep the page view they were placed in, so rotating or
+    /// cropping the page afterwards does not move them on the page.
+    fn buffer_content(&mut self, page: usize, content: &[u8]) -> Result<()> {
+        let view_matrix = self.content_geometry(page)?.matrix;
+        self.buffer_layer_content(page, view_matrix, content, self.underlay);
+        Ok(())
+    }
+
+    /// Buffer content operators over or under the existing page content
+    ///
+    /// Operators placed in the same page view as the previous ones share
+    /// their chunk.
+    fn buffer_layer_content(
+        &mut self,
+        page: usize,
+        view_matrix: Option<[f64; 6]>,
+        content: &[u8],
+        underlay: bool,
+    ) {
+        if content.is_empty() {
+            return;
+        }
+        let buffer = if underlay {
+            &mut self.page_underlay_buffer
+        } else {
+            &mut self.page_content_buffer
+        };
+        let chunks = buffer.entry(page).or_default();
+        match chunks.last_mut() {
+            Some((matrix, operators)) if *matrix == view_matrix => {
+                operators.extend_from_slice(content)
+            }
+            _ => chunks.push((view_matrix, content.to_vec())),
         }
+    }
 
-        Err(PdfError::ParseError("Invalid MediaBox format".to_string()))
+    /// Buffer content operators under the current transform and transparency
+    ///
+    /// `origin` (top-left coordinates) is the anchor used when the
+    /// transform has none of its own.
+    fn buffer_graphics(
+        &mut self,
+        page: usize,
+        page_height: f64,
+        content: &[u8],
+        origin: (f64, f64),
+    ) -> Result<()> {
+        let mut operators = content.to_vec();
+        if let Some(matrix) = self.current_matrix(page_height, origin.0, origin.1) {
+            operators = generate_transformed_operators(matrix, &operators);
+        }
+        if let Some(name) = self.current_ext_gstate_ref(page)? {
+            operators = generate_ext_gstate_operators(&name, &operators);
+        }
+        self.buffer_content(page, &operators)
     }
 
-    /// Buffer content operators for a page (written at save time)
+    /// ExtGState resource name of the current transparency on a page
     ///
-    /// Instead of immediately appending to content stream (which creates orphan objects),
-    /// this buffers the operators and flushes them all at once during save.
-    fn buffer_content(&mut self, page: usize, content: &[u8]) {
-        self.page_content_buffer
+    /// Returns None when content is painted opaque. Equal transparencies
+    /// share one ExtGState object across pages.
+    fn current_ext_gstate_ref(&mut self, page: usize) -> Result<Option<String>> {
+        let Some(transparency) = self.current_transparency.filter(|t| !t.is_opaque()) else {
+            return Ok(None);
+        };
+
+        let key = transparency.key();
+        let object_id = match self.ext_gstates.get(&key) {
+            Some(id) => *id,
+            None => {
+                let (fill_alpha, stroke_alpha, blend_mode) = key;
+                let mut dict = Dictionary::new();
+                dict.set("Type", Object::Name(b"ExtGState".to_vec()));
+                dict.set("ca", Object::Real(fill_alpha as f32 / 1000.0));
+                dict.set("CA", Object::Real(stroke_alpha as f32 / 1000.0));
+                dict.set(
+                    "BM",
+                    Object::Name(blend_mode.pdf_name().as_bytes().to_vec()),
+                );
+                let id = self.inner.add_object(dict);
+                self.ext_gstates.insert(key, id);
+                id
+            }
+        };
+
+        // Reuse the page's resource name for this object
+        if let Some(resources) = self.page_ext_gstate_resources.get(&page) {
+            if let Some((name, _)) = resources.iter().find(|(_, id)| **id == object_id) {
+                return Ok(Some(name.clone()));
+            }
+        }
+
+        // Skip names the base PDF already uses on this page
+        let existing = self.page_resource_names(page, b"ExtGState")?;
+        let resource_name = loop {
+            let name = format!("GS{}", self.next_ext_gstate_resource);
+            self.next_ext_gstate_resource += 1;
+            if !existing.iter().any(|used| used == name.as_bytes()) {
+                break name;
+            }
+        };
+
+        self.page_ext_gstate_resources
             .entry(page)
             .or_default()
-            .extend_from_slice(content);
+            .insert(resource_name.clone(), object_id);
+        self.add_to_page_resources(page, b"ExtGState", &[(resource_name.clone(), object_id)])?;
+
+        Ok(Some(resource_name))
+    }
+
+    /// PDF matrix of the current transform, or None when nothing is transformed
+    ///
+    /// `x`, `y` (top-left coordinates) is the anchor used when the transform
+    /// has none of its own.
+    fn current_matrix(&self, page_height: f64, x: f64, y: f64) -> Option<[f64; 6]> {
+        let transform = self.current_transform.filter(|t| !t.is_identity())?;
+        let (x, y) = transform.anchor.unwrap_or((x, y));
+        Some(transform.matrix(x, page_height - y))
     }
 
     /// Flush all buffered content to page streams
@@ -1211,93 +3285,127 @@ impl PdfDocument {
     /// appends all buffered operators, and writes a single new stream object per page.
     fn flush_content_buffers(&mut self) -> Result<()> {
         // Take ownership of buffer to avoid borrow issues
-        let buffers: Vec<(usize, Vec<u8>)> = self.page_content_buffer.drain().collect();
-
-        for (page, content) in buffers {
+        let buffers: Vec<(usize, Vec<ContentChunk>)> = self.page_content_buffer.drain().collect();
+
+        // Content is drawn in view coordinates, mapped onto rotated or
+        // offset page boxes by the matrix of the view it was placed in
+        for (page, chunks) in buffers {
+            let mut content = Vec::new();
+            for (view_matrix, operators) in chunks {
+                match view_matrix {
+                    Some(matrix) => {
+                        content.extend(generate_transformed_operators(matrix, &operators))
+                    }
+                    None => content.extend(operators),
+                }
+            }
             if !content.is_empty() {
                 self.append_to_content_stream(page, &content)?;
             }
         }
 
+        // Underlays go first, in their own graphics state so the base
+        // content starts from the default state
+        let underlays: Vec<(usize, Vec<ContentChunk>)> =
+            self.page_underlay_buffer.drain().collect();
+        for (page, chunks) in underlays {
+            let mut wrapped = Vec::new();
+            for (view_matrix, operators) in chunks {
+                match view_matrix {
+                    Some(matrix) => {
+                        wrapped.extend(generate_transformed_operators(matrix, &operators))
+                    }
+                    None => {
+                        wrapped.extend_from_slice(b"q\n");
+                        wrapped.extend(operators);
+                        wrapped.extend_from_slice(b"Q\n");
+                    }
+                }
+            }
+            if !wrapped.is_empty() {
+                self.add_content_stream(page, &wrapped, true)?;
+            }
+        }
+
         Ok(())
     }
 
     /// Append content to a page's content stream
-    ///
-    /// Handles both compressed and uncompressed content streams.
     fn append_to_content_stream(&mut self, page: usize, content: &[u8]) -> Result<()> {
+        self.add_content_stream(page, content, false)
+    }
+
+    /// Add a content stream before or after a page's existing streams
+    ///
+    /// Before the first stream is appended, the existing streams are wrapped
+    /// in q/Q so a CTM, clipping path or color they leave behind does not
+    /// apply to the new content. Handles both compressed and uncompressed
+    /// content streams.
+    fn add_content_stream(&mut self, page: usize, content: &[u8], prepend: bool) -> Result<()> {
         let pages = self.inner.get_pages();
         let page_id = *pages
             .get(&(page as u32))
             .ok_or(PdfError::InvalidPage(page, pages.len()))?;
 
-        // Create a new stream for our content
-        let new_stream = Stream::new(Dictionary::new(), content.to_vec());
-        let new_stream_id = self.inner.add_object(new_stream);
-
-        // Get page dict and update Contents to include the new stream
-        let page_obj = self.inner.get_object(page_id)?;
-        let page_dict = page_obj
-            .as_dict()
-            .map_err(|_| PdfError::ParseError("Page object is not a dictionary".to_string()))?;
-
-        let mut new_page_dict = page_dict.clone();
+        // Existing streams as references; a direct stream (not allowed by
+        // the spec, but seen in the wild) becomes an object of its own
+        let contents = self
+            .inner
+            .get_dictionary(page_id)?
+            .get(b"Contents")
+            .cloned();
+        let mut streams = match contents {
+            Ok(Object::Reference(id)) => match self.inner.get_object(id) {
+                Ok(Object::Array(items)) => items.clone(),
+                Ok(Object::Stream(_)) => vec![Object::Reference(id)],
+                _ => Vec::new(),
+            },
+            Ok(Object::Array(items)) => items,
+            Ok(Object::Stream(stream)) => vec![Object::Reference(self.inner.add_object(stream))],
+            _ => Vec::new(),
+        };
 
-        // Build a new Contents array that preserves original streams
-        let new_contents = match page_dict.get(b"Contents") {
-            Ok(contents) => {
-                match contents {
-                    Object::Reference(ref_id) => {
-                        // Check what the reference points to
-                        match self.inner.get_object(*ref_id) {
-                            Ok(Object::Array(arr)) => {
-                                // Reference to array - clone array and add new stream ref
-                                let mut new_arr = arr.clone();
-                                new_arr.push(Object::Reference(new_stream_id));
-                                Object::Array(new_arr)
-                            }
-                            Ok(Object::Stream(_)) => {
-                                // Reference to single stream - create array with both
-                                Object::Array(vec![
-                                    Object::Reference(*ref_id),
-                                    Object::Reference(new_stream_id),
-                                ])
-                            }
-                            _ => Object::Reference(new_stream_id),
-                        }
-                    }
-                    Object::Array(arr) => {
-                        // Direct array - clone and add new stream ref
-                        let mut new_arr = arr.clone();
-                        new_arr.push(Object::Reference(new_stream_id));
-                        Object::Array(new_arr)
-                    }
-                    Object::Stream(_) => {
-                        // Direct stream (unusual) - wrap original in ref would be complex
-                        // For simplicity, create array with just the new stream
-                        // This case is rare in practice
-                        Object::Reference(new_stream_id)
-                    }
-                    _ => Object::Reference(new_stream_id),
-                }
-            }
-            Err(_) => {
-                // No existing contents - just use our new stream
-                Object::Reference(new_stream_id)
+        let mut content = content.to_vec();
+        if !prepend && !self.isolated_pages.contains(&page_id) {
+            let has_content = streams.iter().any(|item| {
+                matches!(
+                    item.as_reference().and_then(|id| self.inner.get_object(id)),
+                    Ok(Object::Stream(stream)) if !stream.content.is_empty()
+                )
+            });
+            if has_content {
+                let save_id = self
+                    .inner
+                    .add_object(Stream::new(Dictionary::new(), b"q\n".to_vec()));
+                streams.insert(0, Object::Reference(save_id));
+                content.splice(0..0, b"\nQ\n".iter().copied());
             }
-        };
+            self.isolated_pages.insert(page_id);
+        }
 
-        new_page_dict.set(b"Contents", new_contents);
+        // Create a new stream for our content
+        let new_stream_id = self
+            .inner
+            .add_object(Stream::new(Dictionary::new(), content));
+        let new_contents = if streams.is_empty() {
+            Object::Reference(new_stream_id)
+        } else {
+            add_stream_ref(&mut streams, new_stream_id, prepend);
+            Object::Array(streams)
+        };
 
-        // Replace page object
-        self.inner.objects.insert(page_id, new_page_dict.into());
+        self.inner
+            .get_dictionary_mut(page_id)?
+            .set(b"Contents", new_contents);
 
         Ok(())
     }
 
     /// Get current font's text width for a string
     ///
-    /// Calculates the text width in points based on the current font and size.
+    /// Calculates the shaped text width in points based on the current font
+    /// variant and size, measuring characters from fallback fonts with the
+    /// font that will render them (same as `insert_text`).
     ///
     /// # Arguments
     /// * `text` - The text to measure
@@ -1311,10 +3419,29 @@ impl PdfDocument {
     /// let width = doc.get_text_width("Hello")?;
     /// ```
     pub fn get_text_width(&self, text: &str) -> Result<f64> {
+        let segments = self.segment_current_text(text)?;
+        self.segments_width(&segments)
+    }
+
+    /// Get current font's vertical extents
+    ///
+    /// Returns the ascent (above the baseline) and descent (below the
+    /// baseline, positive) of the current font variant in points.
+    ///
+    /// # Example
+    /// ```ignore
+    /// doc.set_font("sarabun", 12.0)?;
+    /// let (ascent, descent) = doc.get_font_extents()?;
+    /// ```
+    pub fn get_font_extents(&self) -> Result<(f64, f64)> {
         let font_name = self.get_current_font_name()?;
         let font_data = self.get_font_data(&font_name)?;
+        let scale = self.current_font_size as f64 / font_data.units_per_em() as f64;
 
-        Ok(font_data.text_width_points(text, self.current_font_size) as f64)
+        Ok((
+            font_data.ascender() as f64 * scale,
+            -(font_data.descender() as f64) * scale,
+        ))
     }
 
     /// Get or create an image reference for a specific page
@@ -1336,8 +3463,13 @@ impl PdfDocument {
                     PdfError::ImageError(format!("Failed to create image XObject: {e}"))
                 })?;
 
-            // Convert to PDF stream and add to document
-            let stream = xobject.to_pdf_stream();
+            // Convert to PDF stream and add to document, with the alpha
+            // channel as a soft mask
+            let mut stream = xobject.to_pdf_stream();
+            if let Some(mask) = xobject.soft_mask_stream() {
+                let mask_id = self.inner.add_object(mask);
+                stream.dict.set("SMask", Object::Reference(mask_id));
+            }
             let object_id = self.inner.add_object(stream);
 
             // Store the reference
@@ -1396,49 +3528,112 @@ impl PdfDocument {
         resource_name: &str,
         object_id: ObjectId,
     ) -> Result<()> {
+        self.add_xobjects_to_page_resources(page, &[(resource_name.to_string(), object_id)])
+    }
+
+    /// Add XObjects (resource name, object ID) to a page's Resources dictionary
+    fn add_xobjects_to_page_resources(
+        &mut self,
+        page: usize,
+        xobjects: &[(String, ObjectId)],
+    ) -> Result<()> {
+        self.add_to_page_resources(page, b"XObject", xobjects)
+    }
+
+    /// Names in a category (e.g. `ExtGState`) of a page's own or inherited
+    /// Resources dictionary
+    fn page_resource_names(&self, page: usize, category: &[u8]) -> Result<Vec<Vec<u8>>> {
         let pages = self.inner.get_pages();
         let page_id = *pages
             .get(&(page as u32))
             .ok_or(PdfError::InvalidPage(page, pages.len()))?;
 
-        // Get the page object
-        let page_obj = self.inner.get_object(page_id)?;
-        let page_dict = page_obj
-            .as_dict()
+        let resolve = |object: &Object| -> Option<Dictionary> {
+            match object {
+                Object::Dictionary(dict) => Some(dict.clone()),
+                Object::Reference(id) => self.inner.get_dictionary(*id).ok().cloned(),
+                _ => None,
+            }
+        };
+        let names = self
+            .inherited_attribute(page_id, b"Resources")
+            .as_ref()
+            .and_then(resolve)
+            .and_then(|resources| resources.get(category).ok().and_then(resolve))
+            .map(|dict| dict.iter().map(|(name, _)| name.clone()).collect())
+            .unwrap_or_default();
+
+        Ok(names)
+    }
+
+    /// Add entries (resource name, object ID) to a category of a page's
+    /// Resources dictionary
+    ///
+    /// Like `add_fonts_to_page_resources`, this updates a referenced
+    /// Resources or category dictionary in place.
+    fn add_to_page_resources(
+        &mut self,
+        page: usize,
+        category: &[u8],
+        entries: &[(String, ObjectId)],
+    ) -> Result<()> {
+        let pages = self.inner.get_pages();
+        let page_id = *pages
+            .get(&(page as u32))
+            .ok_or(PdfError::InvalidPage(page, pages.len()))?;
+        // Add to inherited resources rather than hiding them
+        self.copy_inherited_attributes(page_id)?;
+
+        let page_dict = self
+            .inner
+            .get_dictionary(page_id)
             .map_err(|_| PdfError::SaveError("Page object is not a dictionary".to_string()))?;
 
-        // Get or create Resources dictionary
-        let resources_dict = match page_dict.get(b"Resources") {
-            Ok(resources) => match resources.as_dict() {
-                Ok(dict) => dict.clone(),
-                Err(_) => Dictionary::new(),
+        // Get or create Resources dictionary, handling indirect references
+        let (mut resources_dict, resources_obj_id) = match page_dict.get(b"Resources") {
+            Ok(Object::Dictionary(dict)) => (dict.clone(), None),
+            Ok(Object::Reference(ref_id)) => match self.inner.get_dictionary(*ref_id) {
+                Ok(dict) => (dict.clone(), Some(*ref_id)),
+                Err(_) => (Dictionary::new(), None),
             },
-            Err(_) => Dictionary::new(),
+            _ => (Dictionary::new(), None),
         };
 
-        // Get or create XObject dictionary in Resources
-        let xobject_dict = match resources_dict.get(b"XObject") {
-            Ok(xobject) => match xobject.as_dict() {
-                Ok(dict) => dict.clone(),
-                Err(_) => Dictionary::new(),
+        // Get or create the category dictionary in Resources, handling indirect references
+        let (mut category_dict, category_obj_id) = match resources_dict.get(category) {
+            Ok(Object::Dictionary(dict)) => (dict.clone(), None),
+            Ok(Object::Reference(ref_id)) => match self.inner.get_dictionary(*ref_id) {
+                Ok(dict) => (dict.clone(), Some(*ref_id)),
+                Err(_) => (Dictionary::new(), None),
             },
-            Err(_) => Dictionary::new(),
+            _ => (Dictionary::new(), None),
         };
 
-        // Add image reference
-        let mut new_xobject_dict = xobject_dict.clone();
-        new_xobject_dict.set(resource_name.as_bytes(), Object::Reference(object_id));
-
-        // Update Resources dictionary
-        let mut new_resources = resources_dict.clone();
-        new_resources.set(b"XObject", Object::Dictionary(new_xobject_dict));
+        for (resource_name, object_id) in entries {
+            category_dict.set(resource_name.as_bytes(), Object::Reference(*object_id));
+        }
 
-        // Update page dictionary
-        let mut new_page_dict = page_dict.clone();
-        new_page_dict.set(b"Resources", Object::Dictionary(new_resources));
+        match category_obj_id {
+            Some(id) => {
+                self.inner
+                    .objects
+                    .insert(id, Object::Dictionary(category_dict));
+            }
+            None => resources_dict.set(category, Object::Dictionary(category_dict)),
+        }
 
-        // Replace page object by creating a new one
-        self.inner.objects.insert(page_id, new_page_dict.into());
+        match resources_obj_id {
+            Some(id) => {
+                self.inner
+                    .objects
+                    .insert(id, Object::Dictionary(resources_dict));
+            }
+            None => {
+                self.inner
+                    .get_dictionary_mut(page_id)?
+                    .set(b"Resources", Object::Dictionary(resources_dict));
+            }
+        }
 
         Ok(())
     }
@@ -1462,31 +3657,189 @@ impl PdfDocument {
     /// doc.save("two-pages.pdf")?;
     /// ```
     pub fn add_blank_page(&mut self) -> Result<usize> {
+        self.add_page(PageSize::A4)
+    }
+
+    /// Add a page of the given size at the end of the document
+    ///
+    /// With margins, text, images, shapes and imported pages are placed
+    /// from the top-left corner of the area inside them. Form field
+    /// rectangles, page boxes and watermarks still use the whole page.
+    /// Margins follow the page through `duplicate_page` and `extract_pages`.
+    ///
+    /// # Returns
+    /// New page number (1-indexed)
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut doc = PdfDocument::new();
+    /// doc.add_page(PageSize::A4.with_margins(Margins::all(56.7)))?;
+    /// doc.add_page(PageSize::A5.landscape())?;
+    /// ```
+    pub fn add_page(&mut self, size: PageSize) -> Result<usize> {
         // Create empty content stream
         let contents_id = self
             .inner
             .add_object(Object::Stream(Stream::new(Dictionary::new(), vec![])));
 
-        // Get the current page count (this will be the new page number)
-        let page_count = self.page_count();
+        // Get the current page count (this will be the new page number)
+        let page_count = self.page_count();
+
+        // Create new page dictionary with the requested MediaBox
+        let mut page_dict = Dictionary::new();
+        page_dict.set(b"Type", Object::Name(b"Page".to_vec()));
+        page_dict.set(
+            b"MediaBox",
+            Object::Array(vec![
+                Object::Real(0.0),
+                Object::Real(0.0),
+                Object::Real(size.width as f32),
+                Object::Real(size.height as f32),
+            ]),
+        );
+        page_dict.set(b"Resources", Object::Dictionary(Dictionary::new()));
+        page_dict.set(b"Contents", Object::Reference(contents_id));
+
+        // Create the new page object
+        let new_page_id = self.inner.add_object(Object::Dictionary(page_dict));
+        if !size.margins.is_zero() {
+            self.page_margins.insert(new_page_id, size.margins);
+        }
+
+        // Get the root Pages object
+        let trailer =
+            self.inner.trailer.get(b"Root").map_err(|_| {
+                PdfError::ParseError("Document trailer missing Root entry".to_string())
+            })?;
+        let catalog_id = trailer
+            .as_reference()
+            .map_err(|_| PdfError::ParseError("Root is not a reference".to_string()))?;
+        let catalog_obj = self.inner.get_object(catalog_id)?;
+        let catalog_dict = catalog_obj
+            .as_dict()
+            .map_err(|_| PdfError::ParseError("Catalog is not a dictionary".to_string()))?;
+        let pages_ref = catalog_dict
+            .get(b"Pages")
+            .map_err(|_| PdfError::ParseError("Catalog missing Pages entry".to_string()))?;
+        let pages_id = pages_ref
+            .as_reference()
+            .map_err(|_| PdfError::ParseError("Pages is not a reference".to_string()))?;
+
+        // Get the Pages object and update its Kids array
+        let pages_obj = self.inner.get_object(pages_id)?;
+        let pages_dict = pages_obj
+            .as_dict()
+            .map_err(|_| PdfError::ParseError("Pages object is not a dictionary".to_string()))?;
+
+        // Get the current Kids array
+        let kids = pages_dict
+            .get(b"Kids")
+            .map_err(|_| PdfError::ParseError("Pages object missing Kids array".to_string()))?;
+        let mut kids_array = kids
+            .as_array()
+            .map_err(|_| PdfError::ParseError("Kids is not an array".to_string()))?
+            .clone();
+
+        // Add the new page to the Kids array
+        kids_array.push(Object::Reference(new_page_id));
+
+        // Update the Count in the Pages object
+        let count = pages_dict
+            .get(b"Count")
+            .map_err(|_| PdfError::ParseError("Pages object missing Count".to_string()))?;
+        let current_count = count
+            .as_i64()
+            .map_err(|_| PdfError::ParseError("Count is not an integer".to_string()))?;
+
+        // Create updated Pages dictionary
+        let mut new_pages_dict = pages_dict.clone();
+        new_pages_dict.set(b"Kids", Object::Array(kids_array));
+        new_pages_dict.set(b"Count", Object::Integer(current_count + 1));
+
+        // Replace the Pages object
+        self.inner.objects.insert(pages_id, new_pages_dict.into());
+
+        // Return the new page number (1-indexed)
+        Ok(page_count + 1)
+    }
+
+    /// Duplicate a page and return the new page number
+    ///
+    /// # Arguments
+    /// * `page` - Page number to duplicate (1-indexed)
+    ///
+    /// # Returns
+    /// New page number (1-indexed)
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut doc = PdfDocument::open("single-page.pdf")?;
+    /// assert_eq!(doc.page_count(), 1);
+    ///
+    /// let new_page = doc.duplicate_page(1)?;  // Duplicate page 1
+    /// assert_eq!(new_page, 2);
+    /// assert_eq!(doc.page_count(), 2);
+    ///
+    /// doc.save("two-pages.pdf")?;
+    /// ```
+    pub fn duplicate_page(&mut self, page: usize) -> Result<usize> {
+        let pages = self.inner.get_pages();
+        let page_count = pages.len();
+
+        // Validate page number
+        if page == 0 || page > page_count {
+            return Err(PdfError::InvalidPage(page, page_count));
+        }
+
+        // Get the source page object ID
+        let source_page_id = *pages
+            .get(&(page as u32))
+            .ok_or(PdfError::InvalidPage(page, page_count))?;
+
+        // Get the source page object
+        let source_page_obj = self.inner.get_object(source_page_id)?;
+        let source_page_dict = source_page_obj
+            .as_dict()
+            .map_err(|_| PdfError::ParseError("Page object is not a dictionary".to_string()))?;
 
-        // Create new page dictionary with A4 MediaBox
-        let mut page_dict = Dictionary::new();
-        page_dict.set(b"Type", Object::Name(b"Page".to_vec()));
-        page_dict.set(
-            b"MediaBox",
-            Object::Array(vec![
-                Object::Real(0.0),
-                Object::Real(0.0),
-                Object::Real(595.28), // A4 width
-                Object::Real(841.89), // A4 height
-            ]),
-        );
-        page_dict.set(b"Resources", Object::Dictionary(Dictionary::new()));
-        page_dict.set(b"Contents", Object::Reference(contents_id));
+        // Clone the page dictionary
+        let mut new_page_dict = source_page_dict.clone();
+
+        // Clone the content stream if it exists
+        if let Ok(contents) = source_page_dict.get(b"Contents") {
+            match contents {
+                Object::Stream(stream) => {
+                    // Clone the stream content
+                    let new_stream = Stream::new(stream.dict.clone(), stream.content.clone());
+                    new_page_dict.set(b"Contents", new_stream);
+                }
+                Object::Array(arr) => {
+                    // Clone array of streams - collect stream data first to avoid borrow issues
+                    let mut streams_to_add = Vec::new();
+                    for obj in arr {
+                        if let Object::Reference(ref_id) = obj {
+                            if let Ok(Object::Stream(stream)) = self.inner.get_object(*ref_id) {
+                                let new_stream =
+                                    Stream::new(stream.dict.clone(), stream.content.clone());
+                                streams_to_add.push(new_stream);
+                            }
+                        }
+                    }
+
+                    // Now add the streams and build the new array
+                    let mut new_arr = Vec::new();
+                    for stream in streams_to_add {
+                        let new_stream_id = self.inner.add_object(stream);
+                        new_arr.push(Object::Reference(new_stream_id));
+                    }
+                    new_page_dict.set(b"Contents", Object::Array(new_arr));
+                }
+                _ => {}
+            }
+        }
 
         // Create the new page object
-        let new_page_id = self.inner.add_object(Object::Dictionary(page_dict));
+        let new_page_id = self.inner.add_object(new_page_dict.clone());
 
         // Get the root Pages object
         let trailer =
@@ -1541,172 +3894,1032 @@ impl PdfDocument {
         // Replace the Pages object
         self.inner.objects.insert(pages_id, new_pages_dict.into());
 
+        // Copy font resource mappings from source page to new page
+        // This ensures the cloned content stream's font references remain valid
+        if let Some(source_font_resources) = self.page_font_resources.get(&page).cloned() {
+            self.page_font_resources
+                .insert(page_count + 1, source_font_resources);
+        }
+
+        // Also copy image resource mappings
+        if let Some(source_image_resources) = self.page_image_resources.get(&page).cloned() {
+            self.page_image_resources
+                .insert(page_count + 1, source_image_resources);
+        }
+
+        // And ExtGState resource mappings
+        if let Some(source_gstate_resources) = self.page_ext_gstate_resources.get(&page).cloned() {
+            self.page_ext_gstate_resources
+                .insert(page_count + 1, source_gstate_resources);
+        }
+
+        // Content on the copy is placed inside the same margins
+        if let Some(margins) = self.page_margins.get(&source_page_id).copied() {
+            self.page_margins.insert(new_page_id, margins);
+        }
+
         // Return the new page number (1-indexed)
         Ok(page_count + 1)
     }
 
-    /// Duplicate a page and return the new page number
+    /// Delete a page
+    ///
+    /// Pending text, images and shapes of the page are dropped, and so are
+    /// its form fields and the top-level bookmarks opening it.
     ///
     /// # Arguments
-    /// * `page` - Page number to duplicate (1-indexed)
+    /// * `page` - Page number to delete (1-indexed)
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut doc = PdfDocument::open("form.pdf")?;
+    /// doc.delete_page(3)?;  // The instructions page is not delivered
+    /// ```
+    pub fn delete_page(&mut self, page: usize) -> Result<()> {
+        let page_count = self.page_count();
+        if page == 0 || page > page_count {
+            return Err(PdfError::InvalidPage(page, page_count));
+        }
+
+        let order: Vec<usize> = (1..=page_count).filter(|&p| p != page).collect();
+        self.reorder_pages(&order)
+    }
+
+    /// Move a page to a new position
+    ///
+    /// # Arguments
+    /// * `from` - Page number to move (1-indexed)
+    /// * `to` - Page number it has after the move (1-indexed)
+    ///
+    /// # Example
+    /// ```ignore
+    /// let copy = doc.duplicate_page(1)?;
+    /// doc.move_page(copy, 2)?;  // The copy follows the original
+    /// ```
+    pub fn move_page(&mut self, from: usize, to: usize) -> Result<()> {
+        let page_count = self.page_count();
+        for page in [from, to] {
+            if page == 0 || page > page_count {
+                return Err(PdfError::InvalidPage(page, page_count));
+            }
+        }
+
+        let mut order: Vec<usize> = (1..=page_count).filter(|&p| p != from).collect();
+        order.insert(to - 1, from);
+        self.reorder_pages(&order)
+    }
+
+    /// Insert a blank A4 page at a position
+    ///
+    /// # Arguments
+    /// * `page` - Page number of the new page (1-indexed, up to page count + 1)
     ///
     /// # Returns
     /// New page number (1-indexed)
+    pub fn insert_page_at(&mut self, page: usize) -> Result<usize> {
+        let page_count = self.page_count();
+        if page == 0 || page > page_count + 1 {
+            return Err(PdfError::InvalidPage(page, page_count));
+        }
+
+        let new_page = self.add_blank_page()?;
+        if new_page != page {
+            self.move_page(new_page, page)?;
+        }
+        Ok(page)
+    }
+
+    /// Set the rotation a viewer displays a page with (/Rotate)
+    ///
+    /// Content and fields added before turn with the page; content added
+    /// after is placed in the turned view.
+    ///
+    /// # Arguments
+    /// * `page` - Page number (1-indexed)
+    /// * `degrees` - Clockwise rotation, a multiple of 90 (negative values allowed)
+    pub fn set_rotation(&mut self, page: usize, degrees: i32) -> Result<()> {
+        if degrees % 90 != 0 {
+            return Err(PdfError::InvalidRotation(degrees));
+        }
+        let page_id = self.page_id(page)?;
+        self.inner
+            .get_dictionary_mut(page_id)?
+            .set("Rotate", Object::Integer(degrees.rem_euclid(360) as i64));
+        Ok(())
+    }
+
+    /// Set the visible area of a page (/CropBox)
+    ///
+    /// # Arguments
+    /// * `page` - Page number (1-indexed)
+    /// * `rect` - Visible area in top-left page coordinates
+    pub fn set_crop_box(&mut self, page: usize, rect: Rect) -> Result<()> {
+        self.set_page_box(page, b"CropBox", rect)
+    }
+
+    /// Set the page size (/MediaBox)
+    ///
+    /// The rectangle is relative to the current page, so growing the page
+    /// keeps its content in place from the top-left corner.
+    ///
+    /// # Arguments
+    /// * `page` - Page number (1-indexed)
+    /// * `rect` - New page area in top-left page coordinates
+    pub fn set_media_box(&mut self, page: usize, rect: Rect) -> Result<()> {
+        self.set_page_box(page, b"MediaBox", rect)
+    }
+
+    /// Copy a range of pages into a new document
+    ///
+    /// Pending content of this document is included. The document itself
+    /// is left as it was (a copy is saved), so it can still be changed and
+    /// saved. The new document has no fonts loaded; add them again to
+    /// insert text.
+    ///
+    /// # Arguments
+    /// * `range` - Page numbers to keep (1-indexed), e.g. `2..=3` or `4..`
     ///
     /// # Example
     /// ```ignore
-    /// let mut doc = PdfDocument::open("single-page.pdf")?;
-    /// assert_eq!(doc.page_count(), 1);
+    /// let mut doc = renderer.render_to_document(&data)?;
+    /// let mut copy = doc.extract_pages(2..=2)?;
+    /// std::fs::write("copy.pdf", copy.to_bytes()?)?;
+    /// ```
+    pub fn extract_pages<R: RangeBounds<usize>>(&self, range: R) -> Result<PdfDocument> {
+        let page_count = self.page_count();
+        let first = match range.start_bound() {
+            Bound::Included(&page) => page,
+            Bound::Excluded(&page) => page + 1,
+            Bound::Unbounded => 1,
+        };
+        let last = match range.end_bound() {
+            Bound::Included(&page) => page,
+            Bound::Excluded(&page) => page.saturating_sub(1),
+            Bound::Unbounded => page_count,
+        };
+        for page in [first, last] {
+            if page == 0 || page > page_count {
+                return Err(PdfError::InvalidPage(page, page_count));
+            }
+        }
+        if first > last {
+            return Err(PdfError::InvalidPage(first, page_count));
+        }
+
+        // Saving subsets fonts and encodes text for good, so save a copy
+        let bytes = self.clone().to_bytes()?;
+        let mut extracted = PdfDocument::open_from_bytes(&bytes)?;
+        let order: Vec<usize> = (first..=last).collect();
+        extracted.reorder_pages(&order)?;
+        extracted.inner.prune_objects();
+
+        // Saving keeps object IDs, so margins carry over by page ID
+        for page_id in extracted.inner.get_pages().into_values() {
+            if let Some(&margins) = self.page_margins.get(&page_id) {
+                extracted.page_margins.insert(page_id, margins);
+            }
+        }
+        Ok(extracted)
+    }
+
+    /// Get all page object IDs in order
     ///
-    /// let new_page = doc.duplicate_page(1)?;  // Duplicate page 1
-    /// assert_eq!(new_page, 2);
-    /// assert_eq!(doc.page_count(), 2);
+    /// Returns a vector of ObjectId values representing all pages in the document.
     ///
-    /// doc.save("two-pages.pdf")?;
+    /// # Example
+    /// ```ignore
+    /// let doc = PdfDocument::open("document.pdf")?;
+    /// let page_ids = doc.get_page_ids();
+    /// println!("Document has {} pages", page_ids.len());
     /// ```
-    pub fn duplicate_page(&mut self, page: usize) -> Result<usize> {
+    pub fn get_page_ids(&self) -> Vec<ObjectId> {
         let pages = self.inner.get_pages();
-        let page_count = pages.len();
+        pages.values().copied().collect()
+    }
+
+    /// Merge documents into one, with a bookmark for each
+    ///
+    /// The first document becomes the merged document and the others are
+    /// appended in order (see `append_document`).
+    ///
+    /// # Arguments
+    /// * `documents` - Bookmark titles and documents
+    ///
+    /// # Example
+    /// ```ignore
+    /// let merged = PdfDocument::merge(vec![
+    ///     ("Invoice 001".to_string(), first),
+    ///     ("Invoice 002".to_string(), second),
+    /// ])?;
+    /// ```
+    pub fn merge(documents: Vec<(String, PdfDocument)>) -> Result<PdfDocument> {
+        let mut documents = documents.into_iter();
+        let (title, mut merged) = documents
+            .next()
+            .ok_or_else(|| PdfError::InvalidArgument("No documents to merge".to_string()))?;
+        merged.add_bookmark(&title, 1)?;
+
+        for (title, document) in documents {
+            merged.append_document(document, Some(&title))?;
+        }
+        Ok(merged)
+    }
+
+    /// Append the pages of another document after the last page
+    ///
+    /// Pages are copied with their resources and annotations, renumbering
+    /// their objects. Text and drawings still pending in `other` move with
+    /// its pages, so a font registered in both documents (same name, same
+    /// data) is subsetted and embedded once. Identical images, transparency
+    /// states and font programs of the base PDFs are shared as well.
+    ///
+    /// Fields of `other` are not added to this document's AcroForm, so
+    /// flatten it when its values must be kept. A document with pending
+    /// field appearances or flattening is saved on its own first.
+    ///
+    /// # Arguments
+    /// * `other` - Document to append
+    /// * `bookmark` - Title of an outline entry for the first appended page
+    ///
+    /// # Returns
+    /// Page number of the first appended page (1-indexed)
+    pub fn append_document(
+        &mut self,
+        mut other: PdfDocument,
+        bookmark: Option<&str>,
+    ) -> Result<usize> {
+        // Field appearances need the other document's embedded fonts
+        if other.flatten_mode.is_some()
+            || !other.buffered_field_appearances.is_empty()
+            || !other.form_fonts.is_empty()
+        {
+            let bytes = other.to_bytes()?;
+            other = PdfDocument::open_from_bytes(&bytes)?;
+        }
+
+        let offset = self.page_count();
+        let source_pages = other.get_page_ids();
+        if source_pages.is_empty() {
+            return Err(PdfError::InvalidPage(1, 0));
+        }
+
+        // Pages leave their page tree, so they keep what they inherited
+        for &page_id in &source_pages {
+            other.copy_inherited_attributes(page_id)?;
+        }
+
+        self.merge_fonts(&mut other)?;
+
+        // Objects already in this document are shared instead of copied
+        let mut id_map: HashMap<ObjectId, ObjectId> = HashMap::new();
+        for (hash, id) in &other.embedded_images {
+            if let Some(existing) = self.embedded_images.get(hash) {
+                id_map.insert(*id, *existing);
+            }
+        }
+        for (key, id) in &other.ext_gstates {
+            if let Some(existing) = self.ext_gstates.get(key) {
+                id_map.insert(*id, *existing);
+            }
+        }
+        for (key, imported) in &other.imported_pages {
+            if let Some(existing) = self.imported_pages.get(key) {
+                id_map.insert(imported.object_id, existing.object_id);
+            }
+        }
+        let font_files = self
+            .font_files
+            .get_or_insert_with(|| font_file_streams(&self.inner));
+        let other_font_files = font_file_streams(&other.inner);
+        for (hash, id) in &other_font_files {
+            if let Some(existing) = font_files.get(hash) {
+                id_map.insert(*id, *existing);
+            }
+        }
+
+        self.import_objects(&other.inner, &source_pages, &mut id_map)?;
+
+        // Later insertions reuse what was copied
+        let font_files = self.font_files.get_or_insert_with(HashMap::new);
+        for (hash, id) in other_font_files {
+            if let Some(&new_id) = id_map.get(&id) {
+                font_files.entry(hash).or_insert(new_id);
+            }
+        }
+        for (hash, id) in other.embedded_images {
+            if let Some(&new_id) = id_map.get(&id) {
+                self.embedded_images.entry(hash).or_insert(new_id);
+            }
+        }
+        for (key, id) in other.ext_gstates {
+            if let Some(&new_id) = id_map.get(&id) {
+                self.ext_gstates.entry(key).or_insert(new_id);
+            }
+        }
+        for (key, imported) in other.imported_pages {
+            if let Some(&object_id) = id_map.get(&imported.object_id) {
+                self.imported_pages.entry(key).or_insert(ImportedPage {
+                    object_id,
+                    ..imported
+                });
+            }
+        }
+        for (id, margins) in other.page_margins {
+            if let Some(&new_id) = id_map.get(&id) {
+                self.page_margins.insert(new_id, margins);
+            }
+        }
+
+        // Hang the copied pages under this document's page tree
+        let pages_id = self.inner.catalog()?.get(b"Pages")?.as_reference()?;
+        let mut kids = Vec::new();
+        for page_id in &source_pages {
+            let new_id = id_map[page_id];
+            self.inner
+                .get_dictionary_mut(new_id)?
+                .set("Parent", Object::Reference(pages_id));
+            kids.push(Object::Reference(new_id));
+        }
+        let pages_dict = self.inner.get_dictionary_mut(pages_id)?;
+        let count = pages_dict.get(b"Count")?.as_i64()?;
+        pages_dict.get_mut(b"Kids")?.as_array_mut()?.extend(kids);
+        pages_dict.set("Count", Object::Integer(count + source_pages.len() as i64));
+
+        // Pending content and resource mappings move with their pages.
+        // Text already encoded by a save of `other` keeps its own fonts.
+        if !other.buffered_text_ops.is_empty() {
+            for (page, fonts) in other.page_font_resources {
+                self.page_font_resources.insert(page + offset, fonts);
+            }
+        }
+        for mut op in other.buffered_text_ops {
+            op.page += offset;
+            self.buffered_text_ops.push(op);
+        }
+        for (page, content) in other.page_content_buffer {
+            self.page_content_buffer.insert(page + offset, content);
+        }
+        for (page, content) in other.page_underlay_buffer {
+            self.page_underlay_buffer.insert(page + offset, content);
+        }
+        for (page, images) in other.page_image_resources {
+            let images = images
+                .into_iter()
+                .filter_map(|(name, id)| Some((name, *id_map.get(&id)?)))
+                .collect();
+            self.page_image_resources.insert(page + offset, images);
+        }
+        for (page, gstates) in other.page_ext_gstate_resources {
+            let gstates = gstates
+                .into_iter()
+                .filter_map(|(name, id)| Some((name, *id_map.get(&id)?)))
+                .collect();
+            self.page_ext_gstate_resources
+                .insert(page + offset, gstates);
+        }
+        for (family, fallbacks) in other.font_fallbacks {
+            self.font_fallbacks.entry(family).or_insert(fallbacks);
+        }
+
+        // New resource names must not clash with the copied ones
+        self.next_font_resource = self.next_font_resource.max(other.next_font_resource);
+        self.next_image_resource = self.next_image_resource.max(other.next_image_resource);
+        self.next_ext_gstate_resource = self
+            .next_ext_gstate_resource
+            .max(other.next_ext_gstate_resource);
+
+        if let Some(title) = bookmark {
+            self.add_bookmark(title, offset + 1)?;
+        }
+
+        Ok(offset + 1)
+    }
+
+    /// Add a top-level bookmark (outline entry) that opens a page
+    ///
+    /// # Arguments
+    /// * `title` - Bookmark title (Thai is encoded as UTF-16)
+    /// * `page` - Target page number (1-indexed)
+    pub fn add_bookmark(&mut self, title: &str, page: usize) -> Result<()> {
+        let pages = self.inner.get_pages();
+        let page_id = *pages
+            .get(&(page as u32))
+            .ok_or(PdfError::InvalidPage(page, pages.len()))?;
+
+        let outlines_id = match self.inner.catalog()?.get(b"Outlines") {
+            Ok(Object::Reference(id)) => *id,
+            _ => {
+                let id = self.inner.add_object(dictionary! {
+                    "Type" => "Outlines",
+                    "Count" => 0,
+                });
+                self.inner
+                    .catalog_mut()?
+                    .set("Outlines", Object::Reference(id));
+                id
+            }
+        };
+
+        let last = self
+            .inner
+            .get_dictionary(outlines_id)?
+            .get(b"Last")
+            .and_then(Object::as_reference)
+            .ok();
+
+        let mut item = dictionary! {
+            "Title" => form::encode_text_string(title),
+            "Parent" => outlines_id,
+            "Dest" => vec![Object::Reference(page_id), Object::Name(b"Fit".to_vec())],
+        };
+        if let Some(last) = last {
+            item.set("Prev", Object::Reference(last));
+        }
+        let item_id = self.inner.add_object(item);
+
+        match last {
+            Some(last) => self
+                .inner
+                .get_dictionary_mut(last)?
+                .set("Next", Object::Reference(item_id)),
+            None => self
+                .inner
+                .get_dictionary_mut(outlines_id)?
+                .set("First", Object::Reference(item_id)),
+        }
+        let outlines = self.inner.get_dictionary_mut(outlines_id)?;
+        let count = outlines.get(b"Count").and_then(Object::as_i64).unwrap_or(0);
+        outlines.set("Last", Object::Reference(item_id));
+        outlines.set("Count", Object::Integer(count.abs() + 1));
+
+        Ok(())
+    }
+
+    /// Take over the fonts of a document being appended
+    ///
+    /// A font with the same name and data as one of ours is merged into it,
+    /// so one subset covers the text of both documents.
+    fn merge_fonts(&mut self, other: &mut PdfDocument) -> Result<()> {
+        for (name, family) in std::mem::take(&mut other.font_families) {
+            let Some(existing) = self.font_families.get_mut(&name) else {
+                self.font_families.insert(name, family);
+                continue;
+            };
+
+            for (slot, variant) in [
+                (&mut existing.regular, family.regular),
+                (&mut existing.bold, family.bold),
+                (&mut existing.italic, family.italic),
+                (&mut existing.bold_italic, family.bold_italic),
+            ] {
+                match (slot, variant) {
+                    (_, None) => {}
+                    (Some(font), Some(variant)) => merge_font_data(font, &variant)?,
+                    (slot, variant) => *slot = variant,
+                }
+            }
+        }
+
+        for (name, font) in std::mem::take(&mut other.fonts) {
+            match self.fonts.get_mut(&name) {
+                Some(existing) => merge_font_data(existing, &font)?,
+                None => {
+                    self.fonts.insert(name, font);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copy the page tree attributes a page inherits onto the page itself
+    fn copy_inherited_attributes(&mut self, page_id: ObjectId) -> Result<()> {
+        let mut inherited = Vec::new();
+        let page = self.inner.get_dictionary(page_id)?;
+        for key in [b"Resources".as_slice(), b"MediaBox", b"CropBox", b"Rotate"] {
+            if page.has(key) {
+                continue;
+            }
 
-        // Validate page number
-        if page == 0 || page > page_count {
-            return Err(PdfError::InvalidPage(page, page_count));
+            // Walk up the tree (bounded in case of a Parent cycle)
+            let mut parent = page.get(b"Parent").and_then(Object::as_reference).ok();
+            for _ in 0..32 {
+                let Some(node) = parent.and_then(|id| self.inner.get_dictionary(id).ok()) else {
+                    break;
+                };
+                if let Ok(value) = node.get(key) {
+                    inherited.push((key, value.clone()));
+                    break;
+                }
+                parent = node.get(b"Parent").and_then(Object::as_reference).ok();
+            }
         }
 
-        // Get the source page object ID
-        let source_page_id = *pages
+        let page = self.inner.get_dictionary_mut(page_id)?;
+        for (key, value) in inherited {
+            page.set(key, value);
+        }
+        Ok(())
+    }
+
+    /// Object ID of a page (1-indexed)
+    fn page_id(&self, page: usize) -> Result<ObjectId> {
+        let pages = self.inner.get_pages();
+        pages
             .get(&(page as u32))
-            .ok_or(PdfError::InvalidPage(page, page_count))?;
+            .copied()
+            .ok_or(PdfError::InvalidPage(page, pages.len()))
+    }
 
-        // Get the source page object
-        let source_page_obj = self.inner.get_object(source_page_id)?;
-        let source_page_dict = source_page_obj
-            .as_dict()
-            .map_err(|_| PdfError::ParseError("Page object is not a dictionary".to_string()))?;
+    /// Set a page box from a rectangle in top-left page coordinates
+    fn set_page_box(&mut self, page: usize, key: &[u8], rect: Rect) -> Result<()> {
+        let page_id = self.page_id(page)?;
+        let (_, geometry) = self.page_geometry(page)?;
+        let page_box = geometry.pdf_rect(rect);
+        self.inner.get_dictionary_mut(page_id)?.set(
+            key,
+            Object::Array(page_box.map(|v| Object::Real(v as f32)).to_vec()),
+        );
+        Ok(())
+    }
 
-        // Clone the page dictionary
-        let mut new_page_dict = source_page_dict.clone();
+    /// Rebuild the page tree with the given pages in order
+    ///
+    /// `order` lists current page numbers (1-indexed); pages left out are
+    /// deleted. The tree is flattened under the root Pages node, with
+    /// inherited attributes copied onto the pages, and pending content and
+    /// resource mappings follow their pages.
+    fn reorder_pages(&mut self, order: &[usize]) -> Result<()> {
+        let page_ids = self.get_page_ids();
+        let mut new_numbers = HashMap::new();
+        for (index, &page) in order.iter().enumerate() {
+            if page == 0 || page > page_ids.len() || new_numbers.insert(page, index + 1).is_some() {
+                return Err(PdfError::InvalidPage(page, page_ids.len()));
+            }
+        }
 
-        // Clone the content stream if it exists
-        if let Ok(contents) = source_page_dict.get(b"Contents") {
-            match contents {
-                Object::Stream(stream) => {
-                    // Clone the stream content
-                    let new_stream = Stream::new(stream.dict.clone(), stream.content.clone());
-                    new_page_dict.set(b"Contents", new_stream);
+        for &page_id in &page_ids {
+            self.copy_inherited_attributes(page_id)?;
+        }
+
+        // Intermediate Pages nodes are dropped with the old tree
+        let pages_id = self.inner.catalog()?.get(b"Pages")?.as_reference()?;
+        let mut nodes = vec![pages_id];
+        let mut index = 0;
+        while index < nodes.len() && nodes.len() < 10_000 {
+            let kids = self
+                .inner
+                .get_dictionary(nodes[index])?
+                .get_deref(b"Kids", &self.inner)
+                .and_then(Object::as_array)
+                .cloned()
+                .unwrap_or_default();
+            for kid in kids {
+                let Ok(id) = kid.as_reference() else { continue };
+                let is_node = self
+                    .inner
+                    .get_dictionary(id)
+                    .and_then(|dict| dict.get(b"Type"))
+                    .and_then(Object::as_name)
+                    .is_ok_and(|name| name == b"Pages");
+                if is_node && !nodes.contains(&id) {
+                    nodes.push(id);
                 }
-                Object::Array(arr) => {
-                    // Clone array of streams - collect stream data first to avoid borrow issues
-                    let mut streams_to_add = Vec::new();
-                    for obj in arr {
-                        if let Object::Reference(ref_id) = obj {
-                            if let Ok(Object::Stream(stream)) = self.inner.get_object(*ref_id) {
-                                let new_stream =
-                                    Stream::new(stream.dict.clone(), stream.content.clone());
-                                streams_to_add.push(new_stream);
-                            }
+            }
+            index += 1;
+        }
+        for node in &nodes[1..] {
+            self.inner.objects.remove(node);
+        }
+
+        let kept: Vec<ObjectId> = order.iter().map(|&page| page_ids[page - 1]).collect();
+        let removed: Vec<ObjectId> = page_ids
+            .iter()
+            .filter(|id| !kept.contains(id))
+            .copied()
+            .collect();
+        if !removed.is_empty() {
+            self.remove_pages(&removed, &kept)?;
+        }
+
+        for &page_id in &kept {
+            self.inner
+                .get_dictionary_mut(page_id)?
+                .set("Parent", Object::Reference(pages_id));
+        }
+        let pages_dict = self.inner.get_dictionary_mut(pages_id)?;
+        pages_dict.set("Count", Object::Integer(kept.len() as i64));
+        pages_dict.set(
+            "Kids",
+            Object::Array(kept.into_iter().map(Object::Reference).collect()),
+        );
+
+        rekey_pages(&mut self.page_font_resources, &new_numbers);
+        rekey_pages(&mut self.page_image_resources, &new_numbers);
+        rekey_pages(&mut self.page_ext_gstate_resources, &new_numbers);
+        rekey_pages(&mut self.page_content_buffer, &new_numbers);
+        rekey_pages(&mut self.page_underlay_buffer, &new_numbers);
+        self.buffered_text_ops
+            .retain_mut(|op| match new_numbers.get(&op.page) {
+                Some(&page) => {
+                    op.page = page;
+                    true
+                }
+                None => false,
+            });
+
+        Ok(())
+    }
+
+    /// Delete page objects with their form fields, bookmarks, annotations
+    /// and content streams (those not shared with `kept` pages)
+    fn remove_pages(&mut self, removed: &[ObjectId], kept: &[ObjectId]) -> Result<()> {
+        let page_refs = |doc: &Document, pages: &[ObjectId], key: &[u8]| {
+            let mut ids = HashSet::new();
+            for &page_id in pages {
+                let Ok(page) = doc.get_dictionary(page_id) else {
+                    continue;
+                };
+                match page.get(key) {
+                    Ok(Object::Reference(id)) => {
+                        ids.insert(*id);
+                        if let Ok(Object::Array(items)) = doc.get_object(*id) {
+                            ids.extend(items.iter().filter_map(|o| o.as_reference().ok()));
                         }
                     }
+                    Ok(Object::Array(items)) => {
+                        ids.extend(items.iter().filter_map(|o| o.as_reference().ok()));
+                    }
+                    _ => {}
+                }
+            }
+            ids
+        };
+        let mut orphans = HashSet::new();
+        for key in [b"Annots".as_slice(), b"Contents"] {
+            let shared = page_refs(&self.inner, kept, key);
+            orphans.extend(
+                page_refs(&self.inner, removed, key)
+                    .into_iter()
+                    .filter(|id| !shared.contains(id)),
+            );
+        }
 
-                    // Now add the streams and build the new array
-                    let mut new_arr = Vec::new();
-                    for stream in streams_to_add {
-                        let new_stream_id = self.inner.add_object(stream);
-                        new_arr.push(Object::Reference(new_stream_id));
+        // Form fields whose widgets are all gone
+        if form::acroform(&self.inner).is_some() {
+            let acroform_id = self.acroform_id()?;
+            let fields = self
+                .inner
+                .get_dictionary(acroform_id)?
+                .get(b"Fields")
+                .cloned();
+            match fields {
+                Ok(Object::Reference(id)) => {
+                    if let Ok(Object::Array(fields)) = self.inner.get_object(id) {
+                        let fields = self.retain_fields(&fields.clone(), &orphans, 0)?;
+                        self.inner.objects.insert(id, Object::Array(fields));
                     }
-                    new_page_dict.set(b"Contents", Object::Array(new_arr));
+                }
+                Ok(Object::Array(fields)) => {
+                    let fields = self.retain_fields(&fields, &orphans, 0)?;
+                    self.inner
+                        .get_dictionary_mut(acroform_id)?
+                        .set("Fields", Object::Array(fields));
                 }
                 _ => {}
             }
         }
 
-        // Create the new page object
-        let new_page_id = self.inner.add_object(new_page_dict.clone());
+        self.remove_bookmarks(removed)?;
 
-        // Get the root Pages object
-        let trailer =
-            self.inner.trailer.get(b"Root").map_err(|_| {
-                PdfError::ParseError("Document trailer missing Root entry".to_string())
-            })?;
-        let catalog_id = trailer
-            .as_reference()
-            .map_err(|_| PdfError::ParseError("Root is not a reference".to_string()))?;
-        let catalog_obj = self.inner.get_object(catalog_id)?;
-        let catalog_dict = catalog_obj
-            .as_dict()
-            .map_err(|_| PdfError::ParseError("Catalog is not a dictionary".to_string()))?;
-        let pages_ref = catalog_dict
-            .get(b"Pages")
-            .map_err(|_| PdfError::ParseError("Catalog missing Pages entry".to_string()))?;
-        let pages_id = pages_ref
-            .as_reference()
-            .map_err(|_| PdfError::ParseError("Pages is not a reference".to_string()))?;
+        self.buffered_field_appearances
+            .retain(|appearance| !orphans.contains(&appearance.widget_id));
+        for id in removed.iter().chain(&orphans) {
+            self.inner.objects.remove(id);
+        }
+        Ok(())
+    }
 
-        // Get the Pages object and update its Kids array
-        let pages_obj = self.inner.get_object(pages_id)?;
-        let pages_dict = pages_obj
-            .as_dict()
-            .map_err(|_| PdfError::ParseError("Pages object is not a dictionary".to_string()))?;
+    /// Fields of `fields` that still have a widget outside `removed`
+    fn retain_fields(
+        &mut self,
+        fields: &[Object],
+        removed: &HashSet<ObjectId>,
+        depth: usize,
+    ) -> Result<Vec<Object>> {
+        let mut kept = Vec::new();
+        for field in fields {
+            let Ok(id) = field.as_reference() else {
+                kept.push(field.clone());
+                continue;
+            };
+            if removed.contains(&id) {
+                continue;
+            }
 
-        // Get the current Kids array
-        let kids = pages_dict
-            .get(b"Kids")
-            .map_err(|_| PdfError::ParseError("Pages object missing Kids array".to_string()))?;
-        let mut kids_array = kids
-            .as_array()
-            .map_err(|_| PdfError::ParseError("Kids is not an array".to_string()))?
-            .clone();
+            let kids = self
+                .inner
+                .get_dictionary(id)
+                .and_then(|dict| dict.get(b"Kids"))
+                .and_then(Object::as_array)
+                .cloned();
+            if let (Ok(kids), true) = (kids, depth < 32) {
+                let remaining = self.retain_fields(&kids, removed, depth + 1)?;
+                if remaining.is_empty() && !kids.is_empty() {
+                    continue;
+                }
+                self.inner
+                    .get_dictionary_mut(id)?
+                    .set("Kids", Object::Array(remaining));
+            }
+            kept.push(field.clone());
+        }
+        Ok(kept)
+    }
 
-        // Add the new page to the Kids array
-        kids_array.push(Object::Reference(new_page_id));
+    /// Unlink top-level bookmarks that open one of `pages`
+    fn remove_bookmarks(&mut self, pages: &[ObjectId]) -> Result<()> {
+        let outlines_id = match self.inner.catalog()?.get(b"Outlines") {
+            Ok(Object::Reference(id)) => *id,
+            _ => return Ok(()),
+        };
 
-        // Update the Count in the Pages object
-        let count = pages_dict
-            .get(b"Count")
-            .map_err(|_| PdfError::ParseError("Pages object missing Count".to_string()))?;
-        let current_count = count
-            .as_i64()
-            .map_err(|_| PdfError::ParseError("Count is not an integer".to_string()))?;
+        let mut items = Vec::new();
+        let mut next = self
+            .inner
+            .get_dictionary(outlines_id)?
+            .get(b"First")
+            .and_then(Object::as_reference)
+            .ok();
+        while let Some(id) = next.filter(|id| !items.contains(id)) {
+            items.push(id);
+            next = self
+                .inner
+                .get_dictionary(id)?
+                .get(b"Next")
+                .and_then(Object::as_reference)
+                .ok();
+        }
 
-        // Create updated Pages dictionary
-        let mut new_pages_dict = pages_dict.clone();
-        new_pages_dict.set(b"Kids", Object::Array(kids_array));
-        new_pages_dict.set(b"Count", Object::Integer(current_count + 1));
+        let opens_removed = |doc: &Document, id: ObjectId| {
+            let Ok(item) = doc.get_dictionary(id) else {
+                return false;
+            };
+            let dest = item.get(b"Dest").or_else(|_| {
+                item.get_deref(b"A", doc)
+                    .and_then(Object::as_dict)
+                    .and_then(|action| action.get(b"D"))
+            });
+            dest.and_then(Object::as_array)
+                .ok()
+                .and_then(|dest| dest.first())
+                .and_then(|target| target.as_reference().ok())
+                .is_some_and(|target| pages.contains(&target))
+        };
+        let (removed, kept): (Vec<ObjectId>, Vec<ObjectId>) = items
+            .into_iter()
+            .partition(|&id| opens_removed(&self.inner, id));
+        if removed.is_empty() {
+            return Ok(());
+        }
 
-        // Replace the Pages object
-        self.inner.objects.insert(pages_id, new_pages_dict.into());
+        for (index, &id) in kept.iter().enumerate() {
+            let item = self.inner.get_dictionary_mut(id)?;
+            item.remove(b"Prev");
+            item.remove(b"Next");
+            if index > 0 {
+                item.set("Prev", Object::Reference(kept[index - 1]));
+            }
+            if let Some(&next) = kept.get(index + 1) {
+                item.set("Next", Object::Reference(next));
+            }
+        }
+        for id in &removed {
+            self.inner.objects.remove(id);
+        }
 
-        // Copy font resource mappings from source page to new page
-        // This ensures the cloned content stream's font references remain valid
-        if let Some(source_font_resources) = self.page_font_resources.get(&page).cloned() {
-            self.page_font_resources
-                .insert(page_count + 1, source_font_resources);
+        // Each kept item is visible, and so are the descendants an open item counts
+        let count: i64 = kept
+            .iter()
+            .map(|&id| {
+                let open = self
+                    .inner
+                    .get_dictionary(id)
+                    .and_then(|item| item.get(b"Count"))
+                    .and_then(Object::as_i64)
+                    .unwrap_or(0);
+                1 + open.max(0)
+            })
+            .sum();
+        let outlines = self.inner.get_dictionary_mut(outlines_id)?;
+        match (kept.first(), kept.last()) {
+            (Some(&first), Some(&last)) => {
+                outlines.set("First", Object::Reference(first));
+                outlines.set("Last", Object::Reference(last));
+            }
+            _ => {
+                outlines.remove(b"First");
+                outlines.remove(b"Last");
+            }
         }
+        outlines.set("Count", Object::Integer(count));
+        Ok(())
+    }
 
-        // Also copy image resource mappings
-        if let Some(source_image_resources) = self.page_image_resources.get(&page).cloned() {
-            self.page_image_resources
-                .insert(page_count + 1, source_image_resources);
+    /// Copy the objects reachable from `roots` in `source` into this document
+    ///
+    /// `id_map` maps source object IDs to IDs in this document; objects
+    /// already mapped are shared, not copied. The page tree and catalog of
+    /// `source` are left behind (references to them become null).
+    fn import_objects(
+        &mut self,
+        source: &Document,
+        roots: &[ObjectId],
+        id_map: &mut HashMap<ObjectId, ObjectId>,
+    ) -> Result<()> {
+        let mut copied = Vec::new();
+        let mut pending: Vec<ObjectId> = roots.to_vec();
+
+        while let Some(id) = pending.pop() {
+            if id_map.contains_key(&id) {
+                continue;
+            }
+            let Ok(object) = source.get_object(id) else {
+                continue;
+            };
+            let kind = match object {
+                Object::Dictionary(dict) => dict.get(b"Type").and_then(Object::as_name).ok(),
+                _ => None,
+            };
+            if matches!(kind, Some(b"Pages" | b"Catalog")) {
+                continue;
+            }
+
+            id_map.insert(id, self.inner.new_object_id());
+            copied.push(id);
+            object_references(object, &mut pending);
         }
 
-        // Return the new page number (1-indexed)
-        Ok(page_count + 1)
+        for id in copied {
+            let mut object = source.get_object(id)?.clone();
+            remap_references(&mut object, id_map);
+            self.inner.objects.insert(id_map[&id], object);
+        }
+
+        Ok(())
     }
+}
 
-    /// Get all page object IDs in order
-    ///
-    /// Returns a vector of ObjectId values representing all pages in the document.
-    ///
-    /// # Example
-    /// ```ignore
-    /// let doc = PdfDocument::open("document.pdf")?;
-    /// let page_ids = doc.get_page_ids();
-    /// println!("Document has {} pages", page_ids.len());
-    /// ```
-    pub fn get_page_ids(&self) -> Vec<ObjectId> {
-        let pages = self.inner.get_pages();
-        pages.values().copied().collect()
+/// Numbers of a box array ([x0 y0 x1 y1])
+fn box_numbers(object: &Object) -> Option<[f64; 4]> {
+    let values = object.as_array().ok()?;
+    let number = |v: &Object| {
+        v.as_f32()
+            .map(|v| v as f64)
+            .ok()
+            .or_else(|| v.as_i64().ok().map(|v| v as f64))
+    };
+    match values.as_slice() {
+        [x0, y0, x1, y1] => Some([number(x0)?, number(y0)?, number(x1)?, number(y1)?]),
+        _ => None,
+    }
+}
+
+/// Merge another copy of a font into `font`, which must have the same data
+fn merge_font_data(font: &mut FontData, other: &FontData) -> Result<()> {
+    if font.ttf_data != other.ttf_data {
+        return Err(PdfError::FontAlreadyExists(format!(
+            "{} (different font data in merged documents)",
+            other.name
+        )));
+    }
+    font.merge_usage(other);
+    Ok(())
+}
+
+/// Embedded font programs of a document (content hash -> stream object ID)
+fn font_file_streams(doc: &Document) -> HashMap<u64, ObjectId> {
+    let mut streams = HashMap::new();
+    for object in doc.objects.values() {
+        let Object::Dictionary(dict) = object else {
+            continue;
+        };
+        for key in [b"FontFile".as_slice(), b"FontFile2", b"FontFile3"] {
+            let Ok(id) = dict.get(key).and_then(Object::as_reference) else {
+                continue;
+            };
+            if let Ok(Object::Stream(stream)) = doc.get_object(id) {
+                let mut hasher = DefaultHasher::new();
+                format!("{:?}", stream.dict).hash(&mut hasher);
+                stream.content.hash(&mut hasher);
+                streams.entry(hasher.finish()).or_insert(id);
+            }
+        }
+    }
+    streams
+}
+
+/// Collect the object IDs an object refers to
+///
+/// A page's /Parent is skipped so copying a page does not pull in its tree.
+fn object_references(object: &Object, references: &mut Vec<ObjectId>) {
+    match object {
+        Object::Reference(id) => references.push(*id),
+        Object::Array(items) => {
+            for item in items {
+                object_references(item, references);
+            }
+        }
+        Object::Dictionary(dict) => dictionary_references(dict, references),
+        Object::Stream(stream) => dictionary_references(&stream.dict, references),
+        _ => {}
+    }
+}
+
+fn dictionary_references(dict: &Dictionary, references: &mut Vec<ObjectId>) {
+    let is_page = dict
+        .get(b"Type")
+        .and_then(Object::as_name)
+        .is_ok_and(|kind| kind == b"Page");
+    for (key, value) in dict.iter() {
+        if !(is_page && key == b"Parent") {
+            object_references(value, references);
+        }
+    }
+}
+
+/// Point references at the copied objects (null when not copied)
+fn remap_references(object: &mut Object, id_map: &HashMap<ObjectId, ObjectId>) {
+    match object {
+        Object::Reference(id) => {
+            *object = match id_map.get(id) {
+                Some(new_id) => Object::Reference(*new_id),
+                None => Object::Null,
+            };
+        }
+        Object::Array(items) => {
+            for item in items {
+                remap_references(item, id_map);
+            }
+        }
+        Object::Dictionary(dict) => {
+            for (_, value) in dict.iter_mut() {
+                remap_references(value, id_map);
+            }
+        }
+        Object::Stream(stream) => {
+            for (_, value) in stream.dict.iter_mut() {
+                remap_references(value, id_map);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Add a content stream reference at the start or end of a Contents array
+fn add_stream_ref(contents: &mut Vec<Object>, stream_id: ObjectId, prepend: bool) {
+    if prepend {
+        contents.insert(0, Object::Reference(stream_id));
+    } else {
+        contents.push(Object::Reference(stream_id));
+    }
+}
+
+/// Move per-page entries to their new page numbers, dropping deleted pages
+fn rekey_pages<T>(map: &mut HashMap<usize, T>, new_numbers: &HashMap<usize, usize>) {
+    *map = std::mem::take(map)
+        .into_iter()
+        .filter_map(|(page, value)| Some((*new_numbers.get(&page)?, value)))
+        .collect();
+}
+
+/// Dictionary of a Form XObject covering `width` x `height`
+fn form_xobject_dict(width: f64, height: f64) -> Dictionary {
+    let mut dict = Dictionary::new();
+    dict.set("Type", Object::Name(b"XObject".to_vec()));
+    dict.set("Subtype", Object::Name(b"Form".to_vec()));
+    dict.set(
+        "BBox",
+        Object::Array(vec![
+            Object::Integer(0),
+            Object::Integer(0),
+            Object::Real(width as f32),
+            Object::Real(height as f32),
+        ]),
+    );
+    dict
+}
+
+/// Form XObject dictionary of a widget appearance, turned by `matrix`
+fn appearance_xobject_dict(width: f64, height: f64, matrix: Option<[f64; 6]>) -> Dictionary {
+    let mut dict = form_xobject_dict(width, height);
+    if let Some(matrix) = matrix {
+        dict.set(
+            "Matrix",
+            Object::Array(matrix.iter().map(|&v| Object::Real(v as f32)).collect()),
+        );
     }
+    dict
 }
 
 #[cfg(test)]
@@ -1719,4 +4932,46 @@ mod tests {
         // For now, just verify the struct compiles
         let _align = Align::Left;
     }
+
+    #[test]
+    fn test_page_geometry() {
+        let visible = [10.0, 20.0, 110.0, 220.0];
+
+        let upright = PageGeometry::new([0.0, 0.0, 100.0, 200.0], 0);
+        assert_eq!(
+            (upright.width, upright.height, upright.matrix),
+            (100.0, 200.0, None)
+        );
+
+        // The view's top-left corner is a different page corner per rotation
+        let corners = [
+            (0, (10.0, 220.0)),
+            (90, (10.0, 20.0)),
+            (180, (110.0, 20.0)),
+            (-90, (110.0, 220.0)),
+        ];
+        for (rotate, corner) in corners {
+            let geometry = PageGeometry::new(visible, rotate);
+            let expected = if rotate % 180 == 0 {
+                (100.0, 200.0)
+            } else {
+                (200.0, 100.0)
+            };
+            assert_eq!((geometry.width, geometry.height), expected);
+            assert_eq!(geometry.pdf_point(0.0, geometry.height), corner);
+            assert_eq!(
+                geometry.view_point(corner.0, corner.1),
+                (0.0, geometry.height)
+            );
+
+            let rect = Rect::new(5.0, 10.0, 30.0, 40.0);
+            assert_eq!(geometry.view_rect(geometry.pdf_rect(rect)), rect);
+        }
+
+        let rotated = PageGeometry::new(visible, 90);
+        assert_eq!(
+            rotated.pdf_rect(Rect::new(0.0, 0.0, 30.0, 40.0)),
+            [10.0, 20.0, 50.0, 50.0]
+        );
+    }
 }
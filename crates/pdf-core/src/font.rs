@@ -1,8 +1,9 @@
 //! Font handling for PDF documents
 
+use crate::text::PositionedGlyph;
 use crate::{PdfError, Result};
 use lopdf::{Dictionary, Object, Stream};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use subsetter::GlyphRemapper;
 
 /// Font weight
@@ -30,14 +31,48 @@ pub struct FontData {
     pub ttf_data: Vec<u8>,
     /// Characters used (for subsetting)
     pub used_chars: HashSet<char>,
+    /// Glyphs produced by shaping (original GIDs, for subsetting)
+    pub used_glyphs: HashSet<u16>,
+    /// Unicode text for shaped glyphs not reachable through the cmap
+    glyph_text: HashMap<u16, String>,
     /// Parsed font face
     face: Option<ttf_parser::Face<'static>>,
+    /// OpenType shaping face (GSUB/GPOS)
+    shaper: Option<Shaper>,
     /// Subset TTF data (created during save)
     subset_data: Option<Vec<u8>>,
     /// Glyph remapper (maps old GID -> new GID after subsetting)
     glyph_remapper: Option<GlyphRemapper>,
 }
 
+/// OpenType shaping face wrapper
+#[derive(Clone)]
+struct Shaper(rustybuzz::Face<'static>);
+
+impl std::fmt::Debug for Shaper {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Shaper")
+    }
+}
+
+/// A glyph produced by the shaping stage
+///
+/// All metrics are in font units and the glyph ID refers to the original
+/// (non-subsetted) font.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShapedGlyph {
+    /// Glyph ID in the original font
+    pub gid: u16,
+    /// Byte offset of the source cluster in the shaped text
+    pub cluster: usize,
+    /// Horizontal advance (includes kerning)
+    pub x_advance: i32,
+    /// Horizontal placement offset (mark positioning)
+    pub x_offset: i32,
+    /// Vertical placement offset (mark positioning)
+    pub y_offset: i32,
+}
+
 /// PDF objects generated for font embedding
 pub struct FontObjects {
     /// Type0 font dictionary
@@ -239,12 +274,16 @@ impl FontData {
 
         let face = ttf_parser::Face::parse(static_data, 0)
             .map_err(|e| PdfError::FontParseError(format!("{e:?}")))?;
+        let shaper = rustybuzz::Face::from_face(face.clone());
 
         Ok(Self {
             name: name.to_string(),
             ttf_data: data,
             used_chars: HashSet::new(),
+            used_glyphs: HashSet::new(),
+            glyph_text: HashMap::new(),
             face: Some(face),
+            shaper: Some(Shaper(shaper)),
             subset_data: None,
             glyph_remapper: None,
         })
@@ -257,6 +296,110 @@ impl FontData {
         }
     }
 
+    /// Shape text and record the resulting glyphs for subsetting
+    ///
+    /// Returns the shaped glyphs so they can be encoded at save time.
+    pub fn add_text(&mut self, text: &str) -> Vec<ShapedGlyph> {
+        self.add_chars(text);
+        let glyphs = self.shape(text);
+        self.record_glyphs(text, &glyphs);
+        glyphs
+    }
+
+    /// Add the characters and glyphs used with another copy of this font
+    ///
+    /// Lets documents that share a font embed one subset covering all
+    /// of their text.
+    pub(crate) fn merge_usage(&mut self, other: &FontData) {
+        self.used_chars.extend(&other.used_chars);
+        self.used_glyphs.extend(&other.used_glyphs);
+        for (gid, text) in &other.glyph_text {
+            self.glyph_text.entry(*gid).or_insert_with(|| text.clone());
+        }
+    }
+
+    /// Shape text with the font's GSUB/GPOS tables
+    ///
+    /// Applies substitutions (e.g. SARA AM decomposition, tone mark
+    /// alternates), mark-to-base/mark-to-mark positioning and kerning.
+    /// Without a parsed face, each character maps to one glyph.
+    pub fn shape(&self, text: &str) -> Vec<ShapedGlyph> {
+        let Some(Shaper(face)) = &self.shaper else {
+            return text
+                .char_indices()
+                .map(|(cluster, c)| ShapedGlyph {
+                    gid: self.glyph_id(c).unwrap_or(0),
+                    cluster,
+                    x_advance: self.glyph_advance(c).unwrap_or(0) as i32,
+                    x_offset: 0,
+                    y_offset: 0,
+                })
+                .collect();
+        };
+
+        let mut buffer = rustybuzz::UnicodeBuffer::new();
+        buffer.push_str(text);
+        buffer.guess_segment_properties();
+
+        let output = rustybuzz::shape(face, &[], buffer);
+        output
+            .glyph_infos()
+            .iter()
+            .zip(output.glyph_positions())
+            .map(|(info, pos)| ShapedGlyph {
+                gid: info.glyph_id as u16,
+                cluster: info.cluster as usize,
+                x_advance: pos.x_advance,
+                x_offset: pos.x_offset,
+                y_offset: pos.y_offset,
+            })
+            .collect()
+    }
+
+    /// Record shaped glyphs and their Unicode text for the ToUnicode CMap
+    ///
+    /// Glyphs that are not the cmap glyph of a character in their cluster
+    /// (ligatures, alternates, decompositions) take the cluster characters
+    /// whose own glyph did not survive shaping.
+    fn record_glyphs(&mut self, text: &str, glyphs: &[ShapedGlyph]) {
+        let mut starts: Vec<usize> = glyphs.iter().map(|g| g.cluster).collect();
+        starts.sort_unstable();
+        starts.dedup();
+
+        for (i, &start) in starts.iter().enumerate() {
+            let end = starts.get(i + 1).copied().unwrap_or(text.len());
+            let cluster_text = text.get(start..end).unwrap_or_default();
+            let cluster_gids: Vec<u16> = glyphs
+                .iter()
+                .filter(|g| g.cluster == start)
+                .map(|g| g.gid)
+                .collect();
+            let cmap_gids: Vec<u16> = cluster_text
+                .chars()
+                .filter_map(|c| self.glyph_id(c))
+                .collect();
+
+            let missing: String = cluster_text
+                .chars()
+                .filter(|&c| {
+                    self.glyph_id(c)
+                        .map(|gid| !cluster_gids.contains(&gid))
+                        .unwrap_or(true)
+                })
+                .collect();
+            let mut missing = Some(missing).filter(|m| !m.is_empty());
+
+            for gid in cluster_gids {
+                self.used_glyphs.insert(gid);
+                if !cmap_gids.contains(&gid) {
+                    if let Some(text) = missing.take() {
+                        self.glyph_text.entry(gid).or_insert(text);
+                    }
+                }
+            }
+        }
+    }
+
     /// Create a subset of the font containing only used glyphs
     ///
     /// This should be called during save, after all text has been rendered.
@@ -273,6 +416,11 @@ impl FontData {
             }
         }
 
+        // Add glyphs produced by shaping (substitutions may add new ones)
+        for &gid in &self.used_glyphs {
+            remapper.remap(gid);
+        }
+
         // Create subset
         let subset = subsetter::subset(&self.ttf_data, 0, &remapper)
             .map_err(|e| crate::PdfError::FontSubsetError(format!("{:?}", e)))?;
@@ -296,6 +444,16 @@ impl FontData {
         remapper.get(old_gid)
     }
 
+    /// Get remapped glyph ID for an original glyph ID
+    ///
+    /// Falls back to the original GID if the font hasn't been subsetted.
+    pub fn remap_gid(&self, gid: u16) -> u16 {
+        self.glyph_remapper
+            .as_ref()
+            .and_then(|r| r.get(gid))
+            .unwrap_or(gid)
+    }
+
     /// Check if font has been subsetted
     pub fn is_subsetted(&self) -> bool {
         self.subset_data.is_some()
@@ -321,6 +479,19 @@ impl FontData {
         })
     }
 
+    /// Get glyph advance width in PDF glyph space (1000 units per em)
+    ///
+    /// This is the width written to the /W array, i.e. the distance a PDF
+    /// viewer advances after showing the glyph.
+    pub fn glyph_width_1000(&self, gid: u16) -> f64 {
+        let advance = self
+            .face
+            .as_ref()
+            .and_then(|face| face.glyph_hor_advance(ttf_parser::GlyphId(gid)))
+            .unwrap_or(1000);
+        advance as f64 * 1000.0 / self.units_per_em() as f64
+    }
+
     /// Get font units per em
     pub fn units_per_em(&self) -> u16 {
         self.face
@@ -345,12 +516,10 @@ impl FontData {
             .unwrap_or(-200)
     }
 
-    /// Calculate text width in font units
+    /// Calculate shaped text width in font units
     pub fn text_width(&self, text: &str) -> u32 {
-        text.chars()
-            .filter_map(|c| self.glyph_advance(c))
-            .map(|w| w as u32)
-            .sum()
+        let width: i32 = self.shape(text).iter().map(|g| g.x_advance).sum();
+        width.max(0) as u32
     }
 
     /// Calculate text width in points for a given font size
@@ -362,10 +531,59 @@ impl FontData {
 
     /// Generate all PDF objects needed to embed this font
     pub fn to_pdf_objects(&self) -> Result<FontObjects> {
+        // Use subset data if available, otherwise use full font
+        let font_data_to_embed = self.subset_data.as_ref().unwrap_or(&self.ttf_data);
+        Ok(self.build_pdf_objects(
+            font_data_to_embed,
+            self.generate_widths_array(),
+            self.generate_tounicode_cmap(),
+        ))
+    }
+
+    /// Generate PDF objects embedding the whole font, for editable fields
+    ///
+    /// The subset drops the `cmap` table and renumbers glyphs, so viewers
+    /// cannot map typed characters to glyphs with it. This embeds the
+    /// original font program with its original glyph IDs instead.
+    pub fn to_full_pdf_objects(&self) -> Result<FontObjects> {
+        let mut mappings: Vec<(u16, String)> = Vec::new();
+        let mut widths = Vec::new();
+        if let Some(face) = &self.face {
+            if let Some(cmap) = face.tables().cmap {
+                let mut seen = HashSet::new();
+                for subtable in cmap.subtables.into_iter().filter(|t| t.is_unicode()) {
+                    subtable.codepoints(|cp| {
+                        let gid =
+                            char::from_u32(cp).and_then(|c| Some((c, subtable.glyph_index(cp)?.0)));
+                        if let Some((c, gid)) = gid.filter(|&(_, gid)| gid != 0) {
+                            if seen.insert(gid) {
+                                mappings.push((gid, c.to_string()));
+                            }
+                        }
+                    });
+                }
+            }
+            mappings.sort();
+
+            let all: Vec<Object> = (0..face.number_of_glyphs())
+                .map(|gid| (self.glyph_width_1000(gid).round() as i64).into())
+                .collect();
+            widths = vec![0.into(), all.into()];
+        }
+
+        Ok(self.build_pdf_objects(&self.ttf_data, widths, tounicode_cmap(&mappings)))
+    }
+
+    /// Build the font dictionaries around a font program
+    fn build_pdf_objects(
+        &self,
+        font_data_to_embed: &[u8],
+        widths_array: Vec<Object>,
+        tounicode_content: String,
+    ) -> FontObjects {
         let font_name = Object::Name(self.name.clone().into());
 
         // Generate ToUnicode CMap
-        let tounicode_content = self.generate_tounicode_cmap();
         let tounicode_stream = Stream::new(
             Dictionary::from_iter(vec![
                 ("Type", "CMap".into()),
@@ -374,13 +592,10 @@ impl FontData {
             tounicode_content.as_bytes().to_vec(),
         );
 
-        // Use subset data if available, otherwise use full font
-        let font_data_to_embed = self.subset_data.as_ref().unwrap_or(&self.ttf_data);
-
         // Generate font file stream
         let font_file_stream = Stream::new(
             Dictionary::from_iter(vec![("Length1", (font_data_to_embed.len() as i32).into())]),
-            font_data_to_embed.clone(),
+            font_data_to_embed.to_vec(),
         );
 
         // Generate font descriptor
@@ -409,9 +624,6 @@ impl FontData {
             ("FontFile2", Object::Reference((0, 0))), // Placeholder, will be set when embedding
         ]);
 
-        // Generate widths array
-        let widths_array = self.generate_widths_array();
-
         // Generate CIDFont Type2 dictionary
         let cid_system_info = Dictionary::from_iter(vec![
             ("Registry", "Adobe".into()),
@@ -439,13 +651,13 @@ impl FontData {
             ("ToUnicode", Object::Reference((0, 0))), // Placeholder, will be set when embedding
         ]);
 
-        Ok(FontObjects {
+        FontObjects {
             type0_font,
             cid_font,
             font_descriptor,
             font_file_stream,
             tounicode_stream,
-        })
+        }
     }
 
     /// Encode text as hex string for PDF Tj operator
@@ -478,6 +690,24 @@ impl FontData {
         format!("<{result}>")
     }
 
+    /// Convert shaped glyphs to positioned glyphs for the content stream
+    ///
+    /// Remaps glyph IDs to the subset (call after create_subset()) and
+    /// scales metrics to PDF glyph space (1000 units per em).
+    pub fn position_glyphs(&self, glyphs: &[ShapedGlyph]) -> Vec<PositionedGlyph> {
+        let scale = 1000.0 / self.units_per_em() as f64;
+        glyphs
+            .iter()
+            .map(|g| PositionedGlyph {
+                gid: self.remap_gid(g.gid),
+                width: self.glyph_width_1000(g.gid),
+                x_advance: g.x_advance as f64 * scale,
+                x_offset: g.x_offset as f64 * scale,
+                y_offset: g.y_offset as f64 * scale,
+            })
+            .collect()
+    }
+
     /// Generate /W array for glyph widths
     fn generate_widths_array(&self) -> Vec<Object> {
         let mut widths = Vec::new();
@@ -486,11 +716,12 @@ impl FontData {
             None => return widths,
         };
 
-        // Collect unique GIDs used in the document
+        // Collect unique GIDs used in the document (cmap and shaped glyphs)
         let mut gids: Vec<u16> = self
             .used_chars
             .iter()
             .filter_map(|&c| self.glyph_id(c))
+            .chain(self.used_glyphs.iter().copied())
             .collect();
         gids.sort();
         gids.dedup();
@@ -504,16 +735,13 @@ impl FontData {
         // This is less optimal than ranges but works correctly for any GID distribution
         for old_gid in gids {
             // Use remapped GID if font has been subsetted
-            let new_gid = self
-                .glyph_remapper
-                .as_ref()
-                .and_then(|r| r.get(old_gid))
-                .unwrap_or(old_gid);
+            let new_gid = self.remap_gid(old_gid);
 
             let glyph_id = ttf_parser::GlyphId(old_gid);
-            let advance = face.glyph_hor_advance(glyph_id).unwrap_or(1000);
+            let advance = face.glyph_hor_advance(glyph_id).unwrap_or(1000) as f64;
+            let width = (advance * 1000.0 / self.units_per_em() as f64).round() as i64;
             widths.push(new_gid.into());
-            widths.push(vec![advance.into()].into());
+            widths.push(vec![width.into()].into());
         }
 
         widths
@@ -521,56 +749,74 @@ impl FontData {
 
     /// Generate ToUnicode CMap stream content
     fn generate_tounicode_cmap(&self) -> String {
-        let mut cmap = String::new();
-
-        // Header
-        cmap.push_str("/CIDInit /ProcSet findresource begin\n");
-        cmap.push_str("12 dict begin\n");
-        cmap.push_str("begincmap\n");
-        cmap.push_str("/CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n");
-        cmap.push_str("/CMapName /Adobe-Identity-UCS def\n");
-        cmap.push_str("/CMapType 2 def\n");
-
-        // Code space range (all 16-bit values)
-        cmap.push_str("1 begincodespacerange\n");
-        cmap.push_str("<0000> <FFFF>\n");
-        cmap.push_str("endcodespacerange\n");
-
         // Character mappings: map GID (CID) to Unicode codepoint
         let mut char_list: Vec<char> = self.used_chars.iter().copied().collect();
         char_list.sort_by_key(|c| *c as u32);
 
-        if !char_list.is_empty() {
-            // PDF spec recommends limiting bfchar sections to 100 entries
-            let chunks: Vec<_> = char_list.chunks(100).collect();
-
-            for chunk in chunks {
-                cmap.push_str(&format!("{} beginbfchar\n", chunk.len()));
-                for c in chunk {
-                    let old_gid = self.glyph_id(*c).unwrap_or(0);
-                    // Use remapped GID if font has been subsetted
-                    let gid = self
-                        .glyph_remapper
-                        .as_ref()
-                        .and_then(|r| r.get(old_gid))
-                        .unwrap_or(old_gid);
-                    let unicode = *c as u32;
-                    cmap.push_str(&format!("<{gid:04X}> <{unicode:04X}>\n"));
-                }
-                cmap.push_str("endbfchar\n");
-            }
+        let mut mappings: Vec<(u16, String)> = Vec::new();
+        let mut mapped_gids: HashSet<u16> = HashSet::new();
+        for c in char_list {
+            let old_gid = self.glyph_id(c).unwrap_or(0);
+            mapped_gids.insert(old_gid);
+            mappings.push((old_gid, c.to_string()));
         }
 
-        // Footer
-        cmap.push_str("endcmap\n");
-        cmap.push_str("CMapName currentdict /CMap defineresource pop\n");
-        cmap.push_str("end\n");
-        cmap.push_str("end\n");
+        // Shaped glyphs without a cmap entry (ligatures, alternates)
+        let mut extra: Vec<(&u16, &String)> = self
+            .glyph_text
+            .iter()
+            .filter(|(gid, _)| !mapped_gids.contains(gid))
+            .collect();
+        extra.sort();
+        mappings.extend(extra.into_iter().map(|(gid, text)| (*gid, text.clone())));
 
-        cmap
+        // Use remapped GIDs if font has been subsetted
+        for (gid, _) in &mut mappings {
+            *gid = self.remap_gid(*gid);
+        }
+        tounicode_cmap(&mappings)
     }
 }
 
+/// ToUnicode CMap content mapping CIDs to the text they show
+fn tounicode_cmap(mappings: &[(u16, String)]) -> String {
+    let mut cmap = String::new();
+
+    // Header
+    cmap.push_str("/CIDInit /ProcSet findresource begin\n");
+    cmap.push_str("12 dict begin\n");
+    cmap.push_str("begincmap\n");
+    cmap.push_str("/CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n");
+    cmap.push_str("/CMapName /Adobe-Identity-UCS def\n");
+    cmap.push_str("/CMapType 2 def\n");
+
+    // Code space range (all 16-bit values)
+    cmap.push_str("1 begincodespacerange\n");
+    cmap.push_str("<0000> <FFFF>\n");
+    cmap.push_str("endcodespacerange\n");
+
+    // PDF spec recommends limiting bfchar sections to 100 entries
+    for chunk in mappings.chunks(100) {
+        cmap.push_str(&format!("{} beginbfchar\n", chunk.len()));
+        for (gid, text) in chunk {
+            let unicode: String = text
+                .encode_utf16()
+                .map(|unit| format!("{unit:04X}"))
+                .collect();
+            cmap.push_str(&format!("<{gid:04X}> <{unicode}>\n"));
+        }
+        cmap.push_str("endbfchar\n");
+    }
+
+    // Footer
+    cmap.push_str("endcmap\n");
+    cmap.push_str("CMapName currentdict /CMap defineresource pop\n");
+    cmap.push_str("end\n");
+    cmap.push_str("end\n");
+
+    cmap
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -601,7 +847,10 @@ mod tests {
             name: "test".to_string(),
             ttf_data: ttf_data.clone(),
             used_chars: HashSet::new(),
+            used_glyphs: HashSet::new(),
+            glyph_text: HashMap::new(),
             face: None,
+            shaper: None,
             subset_data: None,
             glyph_remapper: None,
         };
@@ -621,7 +870,10 @@ mod tests {
             name: "test".to_string(),
             ttf_data,
             used_chars: HashSet::new(),
+            used_glyphs: HashSet::new(),
+            glyph_text: HashMap::new(),
             face: None,
+            shaper: None,
             subset_data: None,
             glyph_remapper: None,
         };
@@ -642,7 +894,10 @@ mod tests {
             name: "test".to_string(),
             ttf_data,
             used_chars: HashSet::new(),
+            used_glyphs: HashSet::new(),
+            glyph_text: HashMap::new(),
             face: None,
+            shaper: None,
             subset_data: None,
             glyph_remapper: None,
         };
@@ -662,7 +917,10 @@ mod tests {
             name: "test".to_string(),
             ttf_data: vec![0u8; 100],
             used_chars: HashSet::new(),
+            used_glyphs: HashSet::new(),
+            glyph_text: HashMap::new(),
             face: None,
+            shaper: None,
             subset_data: None,
             glyph_remapper: None,
         };
@@ -677,7 +935,10 @@ mod tests {
             name: "test".to_string(),
             ttf_data: vec![0u8; 100],
             used_chars: HashSet::new(),
+            used_glyphs: HashSet::new(),
+            glyph_text: HashMap::new(),
             face: None,
+            shaper: None,
             subset_data: None,
             glyph_remapper: None,
         };
@@ -695,7 +956,10 @@ mod tests {
             name: "test".to_string(),
             ttf_data: vec![0u8; 100],
             used_chars: HashSet::new(),
+            used_glyphs: HashSet::new(),
+            glyph_text: HashMap::new(),
             face: None,
+            shaper: None,
             subset_data: None,
             glyph_remapper: None,
         };
@@ -710,7 +974,10 @@ mod tests {
             name: "test".to_string(),
             ttf_data: vec![0u8; 100],
             used_chars: HashSet::new(),
+            used_glyphs: HashSet::new(),
+            glyph_text: HashMap::new(),
             face: None,
+            shaper: None,
             subset_data: None,
             glyph_remapper: None,
         };
@@ -725,7 +992,10 @@ mod tests {
             name: "test".to_string(),
             ttf_data: vec![0u8; 100],
             used_chars: HashSet::new(),
+            used_glyphs: HashSet::new(),
+            glyph_text: HashMap::new(),
             face: None,
+            shaper: None,
             subset_data: None,
             glyph_remapper: None,
         };
@@ -744,7 +1014,10 @@ mod tests {
             name: "test".to_string(),
             ttf_data: vec![0u8; 100],
             used_chars: HashSet::new(),
+            used_glyphs: HashSet::new(),
+            glyph_text: HashMap::new(),
             face: None,
+            shaper: None,
             subset_data: None,
             glyph_remapper: None,
         };
@@ -759,7 +1032,10 @@ mod tests {
             name: "test".to_string(),
             ttf_data: vec![0u8; 100],
             used_chars: HashSet::new(),
+            used_glyphs: HashSet::new(),
+            glyph_text: HashMap::new(),
             face: None,
+            shaper: None,
             subset_data: None,
             glyph_remapper: None,
         };
@@ -778,7 +1054,10 @@ mod tests {
             name: "test".to_string(),
             ttf_data: vec![0u8; 100],
             used_chars: HashSet::new(),
+            used_glyphs: HashSet::new(),
+            glyph_text: HashMap::new(),
             face: None,
+            shaper: None,
             subset_data: None,
             glyph_remapper: None,
         };
@@ -804,7 +1083,10 @@ mod tests {
             name: "test".to_string(),
             ttf_data: vec![0u8; 100],
             used_chars: HashSet::new(),
+            used_glyphs: HashSet::new(),
+            glyph_text: HashMap::new(),
             face: None,
+            shaper: None,
             subset_data: None,
             glyph_remapper: None,
         };
@@ -824,7 +1106,10 @@ mod tests {
             name: "test".to_string(),
             ttf_data: vec![0u8; 100],
             used_chars: HashSet::new(),
+            used_glyphs: HashSet::new(),
+            glyph_text: HashMap::new(),
             face: None,
+            shaper: None,
             subset_data: None,
             glyph_remapper: None,
         };
@@ -847,7 +1132,10 @@ mod tests {
             name: "test".to_string(),
             ttf_data: vec![0u8; 100],
             used_chars: HashSet::new(),
+            used_glyphs: HashSet::new(),
+            glyph_text: HashMap::new(),
             face: None,
+            shaper: None,
             subset_data: None,
             glyph_remapper: None,
         };
@@ -866,7 +1154,10 @@ mod tests {
             name: "test".to_string(),
             ttf_data: vec![0u8; 100],
             used_chars: HashSet::new(),
+            used_glyphs: HashSet::new(),
+            glyph_text: HashMap::new(),
             face: None,
+            shaper: None,
             subset_data: None,
             glyph_remapper: None,
         };
@@ -880,13 +1171,65 @@ mod tests {
         assert!(cmap.contains("<0000> <0E27>")); // ว -> GID 0
     }
 
+    #[test]
+    fn test_shape_no_face() {
+        let mut font = FontData {
+            name: "test".to_string(),
+            ttf_data: vec![0u8; 100],
+            used_chars: HashSet::new(),
+            used_glyphs: HashSet::new(),
+            glyph_text: HashMap::new(),
+            face: None,
+            shaper: None,
+            subset_data: None,
+            glyph_remapper: None,
+        };
+
+        // Without a face, each character becomes one glyph with its byte cluster
+        let glyphs = font.add_text("กข");
+        assert_eq!(glyphs.len(), 2);
+        assert_eq!(glyphs[0].cluster, 0);
+        assert_eq!(glyphs[1].cluster, 3);
+        assert!(glyphs.iter().all(|g| g.gid == 0 && g.x_advance == 0));
+        assert_eq!(font.used_chars.len(), 2);
+    }
+
+    #[test]
+    fn test_generate_tounicode_cmap_shaped_glyph() {
+        let mut font = FontData {
+            name: "test".to_string(),
+            ttf_data: vec![0u8; 100],
+            used_chars: HashSet::new(),
+            used_glyphs: HashSet::new(),
+            glyph_text: HashMap::new(),
+            face: None,
+            shaper: None,
+            subset_data: None,
+            glyph_remapper: None,
+        };
+
+        // A ligature glyph maps back to all of its characters
+        font.used_glyphs.insert(42);
+        font.glyph_text.insert(42, "ffi".to_string());
+        // Characters outside the BMP use surrogate pairs
+        font.glyph_text.insert(43, "😀".to_string());
+
+        let cmap = font.generate_tounicode_cmap();
+
+        assert!(cmap.contains("<002A> <006600660069>"));
+        assert!(cmap.contains("<002B> <D83DDE00>"));
+    }
+
     #[test]
     fn test_has_glyph_no_face() {
         let font = FontData {
             name: "test".to_string(),
             ttf_data: vec![0u8; 100],
             used_chars: HashSet::new(),
+            used_glyphs: HashSet::new(),
+            glyph_text: HashMap::new(),
             face: None,
+            shaper: None,
             subset_data: None,
             glyph_remapper: None,
         };
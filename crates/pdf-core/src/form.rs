@@ -0,0 +1,805 @@
+//! AcroForm fields
+//!
+//! Reads the interactive form field tree of a PDF (`/AcroForm /Fields`)
+//! and provides the low-level pieces used by `PdfDocument` to fill it:
+//! text string encoding, widget states and appearance stream content.
+//! Also places annotation appearances on the page when flattening.
+
+use crate::document::{Color, Rect};
+use crate::graphics::{
+    generate_check_mark_operators, generate_path_operators, CheckMark, Path, ShapeStyle,
+};
+use crate::{Align, PdfError, Result};
+use lopdf::{Dictionary, Document, Object, ObjectId, StringFormat};
+use std::collections::HashMap;
+
+/// Field flag bits (/Ff)
+pub mod flags {
+    /// The user may not change the value
+    pub const READ_ONLY: u32 = 1 << 0;
+    /// A value is required before the form is submitted
+    pub const REQUIRED: u32 = 1 << 1;
+    /// Text field: may hold several lines
+    pub const MULTILINE: u32 = 1 << 12;
+    /// Text field: input is masked
+    pub const PASSWORD: u32 = 1 << 13;
+    /// Radio group: one button always stays selected
+    pub const NO_TOGGLE_TO_OFF: u32 = 1 << 14;
+    /// Button field is a radio group
+    pub const RADIO: u32 = 1 << 15;
+    /// Button field is a push button
+    pub const PUSH_BUTTON: u32 = 1 << 16;
+    /// Choice field is a combo box
+    pub const COMBO: u32 = 1 << 17;
+    /// Combo box: accepts text that is not an option
+    pub const EDIT: u32 = 1 << 18;
+    /// Text field: no spell checking
+    pub const DO_NOT_SPELL_CHECK: u32 = 1 << 22;
+    /// Text field: no scrolling beyond the widget
+    pub const DO_NOT_SCROLL: u32 = 1 << 23;
+    /// Text field: one character per cell (needs /MaxLen)
+    pub const COMB: u32 = 1 << 24;
+}
+
+/// Annotation flag bits (/F)
+pub(crate) mod annot_flags {
+    pub const HIDDEN: u32 = 1 << 1;
+    pub const NO_VIEW: u32 = 1 << 5;
+}
+
+/// Which annotations `PdfDocument::flatten` merges into page content
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FlattenMode {
+    /// Form field widgets only; other annotations (links, comments) stay interactive
+    #[default]
+    Forms,
+    /// Every annotation with an appearance stream; popups are removed
+    All,
+}
+
+/// Kind of form field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormFieldType {
+    /// Text field (/Tx)
+    Text,
+    /// Checkbox (/Btn)
+    Checkbox,
+    /// Radio button group (/Btn with the Radio flag)
+    Radio,
+    /// Push button (/Btn with the Pushbutton flag), has no value
+    PushButton,
+    /// Drop-down choice (/Ch with the Combo flag)
+    ComboBox,
+    /// Scrollable list choice (/Ch)
+    ListBox,
+    /// Signature field (/Sig)
+    Signature,
+}
+
+/// A visible box of a form field on a page
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldWidget {
+    /// Page number (1-indexed), if the widget is placed on a page
+    pub page: Option<usize>,
+    /// Widget rectangle in top-left page coordinates
+    pub rect: Rect,
+    /// Appearance state shown when a checkbox or radio button is on
+    pub on_state: Option<String>,
+}
+
+/// A terminal form field of the base PDF
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormField {
+    /// Fully qualified name (partial names joined with '.')
+    pub name: String,
+    /// Field kind
+    pub field_type: FormFieldType,
+    /// Current value (/V); "Off" or an on-state name for buttons
+    pub value: Option<String>,
+    /// Export values of a choice field, or the on-states of a checkbox or radio group
+    pub options: Vec<String>,
+    /// Field flags (/Ff)
+    pub flags: u32,
+    /// Widgets of this field (a radio group has one per button)
+    pub widgets: Vec<FieldWidget>,
+}
+
+impl FormField {
+    /// Page of the first widget
+    pub fn page(&self) -> Option<usize> {
+        self.widgets.first().and_then(|w| w.page)
+    }
+
+    /// Rectangle of the first widget (top-left page coordinates)
+    pub fn rect(&self) -> Option<Rect> {
+        self.widgets.first().map(|w| w.rect)
+    }
+
+    /// Whether the field is marked read-only
+    pub fn is_read_only(&self) -> bool {
+        self.flags & flags::READ_ONLY != 0
+    }
+
+    /// Whether the field is marked required
+    pub fn is_required(&self) -> bool {
+        self.flags & flags::REQUIRED != 0
+    }
+}
+
+/// A new interactive field for `PdfDocument::add_form_field`
+///
+/// Text fields use the document's current font, size and color for their
+/// default appearance (/DA), so the recipient can edit them with the same
+/// font. Radio groups get one widget per `with_button` call.
+///
+/// # Example
+/// ```ignore
+/// let remarks = FieldDefinition::text("remarks", 1, Rect::new(50.0, 700.0, 300.0, 60.0))
+///     .multiline()
+///     .with_value("-");
+/// let gender = FieldDefinition::radio("gender")
+///     .with_button("M", 1, Rect::new(50.0, 650.0, 12.0, 12.0))
+///     .with_button("F", 1, Rect::new(120.0, 650.0, 12.0, 12.0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDefinition {
+    /// Partial field name (must not contain '.')
+    pub name: String,
+    /// Field kind: text, checkbox, radio or signature
+    pub field_type: FormFieldType,
+    /// Widgets (page and top-left rectangle; `on_state` names a radio button)
+    pub widgets: Vec<FieldWidget>,
+    /// Default value, set as if by `set_field_value`
+    pub value: Option<String>,
+    /// Field flags (/Ff), see `field_flags`
+    pub flags: u32,
+    /// Maximum length of a text field
+    pub max_len: Option<usize>,
+    /// Text alignment inside a text field
+    pub align: Align,
+}
+
+impl FieldDefinition {
+    fn new(name: &str, field_type: FormFieldType) -> Self {
+        Self {
+            name: name.to_string(),
+            field_type,
+            widgets: Vec::new(),
+            value: None,
+            flags: 0,
+            max_len: None,
+            align: Align::Left,
+        }
+    }
+
+    /// Text field
+    pub fn text(name: &str, page: usize, rect: Rect) -> Self {
+        Self::new(name, FormFieldType::Text).with_widget(page, rect)
+    }
+
+    /// Checkbox (on state "Yes")
+    pub fn checkbox(name: &str, page: usize, rect: Rect) -> Self {
+        Self::new(name, FormFieldType::Checkbox).with_widget(page, rect)
+    }
+
+    /// Radio group without buttons; add them with `with_button`
+    pub fn radio(name: &str) -> Self {
+        Self::new(name, FormFieldType::Radio).with_flags(flags::NO_TOGGLE_TO_OFF)
+    }
+
+    /// Signature field
+    pub fn signature(name: &str, page: usize, rect: Rect) -> Self {
+        Self::new(name, FormFieldType::Signature).with_widget(page, rect)
+    }
+
+    /// Show the field in another place (widgets of one field share its value)
+    pub fn with_widget(mut self, page: usize, rect: Rect) -> Self {
+        self.widgets.push(FieldWidget {
+            page: Some(page),
+            rect,
+            on_state: None,
+        });
+        self
+    }
+
+    /// Add a radio button selected by `state`
+    pub fn with_button(mut self, state: &str, page: usize, rect: Rect) -> Self {
+        self.widgets.push(FieldWidget {
+            page: Some(page),
+            rect,
+            on_state: Some(state.to_string()),
+        });
+        self
+    }
+
+    /// Set the default value
+    pub fn with_value(mut self, value: &str) -> Self {
+        self.value = Some(value.to_string());
+        self
+    }
+
+    /// Add field flags (/Ff bits)
+    pub fn with_flags(mut self, flags: u32) -> Self {
+        self.flags |= flags;
+        self
+    }
+
+    /// Mark the field read-only
+    pub fn read_only(self) -> Self {
+        self.with_flags(flags::READ_ONLY)
+    }
+
+    /// Mark the field required
+    pub fn required(self) -> Self {
+        self.with_flags(flags::REQUIRED)
+    }
+
+    /// Allow several lines of text
+    pub fn multiline(self) -> Self {
+        self.with_flags(flags::MULTILINE)
+    }
+
+    /// Limit a text field to `max_len` characters
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    /// Spread `cells` characters evenly over the field (e.g. ID number boxes)
+    pub fn comb(self, cells: usize) -> Self {
+        self.with_max_len(cells).with_flags(flags::COMB)
+    }
+
+    /// Set the text alignment
+    pub fn with_align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+}
+
+/// An option of a choice field
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ChoiceOption {
+    /// Value stored in /V
+    pub export: String,
+    /// Text shown to the user
+    pub display: String,
+}
+
+/// A terminal field with the attributes needed to fill it
+#[derive(Debug, Clone)]
+pub(crate) struct FieldNode {
+    /// Object holding /V
+    pub id: ObjectId,
+    pub name: String,
+    pub field_type: FormFieldType,
+    pub flags: u32,
+    pub value: Option<String>,
+    /// Choice options (/Opt)
+    pub options: Vec<ChoiceOption>,
+    /// Widget annotations (may be the field object itself)
+    pub widgets: Vec<ObjectId>,
+    /// Text alignment: 0 left, 1 center, 2 right
+    pub quadding: i64,
+    /// Maximum length of a text field
+    pub max_len: Option<usize>,
+}
+
+impl FieldNode {
+    pub fn has_flag(&self, flag: u32) -> bool {
+        self.flags & flag != 0
+    }
+}
+
+/// Attributes inherited from ancestor fields
+#[derive(Debug, Clone, Default)]
+struct Inherited {
+    name: String,
+    field_type: Option<Vec<u8>>,
+    flags: u32,
+    value: Option<Object>,
+    quadding: i64,
+    max_len: Option<usize>,
+}
+
+/// Collect the terminal fields of the document's AcroForm
+///
+/// Returns an empty list when the document has no form.
+pub(crate) fn collect_fields(doc: &Document) -> Result<Vec<FieldNode>> {
+    let Some(acroform) = acroform(doc) else {
+        return Ok(Vec::new());
+    };
+    let Ok(fields) = acroform
+        .get_deref(b"Fields", doc)
+        .and_then(Object::as_array)
+    else {
+        return Ok(Vec::new());
+    };
+
+    let mut nodes = Vec::new();
+    for field in fields {
+        if let Ok(id) = field.as_reference() {
+            walk_field(doc, id, &Inherited::default(), &mut nodes, 0)?;
+        }
+    }
+    Ok(nodes)
+}
+
+fn walk_field(
+    doc: &Document,
+    id: ObjectId,
+    parent: &Inherited,
+    nodes: &mut Vec<FieldNode>,
+    depth: usize,
+) -> Result<()> {
+    if depth > 32 {
+        return Err(PdfError::ParseError(
+            "Form field tree is too deep".to_string(),
+        ));
+    }
+    let dict = doc.get_dictionary(id)?;
+
+    let mut attrs = parent.clone();
+    if let Some(partial) = dict.get(b"T").ok().and_then(decode_text_string) {
+        attrs.name = if attrs.name.is_empty() {
+            partial
+        } else {
+            format!("{}.{}", attrs.name, partial)
+        };
+    }
+    if let Ok(ft) = dict.get(b"FT").and_then(Object::as_name) {
+        attrs.field_type = Some(ft.to_vec());
+    }
+    if let Ok(ff) = dict.get_deref(b"Ff", doc).and_then(Object::as_i64) {
+        attrs.flags = ff as u32;
+    }
+    if let Ok(value) = dict.get_deref(b"V", doc) {
+        attrs.value = Some(value.clone());
+    }
+    if let Ok(q) = dict.get_deref(b"Q", doc).and_then(Object::as_i64) {
+        attrs.quadding = q;
+    }
+    if let Ok(max_len) = dict.get_deref(b"MaxLen", doc).and_then(Object::as_i64) {
+        attrs.max_len = usize::try_from(max_len).ok();
+    }
+
+    let kids: Vec<ObjectId> = dict
+        .get_deref(b"Kids", doc)
+        .and_then(Object::as_array)
+        .map(|kids| kids.iter().filter_map(|k| k.as_reference().ok()).collect())
+        .unwrap_or_default();
+
+    // Kids with a partial name are child fields; kids without are widgets
+    let child_fields: Vec<ObjectId> = kids
+        .iter()
+        .copied()
+        .filter(|&kid| {
+            doc.get_dictionary(kid)
+                .map(|d| d.has(b"T"))
+                .unwrap_or(false)
+        })
+        .collect();
+    if !child_fields.is_empty() {
+        for kid in child_fields {
+            walk_field(doc, kid, &attrs, nodes, depth + 1)?;
+        }
+        return Ok(());
+    }
+
+    let Some(field_type) = attrs
+        .field_type
+        .as_deref()
+        .map(|ft| classify(ft, attrs.flags))
+    else {
+        // Not a field (e.g. an orphan widget)
+        return Ok(());
+    };
+
+    let widgets = if kids.is_empty() { vec![id] } else { kids };
+    let options = match field_type {
+        FormFieldType::ComboBox | FormFieldType::ListBox => choice_options(doc, dict),
+        _ => Vec::new(),
+    };
+
+    nodes.push(FieldNode {
+        id,
+        name: attrs.name,
+        field_type,
+        flags: attrs.flags,
+        value: attrs.value.as_ref().and_then(decode_text_string),
+        options,
+        widgets,
+        quadding: attrs.quadding,
+        max_len: attrs.max_len,
+    });
+    Ok(())
+}
+
+/// Determine the field type from /FT and /Ff
+fn classify(field_type: &[u8], flags: u32) -> FormFieldType {
+    match field_type {
+        b"Btn" if flags & flags::PUSH_BUTTON != 0 => FormFieldType::PushButton,
+        b"Btn" if flags & flags::RADIO != 0 => FormFieldType::Radio,
+        b"Btn" => FormFieldType::Checkbox,
+        b"Ch" if flags & flags::COMBO != 0 => FormFieldType::ComboBox,
+        b"Ch" => FormFieldType::ListBox,
+        b"Sig" => FormFieldType::Signature,
+        _ => FormFieldType::Text,
+    }
+}
+
+/// Read /Opt entries: plain strings or [export display] pairs
+fn choice_options(doc: &Document, dict: &Dictionary) -> Vec<ChoiceOption> {
+    let Ok(opts) = dict.get_deref(b"Opt", doc).and_then(Object::as_array) else {
+        return Vec::new();
+    };
+
+    opts.iter()
+        .filter_map(|opt| {
+            let opt = doc.dereference(opt).ok()?.1;
+            match opt {
+                Object::Array(pair) if pair.len() >= 2 => Some(ChoiceOption {
+                    export: decode_text_string(&pair[0])?,
+                    display: decode_text_string(&pair[1])?,
+                }),
+                other => {
+                    let text = decode_text_string(other)?;
+                    Some(ChoiceOption {
+                        export: text.clone(),
+                        display: text,
+                    })
+                }
+            }
+        })
+        .collect()
+}
+
+/// The document's AcroForm dictionary, if any
+pub(crate) fn acroform(doc: &Document) -> Option<&Dictionary> {
+    doc.catalog()
+        .ok()?
+        .get_deref(b"AcroForm", doc)
+        .and_then(Object::as_dict)
+        .ok()
+}
+
+/// Names of the "on" appearance states of a checkbox or radio widget
+pub(crate) fn widget_on_states(doc: &Document, widget: ObjectId) -> Vec<String> {
+    let Ok(dict) = doc.get_dictionary(widget) else {
+        return Vec::new();
+    };
+    let normal = dict
+        .get_deref(b"AP", doc)
+        .and_then(Object::as_dict)
+        .and_then(|ap| ap.get_deref(b"N", doc))
+        .and_then(Object::as_dict);
+
+    match normal {
+        Ok(states) => states
+            .iter()
+            .map(|(name, _)| String::from_utf8_lossy(name).to_string())
+            .filter(|name| name != "Off")
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Widget rectangle as normalized PDF coordinates [llx, lly, urx, ury]
+pub(crate) fn widget_rect(doc: &Document, widget: ObjectId) -> Option<[f64; 4]> {
+    annotation_rect(doc, doc.get_dictionary(widget).ok()?)
+}
+
+/// Size of a widget's appearance and the /Matrix turning it into the /Rect
+///
+/// A widget rotated by /MK /R (as on a /Rotate page) lays out its
+/// appearance upright in width x height as the page is viewed; the matrix
+/// turns it counterclockwise onto the page. `None` for an unrotated widget.
+pub(crate) fn widget_appearance_box(
+    doc: &Document,
+    widget: ObjectId,
+) -> Option<(f64, f64, Option<[f64; 6]>)> {
+    let dict = doc.get_dictionary(widget).ok()?;
+    let [llx, lly, urx, ury] = annotation_rect(doc, dict)?;
+    let rotation = dict
+        .get_deref(b"MK", doc)
+        .and_then(Object::as_dict)
+        .and_then(|mk| mk.get(b"R"))
+        .ok()
+        .and_then(number)
+        .unwrap_or(0.0) as i64;
+    let (width, height) = (urx - llx, ury - lly);
+    Some(match rotation.rem_euclid(360) {
+        90 => (height, width, Some([0.0, 1.0, -1.0, 0.0, width, 0.0])),
+        180 => (width, height, Some([-1.0, 0.0, 0.0, -1.0, width, height])),
+        270 => (height, width, Some([0.0, -1.0, 1.0, 0.0, 0.0, height])),
+        _ => (width, height, None),
+    })
+}
+
+/// Annotation rectangle as normalized PDF coordinates [llx, lly, urx, ury]
+pub(crate) fn annotation_rect(doc: &Document, annot: &Dictionary) -> Option<[f64; 4]> {
+    let values: [f64; 4] = number_array(annot.get_deref(b"Rect", doc).ok()?)?;
+    Some([
+        values[0].min(values[2]),
+        values[1].min(values[3]),
+        values[0].max(values[2]),
+        values[1].max(values[3]),
+    ])
+}
+
+/// The normal appearance stream an annotation currently shows
+///
+/// For checkboxes and radio buttons /AP /N holds one stream per state;
+/// the one named by /AS is picked.
+pub(crate) fn normal_appearance(doc: &Document, annot: &Dictionary) -> Option<ObjectId> {
+    let ap = annot.get_deref(b"AP", doc).and_then(Object::as_dict).ok()?;
+    let normal = ap.get(b"N").ok()?;
+    let states = match normal {
+        Object::Reference(id) => match doc.get_object(*id).ok()? {
+            Object::Stream(_) => return Some(*id),
+            Object::Dictionary(states) => states,
+            _ => return None,
+        },
+        Object::Dictionary(states) => states,
+        _ => return None,
+    };
+
+    let state = annot.get(b"AS").and_then(Object::as_name).ok()?;
+    states.get(state).and_then(Object::as_reference).ok()
+}
+
+/// Transform `[sx, sy, tx, ty]` that draws a Form XObject inside an annotation rectangle
+///
+/// The form's bounding box, after its own /Matrix, is scaled and moved onto
+/// `rect` (the algorithm viewers use to show annotation appearances).
+///
+/// # Arguments
+/// * `bbox` - Form /BBox [llx, lly, urx, ury]
+/// * `matrix` - Form /Matrix [a, b, c, d, e, f]
+/// * `rect` - Normalized annotation rectangle [llx, lly, urx, ury]
+pub(crate) fn appearance_transform(bbox: [f64; 4], matrix: [f64; 6], rect: [f64; 4]) -> [f64; 4] {
+    let [a, b, c, d, e, f] = matrix;
+    let corners = [
+        (bbox[0], bbox[1]),
+        (bbox[2], bbox[1]),
+        (bbox[0], bbox[3]),
+        (bbox[2], bbox[3]),
+    ]
+    .map(|(x, y)| (a * x + c * y + e, b * x + d * y + f));
+
+    let min_x = corners.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+    let max_x = corners
+        .iter()
+        .map(|p| p.0)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let min_y = corners.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let max_y = corners
+        .iter()
+        .map(|p| p.1)
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    // A degenerate box would divide by zero; draw it unscaled instead
+    let scale = |target: f64, source: f64| {
+        if source.abs() < f64::EPSILON {
+            1.0
+        } else {
+            target / source
+        }
+    };
+    let sx = scale(rect[2] - rect[0], max_x - min_x);
+    let sy = scale(rect[3] - rect[1], max_y - min_y);
+
+    [sx, sy, rect[0] - sx * min_x, rect[1] - sy * min_y]
+}
+
+/// Read a fixed-size number array such as /BBox or /Matrix
+pub(crate) fn number_array<const N: usize>(obj: &Object) -> Option<[f64; N]> {
+    let array = obj.as_array().ok()?;
+    if array.len() < N {
+        return None;
+    }
+    let mut values = [0.0; N];
+    for (value, obj) in values.iter_mut().zip(array) {
+        *value = number(obj)?;
+    }
+    Some(values)
+}
+
+/// Map each widget annotation to the page (1-indexed) that lists it in /Annots
+pub(crate) fn widget_pages(doc: &Document) -> HashMap<ObjectId, usize> {
+    let mut pages = HashMap::new();
+    for (page_number, page_id) in doc.get_pages() {
+        let annots = doc
+            .get_dictionary(page_id)
+            .and_then(|page| page.get_deref(b"Annots", doc))
+            .and_then(Object::as_array);
+        if let Ok(annots) = annots {
+            for annot in annots {
+                if let Ok(id) = annot.as_reference() {
+                    pages.insert(id, page_number as usize);
+                }
+            }
+        }
+    }
+    pages
+}
+
+/// Read a number (integer or real)
+pub(crate) fn number(obj: &Object) -> Option<f64> {
+    match obj {
+        Object::Integer(i) => Some(*i as f64),
+        Object::Real(r) => Some(*r as f64),
+        _ => None,
+    }
+}
+
+/// Decode a PDF text string (UTF-16BE with BOM, UTF-8 with BOM or PDFDocEncoding)
+///
+/// Names are returned as-is, so button states read the same way as strings.
+pub(crate) fn decode_text_string(obj: &Object) -> Option<String> {
+    match obj {
+        Object::String(bytes, _) => {
+            if let Some(utf16) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+                let units: Vec<u16> = utf16
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                    .collect();
+                Some(String::from_utf16_lossy(&units))
+            } else if let Some(utf8) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+                Some(String::from_utf8_lossy(utf8).to_string())
+            } else {
+                // PDFDocEncoding matches Latin-1 for printable characters
+                Some(bytes.iter().map(|&b| b as char).collect())
+            }
+        }
+        Object::Name(name) => Some(String::from_utf8_lossy(name).to_string()),
+        _ => None,
+    }
+}
+
+/// Encode a PDF text string: literal for ASCII, UTF-16BE with BOM otherwise
+pub(crate) fn encode_text_string(text: &str) -> Object {
+    if text.is_ascii() {
+        return Object::String(text.as_bytes().to_vec(), StringFormat::Literal);
+    }
+
+    let mut bytes = vec![0xFE, 0xFF];
+    for unit in text.encode_utf16() {
+        bytes.extend_from_slice(&unit.to_be_bytes());
+    }
+    Object::String(bytes, StringFormat::Hexadecimal)
+}
+
+/// Whether a value turns a checkbox off
+pub(crate) fn is_off_value(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "" | "off" | "false" | "0" | "no" | "n"
+    )
+}
+
+/// Split text into grapheme-like clusters: a base character followed by
+/// its combining marks (Thai vowels and tone marks above/below the line)
+///
+/// Used to place one cluster per cell in comb fields.
+pub(crate) fn char_clusters(text: &str) -> Vec<String> {
+    let mut clusters: Vec<String> = Vec::new();
+    for c in text.chars() {
+        match clusters.last_mut() {
+            Some(last) if is_combining(c) => last.push(c),
+            _ => clusters.push(c.to_string()),
+        }
+    }
+    clusters
+}
+
+fn is_combining(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'
+        | '\u{0E31}'
+        | '\u{0E34}'..='\u{0E3A}'
+        | '\u{0E47}'..='\u{0E4E}')
+}
+
+/// Operators for a checkbox tick filling a `width` x `height` box
+pub(crate) fn generate_check_operators(width: f64, height: f64) -> Vec<u8> {
+    let size = width.min(height);
+    generate_check_mark_operators(
+        CheckMark::Tick,
+        (width - size) / 2.0,
+        (height - size) / 2.0,
+        size,
+        Color::black(),
+    )
+}
+
+/// Operators for a radio button dot centered in a `width` x `height` box
+pub(crate) fn generate_radio_operators(width: f64, height: f64) -> Vec<u8> {
+    let radius = width.min(height) * 0.3;
+    let dot = Path::new().ellipse(width / 2.0, height / 2.0, radius, radius);
+    generate_path_operators(&dot, &ShapeStyle::fill(Color::black()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_text_string_roundtrip() {
+        for text in ["Hello", "สวัสดี", "นาย ก."] {
+            assert_eq!(
+                decode_text_string(&encode_text_string(text)).as_deref(),
+                Some(text)
+            );
+        }
+        assert!(matches!(
+            encode_text_string("ABC"),
+            Object::String(_, StringFormat::Literal)
+        ));
+        assert_eq!(
+            decode_text_string(&Object::Name(b"Yes".to_vec())).as_deref(),
+            Some("Yes")
+        );
+    }
+
+    #[test]
+    fn test_char_clusters() {
+        assert_eq!(char_clusters("ที่ดิน"), vec!["ที่", "ดิ", "น"]);
+        assert_eq!(char_clusters("A1"), vec!["A", "1"]);
+    }
+
+    #[test]
+    fn test_collect_fields() {
+        let mut doc = Document::with_version("1.5");
+        let name_id = doc.add_object(dictionary! {
+            "T" => Object::string_literal("name"),
+            "Subtype" => "Widget",
+            "Rect" => vec![100.into(), 700.into(), 300.into(), 720.into()],
+        });
+        let yes_id = doc.add_object(dictionary! {
+            "Subtype" => "Widget",
+            "Rect" => vec![100.into(), 650.into(), 112.into(), 662.into()],
+            "AP" => dictionary! { "N" => dictionary! { "A" => Object::Null, "Off" => Object::Null } },
+        });
+        let no_id = doc.add_object(dictionary! {
+            "Subtype" => "Widget",
+            "Rect" => vec![150.into(), 650.into(), 162.into(), 662.into()],
+            "AP" => dictionary! { "N" => dictionary! { "B" => Object::Null, "Off" => Object::Null } },
+        });
+        let gender_id = doc.add_object(dictionary! {
+            "T" => Object::string_literal("gender"),
+            "FT" => "Btn",
+            "Ff" => flags::RADIO as i64,
+            "V" => "B",
+            "Kids" => vec![yes_id.into(), no_id.into()],
+        });
+        let person_id = doc.add_object(dictionary! {
+            "T" => Object::string_literal("person"),
+            "FT" => "Tx",
+            "Kids" => vec![name_id.into(), gender_id.into()],
+        });
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "AcroForm" => dictionary! { "Fields" => vec![person_id.into()] },
+        });
+        doc.trailer.set("Root", catalog_id);
+
+        let fields = collect_fields(&doc).unwrap();
+        assert_eq!(fields.len(), 2);
+
+        assert_eq!(fields[0].name, "person.name");
+        assert_eq!(fields[0].field_type, FormFieldType::Text);
+        assert_eq!(fields[0].widgets, vec![name_id]);
+
+        assert_eq!(fields[1].name, "person.gender");
+        assert_eq!(fields[1].field_type, FormFieldType::Radio);
+        assert_eq!(fields[1].value.as_deref(), Some("B"));
+        assert_eq!(fields[1].widgets, vec![yes_id, no_id]);
+        assert_eq!(widget_on_states(&doc, no_id), vec!["B"]);
+        assert_eq!(
+            widget_rect(&doc, yes_id),
+            Some([100.0, 650.0, 112.0, 662.0])
+        );
+    }
+}
@@ -0,0 +1,963 @@
+//! Vector graphics (lines, rectangles, ellipses, paths and check marks),
+//! transforms and transparency
+//!
+//! Shapes are written as path construction and painting operators wrapped
+//! in `q`/`Q`, so their graphics state never leaks into later content.
+
+use crate::document::Color;
+
+/// Bezier control point distance for a quarter circle (4/3 * (sqrt(2) - 1))
+const KAPPA: f64 = 0.552_284_749_831;
+
+/// Shape of open stroke ends
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineCap {
+    /// Square end at the endpoint
+    #[default]
+    Butt,
+    /// Semicircle around the endpoint
+    Round,
+    /// Square end extending half the line width past the endpoint
+    Square,
+}
+
+/// Shape of stroke corners
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineJoin {
+    /// Sharp corner
+    #[default]
+    Miter,
+    /// Rounded corner
+    Round,
+    /// Cut-off corner
+    Bevel,
+}
+
+/// Mark drawn in a checkbox
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CheckMark {
+    /// Tick (✓)
+    #[default]
+    Tick,
+    /// Cross (✗)
+    Cross,
+    /// Filled square inside the box
+    Fill,
+}
+
+/// Rotation, skew and scale applied around an anchor point
+///
+/// Angles are in degrees. Rotation is counter-clockwise as seen on the
+/// page, so 90° turns text to read bottom to top. The transform is applied
+/// as scale, then skew, then rotation, all around the anchor.
+///
+/// # Example
+/// ```ignore
+/// // Vertical label on the left margin
+/// doc.set_transform(Some(Transform::rotate(90.0)));
+/// doc.insert_text("สำเนา", 1, 30.0, 500.0, Align::Center)?;
+/// doc.set_transform(None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    /// Rotation in degrees (counter-clockwise)
+    pub rotation: f64,
+    /// Horizontal scale factor
+    pub scale_x: f64,
+    /// Vertical scale factor
+    pub scale_y: f64,
+    /// Horizontal skew in degrees (positive slants the top to the right)
+    pub skew_x: f64,
+    /// Vertical skew in degrees (positive raises the right side)
+    pub skew_y: f64,
+    /// Anchor in top-left page coordinates (None = the drawing's own origin)
+    pub anchor: Option<(f64, f64)>,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            rotation: 0.0,
+            scale_x: 1.0,
+            scale_y: 1.0,
+            skew_x: 0.0,
+            skew_y: 0.0,
+            anchor: None,
+        }
+    }
+}
+
+impl Transform {
+    /// Rotation only
+    pub fn rotate(degrees: f64) -> Self {
+        Self {
+            rotation: degrees,
+            ..Self::default()
+        }
+    }
+
+    /// Scale only
+    pub fn scale(scale_x: f64, scale_y: f64) -> Self {
+        Self {
+            scale_x,
+            scale_y,
+            ..Self::default()
+        }
+    }
+
+    /// Skew only
+    pub fn skew(skew_x: f64, skew_y: f64) -> Self {
+        Self {
+            skew_x,
+            skew_y,
+            ..Self::default()
+        }
+    }
+
+    /// Set the rotation in degrees
+    pub fn with_rotation(mut self, degrees: f64) -> Self {
+        self.rotation = degrees;
+        self
+    }
+
+    /// Set the scale factors
+    pub fn with_scale(mut self, scale_x: f64, scale_y: f64) -> Self {
+        self.scale_x = scale_x;
+        self.scale_y = scale_y;
+        self
+    }
+
+    /// Set the skew angles in degrees
+    pub fn with_skew(mut self, skew_x: f64, skew_y: f64) -> Self {
+        self.skew_x = skew_x;
+        self.skew_y = skew_y;
+        self
+    }
+
+    /// Transform around a fixed point (top-left page coordinates)
+    pub fn with_anchor(mut self, x: f64, y: f64) -> Self {
+        self.anchor = Some((x, y));
+        self
+    }
+
+    /// Whether the transform leaves everything in place
+    pub fn is_identity(&self) -> bool {
+        self.rotation == 0.0
+            && self.scale_x == 1.0
+            && self.scale_y == 1.0
+            && self.skew_x == 0.0
+            && self.skew_y == 0.0
+    }
+
+    /// PDF matrix `[a b c d e f]` of the transform around an anchor
+    ///
+    /// # Arguments
+    /// * `x`, `y` - Anchor in PDF coordinates (from bottom)
+    pub fn matrix(&self, x: f64, y: f64) -> [f64; 6] {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let (tan_x, tan_y) = (
+            self.skew_x.to_radians().tan(),
+            self.skew_y.to_radians().tan(),
+        );
+
+        // Linear part: rotation * skew * scale
+        let k = [
+            [self.scale_x, tan_x * self.scale_y],
+            [tan_y * self.scale_x, self.scale_y],
+        ];
+        let a = cos * k[0][0] - sin * k[1][0];
+        let c = cos * k[0][1] - sin * k[1][1];
+        let b = sin * k[0][0] + cos * k[1][0];
+        let d = sin * k[0][1] + cos * k[1][1];
+
+        // Keep the anchor fixed
+        let e = x - (a * x + c * y);
+        let f = y - (b * x + d * y);
+
+        [a, b, c, d, e, f].map(round6)
+    }
+}
+
+/// Round to 6 decimals so `cos(90°)` is written as 0
+fn round6(value: f64) -> f64 {
+    // Adding 0.0 turns -0.0 into 0.0
+    (value * 1_000_000.0).round() / 1_000_000.0 + 0.0
+}
+
+/// Wrap content operators in a transformation matrix
+///
+/// # Arguments
+/// * `matrix` - PDF matrix `[a b c d e f]` (see `Transform::matrix`)
+/// * `operators` - Content stream operators to transform
+///
+/// # Returns
+/// The operators between `q <matrix> cm` and `Q`
+pub fn generate_transformed_operators(matrix: [f64; 6], operators: &[u8]) -> Vec<u8> {
+    if operators.is_empty() {
+        return Vec::new();
+    }
+
+    let [a, b, c, d, e, f] = matrix;
+    let mut ops = format!("q\n{a} {b} {c} {d} {e} {f} cm\n").into_bytes();
+    ops.extend_from_slice(operators);
+    ops.extend_from_slice(b"Q\n");
+    ops
+}
+
+/// Blend mode for transparent content (PDF `/BM`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Paint over the backdrop
+    #[default]
+    Normal,
+    /// Darken by multiplying colors (ink on paper)
+    Multiply,
+    /// Lighten by inverse multiplication
+    Screen,
+    /// Multiply or screen depending on the backdrop
+    Overlay,
+    /// Keep the darker color
+    Darken,
+    /// Keep the lighter color
+    Lighten,
+    /// Brighten the backdrop
+    ColorDodge,
+    /// Darken the backdrop
+    ColorBurn,
+    /// Multiply or screen depending on the source
+    HardLight,
+    /// Softer version of hard light
+    SoftLight,
+    /// Absolute difference of colors
+    Difference,
+    /// Lower-contrast difference
+    Exclusion,
+    /// Hue of the source with the backdrop's saturation and luminosity
+    Hue,
+    /// Saturation of the source
+    Saturation,
+    /// Hue and saturation of the source
+    Color,
+    /// Luminosity of the source
+    Luminosity,
+}
+
+impl BlendMode {
+    /// PDF name of the blend mode
+    pub fn pdf_name(&self) -> &'static str {
+        match self {
+            BlendMode::Normal => "Normal",
+            BlendMode::Multiply => "Multiply",
+            BlendMode::Screen => "Screen",
+            BlendMode::Overlay => "Overlay",
+            BlendMode::Darken => "Darken",
+            BlendMode::Lighten => "Lighten",
+            BlendMode::ColorDodge => "ColorDodge",
+            BlendMode::ColorBurn => "ColorBurn",
+            BlendMode::HardLight => "HardLight",
+            BlendMode::SoftLight => "SoftLight",
+            BlendMode::Difference => "Difference",
+            BlendMode::Exclusion => "Exclusion",
+            BlendMode::Hue => "Hue",
+            BlendMode::Saturation => "Saturation",
+            BlendMode::Color => "Color",
+            BlendMode::Luminosity => "Luminosity",
+        }
+    }
+}
+
+/// Opacity and blend mode, written as an `/ExtGState` resource
+///
+/// # Example
+/// ```ignore
+/// doc.set_transparency(Some(Transparency::alpha(0.3)));
+/// doc.insert_text("DRAFT", 1, 300.0, 400.0, Align::Center)?;
+/// doc.set_transparency(None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transparency {
+    /// Opacity of fills, text and images (0.0 - 1.0)
+    pub fill_alpha: f64,
+    /// Opacity of strokes (0.0 - 1.0)
+    pub stroke_alpha: f64,
+    /// How content mixes with what is under it
+    pub blend_mode: BlendMode,
+}
+
+impl Default for Transparency {
+    fn default() -> Self {
+        Self {
+            fill_alpha: 1.0,
+            stroke_alpha: 1.0,
+            blend_mode: BlendMode::Normal,
+        }
+    }
+}
+
+impl Transparency {
+    /// Same opacity for fills and strokes
+    pub fn alpha(alpha: f64) -> Self {
+        Self {
+            fill_alpha: alpha,
+            stroke_alpha: alpha,
+            ..Self::default()
+        }
+    }
+
+    /// Blend mode only
+    pub fn blend(mode: BlendMode) -> Self {
+        Self {
+            blend_mode: mode,
+            ..Self::default()
+        }
+    }
+
+    /// Set the opacity of fills, text and images
+    pub fn with_fill_alpha(mut self, alpha: f64) -> Self {
+        self.fill_alpha = alpha;
+        self
+    }
+
+    /// Set the opacity of strokes
+    pub fn with_stroke_alpha(mut self, alpha: f64) -> Self {
+        self.stroke_alpha = alpha;
+        self
+    }
+
+    /// Set the blend mode
+    pub fn with_blend_mode(mut self, mode: BlendMode) -> Self {
+        self.blend_mode = mode;
+        self
+    }
+
+    /// Whether content is painted fully opaque with normal blending
+    pub fn is_opaque(&self) -> bool {
+        self.fill_alpha >= 1.0 && self.stroke_alpha >= 1.0 && self.blend_mode == BlendMode::Normal
+    }
+
+    /// Alphas clamped to 0.0 - 1.0 and rounded to 3 decimals
+    ///
+    /// Equal keys share one `/ExtGState` resource.
+    pub(crate) fn key(&self) -> (u16, u16, BlendMode) {
+        let quantize = |alpha: f64| (alpha.clamp(0.0, 1.0) * 1000.0).round() as u16;
+        (
+            quantize(self.fill_alpha),
+            quantize(self.stroke_alpha),
+            self.blend_mode,
+        )
+    }
+}
+
+/// Wrap content operators in an `/ExtGState`
+///
+/// # Arguments
+/// * `resource_name` - ExtGState resource name (e.g., "GS1")
+/// * `operators` - Content stream operators to paint with the state
+///
+/// # Returns
+/// The operators between `q /GS1 gs` and `Q`
+pub fn generate_ext_gstate_operators(resource_name: &str, operators: &[u8]) -> Vec<u8> {
+    if operators.is_empty() {
+        return Vec::new();
+    }
+
+    let mut ops = format!("q\n/{resource_name} gs\n").into_bytes();
+    ops.extend_from_slice(operators);
+    ops.extend_from_slice(b"Q\n");
+    ops
+}
+
+/// Stroke and fill settings for a shape
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapeStyle {
+    /// Stroke color (None = no outline)
+    pub stroke: Option<Color>,
+    /// Fill color (None = no fill)
+    pub fill: Option<Color>,
+    /// Stroke width in points
+    pub line_width: f64,
+    /// Dash pattern: alternating dash and gap lengths (empty = solid)
+    pub dash: Vec<f64>,
+    /// Offset into the dash pattern where the stroke starts
+    pub dash_phase: f64,
+    /// Shape of open stroke ends
+    pub line_cap: LineCap,
+    /// Shape of stroke corners
+    pub line_join: LineJoin,
+}
+
+impl Default for ShapeStyle {
+    fn default() -> Self {
+        Self {
+            stroke: Some(Color::black()),
+            fill: None,
+            line_width: 1.0,
+            dash: Vec::new(),
+            dash_phase: 0.0,
+            line_cap: LineCap::default(),
+            line_join: LineJoin::default(),
+        }
+    }
+}
+
+impl ShapeStyle {
+    /// Outline only
+    pub fn stroke(color: Color, line_width: f64) -> Self {
+        Self {
+            stroke: Some(color),
+            line_width,
+            ..Self::default()
+        }
+    }
+
+    /// Fill only
+    pub fn fill(color: Color) -> Self {
+        Self {
+            stroke: None,
+            fill: Some(color),
+            line_width: 0.0,
+            ..Self::default()
+        }
+    }
+
+    /// Add a fill to this style
+    pub fn with_fill(mut self, color: Color) -> Self {
+        self.fill = Some(color);
+        self
+    }
+
+    /// Use a dash pattern (e.g. `[3.0, 2.0]` for 3pt dashes with 2pt gaps)
+    pub fn with_dash(mut self, dash: Vec<f64>, phase: f64) -> Self {
+        self.dash = dash;
+        self.dash_phase = phase;
+        self
+    }
+
+    /// Set the shape of open stroke ends
+    pub fn with_line_cap(mut self, cap: LineCap) -> Self {
+        self.line_cap = cap;
+        self
+    }
+
+    /// Set the shape of stroke corners
+    pub fn with_line_join(mut self, join: LineJoin) -> Self {
+        self.line_join = join;
+        self
+    }
+
+    /// Graphics state operators for this style
+    fn state_operators(&self) -> String {
+        let mut ops = String::new();
+        if let Some(c) = self.stroke {
+            ops.push_str(&format!("{} {} {} RG\n", c.r, c.g, c.b));
+            ops.push_str(&format!("{} w\n", self.line_width));
+
+            // Only emit non-default state to keep streams small
+            if self.line_cap != LineCap::Butt {
+                ops.push_str(&format!("{} J\n", self.line_cap as u8));
+            }
+            if self.line_join != LineJoin::Miter {
+                ops.push_str(&format!("{} j\n", self.line_join as u8));
+            }
+            if !self.dash.is_empty() {
+                let dash: Vec<String> = self.dash.iter().map(|d| d.to_string()).collect();
+                ops.push_str(&format!("[{}] {} d\n", dash.join(" "), self.dash_phase));
+            }
+        }
+        if let Some(c) = self.fill {
+            ops.push_str(&format!("{} {} {} rg\n", c.r, c.g, c.b));
+        }
+        ops
+    }
+
+    /// Painting operator for a path, or None if nothing is painted
+    fn paint_operator(&self) -> Option<&'static str> {
+        match (self.fill.is_some(), self.stroke.is_some()) {
+            (true, true) => Some("B"),
+            (true, false) => Some("f"),
+            (false, true) => Some("S"),
+            (false, false) => None,
+        }
+    }
+}
+
+/// A segment of a path
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PathSegment {
+    MoveTo(f64, f64),
+    LineTo(f64, f64),
+    CurveTo(f64, f64, f64, f64, f64, f64),
+    Close,
+}
+
+/// Path builder for arbitrary shapes
+///
+/// Coordinates are in whatever space the caller draws in; `PdfDocument`
+/// methods take top-left page coordinates, like `insert_text`.
+///
+/// # Example
+/// ```ignore
+/// let path = Path::new()
+///     .move_to(100.0, 100.0)
+///     .line_to(200.0, 100.0)
+///     .line_to(150.0, 50.0)
+///     .close();
+/// doc.draw_path(1, &path, &ShapeStyle::fill(Color::red()))?;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Path {
+    segments: Vec<PathSegment>,
+}
+
+impl Path {
+    /// Create an empty path
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new subpath at a point
+    pub fn move_to(mut self, x: f64, y: f64) -> Self {
+        self.segments.push(PathSegment::MoveTo(x, y));
+        self
+    }
+
+    /// Add a straight line to a point
+    pub fn line_to(mut self, x: f64, y: f64) -> Self {
+        self.segments.push(PathSegment::LineTo(x, y));
+        self
+    }
+
+    /// Add a cubic Bezier curve with two control points
+    pub fn curve_to(mut self, x1: f64, y1: f64, x2: f64, y2: f64, x3: f64, y3: f64) -> Self {
+        self.segments
+            .push(PathSegment::CurveTo(x1, y1, x2, y2, x3, y3));
+        self
+    }
+
+    /// Close the current subpath
+    pub fn close(mut self) -> Self {
+        self.segments.push(PathSegment::Close);
+        self
+    }
+
+    /// Add a rectangle subpath
+    ///
+    /// # Arguments
+    /// * `x`, `y` - Corner point
+    /// * `width`, `height` - Size (extends toward increasing coordinates)
+    pub fn rect(self, x: f64, y: f64, width: f64, height: f64) -> Self {
+        self.move_to(x, y)
+            .line_to(x + width, y)
+            .line_to(x + width, y + height)
+            .line_to(x, y + height)
+            .close()
+    }
+
+    /// Add a rectangle subpath with rounded corners
+    ///
+    /// The radius is clamped to half the shorter side.
+    pub fn rounded_rect(self, x: f64, y: f64, width: f64, height: f64, radius: f64) -> Self {
+        let r = radius
+            .min(width.abs() / 2.0)
+            .min(height.abs() / 2.0)
+            .max(0.0);
+        if r == 0.0 {
+            return self.rect(x, y, width, height);
+        }
+
+        // Work with the size's signs so flipped coordinates still round outward
+        let rx = r * width.signum();
+        let ry = r * height.signum();
+        let k = 1.0 - KAPPA;
+        let (x2, y2) = (x + width, y + height);
+
+        self.move_to(x + rx, y)
+            .line_to(x2 - rx, y)
+            .curve_to(x2 - rx * k, y, x2, y + ry * k, x2, y + ry)
+            .line_to(x2, y2 - ry)
+            .curve_to(x2, y2 - ry * k, x2 - rx * k, y2, x2 - rx, y2)
+            .line_to(x + rx, y2)
+            .curve_to(x + rx * k, y2, x, y2 - ry * k, x, y2 - ry)
+            .line_to(x, y + ry)
+            .curve_to(x, y + ry * k, x + rx * k, y, x + rx, y)
+            .close()
+    }
+
+    /// Add an ellipse subpath approximated by four Bezier curves
+    ///
+    /// # Arguments
+    /// * `cx`, `cy` - Center point
+    /// * `rx`, `ry` - Horizontal and vertical radius
+    pub fn ellipse(self, cx: f64, cy: f64, rx: f64, ry: f64) -> Self {
+        let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+
+        self.move_to(cx + rx, cy)
+            .curve_to(cx + rx, cy + ky, cx + kx, cy + ry, cx, cy + ry)
+            .curve_to(cx - kx, cy + ry, cx - rx, cy + ky, cx - rx, cy)
+            .curve_to(cx - rx, cy - ky, cx - kx, cy - ry, cx, cy - ry)
+            .curve_to(cx + kx, cy - ry, cx + rx, cy - ky, cx + rx, cy)
+            .close()
+    }
+
+    /// Whether the path has no segments
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// First point of the path, if any
+    pub fn start_point(&self) -> Option<(f64, f64)> {
+        self.segments.iter().find_map(|segment| match *segment {
+            PathSegment::MoveTo(x, y) | PathSegment::LineTo(x, y) => Some((x, y)),
+            PathSegment::CurveTo(_, _, _, _, x, y) => Some((x, y)),
+            PathSegment::Close => None,
+        })
+    }
+
+    /// Map every point through a function (e.g. to flip the Y axis)
+    pub fn map_points(mut self, f: impl Fn(f64, f64) -> (f64, f64)) -> Self {
+        for segment in &mut self.segments {
+            *segment = match *segment {
+                PathSegment::MoveTo(x, y) => {
+                    let (x, y) = f(x, y);
+                    PathSegment::MoveTo(x, y)
+                }
+                PathSegment::LineTo(x, y) => {
+                    let (x, y) = f(x, y);
+                    PathSegment::LineTo(x, y)
+                }
+                PathSegment::CurveTo(x1, y1, x2, y2, x3, y3) => {
+                    let (x1, y1) = f(x1, y1);
+                    let (x2, y2) = f(x2, y2);
+                    let (x3, y3) = f(x3, y3);
+                    PathSegment::CurveTo(x1, y1, x2, y2, x3, y3)
+                }
+                PathSegment::Close => PathSegment::Close,
+            };
+        }
+        self
+    }
+
+    /// Path construction operators (without painting)
+    fn construction_operators(&self) -> String {
+        let mut ops = String::new();
+        for segment in &self.segments {
+            match segment {
+                PathSegment::MoveTo(x, y) => ops.push_str(&format!("{x} {y} m\n")),
+                PathSegment::LineTo(x, y) => ops.push_str(&format!("{x} {y} l\n")),
+                PathSegment::CurveTo(x1, y1, x2, y2, x3, y3) => {
+                    ops.push_str(&format!("{x1} {y1} {x2} {y2} {x3} {y3} c\n"))
+                }
+                PathSegment::Close => ops.push_str("h\n"),
+            }
+        }
+        ops
+    }
+}
+
+/// Generate PDF operators to paint a path
+///
+/// # Arguments
+/// * `path` - Path in PDF coordinates
+/// * `style` - Stroke and fill settings
+///
+/// # Returns
+/// PDF content stream operators as bytes (empty if nothing is painted)
+pub fn generate_path_operators(path: &Path, style: &ShapeStyle) -> Vec<u8> {
+    let Some(paint) = style.paint_operator() else {
+        return Vec::new();
+    };
+    if path.is_empty() {
+        return Vec::new();
+    }
+
+    let mut ops = String::from("q\n");
+    ops.push_str(&style.state_operators());
+    ops.push_str(&path.construction_operators());
+    ops.push_str(paint);
+    ops.push_str("\nQ\n");
+    ops.into_bytes()
+}
+
+/// Generate PDF operators to stroke a straight line
+///
+/// # Arguments
+/// * `x1`, `y1` - Start point (PDF coordinates)
+/// * `x2`, `y2` - End point (PDF coordinates)
+/// * `style` - Stroke settings (fill is ignored)
+///
+/// # Returns
+/// PDF content stream operators as bytes (empty if the style has no stroke)
+pub fn generate_line_operators(x1: f64, y1: f64, x2: f64, y2: f64, style: &ShapeStyle) -> Vec<u8> {
+    let style = ShapeStyle {
+        fill: None,
+        ..style.clone()
+    };
+    let path = Path::new().move_to(x1, y1).line_to(x2, y2);
+    generate_path_operators(&path, &style)
+}
+
+/// Generate PDF operators to draw a rectangle
+///
+/// # Arguments
+/// * `x` - X coordinate of the left edge (PDF coordinates)
+/// * `y` - Y coordinate of the bottom edge (PDF coordinates)
+/// * `width` - Width in points
+/// * `height` - Height in points
+/// * `style` - Stroke and fill settings
+///
+/// # Returns
+/// PDF content stream operators as bytes (empty if nothing is painted)
+pub fn generate_rect_operators(
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    style: &ShapeStyle,
+) -> Vec<u8> {
+    let Some(paint) = style.paint_operator() else {
+        return Vec::new();
+    };
+
+    // `re` is shorter than four line segments
+    let mut ops = String::from("q\n");
+    ops.push_str(&style.state_operators());
+    ops.push_str(&format!("{x} {y} {width} {height} re\n{paint}\nQ\n"));
+    ops.into_bytes()
+}
+
+/// Generate PDF operators for a check mark in a `size` x `size` box
+///
+/// Tick and cross strokes scale with the box; the filled square leaves a
+/// margin so a printed box outline stays visible.
+///
+/// # Arguments
+/// * `mark` - Mark shape
+/// * `x` - X coordinate of the left edge of the box (PDF coordinates)
+/// * `y` - Y coordinate of the bottom edge of the box (PDF coordinates)
+/// * `size` - Box side in points
+/// * `color` - Mark color
+pub fn generate_check_mark_operators(
+    mark: CheckMark,
+    x: f64,
+    y: f64,
+    size: f64,
+    color: Color,
+) -> Vec<u8> {
+    let stroke = ShapeStyle::stroke(color, (size * 0.1).max(0.5))
+        .with_line_cap(LineCap::Round)
+        .with_line_join(LineJoin::Round);
+
+    match mark {
+        CheckMark::Tick => {
+            let path = Path::new()
+                .move_to(x + size * 0.2, y + size * 0.5)
+                .line_to(x + size * 0.42, y + size * 0.25)
+                .line_to(x + size * 0.8, y + size * 0.75);
+            generate_path_operators(&path, &stroke)
+        }
+        CheckMark::Cross => {
+            let (low, high) = (size * 0.2, size * 0.8);
+            let path = Path::new()
+                .move_to(x + low, y + low)
+                .line_to(x + high, y + high)
+                .move_to(x + low, y + high)
+                .line_to(x + high, y + low);
+            generate_path_operators(&path, &stroke)
+        }
+        CheckMark::Fill => {
+            let inset = size * 0.2;
+            generate_rect_operators(
+                x + inset,
+                y + inset,
+                size - 2.0 * inset,
+                size - 2.0 * inset,
+                &ShapeStyle::fill(color),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transform_matrix() {
+        assert!(Transform::default().is_identity());
+        assert_eq!(
+            Transform::default().matrix(100.0, 200.0),
+            [1.0, 0.0, 0.0, 1.0, 0.0, 0.0]
+        );
+
+        // 90° keeps the anchor and turns +x into +y
+        let m = Transform::rotate(90.0).matrix(100.0, 200.0);
+        assert_eq!(m, [0.0, 1.0, -1.0, 0.0, 300.0, 100.0]);
+        let (x, y) = (110.0, 200.0);
+        assert_eq!(
+            (m[0] * x + m[2] * y + m[4], m[1] * x + m[3] * y + m[5]),
+            (100.0, 210.0)
+        );
+
+        let m = Transform::scale(2.0, 0.5).matrix(10.0, 10.0);
+        assert_eq!(m, [2.0, 0.0, 0.0, 0.5, -10.0, 5.0]);
+
+        let m = Transform::skew(45.0, 0.0).matrix(0.0, 0.0);
+        assert_eq!(m, [1.0, 0.0, 1.0, 1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_transparency() {
+        assert!(Transparency::default().is_opaque());
+        assert!(!Transparency::alpha(0.5).is_opaque());
+        assert!(!Transparency::blend(BlendMode::Multiply).is_opaque());
+
+        // Out-of-range alphas are clamped; nearly equal ones share a key
+        assert_eq!(
+            Transparency::alpha(1.5).key(),
+            (1000, 1000, BlendMode::Normal)
+        );
+        assert_eq!(
+            Transparency::alpha(0.3).key(),
+            Transparency::alpha(0.3001).key()
+        );
+        assert_eq!(BlendMode::ColorDodge.pdf_name(), "ColorDodge");
+
+        let ops = generate_ext_gstate_operators("GS1", b"/Im1 Do\n");
+        assert_eq!(String::from_utf8(ops).unwrap(), "q\n/GS1 gs\n/Im1 Do\nQ\n");
+    }
+
+    #[test]
+    fn test_transformed_operators() {
+        let ops = generate_transformed_operators([0.0, 1.0, -1.0, 0.0, 300.0, 100.0], b"/Im1 Do\n");
+        assert_eq!(
+            String::from_utf8(ops).unwrap(),
+            "q\n0 1 -1 0 300 100 cm\n/Im1 Do\nQ\n"
+        );
+        assert!(generate_transformed_operators([1.0, 0.0, 0.0, 1.0, 0.0, 0.0], b"").is_empty());
+    }
+
+    #[test]
+    fn test_line_operators() {
+        let style = ShapeStyle::stroke(Color::red(), 0.5);
+        let ops =
+            String::from_utf8(generate_line_operators(10.0, 20.0, 110.0, 20.0, &style)).unwrap();
+
+        assert_eq!(ops, "q\n1 0 0 RG\n0.5 w\n10 20 m\n110 20 l\nS\nQ\n");
+        assert!(
+            generate_line_operators(0.0, 0.0, 1.0, 1.0, &ShapeStyle::fill(Color::red())).is_empty()
+        );
+    }
+
+    #[test]
+    fn test_rect_operators() {
+        let fill = ShapeStyle::fill(Color::white());
+        let ops = String::from_utf8(generate_rect_operators(0.0, 0.0, 50.0, 20.0, &fill)).unwrap();
+        assert_eq!(ops, "q\n1 1 1 rg\n0 0 50 20 re\nf\nQ\n");
+
+        let both = ShapeStyle::stroke(Color::black(), 1.0).with_fill(Color::white());
+        let ops = String::from_utf8(generate_rect_operators(0.0, 0.0, 50.0, 20.0, &both)).unwrap();
+        assert!(ops.contains("0 0 0 RG\n1 w\n1 1 1 rg\n"));
+        assert!(ops.ends_with("re\nB\nQ\n"));
+
+        let none = ShapeStyle {
+            stroke: None,
+            ..ShapeStyle::default()
+        };
+        assert!(generate_rect_operators(0.0, 0.0, 1.0, 1.0, &none).is_empty());
+    }
+
+    #[test]
+    fn test_stroke_state_operators() {
+        let style = ShapeStyle::stroke(Color::black(), 2.0)
+            .with_dash(vec![3.0, 1.5], 0.0)
+            .with_line_cap(LineCap::Round)
+            .with_line_join(LineJoin::Bevel);
+        let ops = String::from_utf8(generate_line_operators(0.0, 0.0, 10.0, 0.0, &style)).unwrap();
+
+        assert!(ops.contains("2 w\n1 J\n2 j\n[3 1.5] 0 d\n"));
+    }
+
+    #[test]
+    fn test_path_operators() {
+        let path = Path::new()
+            .move_to(0.0, 0.0)
+            .line_to(10.0, 0.0)
+            .curve_to(12.0, 0.0, 12.0, 5.0, 10.0, 5.0)
+            .close();
+        let ops = String::from_utf8(generate_path_operators(
+            &path,
+            &ShapeStyle::fill(Color::black()),
+        ))
+        .unwrap();
+
+        assert_eq!(
+            ops,
+            "q\n0 0 0 rg\n0 0 m\n10 0 l\n12 0 12 5 10 5 c\nh\nf\nQ\n"
+        );
+        assert!(generate_path_operators(&Path::new(), &ShapeStyle::default()).is_empty());
+    }
+
+    #[test]
+    fn test_ellipse_and_rounded_rect() {
+        let ellipse = Path::new().ellipse(50.0, 50.0, 20.0, 10.0);
+        let ops = ellipse.construction_operators();
+        assert!(ops.starts_with("70 50 m\n"));
+        assert_eq!(ops.matches(" c\n").count(), 4);
+
+        let rounded = Path::new().rounded_rect(0.0, 0.0, 40.0, 20.0, 5.0);
+        let ops = rounded.construction_operators();
+        assert!(ops.starts_with("5 0 m\n35 0 l\n"));
+        assert_eq!(ops.matches(" c\n").count(), 4);
+
+        // Radius is clamped; zero radius is a plain rectangle
+        let clamped = Path::new().rounded_rect(0.0, 0.0, 40.0, 20.0, 50.0);
+        assert!(clamped.construction_operators().starts_with("10 0 m\n"));
+        let square = Path::new().rounded_rect(0.0, 0.0, 40.0, 20.0, 0.0);
+        assert_eq!(square.construction_operators().matches(" c\n").count(), 0);
+    }
+
+    #[test]
+    fn test_check_mark_operators() {
+        let tick = String::from_utf8(generate_check_mark_operators(
+            CheckMark::Tick,
+            0.0,
+            0.0,
+            10.0,
+            Color::black(),
+        ))
+        .unwrap();
+        assert_eq!(
+            tick,
+            "q\n0 0 0 RG\n1 w\n1 J\n1 j\n2 5 m\n4.2 2.5 l\n8 7.5 l\nS\nQ\n"
+        );
+
+        let cross = String::from_utf8(generate_check_mark_operators(
+            CheckMark::Cross,
+            100.0,
+            200.0,
+            10.0,
+            Color::red(),
+        ))
+        .unwrap();
+        assert!(cross.starts_with("q\n1 0 0 RG\n"));
+        assert_eq!(cross.matches(" m\n").count(), 2);
+
+        let fill = String::from_utf8(generate_check_mark_operators(
+            CheckMark::Fill,
+            0.0,
+            0.0,
+            10.0,
+            Color::black(),
+        ))
+        .unwrap();
+        assert_eq!(fill, "q\n0 0 0 rg\n2 2 6 6 re\nf\nQ\n");
+    }
+
+    #[test]
+    fn test_map_points() {
+        let path = Path::new().move_to(1.0, 2.0).line_to(3.0, 4.0);
+        let flipped = path.map_points(|x, y| (x, 100.0 - y));
+
+        assert_eq!(flipped.construction_operators(), "1 98 m\n3 96 l\n");
+    }
+}
@@ -123,6 +123,8 @@ pub struct ImageXObject {
     pub filter: String,
     /// Raw image data (compressed)
     pub data: Vec<u8>,
+    /// Alpha channel as 8-bit gray, FlateDecode compressed (None = opaque)
+    pub soft_mask: Option<Vec<u8>>,
 }
 
 /// Get image dimensions without fully decoding
@@ -236,13 +238,15 @@ impl ImageXObject {
             bits_per_component: 8,
             filter: "DCTDecode".to_string(),
             data: data.to_vec(),
+            soft_mask: None,
         })
     }
 
     /// Create XObject from PNG data
     ///
     /// PNG images need to be decoded and re-encoded as RGB data with FlateDecode.
-    /// Alpha channels are properly blended with white background.
+    /// Alpha channels are kept as a soft mask so content under transparent
+    /// pixels stays visible; fully opaque images get no mask.
     pub fn from_png(data: &[u8]) -> Result<Self> {
         let cursor = Cursor::new(data);
         let reader = ImageReader::new(cursor).with_guessed_format()?;
@@ -254,52 +258,40 @@ impl ImageXObject {
         // Decode the image
         let image = DynamicImage::from_decoder(decoder)?;
 
-        // Process based on color type, handling alpha properly
-        let (raw_data, color_space) = match color_type {
+        // Split color and alpha based on color type
+        let (raw_data, color_space, alpha) = match color_type {
             // Pure grayscale - keep as grayscale for smaller size
             image::ColorType::L8 | image::ColorType::L16 => {
                 let gray = image.to_luma8();
-                (gray.into_raw(), "DeviceGray".to_string())
+                (gray.into_raw(), "DeviceGray".to_string(), None)
             }
-            // Grayscale with alpha - blend with white, output grayscale
+            // Grayscale with alpha - output grayscale plus mask
             image::ColorType::La8 | image::ColorType::La16 => {
                 let la = image.to_luma_alpha8();
-                let mut gray_data = Vec::with_capacity((dims.0 * dims.1) as usize);
-                for pixel in la.pixels() {
-                    let alpha = pixel[1] as f32 / 255.0;
-                    let gray = (pixel[0] as f32 * alpha + 255.0 * (1.0 - alpha)) as u8;
-                    gray_data.push(gray);
-                }
-                (gray_data, "DeviceGray".to_string())
+                let gray_data = la.pixels().map(|pixel| pixel[0]).collect();
+                let alpha = la.pixels().map(|pixel| pixel[1]).collect();
+                (gray_data, "DeviceGray".to_string(), Some(alpha))
             }
-            // RGBA - blend with white background, output RGB
+            // RGBA - output RGB plus mask
             image::ColorType::Rgba8 | image::ColorType::Rgba16 => {
                 let rgba = image.to_rgba8();
                 let mut rgb_data = Vec::with_capacity((dims.0 * dims.1 * 3) as usize);
+                let mut alpha = Vec::with_capacity((dims.0 * dims.1) as usize);
                 for pixel in rgba.pixels() {
-                    let alpha = pixel[3] as f32 / 255.0;
-                    let r = (pixel[0] as f32 * alpha + 255.0 * (1.0 - alpha)) as u8;
-                    let g = (pixel[1] as f32 * alpha + 255.0 * (1.0 - alpha)) as u8;
-                    let b = (pixel[2] as f32 * alpha + 255.0 * (1.0 - alpha)) as u8;
-                    rgb_data.push(r);
-                    rgb_data.push(g);
-                    rgb_data.push(b);
+                    rgb_data.extend_from_slice(&pixel.0[..3]);
+                    alpha.push(pixel[3]);
                 }
-                (rgb_data, "DeviceRGB".to_string())
+                (rgb_data, "DeviceRGB".to_string(), Some(alpha))
             }
             // RGB and other types - convert to RGB
             _ => {
                 let rgb = image.to_rgb8();
-                (rgb.into_raw(), "DeviceRGB".to_string())
+                (rgb.into_raw(), "DeviceRGB".to_string(), None)
             }
         };
 
-        // Compress with FlateDecode (zlib)
-        let compressed =
-            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
-        let mut encoder = compressed;
-        std::io::Write::write_all(&mut encoder, &raw_data)?;
-        let data = encoder.finish()?;
+        // A mask of fully opaque pixels changes nothing
+        let alpha = alpha.filter(|alpha: &Vec<u8>| alpha.iter().any(|&a| a != 255));
 
         Ok(Self {
             width: dims.0,
@@ -307,7 +299,8 @@ impl ImageXObject {
             color_space,
             bits_per_component: 8,
             filter: "FlateDecode".to_string(),
-            data,
+            data: deflate(&raw_data)?,
+            soft_mask: alpha.map(|alpha| deflate(&alpha)).transpose()?,
         })
     }
 
@@ -332,6 +325,33 @@ impl ImageXObject {
 
         Stream::new(dict, self.data.clone())
     }
+
+    /// Soft mask image for the alpha channel, if any
+    ///
+    /// The caller adds it to the document and references it from the
+    /// image's `/SMask` entry.
+    pub fn soft_mask_stream(&self) -> Option<Stream> {
+        let mask = self.soft_mask.as_ref()?;
+
+        let mut dict = Dictionary::new();
+        dict.set("Type", lopdf::Object::Name(b"XObject".to_vec()));
+        dict.set("Subtype", lopdf::Object::Name(b"Image".to_vec()));
+        dict.set("Width", self.width as i64);
+        dict.set("Height", self.height as i64);
+        dict.set("ColorSpace", lopdf::Object::Name(b"DeviceGray".to_vec()));
+        dict.set("BitsPerComponent", 8);
+        dict.set("Filter", lopdf::Object::Name(b"FlateDecode".to_vec()));
+        dict.set("Length", mask.len() as i64);
+
+        Some(Stream::new(dict, mask.clone()))
+    }
+}
+
+/// Compress data with FlateDecode (zlib)
+fn deflate(data: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    std::io::Write::write_all(&mut encoder, data)?;
+    Ok(encoder.finish()?)
 }
 
 /// Generate operators to draw image at position
@@ -408,6 +428,53 @@ mod tests {
         assert!(ops_str.contains("Q"));
     }
 
+    fn encode_png(image: image::RgbaImage) -> Vec<u8> {
+        let mut png = Vec::new();
+        DynamicImage::ImageRgba8(image)
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        png
+    }
+
+    #[test]
+    fn test_png_alpha_soft_mask() {
+        // Half transparent red, half opaque blue
+        let png = encode_png(image::RgbaImage::from_fn(2, 1, |x, _| {
+            if x == 0 {
+                image::Rgba([255, 0, 0, 0])
+            } else {
+                image::Rgba([0, 0, 255, 255])
+            }
+        }));
+        let xobject = ImageXObject::from_png(&png).unwrap();
+
+        // Color is kept as is instead of being blended onto white
+        let mut decoder = flate2::read::ZlibDecoder::new(xobject.data.as_slice());
+        let mut rgb = Vec::new();
+        std::io::Read::read_to_end(&mut decoder, &mut rgb).unwrap();
+        assert_eq!(rgb, vec![255, 0, 0, 0, 0, 255]);
+
+        let mask = xobject.soft_mask_stream().unwrap();
+        assert_eq!(
+            mask.dict.get(b"ColorSpace").unwrap().as_name().unwrap(),
+            b"DeviceGray"
+        );
+        let mut decoder = flate2::read::ZlibDecoder::new(mask.content.as_slice());
+        let mut alpha = Vec::new();
+        std::io::Read::read_to_end(&mut decoder, &mut alpha).unwrap();
+        assert_eq!(alpha, vec![0, 255]);
+
+        // Opaque images need no mask
+        let png = encode_png(image::RgbaImage::from_pixel(
+            2,
+            2,
+            image::Rgba([1, 2, 3, 255]),
+        ));
+        let xobject = ImageXObject::from_png(&png).unwrap();
+        assert!(xobject.soft_mask.is_none());
+        assert!(xobject.soft_mask_stream().is_none());
+    }
+
     #[test]
     fn test_image_xobject_to_pdf_stream() {
         let xobject = ImageXObject {
@@ -417,6 +484,7 @@ mod tests {
             bits_per_component: 8,
             filter: "DCTDecode".to_string(),
             data: vec![1, 2, 3, 4, 5],
+            soft_mask: None,
         };
 
         let stream = xobject.to_pdf_stream();
@@ -542,6 +610,7 @@ mod tests {
             bits_per_component: 8,
             filter: "DCTDecode".to_string(),
             data: vec![1, 2, 3, 4, 5],
+            soft_mask: None,
         };
 
         let cloned = xobject.clone();
@@ -562,6 +631,7 @@ mod tests {
             bits_per_component: 8,
             filter: "DCTDecode".to_string(),
             data: vec![1, 2, 3],
+            soft_mask: None,
         };
 
         let debug_str = format!("{xobject:?}");
@@ -625,6 +695,7 @@ mod tests {
             bits_per_component: 8,
             filter: "FlateDecode".to_string(),
             data: vec![1, 2, 3],
+            soft_mask: None,
         };
 
         let stream = xobject.to_pdf_stream();
@@ -649,6 +720,7 @@ mod tests {
             bits_per_component: 8,
             filter: "DCTDecode".to_string(),
             data: vec![],
+            soft_mask: None,
         };
 
         let stream = xobject.to_pdf_stream();
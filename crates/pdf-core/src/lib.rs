@@ -2,9 +2,22 @@
 //!
 //! This crate provides functionality for:
 //! - Opening and saving PDF documents
+//! - Creating documents from scratch with A3–Legal or custom page sizes, orientation and margins
 //! - Embedding TrueType fonts
+//! - Shaping text with OpenType GSUB/GPOS (Thai marks, ligatures, kerning)
 //! - Inserting text at specific coordinates
 //! - Inserting images (JPEG, PNG)
+//! - Rotating, skewing and scaling text, images and shapes
+//! - Transparency (opacity and blend modes) and PNG alpha soft masks
+//! - Text and image watermarks, over or under the page content
+//! - Merging documents with shared fonts and a bookmark per document
+//! - Importing pages of other PDFs (letterheads, approval pages) as Form XObjects
+//! - Deleting, reordering, rotating, cropping and extracting pages
+//! - Drawing 1D barcodes and QR module matrices as vector rectangles
+//! - Drawing lines, rectangles, ellipses, paths and check marks
+//! - Filling AcroForm fields (text, checkbox, radio, choice)
+//! - Creating text, checkbox, radio and signature fields
+//! - Flattening form fields and annotations into page content
 //!
 //! # Example
 //!
@@ -18,15 +31,35 @@
 //! doc.save("output.pdf")?;
 //! ```
 
+mod barcode;
 mod document;
 mod font;
+mod form;
+mod graphics;
 mod image;
+mod page;
 mod text;
-
-pub use document::{Color, PdfDocument};
-pub use font::{FontData, FontFamily, FontFamilyBuilder, FontStyle, FontWeight};
+mod watermark;
+
+pub use barcode::{
+    generate_barcode_operators, generate_matrix_operators, Barcode, BarcodeSymbology, ModuleMatrix,
+};
+pub use document::{Color, ImportedPage, PdfDocument, Rect};
+pub use font::{FontData, FontFamily, FontFamilyBuilder, FontStyle, FontWeight, ShapedGlyph};
+pub use form::flags as field_flags;
+pub use form::{FieldDefinition, FieldWidget, FlattenMode, FormField, FormFieldType};
+pub use graphics::{
+    generate_check_mark_operators, generate_ext_gstate_operators, generate_line_operators,
+    generate_path_operators, generate_rect_operators, generate_transformed_operators, BlendMode,
+    CheckMark, LineCap, LineJoin, Path, ShapeStyle, Transform, Transparency,
+};
 pub use image::ImageScaleMode;
-pub use text::{generate_text_operators, simple_word_wrap, TextRenderContext};
+pub use page::{Margins, Orientation, PageSize};
+pub use text::{
+    calculate_y_offset, generate_shaped_text_operators, generate_text_operators, simple_word_wrap,
+    simple_word_wrap_width, PositionedGlyph, TextRenderContext,
+};
+pub use watermark::{Watermark, WatermarkContent, WatermarkLayer};
 
 use thiserror::Error;
 
@@ -54,9 +87,21 @@ pub enum PdfError {
     #[error("Invalid page number: {0} (document has {1} pages)")]
     InvalidPage(usize, usize),
 
+    #[error("Invalid page rotation: {0} (must be a multiple of 90)")]
+    InvalidRotation(i32),
+
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
     #[error("Image error: {0}")]
     ImageError(String),
 
+    #[error("Barcode error: {0}")]
+    BarcodeError(String),
+
+    #[error("Form field error: {0}")]
+    FormError(String),
+
     #[error("PDF parsing error: {0}")]
     ParseError(String),
 
@@ -0,0 +1,148 @@
+//! Page sizes for documents created from scratch
+
+/// Page orientation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Orientation {
+    /// Taller than wide
+    #[default]
+    Portrait,
+    /// Wider than tall
+    Landscape,
+}
+
+/// Page margins in points
+///
+/// On pages added with margins, text, images and shapes are placed from the
+/// top-left corner of the area inside them. Form field rectangles, page
+/// boxes and watermarks still use the whole page.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Margins {
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+    pub left: f64,
+}
+
+impl Margins {
+    /// Create margins (clockwise from the top, like CSS)
+    pub fn new(top: f64, right: f64, bottom: f64, left: f64) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    /// The same margin on every side
+    pub fn all(margin: f64) -> Self {
+        Self::new(margin, margin, margin, margin)
+    }
+
+    /// Whether every margin is zero
+    pub fn is_zero(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Page size in points with margins, for `PdfDocument::add_page`
+///
+/// # Example
+/// ```ignore
+/// let mut doc = PdfDocument::new();
+/// doc.add_page(PageSize::A4.landscape().with_margins(Margins::all(36.0)))?;
+/// doc.add_page(PageSize::custom(288.0, 432.0))?;  // 4 x 6 in label
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageSize {
+    /// Width in points
+    pub width: f64,
+    /// Height in points
+    pub height: f64,
+    /// Margins inside the page
+    pub margins: Margins,
+}
+
+impl PageSize {
+    /// ISO A3 (297 x 420 mm)
+    pub const A3: Self = Self::custom(841.89, 1190.55);
+    /// ISO A4 (210 x 297 mm)
+    pub const A4: Self = Self::custom(595.28, 841.89);
+    /// ISO A5 (148 x 210 mm)
+    pub const A5: Self = Self::custom(419.53, 595.28);
+    /// ISO B5 (176 x 250 mm)
+    pub const B5: Self = Self::custom(498.9, 708.66);
+    /// US Letter (8.5 x 11 in)
+    pub const LETTER: Self = Self::custom(612.0, 792.0);
+    /// US Legal (8.5 x 14 in)
+    pub const LEGAL: Self = Self::custom(612.0, 1008.0);
+
+    /// A page of any size, without margins
+    pub const fn custom(width: f64, height: f64) -> Self {
+        Self {
+            width,
+            height,
+            margins: Margins {
+                top: 0.0,
+                right: 0.0,
+                bottom: 0.0,
+                left: 0.0,
+            },
+        }
+    }
+
+    /// Orientation of the page
+    pub fn orientation(&self) -> Orientation {
+        if self.width > self.height {
+            Orientation::Landscape
+        } else {
+            Orientation::Portrait
+        }
+    }
+
+    /// Turn the page to an orientation, swapping width and height if needed
+    pub fn with_orientation(mut self, orientation: Orientation) -> Self {
+        if self.orientation() != orientation {
+            std::mem::swap(&mut self.width, &mut self.height);
+        }
+        self
+    }
+
+    /// Turn the page to landscape
+    pub fn landscape(self) -> Self {
+        self.with_orientation(Orientation::Landscape)
+    }
+
+    /// Set the margins
+    pub fn with_margins(mut self, margins: Margins) -> Self {
+        self.margins = margins;
+        self
+    }
+}
+
+impl Default for PageSize {
+    fn default() -> Self {
+        Self::A4
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_orientation() {
+        let landscape = PageSize::A4.landscape();
+        assert_eq!((landscape.width, landscape.height), (841.89, 595.28));
+        assert_eq!(landscape.orientation(), Orientation::Landscape);
+        assert_eq!(landscape.landscape(), landscape);
+        assert_eq!(
+            landscape.with_orientation(Orientation::Portrait),
+            PageSize::A4
+        );
+
+        let page = PageSize::LETTER.with_margins(Margins::all(36.0));
+        assert_eq!(page.margins, Margins::new(36.0, 36.0, 36.0, 36.0));
+        assert!(PageSize::LETTER.margins.is_zero());
+    }
+}
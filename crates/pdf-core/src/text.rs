@@ -15,6 +15,24 @@ pub struct TextRenderContext {
     pub color: Color,
 }
 
+/// A shaped glyph ready for the content stream
+///
+/// Metrics are in PDF glyph space (1000 units per em) and `gid` is the
+/// glyph ID written to the content stream (remapped to the subset).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionedGlyph {
+    /// Glyph ID in the embedded font
+    pub gid: u16,
+    /// Width from the /W array (what the viewer advances by)
+    pub width: f64,
+    /// Advance requested by the shaper (includes kerning)
+    pub x_advance: f64,
+    /// Horizontal placement offset
+    pub x_offset: f64,
+    /// Vertical placement offset
+    pub y_offset: f64,
+}
+
 /// Calculate X offset for text alignment
 ///
 /// # Arguments
@@ -30,6 +48,25 @@ pub fn calculate_x_offset(text_width: f64, container_width: f64, align: Align) -
     }
 }
 
+/// Calculate Y offset for vertical alignment
+///
+/// Uses the `position` flags: `MIDDLE` centers the content, `BOTTOM` aligns
+/// it to the bottom of the container, anything else aligns to the top.
+///
+/// # Arguments
+/// * `content_height` - Height of the content in points
+/// * `container_height` - Available height for alignment
+/// * `position` - Position flags (see `crate::position`)
+pub fn calculate_y_offset(content_height: f64, container_height: f64, position: i32) -> f64 {
+    if position & crate::position::MIDDLE != 0 {
+        (container_height - content_height) / 2.0
+    } else if position & crate::position::BOTTOM != 0 {
+        container_height - content_height
+    } else {
+        0.0
+    }
+}
+
 /// Generate PDF operators for text insertion
 ///
 /// Creates the proper PDF text operators (BT, Tf, Td, Tj, ET) to render text
@@ -86,6 +123,92 @@ pub fn generate_text_operators(
     ops.into_bytes()
 }
 
+/// Generate PDF operators for shaped glyphs
+///
+/// Emits a TJ array so GPOS positioning (kerning, mark placement) is kept:
+/// the difference between the shaped pen position and the /W width is
+/// written as a TJ adjustment. Glyphs with a vertical offset are raised
+/// with Ts.
+///
+/// # Arguments
+/// * `glyphs` - Positioned glyphs (see `FontData::position_glyphs`)
+/// * `x` - X coordinate in points (PDF coordinates, from left)
+/// * `y` - Y coordinate in points (PDF coordinates, from bottom)
+/// * `align` - Text alignment
+/// * `ctx` - Text rendering context
+///
+/// # Returns
+/// Vector of bytes containing the PDF operators
+pub fn generate_shaped_text_operators(
+    glyphs: &[PositionedGlyph],
+    x: f64,
+    y: f64,
+    align: Align,
+    ctx: &TextRenderContext,
+) -> Vec<u8> {
+    let mut ops = String::new();
+
+    let x_offset = match align {
+        Align::Left => 0.0,
+        Align::Center => -ctx.text_width / 2.0,
+        Align::Right => -ctx.text_width,
+    };
+    let final_x = x + x_offset;
+
+    ops.push_str("BT\n");
+    ops.push_str(&format!(
+        "{} {} {} rg\n",
+        ctx.color.r, ctx.color.g, ctx.color.b
+    ));
+    ops.push_str(&format!("/{} {} Tf\n", ctx.font_name, ctx.font_size));
+    ops.push_str(&format!("{final_x} {y} Td\n"));
+
+    // pen: shaped position, cursor: where the viewer will draw next
+    let mut pen = 0.0;
+    let mut cursor = 0.0;
+    let mut rise = 0.0;
+    let mut run = String::new();
+
+    for glyph in glyphs {
+        let glyph_rise = round3(glyph.y_offset / 1000.0 * ctx.font_size as f64);
+        if glyph_rise != rise {
+            flush_tj_run(&mut ops, &mut run);
+            ops.push_str(&format!("{glyph_rise} Ts\n"));
+            rise = glyph_rise;
+        }
+
+        let adjust = (pen + glyph.x_offset - cursor).round();
+        if adjust != 0.0 {
+            // TJ numbers move the cursor left, hence the negation
+            run.push_str(&format!("{} ", -adjust));
+            cursor += adjust;
+        }
+        run.push_str(&format!("<{:04X}> ", glyph.gid));
+        cursor += glyph.width;
+        pen += glyph.x_advance;
+    }
+    flush_tj_run(&mut ops, &mut run);
+
+    if rise != 0.0 {
+        ops.push_str("0 Ts\n");
+    }
+    ops.push_str("ET\n");
+
+    ops.into_bytes()
+}
+
+/// Write accumulated TJ elements as one TJ operator
+fn flush_tj_run(ops: &mut String, run: &mut String) {
+    if !run.is_empty() {
+        ops.push_str(&format!("[{}] TJ\n", run.trim_end()));
+        run.clear();
+    }
+}
+
+fn round3(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
 /// Split text into lines based on maximum width
 ///
 /// This is a simple implementation that splits on spaces.
@@ -125,6 +248,53 @@ pub fn simple_word_wrap(text: &str, max_chars: usize) -> Vec<String> {
     lines
 }
 
+/// Split text into lines that fit a measured width
+///
+/// Splits on spaces like `simple_word_wrap()`, but measures each candidate
+/// line with `measure` (e.g. `PdfDocument::get_text_width`) instead of
+/// counting characters.
+///
+/// # Arguments
+/// * `text` - Text to split
+/// * `max_width` - Maximum line width in points
+/// * `measure` - Returns the rendered width of a string in points
+pub fn simple_word_wrap_width<F>(text: &str, max_width: f64, mut measure: F) -> Vec<String>
+where
+    F: FnMut(&str) -> f64,
+{
+    if max_width <= 0.0 {
+        return vec![text.to_string()];
+    }
+
+    let mut lines = Vec::new();
+    let mut current_line = String::new();
+
+    for word in text.split_whitespace() {
+        if current_line.is_empty() {
+            current_line = word.to_string();
+            continue;
+        }
+
+        let candidate = format!("{current_line} {word}");
+        if measure(&candidate) <= max_width {
+            current_line = candidate;
+        } else {
+            lines.push(current_line);
+            current_line = word.to_string();
+        }
+    }
+
+    if !current_line.is_empty() {
+        lines.push(current_line);
+    }
+
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+
+    lines
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -147,6 +317,19 @@ mod tests {
         assert_eq!(offset, 400.0);
     }
 
+    #[test]
+    fn test_y_offset() {
+        use crate::position;
+
+        assert_eq!(calculate_y_offset(20.0, 100.0, position::TOP), 0.0);
+        assert_eq!(calculate_y_offset(20.0, 100.0, position::MIDDLE), 40.0);
+        assert_eq!(calculate_y_offset(20.0, 100.0, position::BOTTOM), 80.0);
+        assert_eq!(
+            calculate_y_offset(20.0, 100.0, position::LEFT | position::BOTTOM),
+            80.0
+        );
+    }
+
     #[test]
     fn test_simple_word_wrap() {
         let text = "Hello world this is a test";
@@ -341,6 +524,29 @@ mod tests {
         assert_eq!(lines[1], "world");
     }
 
+    #[test]
+    fn test_simple_word_wrap_width() {
+        // Narrow "i" and "l", wide "W" and "M"
+        let measure = |s: &str| {
+            s.chars()
+                .map(|c| match c {
+                    'i' | 'l' => 2.0,
+                    'W' | 'M' => 10.0,
+                    _ => 5.0,
+                })
+                .sum::<f64>()
+        };
+
+        let lines = simple_word_wrap_width("ill ill WWW MMM", 24.0, measure);
+        assert_eq!(lines, vec!["ill ill", "WWW", "MMM"]);
+    }
+
+    #[test]
+    fn test_simple_word_wrap_width_zero_max() {
+        let lines = simple_word_wrap_width("Hello world", 0.0, |s| s.len() as f64);
+        assert_eq!(lines, vec!["Hello world"]);
+    }
+
     #[test]
     fn test_simple_word_wrap_thai() {
         // Thai text doesn't use spaces between words
@@ -366,6 +572,80 @@ mod tests {
         assert_eq!(ctx.color, Color::red());
     }
 
+    fn glyph(gid: u16, width: f64, x_advance: f64) -> PositionedGlyph {
+        PositionedGlyph {
+            gid,
+            width,
+            x_advance,
+            x_offset: 0.0,
+            y_offset: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_generate_shaped_text_operators_plain() {
+        let ctx = TextRenderContext {
+            font_name: "F1".to_string(),
+            font_size: 12.0,
+            text_width: 12.0,
+            color: Color::black(),
+        };
+        let glyphs = [glyph(1, 500.0, 500.0), glyph(2, 500.0, 500.0)];
+
+        let ops = generate_shaped_text_operators(&glyphs, 100.0, 700.0, Align::Left, &ctx);
+        let ops_str = String::from_utf8(ops).unwrap();
+
+        assert!(ops_str.contains("100 700 Td"));
+        assert!(ops_str.contains("[<0001> <0002>] TJ"));
+        assert!(!ops_str.contains("Ts"));
+    }
+
+    #[test]
+    fn test_generate_shaped_text_operators_kerning() {
+        let ctx = TextRenderContext {
+            font_name: "F1".to_string(),
+            font_size: 12.0,
+            text_width: 11.4,
+            color: Color::black(),
+        };
+        // First glyph kerned 50 units tighter than its /W width
+        let glyphs = [glyph(1, 500.0, 450.0), glyph(2, 500.0, 500.0)];
+
+        let ops = generate_shaped_text_operators(&glyphs, 100.0, 700.0, Align::Left, &ctx);
+        let ops_str = String::from_utf8(ops).unwrap();
+
+        assert!(ops_str.contains("[<0001> 50 <0002>] TJ"));
+    }
+
+    #[test]
+    fn test_generate_shaped_text_operators_mark_offset() {
+        let ctx = TextRenderContext {
+            font_name: "F1".to_string(),
+            font_size: 10.0,
+            text_width: 5.0,
+            color: Color::black(),
+        };
+        // Zero-advance mark shifted left and raised
+        let glyphs = [
+            glyph(1, 500.0, 500.0),
+            PositionedGlyph {
+                gid: 2,
+                width: 0.0,
+                x_advance: 0.0,
+                x_offset: -100.0,
+                y_offset: 200.0,
+            },
+        ];
+
+        let ops = generate_shaped_text_operators(&glyphs, 0.0, 0.0, Align::Left, &ctx);
+        let ops_str = String::from_utf8(ops).unwrap();
+
+        assert!(ops_str.contains("[<0001>] TJ"));
+        assert!(ops_str.contains("2 Ts"));
+        assert!(ops_str.contains("[100 <0002>] TJ"));
+        assert!(ops_str.contains("0 Ts"));
+    }
+
     #[test]
     fn test_generate_text_operators_with_color() {
         let ctx = TextRenderContext {
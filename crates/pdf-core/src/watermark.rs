@@ -0,0 +1,213 @@
+//! Watermarks and stamps ("COPY", "VOID", "DRAFT" overlays)
+
+use crate::document::Color;
+
+/// What a watermark shows
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatermarkContent {
+    /// Text in the watermark font
+    Text(String),
+    /// Image (JPEG or PNG bytes) fitted into `width` x `height` points
+    Image {
+        data: Vec<u8>,
+        width: f64,
+        height: f64,
+    },
+}
+
+/// Whether a watermark is drawn over or under the page content
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WatermarkLayer {
+    /// Over everything on the page
+    #[default]
+    Overlay,
+    /// Under the base PDF content (hidden by opaque backgrounds)
+    Underlay,
+}
+
+/// Text or image stamped across pages
+///
+/// A watermark is centered on the page and rotated by `angle` around its
+/// center, or repeated in a grid with `tiled`.
+///
+/// # Example
+/// ```ignore
+/// let watermark = Watermark::text("สำเนา")
+///     .with_font("sarabun", 96.0)
+///     .with_color(Color::red())
+///     .with_opacity(0.2);
+/// doc.add_watermark(&watermark)?;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Watermark {
+    /// Text or image
+    pub content: WatermarkContent,
+    /// Font family for text (None = the current font)
+    pub font: Option<String>,
+    /// Font size in points
+    pub font_size: f32,
+    /// Text color
+    pub color: Color,
+    /// Rotation in degrees (counter-clockwise)
+    pub angle: f64,
+    /// Opacity (0.0 - 1.0)
+    pub opacity: f64,
+    /// Pages to stamp (1-indexed, None = all pages)
+    pub pages: Option<Vec<usize>>,
+    /// Over or under the page content
+    pub layer: WatermarkLayer,
+    /// Gaps between repeated watermarks (None = one centered watermark)
+    pub tile: Option<(f64, f64)>,
+}
+
+impl Watermark {
+    fn new(content: WatermarkContent) -> Self {
+        Self {
+            content,
+            font: None,
+            font_size: 72.0,
+            color: Color::rgb(0.5, 0.5, 0.5),
+            angle: 45.0,
+            opacity: 0.3,
+            pages: None,
+            layer: WatermarkLayer::Overlay,
+            tile: None,
+        }
+    }
+
+    /// Text watermark (72pt gray at 45°, 30% opaque)
+    pub fn text(text: &str) -> Self {
+        Self::new(WatermarkContent::Text(text.to_string()))
+    }
+
+    /// Image watermark fitted into `width` x `height` points, keeping its
+    /// aspect ratio
+    pub fn image(data: &[u8], width: f64, height: f64) -> Self {
+        Self::new(WatermarkContent::Image {
+            data: data.to_vec(),
+            width,
+            height,
+        })
+    }
+
+    /// Set the font family and size for text
+    pub fn with_font(mut self, family: &str, size: f32) -> Self {
+        self.font = Some(family.to_string());
+        self.font_size = size;
+        self
+    }
+
+    /// Set the text color
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Set the rotation in degrees
+    pub fn with_angle(mut self, degrees: f64) -> Self {
+        self.angle = degrees;
+        self
+    }
+
+    /// Set the opacity (0.0 - 1.0)
+    pub fn with_opacity(mut self, opacity: f64) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// Stamp only these pages (1-indexed)
+    pub fn on_pages(mut self, pages: Vec<usize>) -> Self {
+        self.pages = Some(pages);
+        self
+    }
+
+    /// Draw over or under the page content
+    pub fn with_layer(mut self, layer: WatermarkLayer) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    /// Repeat the watermark over the whole page with gaps in points
+    pub fn tiled(mut self, gap_x: f64, gap_y: f64) -> Self {
+        self.tile = Some((gap_x, gap_y));
+        self
+    }
+}
+
+/// Centers of the watermarks on a page (top-left coordinates)
+///
+/// # Arguments
+/// * `page_width`, `page_height` - Page size in points
+/// * `width`, `height` - Watermark size in points (before rotation)
+/// * `tile` - Gaps between repeated watermarks, or None for one centered watermark
+pub(crate) fn watermark_centers(
+    page_width: f64,
+    page_height: f64,
+    width: f64,
+    height: f64,
+    tile: Option<(f64, f64)>,
+) -> Vec<(f64, f64)> {
+    let Some((gap_x, gap_y)) = tile else {
+        return vec![(page_width / 2.0, page_height / 2.0)];
+    };
+
+    let step_x = (width + gap_x).max(1.0);
+    let step_y = (height + gap_y).max(1.0);
+
+    // Grid through the page center, one extra cell past each edge so
+    // rotated tiles still reach the corners
+    let columns = (page_width / 2.0 / step_x).ceil() as i64 + 1;
+    let rows = (page_height / 2.0 / step_y).ceil() as i64 + 1;
+
+    let mut centers = Vec::new();
+    for row in -rows..=rows {
+        for column in -columns..=columns {
+            centers.push((
+                page_width / 2.0 + column as f64 * step_x,
+                page_height / 2.0 + row as f64 * step_y,
+            ));
+        }
+    }
+    centers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watermark_builder() {
+        let watermark = Watermark::text("VOID")
+            .with_font("sarabun", 48.0)
+            .with_angle(30.0)
+            .on_pages(vec![2])
+            .with_layer(WatermarkLayer::Underlay);
+
+        assert_eq!(
+            watermark.content,
+            WatermarkContent::Text("VOID".to_string())
+        );
+        assert_eq!(watermark.font.as_deref(), Some("sarabun"));
+        assert_eq!(watermark.font_size, 48.0);
+        assert_eq!(watermark.angle, 30.0);
+        assert_eq!(watermark.opacity, 0.3);
+        assert_eq!(watermark.pages, Some(vec![2]));
+        assert_eq!(watermark.layer, WatermarkLayer::Underlay);
+        assert!(watermark.tile.is_none());
+    }
+
+    #[test]
+    fn test_watermark_centers() {
+        assert_eq!(
+            watermark_centers(600.0, 800.0, 100.0, 50.0, None),
+            vec![(300.0, 400.0)]
+        );
+
+        let centers = watermark_centers(600.0, 800.0, 100.0, 50.0, Some((100.0, 150.0)));
+        // 200pt x 200pt cells: 2 + 1 columns and 2 + 1 rows each side of the center
+        assert_eq!(centers.len(), 7 * 7);
+        assert!(centers.contains(&(300.0, 400.0)));
+        assert!(centers.contains(&(-300.0, -200.0)));
+        assert!(centers.contains(&(900.0, 1000.0)));
+    }
+}
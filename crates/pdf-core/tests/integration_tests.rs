@@ -3,7 +3,11 @@
 //! These tests verify end-to-end functionality with real PDF operations.
 
 use lopdf::dictionary;
-use pdf_core::{Align, PdfDocument, PdfError};
+use pdf_core::{
+    Align, BlendMode, Color, FieldDefinition, FlattenMode, FontData, FormFieldType, LineCap,
+    Margins, PageSize, Path, PdfDocument, PdfError, Rect, ShapeStyle, Transform, Transparency,
+    Watermark, WatermarkLayer,
+};
 
 /// Create a minimal valid PDF for testing
 ///
@@ -107,6 +111,132 @@ fn create_test_pdf_with_pages(page_count: usize) -> Vec<u8> {
     buffer
 }
 
+/// Create a one-page A4 PDF with an AcroForm
+///
+/// Fields: `applicant.name` (text), `applicant.id` (13-cell comb),
+/// `agree` (checkbox with a "Yes" appearance), `gender` (radio "M"/"F")
+/// and `province` (combo box with export/display pairs).
+fn create_form_pdf() -> Vec<u8> {
+    let mut doc = lopdf::Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let page_id = doc.new_object_id();
+
+    let state_stream = |doc: &mut lopdf::Document| {
+        doc.add_object(lopdf::Stream::new(
+            dictionary! { "Type" => "XObject", "Subtype" => "Form", "BBox" => vec![0.into(), 0.into(), 12.into(), 12.into()] },
+            b"0 0 12 12 re f".to_vec(),
+        ))
+    };
+    let button = |doc: &mut lopdf::Document, state: &str, x: i64| {
+        let on = state_stream(doc);
+        let off = state_stream(doc);
+        doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Widget",
+            "P" => page_id,
+            "Rect" => vec![x.into(), 600.into(), (x + 12).into(), 612.into()],
+            "AS" => "Off",
+            "AP" => dictionary! { "N" => dictionary! { state => on, "Off" => off } },
+        })
+    };
+
+    let agree_id = button(&mut doc, "Yes", 100);
+    let agree = doc.get_dictionary_mut(agree_id).unwrap();
+    agree.set("T", lopdf::Object::string_literal("agree"));
+    agree.set("FT", "Btn");
+    let male_id = button(&mut doc, "M", 200);
+    let female_id = button(&mut doc, "F", 250);
+    let gender_id = doc.add_object(dictionary! {
+        "T" => lopdf::Object::string_literal("gender"),
+        "FT" => "Btn",
+        "Ff" => 1 << 15,
+        "Kids" => vec![male_id.into(), female_id.into()],
+    });
+    for kid in [male_id, female_id] {
+        doc.get_dictionary_mut(kid)
+            .unwrap()
+            .set("Parent", gender_id);
+    }
+
+    let text_widget = |rect: [i64; 4], extra: lopdf::Dictionary| {
+        let mut dict = dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Widget",
+            "P" => page_id,
+            "Rect" => rect.iter().map(|&v| v.into()).collect::<Vec<lopdf::Object>>(),
+        };
+        for (key, value) in extra.iter() {
+            dict.set(key.clone(), value.clone());
+        }
+        dict
+    };
+    let name_id = doc.add_object(text_widget(
+        [100, 700, 300, 720],
+        dictionary! { "T" => lopdf::Object::string_literal("name") },
+    ));
+    let id_id = doc.add_object(text_widget(
+        [100, 660, 360, 680],
+        dictionary! { "T" => lopdf::Object::string_literal("id"), "Ff" => 1 << 24, "MaxLen" => 13 },
+    ));
+    let applicant_id = doc.add_object(dictionary! {
+        "T" => lopdf::Object::string_literal("applicant"),
+        "FT" => "Tx",
+        "Kids" => vec![name_id.into(), id_id.into()],
+    });
+    for kid in [name_id, id_id] {
+        doc.get_dictionary_mut(kid)
+            .unwrap()
+            .set("Parent", applicant_id);
+    }
+
+    let province_id = doc.add_object(text_widget(
+        [100, 560, 300, 580],
+        dictionary! {
+            "T" => lopdf::Object::string_literal("province"),
+            "FT" => "Ch",
+            "Ff" => 1 << 17,
+            "Opt" => vec![
+                vec![lopdf::Object::string_literal("BKK"), lopdf::Object::string_literal("Bangkok")].into(),
+                vec![lopdf::Object::string_literal("CNX"), lopdf::Object::string_literal("Chiang Mai")].into(),
+            ],
+        },
+    ));
+
+    let contents_id = doc.add_object(lopdf::Stream::new(dictionary! {}, vec![]));
+    doc.objects.insert(
+        page_id,
+        dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), 595.28.into(), 841.89.into()],
+            "Resources" => dictionary! {},
+            "Contents" => contents_id,
+            "Annots" => vec![
+                name_id.into(), id_id.into(), agree_id.into(),
+                male_id.into(), female_id.into(), province_id.into(),
+            ],
+        }
+        .into(),
+    );
+    doc.objects.insert(
+        pages_id,
+        dictionary! { "Type" => "Pages", "Kids" => vec![page_id.into()], "Count" => 1 }.into(),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+        "AcroForm" => dictionary! {
+            "Fields" => vec![applicant_id.into(), agree_id.into(), gender_id.into(), province_id.into()],
+            "NeedAppearances" => true,
+        },
+    });
+    doc.trailer.set("Root", catalog_id);
+
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer).unwrap();
+    buffer
+}
+
 /// Create a minimal TTF font for testing
 ///
 /// This returns a very small subset of a font with basic glyphs.
@@ -267,6 +397,381 @@ fn test_page_duplication() {
     assert!(!saved_data.is_empty());
 }
 
+#[test]
+fn test_page_management() {
+    let mut doc =
+        PdfDocument::open_from_bytes(&create_test_pdf_with_pages(4)).expect("Failed to open PDF");
+    // Mark each page with a rectangle at x = 11, 22, 33, 44
+    for page in 1..=4 {
+        doc.draw_rect(
+            page,
+            Rect::new(11.0 * page as f64, 100.0, 5.0, 5.0),
+            &ShapeStyle::fill(Color::black()),
+        )
+        .expect("Failed to draw rect");
+    }
+    doc.add_bookmark("Page 2", 2)
+        .expect("Failed to add bookmark");
+    doc.add_bookmark("Page 3", 3)
+        .expect("Failed to add bookmark");
+    doc.add_form_field(&FieldDefinition::checkbox(
+        "agree",
+        2,
+        Rect::new(100.0, 100.0, 10.0, 10.0),
+    ))
+    .expect("Failed to add field");
+    doc.add_font("test", &get_test_font_data())
+        .expect("Failed to add font");
+    doc.set_font("test", 12.0).expect("Failed to set font");
+    doc.add_form_field(
+        &FieldDefinition::text("name", 2, Rect::new(100.0, 200.0, 100.0, 20.0)).with_value("Alice"),
+    )
+    .expect("Failed to add field");
+
+    doc.delete_page(2).expect("Failed to delete page");
+    assert_eq!(doc.page_count(), 3);
+    assert!(doc.form_fields().expect("Failed to list fields").is_empty());
+
+    doc.move_page(3, 1).expect("Failed to move page");
+    assert_eq!(doc.insert_page_at(2).expect("Failed to insert page"), 2);
+    assert_eq!(doc.page_count(), 4);
+    assert!(matches!(
+        doc.move_page(5, 1),
+        Err(PdfError::InvalidPage(5, 4))
+    ));
+
+    doc.set_crop_box(1, Rect::new(10.0, 20.0, 100.0, 50.0))
+        .expect("Failed to crop");
+    doc.set_rotation(1, -90).expect("Failed to rotate");
+    assert!(matches!(
+        doc.set_rotation(1, 45),
+        Err(PdfError::InvalidRotation(45))
+    ));
+
+    // Pages 3 and 4 were pages 1 and 3 before
+    let mut extracted = doc.extract_pages(3..).expect("Failed to extract");
+    assert_eq!(extracted.page_count(), 2);
+    let extracted_data = extracted.to_bytes().expect("Failed to save PDF");
+    let extracted = lopdf::Document::load_mem(&extracted_data).expect("Failed to reload PDF");
+    let extracted_pages: Vec<_> = extracted.page_iter().collect();
+    let content = extracted.get_page_content(extracted_pages[0]).unwrap();
+    assert!(String::from_utf8_lossy(&content).contains("11 "));
+    let content = extracted.get_page_content(extracted_pages[1]).unwrap();
+    assert!(String::from_utf8_lossy(&content).contains("33 "));
+    assert!(extracted.catalog().unwrap().get(b"Outlines").is_ok());
+
+    let saved_data = doc.to_bytes().expect("Failed to save PDF");
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    let pages: Vec<_> = saved.page_iter().collect();
+    assert_eq!(pages.len(), 4);
+    let contents: Vec<String> = pages
+        .iter()
+        .map(|&id| String::from_utf8_lossy(&saved.get_page_content(id).unwrap()).to_string())
+        .collect();
+    assert!(contents[0].contains("44 "));
+    assert!(contents[1].is_empty());
+    assert!(contents[2].contains("11 "));
+    assert!(contents[3].contains("33 "));
+    assert!(!contents.iter().any(|content| content.contains("22 ")));
+
+    let first = saved.get_dictionary(pages[0]).unwrap();
+    assert_eq!(first.get(b"Rotate").unwrap().as_i64().unwrap(), 270);
+    let crop_box: Vec<f32> = first
+        .get(b"CropBox")
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_float().unwrap())
+        .collect();
+    assert_eq!(crop_box[0], 10.0);
+    assert!((crop_box[1] - 771.89).abs() < 0.01);
+    assert!((crop_box[3] - 821.89).abs() < 0.01);
+
+    // Only the bookmark of the kept page remains, now opening page 4
+    let outlines = saved
+        .get_dictionary(
+            saved
+                .catalog()
+                .unwrap()
+                .get(b"Outlines")
+                .unwrap()
+                .as_reference()
+                .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(outlines.get(b"Count").unwrap().as_i64().unwrap(), 1);
+    let item = saved
+        .get_dictionary(outlines.get(b"First").unwrap().as_reference().unwrap())
+        .unwrap();
+    assert_eq!(item.get(b"Title").unwrap().as_str().unwrap(), b"Page 3");
+    let dest = item.get(b"Dest").unwrap().as_array().unwrap();
+    assert_eq!(dest[0].as_reference().unwrap(), pages[3]);
+}
+
+#[test]
+fn test_extract_pages_leaves_source_open() {
+    let mut doc =
+        PdfDocument::open_from_bytes(&create_test_pdf_with_pages(2)).expect("Failed to open PDF");
+    doc.add_font("test", &get_test_font_data())
+        .expect("Failed to add font");
+    doc.set_font("test", 12.0).expect("Failed to set font");
+    doc.insert_text("AB", 1, 50.0, 50.0, Align::Left)
+        .expect("Failed to insert text");
+
+    let mut extracted = doc.extract_pages(1..=1).expect("Failed to extract");
+    extracted.to_bytes().expect("Failed to save PDF");
+
+    // Nothing was encoded into the source, so new characters still work
+    let page_id = doc.get_page_ids()[0];
+    let content = doc.inner().get_page_content(page_id).unwrap();
+    assert!(!String::from_utf8_lossy(&content).contains("BT"));
+    doc.insert_text("CD", 1, 50.0, 80.0, Align::Left)
+        .expect("Failed to insert text");
+
+    let saved_data = doc.to_bytes().expect("Failed to save PDF");
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    let page_id = saved.page_iter().next().unwrap();
+    let content = String::from_utf8_lossy(&saved.get_page_content(page_id).unwrap()).to_string();
+
+    // Every glyph shown maps back to the text through the font's ToUnicode
+    let fonts = saved.get_page_fonts(page_id);
+    let font = fonts.values().next().unwrap();
+    let to_unicode = font.get(b"ToUnicode").unwrap().as_reference().unwrap();
+    let cmap = saved.get_object(to_unicode).unwrap().as_stream().unwrap();
+    let cmap = String::from_utf8_lossy(&cmap.content).to_string();
+    let hex_codes = |text: &str| -> Vec<String> {
+        text.split('<')
+            .skip(1)
+            .filter_map(|part| part.split_once('>'))
+            .map(|(code, _)| code.to_string())
+            .filter(|code| code.len() == 4)
+            .collect()
+    };
+    let unicode: std::collections::HashMap<String, String> = cmap
+        .lines()
+        .filter_map(|line| {
+            let codes = hex_codes(line);
+            Some((codes.first()?.clone(), codes.get(1)?.clone()))
+        })
+        .collect();
+    let shown: String = hex_codes(&content)
+        .iter()
+        .map(|gid| {
+            let code = u32::from_str_radix(&unicode[gid], 16).unwrap();
+            char::from_u32(code).unwrap()
+        })
+        .collect();
+    assert_eq!(shown, "ABCD");
+}
+
+#[test]
+fn test_delete_page_recounts_nested_bookmarks() {
+    let mut doc =
+        PdfDocument::open_from_bytes(&create_test_pdf_with_pages(3)).expect("Failed to open PDF");
+    for page in 1..=3 {
+        doc.add_bookmark(&format!("Page {page}"), page)
+            .expect("Failed to add bookmark");
+    }
+    let data = doc.to_bytes().expect("Failed to save PDF");
+
+    // Give "Page 1" two visible children and "Page 3" one hidden child
+    let mut nested = lopdf::Document::load_mem(&data).expect("Failed to reload PDF");
+    let pages: Vec<_> = nested.page_iter().collect();
+    let outlines_id = nested
+        .catalog()
+        .unwrap()
+        .get(b"Outlines")
+        .unwrap()
+        .as_reference()
+        .unwrap();
+    let mut items = Vec::new();
+    let mut next = nested
+        .get_dictionary(outlines_id)
+        .unwrap()
+        .get(b"First")
+        .and_then(lopdf::Object::as_reference)
+        .ok();
+    while let Some(id) = next {
+        items.push(id);
+        next = nested
+            .get_dictionary(id)
+            .unwrap()
+            .get(b"Next")
+            .and_then(lopdf::Object::as_reference)
+            .ok();
+    }
+    for (parent, page, children, count) in [(items[0], pages[0], 2, 2), (items[2], pages[2], 1, -1)]
+    {
+        let ids: Vec<_> = (0..children)
+            .map(|_| {
+                nested.add_object(dictionary! {
+                    "Title" => lopdf::Object::string_literal("Section"),
+                    "Parent" => parent,
+                    "Dest" => vec![page.into(), "Fit".into()],
+                })
+            })
+            .collect();
+        if let [first, second] = ids[..] {
+            nested
+                .get_dictionary_mut(first)
+                .unwrap()
+                .set("Next", second);
+            nested
+                .get_dictionary_mut(second)
+                .unwrap()
+                .set("Prev", first);
+        }
+        let item = nested.get_dictionary_mut(parent).unwrap();
+        item.set("First", ids[0]);
+        item.set("Last", ids[ids.len() - 1]);
+        item.set("Count", count);
+    }
+    nested
+        .get_dictionary_mut(outlines_id)
+        .unwrap()
+        .set("Count", 5);
+    let mut data = Vec::new();
+    nested.save_to(&mut data).unwrap();
+
+    // "Page 2" and the closed "Page 3" remain, one visible entry each
+    let mut doc = PdfDocument::open_from_bytes(&data).expect("Failed to open PDF");
+    doc.delete_page(1).expect("Failed to delete page");
+    let saved_data = doc.to_bytes().expect("Failed to save PDF");
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    let outlines = saved
+        .get_dictionary(
+            saved
+                .catalog()
+                .unwrap()
+                .get(b"Outlines")
+                .unwrap()
+                .as_reference()
+                .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(outlines.get(b"Count").unwrap().as_i64().unwrap(), 2);
+}
+
+#[test]
+fn test_new_document() {
+    let mut doc = PdfDocument::new();
+    assert_eq!(doc.page_count(), 0);
+
+    let size = PageSize::A5
+        .landscape()
+        .with_margins(Margins::new(20.0, 30.0, 40.0, 50.0));
+    assert_eq!(doc.add_page(size).unwrap(), 1);
+    assert_eq!(doc.add_page(PageSize::custom(288.0, 432.0)).unwrap(), 2);
+
+    doc.add_font("test", &get_test_font_data())
+        .expect("Failed to add font");
+    doc.set_font("test", 12.0).expect("Failed to set font");
+    doc.insert_text("Inside", 1, 10.0, 20.0, Align::Left)
+        .expect("Failed to insert text");
+    doc.draw_rect(
+        1,
+        Rect::new(0.0, 0.0, 100.0, 50.0),
+        &ShapeStyle::stroke(Color::black(), 1.0),
+    )
+    .expect("Failed to draw");
+    doc.insert_text("Label", 2, 10.0, 20.0, Align::Left)
+        .expect("Failed to insert text");
+
+    let saved_data = doc.to_bytes().expect("Failed to save PDF");
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    let pages: Vec<_> = saved.page_iter().collect();
+    assert_eq!(pages.len(), 2);
+
+    let media_box: Vec<f32> = saved
+        .get_dictionary(pages[0])
+        .unwrap()
+        .get(b"MediaBox")
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_float().unwrap())
+        .collect();
+    assert_eq!(media_box, vec![0.0, 0.0, 595.28, 419.53]);
+
+    // Content on the first page is moved to the margins' corner
+    let content = String::from_utf8_lossy(&saved.get_page_content(pages[0]).unwrap()).to_string();
+    assert!(content.starts_with("q\n1 0 0 1 50 40 cm\n"), "{content}");
+    let content = String::from_utf8_lossy(&saved.get_page_content(pages[1]).unwrap()).to_string();
+    assert!(!content.contains(" cm\n"), "{content}");
+    assert!(content.contains("10 412 Td"), "{content}");
+}
+
+#[test]
+fn test_page_margins_follow_pages() {
+    let mut doc = PdfDocument::new();
+    let size = PageSize::A5
+        .landscape()
+        .with_margins(Margins::new(20.0, 30.0, 40.0, 50.0));
+    doc.add_page(size).unwrap();
+    doc.add_page(size).unwrap();
+
+    // A copy of the page keeps the margins for content drawn on it
+    assert_eq!(doc.duplicate_page(1).unwrap(), 3);
+    let style = ShapeStyle::stroke(Color::black(), 1.0);
+    doc.draw_rect(3, Rect::new(0.0, 0.0, 100.0, 50.0), &style)
+        .unwrap();
+
+    // Field rectangles and page boxes are measured on the whole page
+    doc.add_font("test", &get_test_font_data())
+        .expect("Failed to add font");
+    doc.set_font("test", 12.0).expect("Failed to set font");
+    let rect = Rect::new(0.0, 0.0, 50.0, 20.0);
+    doc.add_form_field(&FieldDefinition::text("name", 1, rect))
+        .unwrap();
+    assert_eq!(doc.form_fields().unwrap()[0].rect(), Some(rect));
+    doc.set_crop_box(2, Rect::new(0.0, 0.0, 100.0, 100.0))
+        .unwrap();
+
+    let mut extracted = doc.extract_pages(3..=3).unwrap();
+    extracted
+        .draw_rect(1, Rect::new(0.0, 0.0, 10.0, 10.0), &style)
+        .unwrap();
+
+    let saved_data = doc.to_bytes().expect("Failed to save PDF");
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    let pages: Vec<_> = saved.page_iter().collect();
+    let numbers = |page_id, key: &[u8]| -> Vec<f32> {
+        saved
+            .get_dictionary(page_id)
+            .unwrap()
+            .get(key)
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_float().unwrap())
+            .collect()
+    };
+    let content = String::from_utf8_lossy(&saved.get_page_content(pages[2]).unwrap()).to_string();
+    assert!(content.starts_with("q\n1 0 0 1 50 40 cm\n"), "{content}");
+    assert_eq!(
+        numbers(pages[1], b"CropBox"),
+        vec![0.0, 319.53, 100.0, 419.53]
+    );
+    let annots = saved
+        .get_dictionary(pages[0])
+        .unwrap()
+        .get(b"Annots")
+        .unwrap()
+        .as_array()
+        .unwrap();
+    let widget = annots[0].as_reference().unwrap();
+    assert_eq!(numbers(widget, b"Rect"), vec![0.0, 399.53, 50.0, 419.53]);
+
+    let saved_data = extracted.to_bytes().expect("Failed to save PDF");
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    let page_id = saved.page_iter().next().unwrap();
+    let content = String::from_utf8_lossy(&saved.get_page_content(page_id).unwrap()).to_string();
+    assert_eq!(content.matches("1 0 0 1 50 40 cm").count(), 2, "{content}");
+}
+
 #[test]
 fn test_multiple_fonts() {
     let pdf_data = create_test_pdf();
@@ -380,6 +885,1227 @@ fn test_thai_characters() {
     assert!(!saved_data.is_empty());
 }
 
+#[test]
+fn test_thai_shaping_uses_opentype_features() {
+    let font_data = get_test_font_data();
+    let mut font = FontData::from_ttf("test", &font_data).expect("Failed to parse font");
+
+    // THO THAN loses its tail above SARA UU and MAI EK is lowered over
+    // PO PLA, so shaping produces glyphs that are not in the cmap
+    let glyphs = font.add_text("ฐูปู่");
+    assert!(!glyphs.is_empty());
+    assert!(glyphs.iter().any(|g| !font
+        .used_chars
+        .iter()
+        .any(|&c| font.glyph_id(c) == Some(g.gid))));
+
+    // Every shaped glyph is kept for subsetting and has a ToUnicode entry
+    for glyph in &glyphs {
+        assert!(font.used_glyphs.contains(&glyph.gid));
+    }
+    let objects = font.to_pdf_objects().expect("Failed to build font objects");
+    let cmap = String::from_utf8_lossy(&objects.tounicode_stream.content);
+    for glyph in &glyphs {
+        assert!(cmap.contains(&format!("<{:04X}> <", glyph.gid)));
+    }
+}
+
+#[test]
+fn test_thai_shaped_text_emits_tj() {
+    let pdf_data = create_test_pdf();
+    let font_data = get_test_font_data();
+
+    let mut doc = PdfDocument::open_from_bytes(&pdf_data).expect("Failed to open PDF");
+    doc.add_font("test", &font_data)
+        .expect("Failed to add font");
+    doc.set_font("test", 16.0).expect("Failed to set font");
+
+    doc.insert_text("น้ำที่ปู่ให้", 1, 100.0, 100.0, Align::Left)
+        .expect("Failed to insert Thai text");
+
+    let saved_data = doc.to_bytes().expect("Failed to save PDF");
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    let page_id = saved.page_iter().next().expect("No pages");
+    let content = saved.get_page_content(page_id).expect("No content");
+    let content = String::from_utf8_lossy(&content);
+
+    assert!(content.contains("] TJ"));
+    assert!(!content.contains("> Tj"));
+}
+
+#[test]
+fn test_text_clip() {
+    let pdf_data = create_test_pdf();
+    let font_data = get_test_font_data();
+
+    let mut doc = PdfDocument::open_from_bytes(&pdf_data).expect("Failed to open PDF");
+    doc.add_font("test", &font_data)
+        .expect("Failed to add font");
+    doc.set_font("test", 12.0).expect("Failed to set font");
+
+    let (ascent, descent) = doc.get_font_extents().expect("Failed to get extents");
+    assert!(ascent > 0.0 && descent > 0.0);
+
+    doc.set_text_clip(Some(Rect::new(100.0, 92.0, 50.0, 20.0)));
+    doc.insert_text("Clipped", 1, 100.0, 100.0, Align::Left)
+        .expect("Failed to insert text");
+    doc.set_text_clip(None);
+    doc.insert_text("Free", 1, 100.0, 200.0, Align::Left)
+        .expect("Failed to insert text");
+
+    let saved_data = doc.to_bytes().expect("Failed to save PDF");
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    let page_id = saved.page_iter().next().expect("No pages");
+    let content = saved.get_page_content(page_id).expect("No content");
+    let content = String::from_utf8_lossy(&content);
+
+    // Only the first text run is clipped, inside its own q/Q
+    assert_eq!(content.matches("re W n").count(), 1);
+    assert!(content.contains(" 50 20 re W n\n"));
+}
+
+#[test]
+fn test_draw_shapes() {
+    let pdf_data = create_test_pdf();
+    let mut doc = PdfDocument::open_from_bytes(&pdf_data).expect("Failed to open PDF");
+
+    let dashed = ShapeStyle::stroke(Color::red(), 1.0).with_dash(vec![2.0, 2.0], 0.0);
+    doc.draw_line(1, 100.0, 100.0, 200.0, 100.0, &dashed)
+        .expect("Failed to draw line");
+    let boxed = ShapeStyle::stroke(Color::black(), 0.5).with_fill(Color::white());
+    doc.draw_rect(1, Rect::new(100.0, 200.0, 80.0, 20.0), &boxed)
+        .expect("Failed to draw rect");
+    doc.draw_rounded_rect(1, Rect::new(100.0, 300.0, 80.0, 20.0), 4.0, &boxed)
+        .expect("Failed to draw rounded rect");
+    doc.draw_ellipse(
+        1,
+        300.0,
+        300.0,
+        30.0,
+        20.0,
+        &ShapeStyle::fill(Color::blue()),
+    )
+    .expect("Failed to draw ellipse");
+    let tick = Path::new()
+        .move_to(400.0, 400.0)
+        .line_to(405.0, 406.0)
+        .line_to(415.0, 392.0);
+    let round = ShapeStyle::stroke(Color::black(), 1.5).with_line_cap(LineCap::Round);
+    doc.draw_path(1, &tick, &round)
+        .expect("Failed to draw path");
+
+    assert!(doc.draw_ellipse(2, 0.0, 0.0, 1.0, 1.0, &round).is_err());
+
+    let saved_data = doc.to_bytes().expect("Failed to save PDF");
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    let page_id = saved.page_iter().next().expect("No pages");
+    let content = saved.get_page_content(page_id).expect("No content");
+    let content = String::from_utf8_lossy(&content);
+
+    // Each shape is isolated in its own graphics state
+    assert_eq!(content.matches("q\n").count(), 5);
+    assert!(content.contains("[2 2] 0 d\n"));
+    assert!(content.contains(" 80 20 re\nB\n"));
+    assert!(content.contains("1 J\n"));
+    // Rounded rect and ellipse corners are Bezier curves
+    assert_eq!(content.matches(" c\n").count(), 8);
+    // Y is flipped: the tick starts 400pt from the top
+    assert!(content.contains("400 441.89"));
+}
+
+#[test]
+fn test_transform() {
+    let pdf_data = create_test_pdf();
+    let font_data = get_test_font_data();
+
+    let mut doc = PdfDocument::open_from_bytes(&pdf_data).expect("Failed to open PDF");
+    doc.add_font("test", &font_data)
+        .expect("Failed to add font");
+    doc.set_font("test", 12.0).expect("Failed to set font");
+
+    // Vertical text around its insertion point, clipped in its own frame
+    doc.set_transform(Some(Transform::rotate(90.0)));
+    doc.set_text_clip(Some(Rect::new(100.0, 92.0, 50.0, 20.0)));
+    doc.insert_text("Margin", 1, 100.0, 100.0, Align::Left)
+        .expect("Failed to insert text");
+    doc.set_text_clip(None);
+
+    // Scaled image around an explicit anchor
+    doc.set_transform(Some(Transform::scale(2.0, 2.0).with_anchor(0.0, 841.89)));
+    doc.insert_image(&create_test_jpeg(), 1, 10.0, 0.0, 20.0, 20.0)
+        .expect("Failed to insert image");
+
+    // Identity transforms and cleared transforms emit no matrix
+    doc.set_transform(Some(Transform::default()));
+    doc.draw_rect(1, Rect::new(0.0, 0.0, 10.0, 10.0), &ShapeStyle::default())
+        .expect("Failed to draw rect");
+    doc.set_transform(None);
+    doc.draw_rect(1, Rect::new(0.0, 0.0, 10.0, 10.0), &ShapeStyle::default())
+        .expect("Failed to draw rect");
+
+    let saved_data = doc.to_bytes().expect("Failed to save PDF");
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    let page_id = saved.page_iter().next().expect("No pages");
+    let content = saved.get_page_content(page_id).expect("No content");
+    let content = String::from_utf8_lossy(&content);
+
+    assert_eq!(content.matches(" cm\n").count(), 2 + 1);
+    // The rotation wraps the clip, so the clip turns with the text
+    let rotated = content.find("q\n0 1 -1 0 841.89").expect("No rotation");
+    assert!(content[rotated..]
+        .split('\n')
+        .nth(3)
+        .unwrap()
+        .ends_with(" 50 20 re W n"));
+    assert!(content.contains("q\n2 0 0 2 0 "));
+}
+
+#[test]
+fn test_transparency() {
+    let pdf_data = create_test_pdf();
+    let font_data = get_test_font_data();
+
+    let mut doc = PdfDocument::open_from_bytes(&pdf_data).expect("Failed to open PDF");
+    doc.add_font("test", &font_data)
+        .expect("Failed to add font");
+    doc.set_font("test", 12.0).expect("Failed to set font");
+
+    // The base page already uses /GS1
+    let page_id = doc.get_page_ids()[0];
+    let gs = doc
+        .inner_mut()
+        .add_object(dictionary! { "Type" => "ExtGState", "LW" => 2 });
+    doc.inner_mut().get_dictionary_mut(page_id).unwrap().set(
+        "Resources",
+        dictionary! { "ExtGState" => dictionary! { "GS1" => gs } },
+    );
+
+    doc.set_transparency(Some(Transparency::alpha(0.5)));
+    doc.insert_text("Draft", 1, 100.0, 100.0, Align::Left)
+        .expect("Failed to insert text");
+    doc.draw_rect(
+        1,
+        Rect::new(100.0, 200.0, 50.0, 20.0),
+        &ShapeStyle::default(),
+    )
+    .expect("Failed to draw rect");
+
+    // Transparent PNG keeps its alpha as a soft mask
+    let png = {
+        let img = image::RgbaImage::from_pixel(4, 4, image::Rgba([0, 0, 255, 128]));
+        let mut buffer = Vec::new();
+        img.write_to(
+            &mut std::io::Cursor::new(&mut buffer),
+            image::ImageFormat::Png,
+        )
+        .expect("Failed to create PNG");
+        buffer
+    };
+    doc.set_transparency(Some(Transparency::blend(BlendMode::Multiply)));
+    doc.insert_image(&png, 1, 300.0, 300.0, 40.0, 40.0)
+        .expect("Failed to insert image");
+    doc.set_transparency(None);
+    doc.draw_rect(1, Rect::new(0.0, 0.0, 10.0, 10.0), &ShapeStyle::default())
+        .expect("Failed to draw rect");
+
+    let saved_data = doc.to_bytes().expect("Failed to save PDF");
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    let page_id = saved.page_iter().next().expect("No pages");
+    let content = saved.get_page_content(page_id).expect("No content");
+    let content = String::from_utf8_lossy(&content);
+
+    // Text and rect share one state; the image uses another
+    assert_eq!(content.matches("/GS2 gs\n").count(), 2);
+    assert_eq!(content.matches("/GS3 gs\n").count(), 1);
+    assert!(!content.contains("/GS1 gs"));
+
+    let resources = saved
+        .get_dictionary(page_id)
+        .unwrap()
+        .get(b"Resources")
+        .unwrap();
+    let gstates = resources
+        .as_dict()
+        .unwrap()
+        .get(b"ExtGState")
+        .unwrap()
+        .as_dict()
+        .unwrap();
+    assert!(gstates.has(b"GS1"));
+    let half = saved
+        .get_dictionary(gstates.get(b"GS2").unwrap().as_reference().unwrap())
+        .unwrap();
+    assert_eq!(half.get(b"ca").unwrap().as_float().unwrap(), 0.5);
+    assert_eq!(half.get(b"CA").unwrap().as_float().unwrap(), 0.5);
+    let multiply = saved
+        .get_dictionary(gstates.get(b"GS3").unwrap().as_reference().unwrap())
+        .unwrap();
+    assert_eq!(multiply.get(b"BM").unwrap().as_name().unwrap(), b"Multiply");
+
+    let xobjects = resources
+        .as_dict()
+        .unwrap()
+        .get(b"XObject")
+        .unwrap()
+        .as_dict()
+        .unwrap();
+    let (_, image_ref) = xobjects.iter().next().expect("No image");
+    let image = saved
+        .get_object(image_ref.as_reference().unwrap())
+        .unwrap()
+        .as_stream()
+        .unwrap();
+    let mask = saved
+        .get_object(image.dict.get(b"SMask").unwrap().as_reference().unwrap())
+        .unwrap()
+        .as_stream()
+        .unwrap();
+    assert_eq!(mask.dict.get(b"Width").unwrap().as_i64().unwrap(), 4);
+}
+
+#[test]
+fn test_watermark() {
+    let pdf_data = create_test_pdf_with_pages(2);
+    let font_data = get_test_font_data();
+
+    let mut doc = PdfDocument::open_from_bytes(&pdf_data).expect("Failed to open PDF");
+    doc.add_font("test", &font_data)
+        .expect("Failed to add font");
+    doc.set_font("test", 12.0).expect("Failed to set font");
+
+    doc.add_watermark(&Watermark::text("COPY").on_pages(vec![1]))
+        .expect("Failed to add text watermark");
+    // Document state is left as it was
+    assert_eq!(doc.get_font_size(), 12.0);
+
+    doc.draw_rect(2, Rect::new(10.0, 10.0, 50.0, 20.0), &ShapeStyle::default())
+        .expect("Failed to draw rect");
+    doc.add_watermark(
+        &Watermark::image(&create_test_png(), 100.0, 100.0)
+            .with_angle(0.0)
+            .on_pages(vec![2])
+            .with_layer(WatermarkLayer::Underlay),
+    )
+    .expect("Failed to add image watermark");
+
+    let result = doc.add_watermark(&Watermark::text("COPY").on_pages(vec![3]));
+    assert!(matches!(result, Err(PdfError::InvalidPage(3, 2))));
+
+    let saved_data = doc.to_bytes().expect("Failed to save PDF");
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    let pages: Vec<_> = saved.page_iter().collect();
+
+    // Text is rotated 45° around the page center and 30% opaque
+    let content = saved.get_page_content(pages[0]).expect("No content");
+    let content = String::from_utf8_lossy(&content);
+    assert!(content.contains("q\n/GS1 gs\nq\n0.707107 0.707107 -0.707107 0.707107 "));
+    assert!(content.contains("BT\n"));
+
+    // The underlay image is painted before the base content and the rect
+    let content = saved.get_page_content(pages[1]).expect("No content");
+    let content = String::from_utf8_lossy(&content);
+    let image = content.find(" Do").expect("No image");
+    let rect = content.find(" re").expect("No rect");
+    assert!(content.starts_with("q\n"));
+    assert!(image < rect);
+
+    let gstates = saved
+        .get_dictionary(pages[1])
+        .unwrap()
+        .get(b"Resources")
+        .unwrap()
+        .as_dict()
+        .unwrap()
+        .get(b"ExtGState")
+        .unwrap()
+        .as_dict()
+        .unwrap();
+    let (_, gstate) = gstates.iter().next().expect("No ExtGState");
+    let opacity = saved
+        .get_dictionary(gstate.as_reference().unwrap())
+        .unwrap();
+    assert_eq!(opacity.get(b"ca").unwrap().as_float().unwrap(), 0.3);
+}
+
+#[test]
+fn test_merge_documents() {
+    let font_data = get_test_font_data();
+    let png = create_test_png();
+    let record = |name: &str, pages: usize| {
+        let mut doc = PdfDocument::open_from_bytes(&create_test_pdf_with_pages(pages))
+            .expect("Failed to open PDF");
+        doc.add_font("test", &font_data)
+            .expect("Failed to add font");
+        doc.set_font("test", 12.0).expect("Failed to set font");
+        doc.insert_text(name, 1, 100.0, 100.0, Align::Left)
+            .expect("Failed to insert text");
+        doc.insert_image(&png, pages, 100.0, 200.0, 40.0, 40.0)
+            .expect("Failed to insert image");
+        doc
+    };
+
+    let mut merged = PdfDocument::merge(vec![
+        ("First".to_string(), record("Alice", 1)),
+        ("ใบที่ 2".to_string(), record("Bob", 2)),
+    ])
+    .expect("Failed to merge");
+    assert_eq!(merged.page_count(), 3);
+    assert!(matches!(
+        PdfDocument::merge(Vec::new()),
+        Err(PdfError::InvalidArgument(_))
+    ));
+
+    let page = merged
+        .append_document(record("Carol", 1), None)
+        .expect("Failed to append");
+    assert_eq!(page, 4);
+    // Appended pages take more content
+    merged
+        .insert_text("Page 4", 4, 100.0, 300.0, Align::Left)
+        .expect("Failed to insert text");
+
+    let saved_data = merged.to_bytes().expect("Failed to save PDF");
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    assert_eq!(saved.get_pages().len(), 4);
+
+    // One font and one image shared by every record
+    let count_type = |kind: &[u8]| {
+        saved
+            .objects
+            .values()
+            .filter_map(|object| match object {
+                lopdf::Object::Dictionary(dict) => Some(dict),
+                lopdf::Object::Stream(stream) => Some(&stream.dict),
+                _ => None,
+            })
+            .filter(|dict| {
+                dict.get(b"Type")
+                    .and_then(|t| t.as_name())
+                    .is_ok_and(|t| t == kind)
+                    || dict
+                        .get(b"Subtype")
+                        .and_then(|t| t.as_name())
+                        .is_ok_and(|t| t == kind)
+            })
+            .count()
+    };
+    assert_eq!(count_type(b"FontDescriptor"), 1);
+    assert_eq!(count_type(b"Image"), 1);
+
+    let pages: Vec<_> = saved.page_iter().collect();
+    for &page_id in &pages {
+        let content = saved.get_page_content(page_id).expect("No content");
+        assert!(!content.is_empty());
+    }
+    let content = saved.get_page_content(pages[3]).expect("No content");
+    assert_eq!(String::from_utf8_lossy(&content).matches("TJ").count(), 2);
+
+    // One bookmark per merged document, in order
+    let outlines = saved
+        .get_dictionary(
+            saved
+                .catalog()
+                .unwrap()
+                .get(b"Outlines")
+                .unwrap()
+                .as_reference()
+                .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(outlines.get(b"Count").unwrap().as_i64().unwrap(), 2);
+    let first = saved
+        .get_dictionary(outlines.get(b"First").unwrap().as_reference().unwrap())
+        .unwrap();
+    assert_eq!(first.get(b"Title").unwrap().as_str().unwrap(), b"First");
+    let second = saved
+        .get_dictionary(first.get(b"Next").unwrap().as_reference().unwrap())
+        .unwrap();
+    let dest = second.get(b"Dest").unwrap().as_array().unwrap();
+    assert_eq!(dest[0].as_reference().unwrap(), pages[1]);
+}
+
+/// Create a scanned-style page: offset MediaBox, CropBox inside it and /Rotate 90
+fn create_rotated_pdf() -> Vec<u8> {
+    let mut doc = lopdf::Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let contents_id = doc.add_object(lopdf::Stream::new(dictionary! {}, b"0 0 m\n".to_vec()));
+    let page_id = doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "MediaBox" => vec![50.into(), 100.into(), 450.into(), 700.into()],
+        "CropBox" => vec![100.into(), 150.into(), 400.into(), 650.into()],
+        "Rotate" => 90,
+        "Contents" => contents_id,
+    });
+    doc.objects.insert(
+        pages_id,
+        lopdf::Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![page_id.into()],
+            "Count" => 1,
+            "Resources" => dictionary! {},
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer).unwrap();
+    buffer
+}
+
+#[test]
+fn test_rotate_page_after_insert() {
+    let mut doc = PdfDocument::open_from_bytes(&create_test_pdf()).expect("Failed to open PDF");
+    doc.add_font("test", &get_test_font_data())
+        .expect("Failed to add font");
+    doc.set_font("test", 12.0).expect("Failed to set font");
+
+    // Placed on the upright A4 page, then the page is turned
+    doc.insert_text("Hello", 1, 100.0, 100.0, Align::Left)
+        .expect("Failed to insert text");
+    doc.add_form_field(
+        &FieldDefinition::text("name", 1, Rect::new(0.0, 0.0, 50.0, 20.0)).with_value("Hi"),
+    )
+    .expect("Failed to add field");
+    doc.set_rotation(1, 90).expect("Failed to rotate");
+    doc.insert_text("World", 1, 100.0, 100.0, Align::Left)
+        .expect("Failed to insert text");
+
+    let saved_data = doc.to_bytes().expect("Failed to save PDF");
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    let page_id = saved.page_iter().next().unwrap();
+    let content = String::from_utf8_lossy(&saved.get_page_content(page_id).unwrap()).to_string();
+
+    // Earlier text turns with the page; later text is placed in the turned view
+    let hello = content.find("100 741.89").expect("Text moved");
+    let turned = content.find("0 1 -1 0 595.28").expect("No view matrix");
+    assert!(hello < turned);
+    assert!(content[turned..].contains("100 495.28"));
+
+    // So does the widget, which keeps its upright rectangle and appearance
+    let page = saved.get_dictionary(page_id).unwrap();
+    let annots = page.get(b"Annots").unwrap().as_array().unwrap();
+    let widget = saved
+        .get_dictionary(annots[0].as_reference().unwrap())
+        .unwrap();
+    let rect: Vec<f32> = widget
+        .get(b"Rect")
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_float().unwrap())
+        .collect();
+    assert_eq!(rect, vec![0.0, 821.89, 50.0, 841.89]);
+    assert!(widget.get(b"MK").is_err());
+    let ap = widget.get(b"AP").unwrap().as_dict().unwrap();
+    let normal = ap.get(b"N").unwrap().as_reference().unwrap();
+    let Ok(lopdf::Object::Stream(appearance)) = saved.get_object(normal) else {
+        panic!("Appearance is not a stream");
+    };
+    assert!(appearance.dict.get(b"Matrix").is_err());
+}
+
+#[test]
+fn test_rotated_page_geometry() {
+    let rotated = create_rotated_pdf();
+    let mut doc = PdfDocument::open_from_bytes(&rotated).expect("Failed to open PDF");
+    doc.add_font("test", &get_test_font_data())
+        .expect("Failed to add font");
+    doc.set_font("test", 12.0).expect("Failed to set font");
+
+    // The visible 300 x 500 crop area shows as 500 x 300 when turned
+    doc.insert_text("Top left", 1, 10.0, 20.0, Align::Left)
+        .expect("Failed to insert text");
+    doc.insert_image(&create_test_png(), 1, 0.0, 0.0, 10.0, 10.0)
+        .expect("Failed to insert image");
+    doc.add_form_field(
+        &FieldDefinition::text("name", 1, Rect::new(0.0, 0.0, 50.0, 20.0)).with_value("Hi"),
+    )
+    .expect("Failed to add field");
+
+    let fields = doc.form_fields().expect("Failed to list fields");
+    assert_eq!(fields[0].rect(), Some(Rect::new(0.0, 0.0, 50.0, 20.0)));
+
+    let saved_data = doc.to_bytes().expect("Failed to save PDF");
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    let page_id = saved.page_iter().next().unwrap();
+    let content = String::from_utf8_lossy(&saved.get_page_content(page_id).unwrap()).to_string();
+
+    // Content is turned into the view and moved to the crop corner
+    let ours = &content[content.find("\nq\n").unwrap() + 1..];
+    assert!(ours.starts_with("q\n0 1 -1 0 400 150 cm\n"));
+    assert!(ours.contains("10 0 0 10 0 290 cm\n/Im1 Do"));
+    assert!(ours.contains("10 280 Td"));
+
+    // The widget sits at the view's top-left corner: the crop box's
+    // bottom-left corner on the unturned page
+    let page = saved.get_dictionary(page_id).unwrap();
+    let annots = page.get(b"Annots").unwrap().as_array().unwrap();
+    let widget = saved
+        .get_dictionary(annots[0].as_reference().unwrap())
+        .unwrap();
+    let rect: Vec<f32> = widget
+        .get(b"Rect")
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_float().unwrap())
+        .collect();
+    assert_eq!(rect, vec![100.0, 150.0, 120.0, 200.0]);
+
+    // The widget turns with the page: its appearance is laid out upright
+    // in the 50 x 20 view box and rotated onto the 20 x 50 rectangle
+    let mk = widget.get(b"MK").unwrap().as_dict().unwrap();
+    assert_eq!(mk.get(b"R").unwrap().as_i64().unwrap(), 90);
+    let ap = widget.get(b"AP").unwrap().as_dict().unwrap();
+    let normal = ap.get(b"N").unwrap().as_reference().unwrap();
+    let Ok(lopdf::Object::Stream(appearance)) = saved.get_object(normal) else {
+        panic!("Appearance is not a stream");
+    };
+    let numbers = |key: &[u8]| -> Vec<f32> {
+        appearance
+            .dict
+            .get(key)
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_float().unwrap())
+            .collect()
+    };
+    assert_eq!(numbers(b"BBox"), vec![0.0, 0.0, 50.0, 20.0]);
+    assert_eq!(numbers(b"Matrix"), vec![0.0, 1.0, -1.0, 0.0, 20.0, 0.0]);
+
+    // An imported rotated page is upright, sized as it shows
+    let mut target = PdfDocument::open_from_bytes(&create_test_pdf()).expect("Failed to open PDF");
+    let imported = target.import_page(&rotated, 1).expect("Failed to import");
+    assert_eq!((imported.width, imported.height), (500.0, 300.0));
+    let saved_data = target.to_bytes().expect("Failed to save PDF");
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    let Ok(lopdf::Object::Stream(form)) = saved.get_object(imported.object_id) else {
+        panic!("Imported page is not a stream");
+    };
+    let matrix: Vec<f32> = form
+        .dict
+        .get(b"Matrix")
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_float().unwrap())
+        .collect();
+    assert_eq!(matrix, vec![0.0, -1.0, 1.0, 0.0, -150.0, 400.0]);
+}
+
+/// Create a 200 x 100 pt "letterhead" page with its font in an inherited
+/// Resources dictionary
+fn create_letterhead_pdf() -> Vec<u8> {
+    let mut doc = lopdf::Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+    });
+    let contents_id = doc.add_object(lopdf::Stream::new(
+        dictionary! {},
+        b"BT /F1 12 Tf 20 50 Td (ACME) Tj ET".to_vec(),
+    ));
+    let page_id = doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "Contents" => contents_id,
+    });
+    doc.objects.insert(
+        pages_id,
+        lopdf::Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![page_id.into()],
+            "Count" => 1,
+            "MediaBox" => vec![0.into(), 0.into(), 200.into(), 100.into()],
+            "Resources" => dictionary! { "Font" => dictionary! { "F1" => font_id } },
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer).unwrap();
+    buffer
+}
+
+#[test]
+fn test_import_page() {
+    let letterhead_pdf = create_letterhead_pdf();
+    let mut doc = PdfDocument::open_from_bytes(&create_test_pdf()).expect("Failed to open PDF");
+
+    let letterhead = doc
+        .import_page(&letterhead_pdf, 1)
+        .expect("Failed to import page");
+    assert_eq!((letterhead.width, letterhead.height), (200.0, 100.0));
+    let again = doc
+        .import_page(&letterhead_pdf, 1)
+        .expect("Failed to import page");
+    assert_eq!(again.object_id, letterhead.object_id);
+    assert!(matches!(
+        doc.import_page(&letterhead_pdf, 2),
+        Err(PdfError::InvalidPage(2, 1))
+    ));
+
+    doc.place_page(&letterhead, 1, 10.0, 20.0, 0.5)
+        .expect("Failed to place page");
+    doc.set_underlay(true);
+    doc.place_page(&letterhead, 1, 0.0, 0.0, 1.0)
+        .expect("Failed to place page");
+    doc.set_underlay(false);
+
+    let saved_data = doc.to_bytes().expect("Failed to save PDF");
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    let page_id = saved.page_iter().next().expect("No pages");
+    let content = saved.get_page_content(page_id).expect("No content");
+    let content = String::from_utf8_lossy(&content);
+
+    // The underlay comes first; the overlay is half size, 20pt from the top
+    let underlay = content
+        .find("1 0 0 1 0 741.89")
+        .expect("No underlay placement");
+    let overlay = content
+        .find("0.5 0 0 0.5 10 771.89")
+        .expect("No overlay placement");
+    assert!(underlay < overlay);
+    assert_eq!(content.matches("/Pg1 Do").count(), 2);
+
+    // The XObject carries the page content and its inherited font
+    let xobject = saved
+        .get_object(letterhead_xobject(&saved, page_id))
+        .unwrap()
+        .as_stream()
+        .unwrap();
+    assert_eq!(
+        xobject.dict.get(b"Subtype").unwrap().as_name().unwrap(),
+        b"Form"
+    );
+    let xobject_content = xobject
+        .decompressed_content()
+        .unwrap_or_else(|_| xobject.content.clone());
+    assert!(String::from_utf8_lossy(&xobject_content).contains("(ACME) Tj"));
+    let font = xobject
+        .dict
+        .get(b"Resources")
+        .unwrap()
+        .as_dict()
+        .unwrap()
+        .get(b"Font")
+        .unwrap()
+        .as_dict()
+        .unwrap()
+        .get(b"F1")
+        .unwrap()
+        .as_reference()
+        .unwrap();
+    assert_eq!(
+        saved
+            .get_dictionary(font)
+            .unwrap()
+            .get(b"BaseFont")
+            .unwrap()
+            .as_name()
+            .unwrap(),
+        b"Helvetica"
+    );
+}
+
+/// Object ID of the `Pg1` XObject of a page
+fn letterhead_xobject(doc: &lopdf::Document, page_id: lopdf::ObjectId) -> lopdf::ObjectId {
+    doc.get_dictionary(page_id)
+        .unwrap()
+        .get(b"Resources")
+        .unwrap()
+        .as_dict()
+        .unwrap()
+        .get(b"XObject")
+        .unwrap()
+        .as_dict()
+        .unwrap()
+        .get(b"Pg1")
+        .unwrap()
+        .as_reference()
+        .unwrap()
+}
+
+#[test]
+fn test_base_content_isolation() {
+    let font_data = get_test_font_data();
+
+    // Base content that leaves a scaled CTM and a clip behind, with its
+    // font in inherited resources
+    let mut base = lopdf::Document::load_mem(&create_letterhead_pdf()).expect("Failed to load");
+    let page_id = base.page_iter().next().unwrap();
+    let contents_id = base
+        .get_dictionary(page_id)
+        .unwrap()
+        .get(b"Contents")
+        .unwrap()
+        .as_reference()
+        .unwrap();
+    base.objects.insert(
+        contents_id,
+        lopdf::Object::Stream(lopdf::Stream::new(
+            dictionary! {},
+            b"2 0 0 2 0 0 cm 0 0 50 50 re W n BT /F1 12 Tf 20 50 Td (ACME) Tj ET".to_vec(),
+        )),
+    );
+    let mut base_data = Vec::new();
+    base.save_to(&mut base_data).unwrap();
+
+    let mut doc = PdfDocument::open_from_bytes(&base_data).expect("Failed to open PDF");
+    doc.add_font("test", &font_data)
+        .expect("Failed to add font");
+    doc.set_font("test", 12.0).expect("Failed to set font");
+    doc.insert_text("สวัสดี", 1, 10.0, 20.0, Align::Left)
+        .expect("Failed to insert text");
+    doc.draw_rect(
+        1,
+        Rect::new(150.0, 10.0, 20.0, 20.0),
+        &ShapeStyle::fill(Color::red()),
+    )
+    .expect("Failed to draw rect");
+
+    let saved_data = doc.to_bytes().expect("Failed to save PDF");
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    let page_id = saved.page_iter().next().unwrap();
+    let content = String::from_utf8_lossy(&saved.get_page_content(page_id).unwrap()).to_string();
+    assert!(content.starts_with("q\n2 0 0 2 0 0 cm"));
+    let restore = content.find("ET\nQ\n").expect("Base content not restored");
+    assert!(content[restore..].contains("/F2 12 Tf"));
+
+    // The inherited base font stays next to ours
+    let page = saved.get_dictionary(page_id).unwrap();
+    let fonts = page
+        .get_deref(b"Resources", &saved)
+        .and_then(|r| r.as_dict())
+        .and_then(|r| r.get_deref(b"Font", &saved))
+        .and_then(|f| f.as_dict())
+        .expect("No page fonts");
+    assert!(fonts.has(b"F1"));
+    assert!(fonts.has(b"F2"));
+    let f1 = saved
+        .get_dictionary(fonts.get(b"F1").unwrap().as_reference().unwrap())
+        .unwrap();
+    assert_eq!(
+        f1.get(b"BaseFont").unwrap().as_name().unwrap(),
+        b"Helvetica"
+    );
+
+    // A direct Contents stream is kept, not replaced
+    let mut doc = PdfDocument::open_from_bytes(&base_data).expect("Failed to open PDF");
+    let page_id = doc.get_page_ids()[0];
+    let stream = lopdf::Stream::new(dictionary! {}, b"0 0 m 10 10 l S".to_vec());
+    doc.inner_mut()
+        .get_dictionary_mut(page_id)
+        .unwrap()
+        .set("Contents", lopdf::Object::Stream(stream));
+    doc.draw_line(
+        1,
+        0.0,
+        0.0,
+        10.0,
+        10.0,
+        &ShapeStyle::stroke(Color::black(), 1.0),
+    )
+    .expect("Failed to draw line");
+    let saved_data = doc.to_bytes().expect("Failed to save PDF");
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    let page_id = saved.page_iter().next().unwrap();
+    let content = String::from_utf8_lossy(&saved.get_page_content(page_id).unwrap()).to_string();
+    assert!(content.starts_with("q\n0 0 m 10 10 l S\nQ\n"));
+    assert!(content.len() > "q\n0 0 m 10 10 l S\nQ\n".len());
+}
+
+#[test]
+fn test_form_fields() {
+    let doc = PdfDocument::open_from_bytes(&create_form_pdf()).expect("Failed to open PDF");
+    let fields = doc.form_fields().expect("Failed to read fields");
+
+    let names: Vec<&str> = fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "applicant.name",
+            "applicant.id",
+            "agree",
+            "gender",
+            "province"
+        ]
+    );
+
+    let name = &fields[0];
+    assert_eq!(name.field_type, FormFieldType::Text);
+    assert_eq!(name.page(), Some(1));
+    let rect = name.rect().unwrap();
+    assert_eq!((rect.x, rect.width, rect.height), (100.0, 200.0, 20.0));
+    assert!((rect.y - 121.89).abs() < 0.01);
+
+    assert_eq!(fields[2].field_type, FormFieldType::Checkbox);
+    assert_eq!(fields[2].options, vec!["Yes"]);
+    assert_eq!(fields[3].field_type, FormFieldType::Radio);
+    assert_eq!(fields[3].options, vec!["M", "F"]);
+    assert_eq!(fields[3].widgets.len(), 2);
+    assert_eq!(fields[4].field_type, FormFieldType::ComboBox);
+    assert_eq!(fields[4].options, vec!["BKK", "CNX"]);
+}
+
+#[test]
+fn test_fill_form_fields() {
+    let mut doc = PdfDocument::open_from_bytes(&create_form_pdf()).expect("Failed to open PDF");
+    doc.add_font("sarabun", &get_test_font_data())
+        .expect("Failed to add font");
+    doc.set_font("sarabun", 14.0).expect("Failed to set font");
+
+    doc.set_field_value("applicant.name", "นายสมชาย ใจดี")
+        .expect("Failed to fill text");
+    doc.set_field_value("applicant.id", "1234567890123")
+        .expect("Failed to fill comb");
+    doc.set_field_value("agree", "true")
+        .expect("Failed to check");
+    doc.set_field_value("gender", "F")
+        .expect("Failed to select radio");
+    doc.set_field_value("province", "Chiang Mai")
+        .expect("Failed to select choice");
+
+    assert!(matches!(
+        doc.set_field_value("gender", "X"),
+        Err(PdfError::FormError(_))
+    ));
+    assert!(matches!(
+        doc.set_field_value("province", "Phuket"),
+        Err(PdfError::FormError(_))
+    ));
+    assert!(matches!(
+        doc.set_field_value("missing", "x"),
+        Err(PdfError::FormError(_))
+    ));
+
+    let saved_data = doc.to_bytes().expect("Failed to save PDF");
+    let reopened = PdfDocument::open_from_bytes(&saved_data).expect("Failed to reopen PDF");
+    let fields = reopened.form_fields().expect("Failed to read fields");
+    let value = |name: &str| {
+        fields
+            .iter()
+            .find(|f| f.name == name)
+            .and_then(|f| f.value.clone())
+    };
+    assert_eq!(value("applicant.name").as_deref(), Some("นายสมชาย ใจดี"));
+    assert_eq!(value("agree").as_deref(), Some("Yes"));
+    assert_eq!(value("gender").as_deref(), Some("F"));
+    assert_eq!(value("province").as_deref(), Some("CNX"));
+
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    let widget = |name: &str| {
+        saved
+            .objects
+            .values()
+            .filter_map(|o| o.as_dict().ok())
+            .find(|d| d.get(b"T").ok().and_then(|t| t.as_str().ok()) == Some(name.as_bytes()))
+            .unwrap()
+            .clone()
+    };
+
+    // Text appearance uses the embedded Thai font
+    let ap = widget("name")
+        .get(b"AP")
+        .unwrap()
+        .as_dict()
+        .unwrap()
+        .clone();
+    let stream_id = ap.get(b"N").unwrap().as_reference().unwrap();
+    let stream = saved.get_object(stream_id).unwrap().as_stream().unwrap();
+    let content = String::from_utf8_lossy(&stream.content);
+    assert!(content.starts_with("/Tx BMC\n"));
+    assert!(content.contains("/F1 14 Tf"));
+    let font_ref = stream
+        .dict
+        .get(b"Resources")
+        .and_then(|r| r.as_dict())
+        .and_then(|r| r.get(b"Font"))
+        .and_then(|f| f.as_dict())
+        .and_then(|f| f.get(b"F1"))
+        .and_then(|f| f.as_reference())
+        .unwrap();
+    let font = saved.get_dictionary(font_ref).unwrap();
+    assert_eq!(font.get(b"Subtype").unwrap().as_name().unwrap(), b"Type0");
+
+    // Comb field: one Td per digit cell
+    let ap = widget("id").get(b"AP").unwrap().as_dict().unwrap().clone();
+    let stream_id = ap.get(b"N").unwrap().as_reference().unwrap();
+    let stream = saved.get_object(stream_id).unwrap().as_stream().unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&stream.content)
+            .matches(" Td")
+            .count(),
+        13
+    );
+
+    // Checkbox and radio appearance states
+    assert_eq!(
+        widget("agree").get(b"AS").unwrap().as_name().unwrap(),
+        b"Yes"
+    );
+    let radio_states: Vec<Vec<u8>> = saved
+        .objects
+        .values()
+        .filter_map(|o| o.as_dict().ok())
+        .filter(|d| d.has(b"Parent") && d.has(b"AS"))
+        .map(|d| d.get(b"AS").unwrap().as_name().unwrap().to_vec())
+        .collect();
+    assert!(radio_states.contains(&b"F".to_vec()));
+    assert!(radio_states.contains(&b"Off".to_vec()));
+
+    // Viewers must keep the generated appearances
+    let acroform = saved
+        .catalog()
+        .unwrap()
+        .get(b"AcroForm")
+        .unwrap()
+        .as_dict()
+        .unwrap();
+    assert!(!acroform.get(b"NeedAppearances").unwrap().as_bool().unwrap());
+}
+
+#[test]
+fn test_flatten_form_fields() {
+    let mut doc = PdfDocument::open_from_bytes(&create_form_pdf()).expect("Failed to open PDF");
+    doc.add_font("sarabun", &get_test_font_data())
+        .expect("Failed to add font");
+    doc.set_font("sarabun", 14.0).expect("Failed to set font");
+    doc.set_field_value("applicant.name", "นายสมชาย ใจดี")
+        .expect("Failed to fill text");
+    doc.set_field_value("agree", "true")
+        .expect("Failed to check");
+
+    // A link annotation survives form flattening
+    let link_id = doc.inner_mut().add_object(dictionary! {
+        "Type" => "Annot",
+        "Subtype" => "Link",
+        "Rect" => vec![10.into(), 10.into(), 50.into(), 20.into()],
+    });
+    let page_id = doc.get_page_ids()[0];
+    let page = doc.inner_mut().get_dictionary_mut(page_id).unwrap();
+    let mut annots = page.get(b"Annots").unwrap().as_array().unwrap().clone();
+    annots.push(link_id.into());
+    page.set("Annots", annots);
+
+    doc.flatten(FlattenMode::Forms);
+    let saved_data = doc.to_bytes().expect("Failed to save PDF");
+
+    let saved = lopdf::Document::load_mem(&saved_data).expect("Failed to reload PDF");
+    assert!(saved.catalog().unwrap().get(b"AcroForm").is_err());
+
+    let page_id = *saved.get_pages().get(&1).unwrap();
+    let page = saved.get_dictionary(page_id).unwrap();
+    let annots = page.get(b"Annots").unwrap().as_array().unwrap();
+    assert_eq!(annots, &vec![lopdf::Object::Reference(link_id)]);
+
+    // Filled text and the checked box are drawn as Form XObjects in their rects
+    let content = String::from_utf8_lossy(&saved.get_page_content(page_id).unwrap()).to_string();
+    assert!(content.contains("1 0 0 1 100 700 cm\n/Flat"));
+    let xobjects = page
+        .get(b"Resources")
+        .and_then(|r| r.as_dict())
+        .and_then(|r| r.get(b"XObject"))
+        .and_then(|x| x.as_dict())
+        .unwrap();
+    assert_eq!(content.matches(" Do\n").count(), xobjects.len());
+    // Name, the checked box and both unselected radio buttons; empty widgets are dropped
+    assert_eq!(xobjects.len(), 4);
+
+    // Flattening everything keeps the link because it has no appearance to draw
+    let mut doc = PdfDocument::open_from_bytes(&saved_data).expect("Failed to reopen PDF");
+    doc.flatten(FlattenMode::All);
+    let saved = lopdf::Document::load_mem(&doc.to_bytes().unwrap()).unwrap();
+    let page = saved.get_dictionary(page_id).unwrap();
+    assert_eq!(page.get(b"Annots").unwrap().as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_create_form_fields() {
+    let mut doc = PdfDocument::open_from_bytes(&create_test_pdf()).expect("Failed to open PDF");
+    doc.add_font("sarabun", &get_test_font_data())
+        .expect("Failed to add font");
+    doc.set_font("sarabun", 14.0).expect("Failed to set font");
+
+    doc.add_form_field(
+        &FieldDefinition::text("remarks", 1, Rect::new(50.0, 100.0, 300.0, 60.0))
+            .multiline()
+            .with_value("ไม่มี"),
+    )
+    .expect("Failed to add text field");
+    doc.add_form_field(&FieldDefinition::checkbox(
+        "agree",
+        1,
+        Rect::new(50.0, 200.0, 12.0, 12.0),
+    ))
+    .expect("Failed to add checkbox");
+    doc.add_form_field(
+        &FieldDefinition::radio("gender")
+            .with_button("M", 1, Rect::new(50.0, 250.0, 12.0, 12.0))
+            .with_button("F", 1, Rect::new(100.0, 250.0, 12.0, 12.0))
+            .with_value("F"),
+    )
+    .expect("Failed to add radio group");
+    doc.add_form_field(&FieldDefinition::signature(
+        "signature",
+        1,
+        Rect::new(300.0, 700.0, 200.0, 50.0),
+    ))
+    .expect("Failed to add signature field");
+
+    // A second widget of the same field shares its value; other clashes are errors
+    doc.add_form_field(&FieldDefinition::checkbox(
+        "agree",
+        1,
+        Rect::new(400.0, 200.0, 12.0, 12.0),
+    ))
+    .expect("Failed to add widget");
+    assert!(matches!(
+        doc.add_form_field(&FieldDefinition::signature(
+            "agree",
+            1,
+            Rect::new(0.0, 0.0, 10.0, 10.0)
+        )),
+        Err(PdfError::FormError(_))
+    ));
+    assert!(doc
+        .add_form_field(&FieldDefinition::text(
+            "a.b",
+            1,
+            Rect::new(0.0, 0.0, 10.0, 10.0)
+        ))
+        .is_err());
+
+    let saved_data = doc.to_bytes().expect("Failed to save PDF");
+    let doc = PdfDocument::open_from_bytes(&saved_data).expect("Failed to reload PDF");
+    let fields = doc.form_fields().unwrap();
+    let summary: Vec<(&str, FormFieldType, Option<&str>, usize)> = fields
+        .iter()
+        .map(|f| {
+            (
+                f.name.as_str(),
+                f.field_type,
+                f.value.as_deref(),
+                f.widgets.len(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("remarks", FormFieldType::Text, Some("ไม่มี"), 1),
+            ("agree", FormFieldType::Checkbox, Some("Off"), 2),
+            ("gender", FormFieldType::Radio, Some("F"), 2),
+            ("signature", FormFieldType::Signature, None, 1),
+        ]
+    );
+    assert_eq!(fields[0].rect(), Some(Rect::new(50.0, 100.0, 300.0, 60.0)));
+    assert_eq!(fields[2].options, vec!["M", "F"]);
+
+    // The text field's whole font is in the form resources, with its cmap
+    // so typed characters map to glyphs
+    let saved = lopdf::Document::load_mem(&saved_data).unwrap();
+    let acroform = saved
+        .catalog()
+        .unwrap()
+        .get_deref(b"AcroForm", &saved)
+        .unwrap()
+        .as_dict()
+        .unwrap();
+    let font_ref = acroform
+        .get(b"DR")
+        .and_then(|dr| dr.as_dict())
+        .and_then(|dr| dr.get(b"Font"))
+        .and_then(|f| f.as_dict())
+        .and_then(|f| f.get(b"FF1"))
+        .and_then(|f| f.as_reference())
+        .expect("Missing /DR font");
+    let font = saved.get_dictionary(font_ref).unwrap();
+    let to_unicode = font.get(b"ToUnicode").unwrap().as_reference().unwrap();
+    let cmap = saved.get_object(to_unicode).unwrap().as_stream().unwrap();
+    let cmap = String::from_utf8_lossy(&cmap.content);
+    assert!(cmap.contains("<0E2E>"), "form font lost ฮ");
+    assert!(cmap.contains("<007A>"), "form font lost z");
+
+    let descendant = font.get(b"DescendantFonts").unwrap().as_array().unwrap()[0]
+        .as_reference()
+        .unwrap();
+    let descriptor = saved
+        .get_dictionary(descendant)
+        .unwrap()
+        .get(b"FontDescriptor")
+        .unwrap()
+        .as_reference()
+        .unwrap();
+    let font_file = saved
+        .get_dictionary(descriptor)
+        .unwrap()
+        .get(b"FontFile2")
+        .unwrap()
+        .as_reference()
+        .unwrap();
+    let font_file = saved.get_object(font_file).unwrap().as_stream().unwrap();
+    let program = font_file
+        .decompressed_content()
+        .unwrap_or_else(|_| font_file.content.clone());
+    let num_tables = u16::from_be_bytes([program[4], program[5]]) as usize;
+    let has_cmap = program[12..12 + num_tables * 16]
+        .chunks(16)
+        .any(|record| &record[..4] == b"cmap");
+    assert!(has_cmap, "form font program has no cmap");
+    assert_eq!(program, get_test_font_data());
+
+    let remarks = saved
+        .get_dictionary(top_level_field(&saved, "remarks"))
+        .unwrap();
+    let da = remarks.get(b"DA").unwrap().as_str().unwrap();
+    assert_eq!(da, b"/FF1 14 Tf 0 0 0 rg");
+}
+
+/// Object ID of the top-level field named `name`
+fn top_level_field(doc: &lopdf::Document, name: &str) -> lopdf::ObjectId {
+    let acroform = doc
+        .catalog()
+        .unwrap()
+        .get_deref(b"AcroForm", doc)
+        .unwrap()
+        .as_dict()
+        .unwrap();
+    acroform
+        .get(b"Fields")
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f.as_reference().unwrap())
+        .find(|&id| {
+            doc.get_dictionary(id)
+                .unwrap()
+                .get(b"T")
+                .unwrap()
+                .as_str()
+                .unwrap()
+                == name.as_bytes()
+        })
+        .unwrap()
+}
+
 #[test]
 fn test_invalid_page_number() {
     let pdf_data = create_test_pdf();
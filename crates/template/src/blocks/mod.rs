@@ -5,7 +5,11 @@
 //! can be used for block-specific utilities.
 
 // Re-export block types from schema
-pub use crate::schema::{Block, FieldFormBlock, QRCodeBlock, TableBlock, TableColumn, TextBlock};
+pub use crate::schema::{
+    BarcodeBlock, Block, CheckboxBlock, EllipseBlock, FieldBlock, FieldFormBlock, FormFieldBlock,
+    ImageBlock, LineBlock, PdfPageBlock, QRCodeBlock, RectBlock, TableBlock, TableColumn,
+    TextBlock,
+};
 
 /// Trait for blocks that can provide their text content
 pub trait TextContent {
@@ -49,8 +53,10 @@ mod tests {
             font: None,
             align: crate::schema::Align::Left,
             word_wrap: None,
+            text_box: None,
             format: None,
             format_type: None,
+            rotation: None,
             pages: None,
             enable: None,
         };
@@ -70,8 +76,10 @@ mod tests {
             font: None,
             align: crate::schema::Align::Left,
             word_wrap: None,
+            text_box: None,
             format: None,
             format_type: None,
+            rotation: None,
             pages: None,
             enable: None,
         };
@@ -3,7 +3,8 @@
 //! This crate provides:
 //! - Template JSON schema types
 //! - Template parsing from JSON
-//! - Block rendering (text, fieldform, table, qrcode)
+//! - Block rendering (text, fieldform, table, qrcode, image, barcode, line, rect, ellipse,
+//!   formfield, field, checkbox, pdfpage)
 //! - Data binding via JSONPath-like expressions
 //!
 //! # Example
@@ -28,8 +29,8 @@ pub use renderer::TemplateRenderer;
 pub use schema::*;
 
 // Re-export pdf_core types for direct manipulation after render_to_document
-pub use pdf_core::PdfDocument;
 pub use pdf_core::{Align as PdfAlign, Color as PdfColor};
+pub use pdf_core::{FlattenMode, PdfDocument};
 pub use pdf_core::{FontStyle as PdfFontStyle, FontWeight as PdfFontWeight};
 
 // Re-export the embedded schema
@@ -3,7 +3,10 @@
 use crate::parser::{parse_template, resolve_binding, value_to_string};
 use crate::schema::*;
 use crate::{Result, TemplateError};
-use pdf_core::{FontStyle as PdfFontStyle, FontWeight, PdfDocument};
+use pdf_core::{
+    FieldDefinition, FlattenMode, FontFamilyBuilder, FontStyle as PdfFontStyle, FontWeight,
+    PdfDocument, Transform, Watermark, WatermarkLayer,
+};
 use std::collections::HashMap;
 use thai_text::ThaiWordcut;
 
@@ -28,10 +31,14 @@ pub struct TemplateRenderer {
     template: Template,
     /// Base PDF bytes
     pdf_bytes: Vec<u8>,
-    /// Fonts loaded from bytes (font_id -> font_bytes)
-    fonts: HashMap<String, Vec<u8>>,
+    /// Fonts loaded from bytes (font_id -> variant bytes)
+    fonts: HashMap<String, FontVariants>,
     /// Thai word segmentation (owned)
     wordcut: Option<ThaiWordcut>,
+    /// Flatten form fields/annotations into page content on render
+    flatten: Option<FlattenMode>,
+    /// PDFs for pdfpage blocks (source name -> bytes)
+    pdf_sources: HashMap<String, Vec<u8>>,
 }
 
 impl TemplateRenderer {
@@ -62,6 +69,8 @@ impl TemplateRenderer {
             pdf_bytes,
             fonts: HashMap::new(),
             wordcut: None,
+            flatten: None,
+            pdf_sources: HashMap::new(),
         };
 
         // Auto-load fonts if base_path provided
@@ -81,12 +90,41 @@ impl TemplateRenderer {
             pdf_bytes,
             fonts: HashMap::new(),
             wordcut: None,
+            flatten: None,
+            pdf_sources: HashMap::new(),
         })
     }
 
-    /// Add font from bytes
+    /// Add font from bytes (regular variant)
     pub fn add_font(&mut self, name: &str, data: Vec<u8>) {
-        self.fonts.insert(name.to_string(), data);
+        self.add_font_variant(name, FontStyle::Regular, data);
+    }
+
+    /// Add a font variant (regular, bold, italic, bold-italic) from bytes
+    ///
+    /// Variants of the same `name` are registered as one font family, so
+    /// blocks with `"style": "bold"` pick up the bold file.
+    ///
+    /// # Example
+    /// ```ignore
+    /// renderer.add_font("sarabun", regular_bytes);
+    /// renderer.add_font_variant("sarabun", FontStyle::Bold, bold_bytes);
+    /// ```
+    pub fn add_font_variant(&mut self, name: &str, style: FontStyle, data: Vec<u8>) {
+        self.fonts
+            .entry(name.to_string())
+            .or_default()
+            .set(style, data);
+    }
+
+    /// Add a PDF for `pdfpage` blocks
+    ///
+    /// # Example
+    /// ```ignore
+    /// renderer.add_pdf_source("letterhead", std::fs::read("letterhead.pdf")?);
+    /// ```
+    pub fn add_pdf_source(&mut self, name: &str, data: Vec<u8>) {
+        self.pdf_sources.insert(name.to_string(), data);
     }
 
     /// Set Thai wordcut for word wrapping
@@ -94,6 +132,21 @@ impl TemplateRenderer {
         self.wordcut = Some(wordcut);
     }
 
+    /// Flatten form fields (and optionally other annotations) on render
+    ///
+    /// When set, rendered documents have their widget appearances drawn into
+    /// page content and /AcroForm removed, so filled values are not editable.
+    /// Pass `None` to keep forms interactive (the default).
+    ///
+    /// # Example
+    /// ```ignore
+    /// renderer.set_flatten(Some(FlattenMode::Forms));
+    /// let pdf = renderer.render(&data)?;
+    /// ```
+    pub fn set_flatten(&mut self, mode: Option<FlattenMode>) {
+        self.flatten = mode;
+    }
+
     /// Load fonts from file paths defined in the template
     ///
     /// For native Rust use - reads font files from disk based on paths in template JSON.
@@ -112,48 +165,30 @@ impl TemplateRenderer {
     /// Internal method to load fonts from template paths
     #[cfg(not(target_arch = "wasm32"))]
     fn load_fonts_internal(&mut self, base_path: &std::path::Path) -> Result<()> {
+        let read_font = |path: &str| {
+            std::fs::read(base_path.join(path))
+                .map_err(|e| TemplateError::FontError(format!("Failed to load font {path}: {e}")))
+        };
+
         for font_def in &self.template.fonts {
+            let mut variants = FontVariants::default();
+
             // Load regular variant (or legacy source)
-            if let Some(ref path) = font_def.regular {
-                let full_path = base_path.join(path);
-                let data = std::fs::read(&full_path).map_err(|e| {
-                    TemplateError::FontError(format!("Failed to load font {path}: {e}"))
-                })?;
-                self.fonts.insert(font_def.id.clone(), data);
-            } else if let Some(ref path) = font_def.source {
-                let full_path = base_path.join(path);
-                let data = std::fs::read(&full_path).map_err(|e| {
-                    TemplateError::FontError(format!("Failed to load font {path}: {e}"))
-                })?;
-                self.fonts.insert(font_def.id.clone(), data);
+            if let Some(path) = font_def.regular.as_deref().or(font_def.source.as_deref()) {
+                variants.regular = Some(read_font(path)?);
             }
-
-            // Load bold variant
             if let Some(ref path) = font_def.bold {
-                let full_path = base_path.join(path);
-                let data = std::fs::read(&full_path).map_err(|e| {
-                    TemplateError::FontError(format!("Failed to load font {path}: {e}"))
-                })?;
-                self.fonts.insert(format!("{}-bold", font_def.id), data);
+                variants.bold = Some(read_font(path)?);
             }
-
-            // Load italic variant
             if let Some(ref path) = font_def.italic {
-                let full_path = base_path.join(path);
-                let data = std::fs::read(&full_path).map_err(|e| {
-                    TemplateError::FontError(format!("Failed to load font {path}: {e}"))
-                })?;
-                self.fonts.insert(format!("{}-italic", font_def.id), data);
+                variants.italic = Some(read_font(path)?);
             }
-
-            // Load bold-italic variant
             if let Some(ref path) = font_def.bold_italic {
-                let full_path = base_path.join(path);
-                let data = std::fs::read(&full_path).map_err(|e| {
-                    TemplateError::FontError(format!("Failed to load font {path}: {e}"))
-                })?;
-                self.fonts
-                    .insert(format!("{}-bold-italic", font_def.id), data);
+                variants.bold_italic = Some(read_font(path)?);
+            }
+
+            if !variants.is_empty() {
+                self.fonts.insert(font_def.id.clone(), variants);
             }
         }
         Ok(())
@@ -198,9 +233,22 @@ impl TemplateRenderer {
     /// let bytes = doc.to_bytes()?;
     /// ```
     pub fn render_to_document(&self, data: &serde_json::Value) -> Result<PdfDocument> {
-        // 1. Clone base PDF -> fresh document
-        let mut doc = PdfDocument::open_from_bytes(&self.pdf_bytes)
-            .map_err(|e| TemplateError::RenderError(format!("Failed to open PDF: {e}")))?;
+        // 1. Clone base PDF -> fresh document, then add declared pages
+        let pages = &self.template.template.pages;
+        let mut doc = if !self.pdf_bytes.is_empty() {
+            PdfDocument::open_from_bytes(&self.pdf_bytes)
+                .map_err(|e| TemplateError::RenderError(format!("Failed to open PDF: {e}")))?
+        } else if !pages.is_empty() {
+            PdfDocument::new()
+        } else {
+            return Err(TemplateError::RenderError(
+                "Template has no base PDF and declares no pages".to_string(),
+            ));
+        };
+        for (index, page) in pages.iter().enumerate() {
+            doc.add_page(page_size(page, index + 1)?)
+                .map_err(|e| TemplateError::RenderError(format!("Failed to add page: {e}")))?;
+        }
 
         // 2. Prepare pages for duplication if configured
         if let Some(duplicate) = &self.template.template.duplicate {
@@ -214,23 +262,92 @@ impl TemplateRenderer {
             }
         }
 
-        // 3. Add fonts from stored bytes
-        for (name, font_data) in &self.fonts {
-            doc.add_font(name, font_data).map_err(|e| {
-                TemplateError::RenderError(format!("Failed to add font {name}: {e}"))
-            })?;
+        // 3. Register font families from stored bytes
+        for (name, variants) in &self.fonts {
+            doc.register_font_family(name, variants.to_builder(name)?)
+                .map_err(|e| {
+                    TemplateError::RenderError(format!("Failed to add font {name}: {e}"))
+                })?;
         }
 
-        // 4. Render all blocks
+        // 4. Wire up fallback chains declared in the template
+        self.apply_font_fallbacks(&mut doc)?;
+
+        // 5. Render all blocks
         self.render_blocks(&mut doc, data)?;
 
+        // 6. Stamp the watermark over (or under) everything rendered
+        if let Some(watermark) = &self.template.watermark {
+            self.render_watermark(&mut doc, watermark, data)?;
+        }
+
+        // 7. Flatten forms (applied when the document is saved)
+        if let Some(mode) = self.flatten {
+            doc.flatten(mode);
+        }
+
         Ok(doc)
     }
 
+    /// Render many records into one document
+    ///
+    /// Each record is rendered like `render_to_document()` and appended in
+    /// order, with a bookmark per record ("Record 1", "Record 2", ...).
+    /// Template fonts are embedded once for the whole batch.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut doc = renderer.render_batch(&records)?;
+    /// std::fs::write("all.pdf", doc.to_bytes()?)?;
+    /// ```
+    pub fn render_batch(&self, records: &[serde_json::Value]) -> Result<PdfDocument> {
+        if records.is_empty() {
+            return Err(TemplateError::RenderError(
+                "No records to render".to_string(),
+            ));
+        }
+
+        // Append each record as soon as it is rendered, so only the batch
+        // and one record are held in memory at a time
+        let mut batch = self.render_to_document(&records[0])?;
+        batch.add_bookmark("Record 1", 1)?;
+        for (i, data) in records.iter().enumerate().skip(1) {
+            let record = self.render_to_document(data)?;
+            batch
+                .append_document(record, Some(&format!("Record {}", i + 1)))
+                .map_err(|e| TemplateError::RenderError(format!("Failed to merge records: {e}")))?;
+        }
+        Ok(batch)
+    }
+
+    /// Internal: apply `FontDef.fallback` chains to the document
+    ///
+    /// Fallback families that were never loaded are skipped, so a template
+    /// still renders when an optional symbol font is not supplied.
+    fn apply_font_fallbacks(&self, doc: &mut PdfDocument) -> Result<()> {
+        for font_def in &self.template.fonts {
+            if font_def.fallback.is_empty() || !self.fonts.contains_key(&font_def.id) {
+                continue;
+            }
+
+            let fallbacks: Vec<String> = font_def
+                .fallback
+                .iter()
+                .filter(|id| self.fonts.contains_key(*id))
+                .cloned()
+                .collect();
+
+            if !fallbacks.is_empty() {
+                doc.set_font_fallback(&font_def.id, &fallbacks)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Internal: render all blocks to document
     fn render_blocks(&self, doc: &mut PdfDocument, data: &serde_json::Value) -> Result<()> {
         // Render all blocks
-        for block in &self.template.blocks {
+        for block in self.ordered_blocks() {
             self.render_block(doc, block, data)?;
         }
 
@@ -240,7 +357,7 @@ impl TemplateRenderer {
             let has_page = duplicate.page.is_some();
 
             if has_offset || has_page {
-                for block in &self.template.blocks {
+                for block in self.ordered_blocks() {
                     let mut dup_block = block.clone();
 
                     // Apply position offset if configured
@@ -266,6 +383,19 @@ impl TemplateRenderer {
         Ok(())
     }
 
+    /// Internal: blocks in render order
+    ///
+    /// Paginated tables come last: they append continuation pages, and
+    /// blocks without explicit pages should only land on template pages.
+    fn ordered_blocks(&self) -> impl Iterator<Item = &Block> {
+        let is_flowing = |block: &Block| matches!(block, Block::Table(t) if t.is_paginated());
+        let blocks = &self.template.blocks;
+        blocks
+            .iter()
+            .filter(move |b| !is_flowing(b))
+            .chain(blocks.iter().filter(move |b| is_flowing(b)))
+    }
+
     /// Render an additional item (from duplicate configuration)
     fn render_additional_item(
         &self,
@@ -339,12 +469,35 @@ impl TemplateRenderer {
             return Ok(());
         }
 
-        match block {
+        // Rotate everything the block draws around its position
+        let rotation = block.rotation().filter(|degrees| *degrees != 0.0);
+        if let Some(degrees) = rotation {
+            let position = block.position();
+            doc.set_transform(Some(
+                Transform::rotate(degrees).with_anchor(position.x, position.y),
+            ));
+        }
+
+        let result = match block {
             Block::Text(b) => self.render_text_block(doc, b, data),
             Block::FieldForm(b) => self.render_fieldform_block(doc, b, data),
             Block::Table(b) => self.render_table_block(doc, b, data),
             Block::QRCode(b) => self.render_qrcode_block(doc, b, data),
+            Block::Image(b) => self.render_image_block(doc, b, data),
+            Block::Barcode(b) => self.render_barcode_block(doc, b, data),
+            Block::Line(b) => self.render_line_block(doc, b, data),
+            Block::Rect(b) => self.render_rect_block(doc, b, data),
+            Block::Ellipse(b) => self.render_ellipse_block(doc, b, data),
+            Block::FormField(b) => self.render_formfield_block(doc, b, data),
+            Block::Field(b) => self.render_field_block(doc, b, data),
+            Block::Checkbox(b) => self.render_checkbox_block(doc, b, data),
+            Block::PdfPage(b) => self.render_pdfpage_block(doc, b, data),
+        };
+
+        if rotation.is_some() {
+            doc.set_transform(None);
         }
+        result
     }
 
     /// Check if a block is enabled based on its enable binding
@@ -399,13 +552,14 @@ impl TemplateRenderer {
             doc.set_text_color(pdf_core::Color::black());
         }
 
+        // Bounded text box: wrap to the box and handle overflow
+        if let Some(text_box) = &block.text_box {
+            return self.render_text_box(doc, block, text_box, &formatted_text);
+        }
+
         // Handle word wrapping
         let lines = if let Some(wrap) = &block.word_wrap {
-            if let Some(wordcut) = &self.wordcut {
-                wordcut.word_wrap(&formatted_text, wrap.max_chars)
-            } else {
-                pdf_core::simple_word_wrap(&formatted_text, wrap.max_chars)
-            }
+            self.wrap_text(doc, &formatted_text, wrap.max_chars, wrap.max_width)?
         } else {
             vec![formatted_text]
         };
@@ -432,6 +586,169 @@ impl TemplateRenderer {
         Ok(())
     }
 
+    /// Render a text block into its bounded box
+    ///
+    /// Text is wrapped to the box width and placed according to the block
+    /// align and the box vertical align. Text that doesn't fit is handled by
+    /// the box overflow policy.
+    fn render_text_box(
+        &self,
+        doc: &mut PdfDocument,
+        block: &TextBlock,
+        text_box: &TextBox,
+        text: &str,
+    ) -> Result<()> {
+        let base_size = doc.get_font_size() as f64;
+        let base_line_height = block
+            .word_wrap
+            .as_ref()
+            .map(|w| w.line_height)
+            .unwrap_or(13.5);
+
+        let mut layout = self.layout_text_box(doc, text, text_box, base_size, base_line_height)?;
+        let mut clip = false;
+
+        if !layout.fits {
+            match text_box.overflow {
+                Overflow::Clip => clip = true,
+                Overflow::Ellipsis => {
+                    let max_lines = layout.max_lines().max(1);
+                    let truncated = layout.lines.len() > max_lines;
+                    layout.lines.truncate(max_lines);
+                    if let Some(last) = layout.lines.pop() {
+                        let last = self.ellipsize(doc, &last, text_box.width, truncated)?;
+                        layout.lines.push(last);
+                    }
+                }
+                Overflow::Shrink => {
+                    let mut size = base_size;
+                    while !layout.fits && size > text_box.min_font_size {
+                        size = (size - 0.5).max(text_box.min_font_size);
+                        let line_height = base_line_height * size / base_size;
+                        layout = self.layout_text_box(doc, text, text_box, size, line_height)?;
+                    }
+                    // Still too large at the minimum size
+                    clip = !layout.fits;
+                }
+                Overflow::Error => {
+                    return Err(TemplateError::RenderError(format!(
+                        "Text does not fit in box{}: {} line(s) in {}x{}pt",
+                        block
+                            .id
+                            .as_ref()
+                            .map(|id| format!(" '{id}'"))
+                            .unwrap_or_default(),
+                        layout.lines.len(),
+                        text_box.width,
+                        text_box.height
+                    )));
+                }
+            }
+        }
+
+        let x = block.position.x;
+        let top = block.position.y;
+        let align = convert_align(block.align);
+        let anchor_x = match block.align {
+            Align::Left => x,
+            Align::Center => x + text_box.width / 2.0,
+            Align::Right => x + text_box.width,
+        };
+
+        // Overflowing content stays anchored at the top of the box
+        let content_height = layout.content_height(layout.lines.len());
+        let y_offset = pdf_core::calculate_y_offset(
+            content_height,
+            text_box.height,
+            convert_vertical_align(text_box.vertical_align),
+        )
+        .max(0.0);
+
+        if clip {
+            doc.set_text_clip(Some(pdf_core::Rect::new(
+                x,
+                top,
+                text_box.width,
+                text_box.height,
+            )));
+        }
+
+        let pages = self.resolve_pages(block.pages.as_deref(), doc.page_count());
+        for page in pages {
+            let mut y = top + y_offset + layout.ascent;
+            for line in &layout.lines {
+                // Lines starting below the box are never visible
+                if y - layout.ascent >= top + text_box.height {
+                    break;
+                }
+                doc.insert_text(line, page, anchor_x, y, align)?;
+                y += layout.line_height;
+            }
+        }
+
+        doc.set_text_clip(None);
+        doc.set_font_size(base_size as f32)?;
+        Ok(())
+    }
+
+    /// Wrap text for a text box at the given font size
+    fn layout_text_box(
+        &self,
+        doc: &mut PdfDocument,
+        text: &str,
+        text_box: &TextBox,
+        size: f64,
+        line_height: f64,
+    ) -> Result<TextBoxLayout> {
+        doc.set_font_size(size as f32)?;
+        let (ascent, descent) = doc.get_font_extents()?;
+        let lines = self.wrap_text(doc, text, 0, Some(text_box.width))?;
+
+        let mut layout = TextBoxLayout {
+            lines,
+            line_height,
+            ascent,
+            descent,
+            height: text_box.height,
+            fits: true,
+        };
+
+        let too_wide = layout
+            .lines
+            .iter()
+            .any(|line| doc.get_text_width(line).unwrap_or(0.0) > text_box.width);
+        layout.fits = !too_wide && layout.lines.len() <= layout.max_lines();
+        Ok(layout)
+    }
+
+    /// Shorten a line so it ends with "..." and fits the given width
+    ///
+    /// When `force` is false the line is returned as-is if it already fits.
+    fn ellipsize(&self, doc: &PdfDocument, line: &str, width: f64, force: bool) -> Result<String> {
+        const ELLIPSIS: &str = "...";
+
+        if !force && doc.get_text_width(line)? <= width {
+            return Ok(line.to_string());
+        }
+
+        let mut chars: Vec<char> = line.trim_end().chars().collect();
+        loop {
+            let candidate: String = chars.iter().collect::<String>() + ELLIPSIS;
+            if chars.is_empty() || doc.get_text_width(&candidate)? <= width {
+                return Ok(candidate);
+            }
+            // Drop the last character together with its combining marks
+            while let Some(c) = chars.pop() {
+                if !thai_text::is_above_below_mark(c) {
+                    break;
+                }
+            }
+            while chars.last().is_some_and(|c| c.is_whitespace()) {
+                chars.pop();
+            }
+        }
+    }
+
     /// Render a field form block
     fn render_fieldform_block(
         &self,
@@ -524,54 +841,349 @@ impl TemplateRenderer {
             self.set_font(doc, font)?;
         }
 
+        // Lay out every row first so pagination knows the row heights
+        let header = match &block.header_font {
+            Some(font) => {
+                let body_font = SavedFont::save(doc);
+                self.set_font(doc, font)?;
+                let header = self.layout_table_header(doc, block)?;
+                SavedFont::restore(body_font, doc)?;
+                header
+            }
+            None => self.layout_table_header(doc, block)?,
+        };
+        let body = rows
+            .iter()
+            .map(|row| self.layout_table_row(doc, block, row))
+            .collect::<Result<Vec<_>>>()?;
+        let table = TableGeometry::new(doc, block)?;
+
+        if block.is_paginated() {
+            return self.render_paginated_table(
+                doc,
+                block,
+                &table,
+                &rows,
+                header.as_deref(),
+                &body,
+            );
+        }
+
         // Determine pages to render on
         let pages = self.resolve_pages(block.pages.as_deref(), doc.page_count());
 
         // Render rows
         for page in pages {
-            let mut y = block.position.y;
+            let mut cursor = TableCursor::new(page, block.position.y);
 
-            for row in &rows {
-                let mut max_lines = 1;
-
-                // First pass: determine maximum lines needed for word wrapping
-                for col in &block.columns {
-                    if let Some(max_chars) = col.word_wrap {
-                        let cell_text =
-                            row.get(&col.field).map(value_to_string).unwrap_or_default();
-
-                        let lines = if let Some(wordcut) = &self.wordcut {
-                            wordcut.word_wrap(&cell_text, max_chars)
-                        } else {
-                            pdf_core::simple_word_wrap(&cell_text, max_chars)
-                        };
-                        max_lines = max_lines.max(lines.len());
-                    }
+            if let Some(header) = &header {
+                self.render_table_header(doc, block, &table, &mut cursor, header)?;
+            }
+            for (i, cells) in body.iter().enumerate() {
+                let fill = table.body_fill(i);
+                self.render_table_row(doc, block, &table, &mut cursor, cells, fill)?;
+            }
+
+            if let Some(totals) = &block.totals {
+                if let Some(label) = &totals.page_subtotal {
+                    let sums = table_sums(totals, &rows);
+                    let cells = self.layout_totals_row(block, totals, label, &sums)?;
+                    let fill = table.background;
+                    self.render_table_row(doc, block, &table, &mut cursor, &cells, fill)?;
                 }
+            }
+
+            table.draw_borders(doc, &cursor)?;
+        }
+
+        Ok(())
+    }
+
+    /// Render a table that flows onto continuation pages
+    ///
+    /// Rows start on the first listed page and stop at `maxHeight` or
+    /// `bottom`; remaining rows continue on copies of that page appended to
+    /// the document, below a repeated header and brought-forward row.
+    fn render_paginated_table(
+        &self,
+        doc: &mut PdfDocument,
+        block: &TableBlock,
+        table: &TableGeometry,
+        rows: &[serde_json::Value],
+        header: Option<&[Vec<String>]>,
+        body: &[Vec<Vec<String>>],
+    ) -> Result<()> {
+        let template_page = block
+            .pages
+            .as_deref()
+            .and_then(|p| p.first().copied())
+            .unwrap_or(1);
+        let totals = block.totals.as_ref();
+        let subtotal_label = totals.and_then(|t| t.page_subtotal.as_ref());
+        let carried_label = totals.and_then(|t| t.carried_forward.as_ref());
+        let brought_label = totals.and_then(|t| t.brought_forward.as_ref());
+
+        let limit_from = |start_y: f64| {
+            let by_height = block.max_height.map(|h| start_y - table.top_offset + h);
+            match (by_height, block.bottom) {
+                (Some(a), Some(b)) => a.min(b),
+                (Some(a), None) => a,
+                (None, Some(b)) => b,
+                (None, None) => f64::INFINITY,
+            }
+        };
 
-                // Second pass: render cells
-                for col in &block.columns {
-                    let cell_text = row.get(&col.field).map(value_to_string).unwrap_or_default();
+        let mut cursor = TableCursor::new(template_page, block.position.y);
+        let mut limit = limit_from(cursor.y);
+        if let Some(header) = header {
+            self.render_table_header(doc, block, table, &mut cursor, header)?;
+        }
 
-                    let formatted = if let Some(format) = &col.format {
-                        self.format_text(&cell_text, Some(format), None)?
-                    } else {
-                        cell_text
-                    };
+        // Index of the first row on the current page
+        let mut page_start = 0;
+
+        for (i, cells) in body.iter().enumerate() {
+            let height = table.row_height(cells);
+            let is_last = i + 1 == rows.len();
+
+            // Footer rows that must still fit below this row
+            let footer_rows =
+                subtotal_label.is_some() as usize + (!is_last && carried_label.is_some()) as usize;
+            let reserve = table.row_height(&[]) * footer_rows as f64;
+
+            let bottom = cursor.y - table.top_offset + height + reserve;
+            if i > page_start && bottom > limit {
+                if let (Some(totals), Some(label)) = (totals, subtotal_label) {
+                    let sums = table_sums(totals, &rows[page_start..i]);
+                    let cells = self.layout_totals_row(block, totals, label, &sums)?;
+                    let fill = table.background;
+                    self.render_table_row(doc, block, table, &mut cursor, &cells, fill)?;
+                }
+                if let (Some(totals), Some(label)) = (totals, carried_label) {
+                    let sums = table_sums(totals, &rows[..i]);
+                    let cells = self.layout_totals_row(block, totals, label, &sums)?;
+                    let fill = table.background;
+                    self.render_table_row(doc, block, table, &mut cursor, &cells, fill)?;
+                }
+                table.draw_borders(doc, &cursor)?;
 
-                    let x = block.position.x + col.x;
-                    let align = convert_align(col.align);
+                // Continue on a fresh copy of the template page
+                let page = doc.duplicate_page(template_page).map_err(|e| {
+                    TemplateError::RenderError(format!("Failed to add continuation page: {e}"))
+                })?;
+                cursor = TableCursor::new(page, block.continuation_y.unwrap_or(block.position.y));
+                limit = limit_from(cursor.y);
+                page_start = i;
 
-                    doc.insert_text(&formatted, page, x, y, align)?;
+                if let (Some(header), true) = (header, block.repeat_header) {
+                    self.render_table_header(doc, block, table, &mut cursor, header)?;
+                }
+                if let (Some(totals), Some(label)) = (totals, brought_label) {
+                    let sums = table_sums(totals, &rows[..i]);
+                    let cells = self.layout_totals_row(block, totals, label, &sums)?;
+                    let fill = table.background;
+                    self.render_table_row(doc, block, table, &mut cursor, &cells, fill)?;
                 }
+            }
+
+            let fill = table.body_fill(i);
+            self.render_table_row(doc, block, table, &mut cursor, cells, fill)?;
+        }
+
+        if let (Some(totals), Some(label)) = (totals, subtotal_label) {
+            let sums = table_sums(totals, &rows[page_start..]);
+            let cells = self.layout_totals_row(block, totals, label, &sums)?;
+            let fill = table.background;
+            self.render_table_row(doc, block, table, &mut cursor, &cells, fill)?;
+        }
+        table.draw_borders(doc, &cursor)?;
+
+        Ok(())
+    }
+
+    /// Lay out the header row, if any column has a header
+    fn layout_table_header(
+        &self,
+        doc: &PdfDocument,
+        block: &TableBlock,
+    ) -> Result<Option<Vec<Vec<String>>>> {
+        if block.columns.iter().all(|col| col.header.is_none()) {
+            return Ok(None);
+        }
+
+        block
+            .columns
+            .iter()
+            .map(|col| {
+                let text = col.header.clone().unwrap_or_default();
+                self.wrap_table_cell(doc, block, col, text)
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Some)
+    }
+
+    /// Format and wrap the cells of a data row (one line list per column)
+    fn layout_table_row(
+        &self,
+        doc: &PdfDocument,
+        block: &TableBlock,
+        row: &serde_json::Value,
+    ) -> Result<Vec<Vec<String>>> {
+        block
+            .columns
+            .iter()
+            .map(|col| {
+                let cell_text = row.get(&col.field).map(value_to_string).unwrap_or_default();
+
+                let formatted = if let Some(format) = &col.format {
+                    self.format_text(&cell_text, Some(format), None)?
+                } else {
+                    cell_text
+                };
+
+                self.wrap_table_cell(doc, block, col, formatted)
+            })
+            .collect()
+    }
+
+    /// Lay out a totals row: the label in `labelField`, sums in `fields`
+    fn layout_totals_row(
+        &self,
+        block: &TableBlock,
+        totals: &TableTotals,
+        label: &str,
+        sums: &HashMap<&str, f64>,
+    ) -> Result<Vec<Vec<String>>> {
+        block
+            .columns
+            .iter()
+            .map(|col| {
+                let text = if col.field == totals.label_field {
+                    label.to_string()
+                } else if let Some(sum) = sums.get(col.field.as_str()) {
+                    // Round away binary noise before formatting (e.g. 0.1 + 0.2)
+                    let sum = (sum * 100.0).round() / 100.0;
+                    self.format_text(&sum.to_string(), col.format.as_deref(), None)?
+                } else {
+                    String::new()
+                };
+                Ok(vec![text])
+            })
+            .collect()
+    }
+
+    /// Wrap a cell's text where the column configures wrapping
+    ///
+    /// A column with a `width` and no explicit wrap setting wraps to the
+    /// width inside the cell padding.
+    fn wrap_table_cell(
+        &self,
+        doc: &PdfDocument,
+        block: &TableBlock,
+        col: &TableColumn,
+        text: String,
+    ) -> Result<Vec<String>> {
+        if col.word_wrap.is_some() || col.max_width.is_some() {
+            let max_chars = col.word_wrap.unwrap_or(0);
+            self.wrap_text(doc, &text, max_chars, col.max_width)
+        } else if let Some(width) = col.width {
+            let padding = block.style.as_ref().map_or(0.0, |s| s.padding);
+            self.wrap_text(doc, &text, 0, Some(width - 2.0 * padding))
+        } else {
+            Ok(vec![text])
+        }
+    }
+
+    /// Render the header row in the header font, then restore the body font
+    fn render_table_header(
+        &self,
+        doc: &mut PdfDocument,
+        block: &TableBlock,
+        table: &TableGeometry,
+        cursor: &mut TableCursor,
+        cells: &[Vec<String>],
+    ) -> Result<()> {
+        let body_font = SavedFont::save(doc);
+        if let Some(font) = &block.header_font {
+            self.set_font(doc, font)?;
+        }
+
+        let fill = table.header_background.or(table.background);
+        self.render_table_row(doc, block, table, cursor, cells, fill)?;
+
+        if block.header_font.is_some() {
+            SavedFont::restore(body_font, doc)?;
+        }
+        Ok(())
+    }
+
+    /// Render one laid-out table row and advance the cursor past it
+    fn render_table_row(
+        &self,
+        doc: &mut PdfDocument,
+        block: &TableBlock,
+        table: &TableGeometry,
+        cursor: &mut TableCursor,
+        cells: &[Vec<String>],
+        fill: Option<pdf_core::Color>,
+    ) -> Result<()> {
+        let height = table.row_height(cells);
+        let top = cursor.y - table.top_offset;
+
+        if let Some(color) = fill {
+            let rect = pdf_core::Rect::new(table.left, top, table.right - table.left, height);
+            doc.draw_rect(cursor.page, rect, &pdf_core::ShapeStyle::fill(color))?;
+        }
+
+        for ((col, (left, width)), lines) in block.columns.iter().zip(&table.columns).zip(cells) {
+            let align = convert_align(col.align);
+            let x = match (width, col.align) {
+                (_, Align::Left) => left + table.padding,
+                (Some(w), Align::Center) => left + w / 2.0,
+                (Some(w), Align::Right) => left + w - table.padding,
+                (None, _) => *left,
+            };
 
-                y += block.row_height * max_lines as f64;
+            for (i, line) in lines.iter().enumerate() {
+                let line_y = cursor.y + block.row_height * i as f64;
+                doc.insert_text(line, cursor.page, x, line_y, align)?;
             }
         }
 
+        cursor.rows.push((top, height));
+        cursor.y += height;
         Ok(())
     }
 
+    /// Wrap text into lines
+    ///
+    /// With `max_width`, candidate lines are measured in points with the
+    /// document's current font; otherwise `max_chars` limits characters per
+    /// line. Thai break points come from the wordcut when one is set.
+    fn wrap_text(
+        &self,
+        doc: &PdfDocument,
+        text: &str,
+        max_chars: usize,
+        max_width: Option<f64>,
+    ) -> Result<Vec<String>> {
+        let Some(max_width) = max_width else {
+            return Ok(match &self.wordcut {
+                Some(wordcut) => wordcut.word_wrap(text, max_chars),
+                None => pdf_core::simple_word_wrap(text, max_chars),
+            });
+        };
+
+        // Surface font errors up front; measuring can't fail after this
+        doc.get_text_width(text)?;
+        let measure = |line: &str| doc.get_text_width(line).unwrap_or(0.0);
+
+        Ok(match &self.wordcut {
+            Some(wordcut) => wordcut.word_wrap_width(text, max_width, measure),
+            None => pdf_core::simple_word_wrap_width(text, max_width, measure),
+        })
+    }
+
     /// Render a QR code block
     fn render_qrcode_block(
         &self,
@@ -580,45 +1192,603 @@ impl TemplateRenderer {
         data: &serde_json::Value,
     ) -> Result<()> {
         // Resolve QR data
-        let qr_data = if let Some(bind) = &block.bind {
+        let qr_data = match block.payload {
+            QRPayload::Text => {
+                if let Some(bind) = &block.bind {
+                    resolve_binding(bind, data)
+                        .map(value_to_string)
+                        .unwrap_or_default()
+                } else {
+                    block.data.clone().unwrap_or_default()
+                }
+            }
+            QRPayload::PromptPay => {
+                let value = match &block.bind {
+                    Some(bind) => resolve_binding(bind, data).cloned(),
+                    None => block.data.clone().map(serde_json::Value::String),
+                };
+                match value {
+                    Some(value) => build_promptpay_payload(&value)?,
+                    None => String::new(),
+                }
+            }
+        };
+
+        if qr_data.is_empty() {
+            return Ok(());
+        }
+
+        let matrix = generate_qr_matrix(&qr_data, block.error_correction)?
+            .with_quiet_zone(block.quiet_zone as usize);
+        let foreground = block.foreground.unwrap_or_else(Color::black);
+        let background = block.background.unwrap_or_else(Color::white);
+
+        // Determine pages to render on
+        let pages = self.resolve_pages(block.pages.as_deref(), doc.page_count());
+
+        match block.render_mode {
+            QRRenderMode::Vector => {
+                let rect = pdf_core::Rect::new(
+                    block.position.x,
+                    block.position.y,
+                    block.size.width,
+                    block.size.height,
+                );
+                for page in pages {
+                    doc.insert_module_matrix(
+                        &matrix,
+                        page,
+                        rect,
+                        convert_color(foreground),
+                        Some(convert_color(background)),
+                    )?;
+                }
+            }
+            QRRenderMode::Raster => {
+                let qr_image = generate_qr_image(&matrix, foreground, background)?;
+                for page in pages {
+                    doc.insert_image(
+                        &qr_image,
+                        page,
+                        block.position.x,
+                        block.position.y,
+                        block.size.width,
+                        block.size.height,
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Render an image block
+    fn render_image_block(
+        &self,
+        doc: &mut PdfDocument,
+        block: &ImageBlock,
+        data: &serde_json::Value,
+    ) -> Result<()> {
+        // Resolve image source (base64 or data URI)
+        let src = if let Some(bind) = &block.bind {
             resolve_binding(bind, data)
                 .map(value_to_string)
                 .unwrap_or_default()
         } else {
-            block.data.clone().unwrap_or_default()
+            block.src.clone().unwrap_or_default()
         };
 
-        if qr_data.is_empty() {
+        if src.trim().is_empty() {
             return Ok(());
         }
 
-        // Generate QR code image
-        let qr_image = generate_qr_image(&qr_data, block.error_correction)?;
+        let image_data = decode_image_source(&src)?;
+        let mode = convert_scale_mode(block.scale_mode);
 
         // Determine pages to render on
         let pages = self.resolve_pages(block.pages.as_deref(), doc.page_count());
 
         // Insert image on each page
         for page in pages {
-            doc.insert_image(
-                &qr_image,
+            doc.insert_image_scaled(
+                &image_data,
                 page,
                 block.position.x,
                 block.position.y,
                 block.size.width,
                 block.size.height,
+                mode,
             )?;
         }
 
         Ok(())
     }
 
-    /// Format text with optional format pattern or special format type
-    fn format_text(
+    /// Render a barcode block
+    fn render_barcode_block(
         &self,
-        text: &str,
-        format: Option<&str>,
-        format_type: Option<FormatType>,
+        doc: &mut PdfDocument,
+        block: &BarcodeBlock,
+        data: &serde_json::Value,
+    ) -> Result<()> {
+        // Resolve barcode data
+        let barcode_data = if let Some(bind) = &block.bind {
+            resolve_binding(bind, data)
+                .map(value_to_string)
+                .unwrap_or_default()
+        } else {
+            block.data.clone().unwrap_or_default()
+        };
+
+        if barcode_data.is_empty() {
+            return Ok(());
+        }
+
+        let barcode = pdf_core::Barcode::encode(convert_symbology(block.symbology), &barcode_data)?;
+
+        // Human-readable text is centered below the bars
+        if block.show_text {
+            match &block.font {
+                Some(font) => {
+                    self.set_font(doc, font)?;
+                    let color = font.color.unwrap_or_default();
+                    doc.set_text_color(pdf_core::Color::rgb(
+                        color.r as f32,
+                        color.g as f32,
+                        color.b as f32,
+                    ));
+                }
+                None => {
+                    doc.set_text_color(pdf_core::Color::black());
+                }
+            }
+        }
+
+        let pages = self.resolve_pages(block.pages.as_deref(), doc.page_count());
+        for page in pages {
+            doc.insert_barcode(
+                &barcode,
+                page,
+                block.position.x,
+                block.position.y,
+                block.module_width,
+                block.bar_height,
+            )?;
+
+            if block.show_text {
+                let (ascent, _) = doc.get_font_extents()?;
+                let center_x = block.position.x + barcode.width(block.module_width) / 2.0;
+                let text_y = block.position.y + block.bar_height + ascent;
+                doc.insert_text(
+                    &barcode.text,
+                    page,
+                    center_x,
+                    text_y,
+                    pdf_core::Align::Center,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Render a line block
+    fn render_line_block(
+        &self,
+        doc: &mut PdfDocument,
+        block: &LineBlock,
+        data: &serde_json::Value,
+    ) -> Result<()> {
+        let Some(mut style) = resolve_shape_style(&block.style, data)? else {
+            return Ok(());
+        };
+        // A line has no interior, so a fill-only style strokes with the fill color
+        if style.stroke.is_none() {
+            style.stroke = style.fill.take();
+        }
+
+        let pages = self.resolve_pages(block.pages.as_deref(), doc.page_count());
+        for page in pages {
+            doc.draw_line(
+                page,
+                block.position.x,
+                block.position.y,
+                block.end.x,
+                block.end.y,
+                &style,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Render a rectangle block
+    fn render_rect_block(
+        &self,
+        doc: &mut PdfDocument,
+        block: &RectBlock,
+        data: &serde_json::Value,
+    ) -> Result<()> {
+        let Some(style) = resolve_shape_style(&block.style, data)? else {
+            return Ok(());
+        };
+        let rect = pdf_core::Rect::new(
+            block.position.x,
+            block.position.y,
+            block.size.width,
+            block.size.height,
+        );
+
+        let pages = self.resolve_pages(block.pages.as_deref(), doc.page_count());
+        for page in pages {
+            if block.radius > 0.0 {
+                doc.draw_rounded_rect(page, rect, block.radius, &style)?;
+            } else {
+                doc.draw_rect(page, rect, &style)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Render an ellipse block
+    fn render_ellipse_block(
+        &self,
+        doc: &mut PdfDocument,
+        block: &EllipseBlock,
+        data: &serde_json::Value,
+    ) -> Result<()> {
+        let Some(style) = resolve_shape_style(&block.style, data)? else {
+            return Ok(());
+        };
+        let rx = block.size.width / 2.0;
+        let ry = block.size.height / 2.0;
+
+        let pages = self.resolve_pages(block.pages.as_deref(), doc.page_count());
+        for page in pages {
+            doc.draw_ellipse(
+                page,
+                block.position.x + rx,
+                block.position.y + ry,
+                rx,
+                ry,
+                &style,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Render a checkbox block
+    fn render_checkbox_block(
+        &self,
+        doc: &mut PdfDocument,
+        block: &CheckboxBlock,
+        data: &serde_json::Value,
+    ) -> Result<()> {
+        let color = match &block.color {
+            Some(color) => match resolve_color(color, data)? {
+                Some(color) => color,
+                None => return Ok(()),
+            },
+            None => Color::black(),
+        };
+        let bound = block
+            .bind
+            .as_deref()
+            .map(|bind| resolve_binding(bind, data));
+
+        // Offsets of the boxes to mark
+        let offsets: Vec<Position> = if block.options.is_empty() {
+            let checked = match bound {
+                None => true,
+                Some(value) => value.is_some_and(is_truthy),
+            };
+            if checked {
+                vec![Position { x: 0.0, y: 0.0 }]
+            } else {
+                Vec::new()
+            }
+        } else {
+            let selected: Vec<String> = match bound.flatten() {
+                Some(serde_json::Value::Array(values)) => {
+                    values.iter().map(value_to_string).collect()
+                }
+                Some(value) => vec![value_to_string(value)],
+                None => Vec::new(),
+            };
+            block
+                .options
+                .iter()
+                .filter(|option| selected.contains(&option.value))
+                .map(|option| option.offset)
+                .collect()
+        };
+
+        let mark = match block.mark {
+            CheckMark::Tick => pdf_core::CheckMark::Tick,
+            CheckMark::Cross => pdf_core::CheckMark::Cross,
+            CheckMark::Fill => pdf_core::CheckMark::Fill,
+        };
+        let pages = self.resolve_pages(block.pages.as_deref(), doc.page_count());
+        for page in pages {
+            for offset in &offsets {
+                doc.draw_check_mark(
+                    page,
+                    block.position.x + offset.x,
+                    block.position.y + offset.y,
+                    block.size,
+                    mark,
+                    convert_color(color),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Render a page of a registered PDF
+    fn render_pdfpage_block(
+        &self,
+        doc: &mut PdfDocument,
+        block: &PdfPageBlock,
+        data: &serde_json::Value,
+    ) -> Result<()> {
+        let name = match &block.bind {
+            Some(bind) => resolve_binding(bind, data)
+                .map(value_to_string)
+                .unwrap_or_default(),
+            None => block.source.clone().unwrap_or_default(),
+        };
+        if name.is_empty() {
+            return Ok(());
+        }
+
+        let source = self
+            .pdf_sources
+            .get(&name)
+            .ok_or_else(|| TemplateError::RenderError(format!("PDF source not loaded: {name}")))?;
+        let imported = doc.import_page(source, block.source_page)?;
+        if imported.width <= 0.0 || imported.height <= 0.0 {
+            return Err(TemplateError::RenderError(format!(
+                "PDF source {name} page {} has no size",
+                block.source_page
+            )));
+        }
+        let scale = match block.size {
+            Some(size) => (size.width / imported.width).min(size.height / imported.height),
+            None => block.scale,
+        };
+
+        let pages = self.resolve_pages(block.pages.as_deref(), doc.page_count());
+        doc.set_underlay(block.layer == Layer::Underlay);
+        let result = pages.into_iter().try_for_each(|page| {
+            doc.place_page(&imported, page, block.position.x, block.position.y, scale)
+        });
+        doc.set_underlay(false);
+
+        result?;
+        Ok(())
+    }
+
+    /// Stamp the template watermark
+    ///
+    /// An image (`src`) takes precedence over text. Text without a font
+    /// uses the first template font at 72pt.
+    fn render_watermark(
+        &self,
+        doc: &mut PdfDocument,
+        config: &WatermarkConfig,
+        data: &serde_json::Value,
+    ) -> Result<()> {
+        if let Some(bind) = &config.enable {
+            if !resolve_binding(bind, data).is_some_and(is_truthy) {
+                return Ok(());
+            }
+        }
+
+        let mut watermark = match config.src.as_deref().filter(|src| !src.trim().is_empty()) {
+            Some(src) => {
+                let size = config.size.unwrap_or(Size {
+                    width: 200.0,
+                    height: 200.0,
+                });
+                Watermark::image(&decode_image_source(src)?, size.width, size.height)
+            }
+            None => {
+                let text = match &config.bind {
+                    Some(bind) => resolve_binding(bind, data)
+                        .map(value_to_string)
+                        .unwrap_or_default(),
+                    None => config.text.clone().unwrap_or_default(),
+                };
+                if text.is_empty() {
+                    return Ok(());
+                }
+
+                let mut watermark = Watermark::text(&text);
+                match &config.font {
+                    Some(font) => {
+                        self.set_font(doc, font)?;
+                        watermark.font_size = font.size as f32;
+                        if let Some(color) = font.color {
+                            watermark = watermark.with_color(convert_color(color));
+                        }
+                    }
+                    None => {
+                        let family = self
+                            .template
+                            .fonts
+                            .first()
+                            .map(|font| &font.id)
+                            .ok_or_else(|| {
+                                TemplateError::RenderError(
+                                    "Watermark text needs a font".to_string(),
+                                )
+                            })?;
+                        if !self.fonts.contains_key(family) {
+                            return Err(TemplateError::RenderError(format!(
+                                "Watermark font not loaded: {family}"
+                            )));
+                        }
+                        watermark = watermark.with_font(family, 72.0);
+                    }
+                }
+                if let Some(color) = &config.color {
+                    match resolve_color(color, data)? {
+                        Some(color) => watermark = watermark.with_color(convert_color(color)),
+                        None => return Ok(()),
+                    }
+                }
+                watermark
+            }
+        };
+
+        watermark = watermark
+            .with_angle(config.angle)
+            .with_opacity(config.opacity)
+            .with_layer(match config.layer {
+                Layer::Overlay => WatermarkLayer::Overlay,
+                Layer::Underlay => WatermarkLayer::Underlay,
+            });
+        if let Some(tile) = config.tile {
+            watermark = watermark.tiled(tile.x, tile.y);
+        }
+        if let Some(pages) = config.pages.as_ref().filter(|pages| !pages.is_empty()) {
+            watermark = watermark.on_pages(pages.clone());
+        }
+
+        doc.add_watermark(&watermark)?;
+        Ok(())
+    }
+
+    /// Fill an AcroForm field of the base PDF
+    fn render_formfield_block(
+        &self,
+        doc: &mut PdfDocument,
+        block: &FormFieldBlock,
+        data: &serde_json::Value,
+    ) -> Result<()> {
+        let value = if let Some(bind) = &block.bind {
+            match resolve_binding(bind, data) {
+                None | Some(serde_json::Value::Null) => return Ok(()),
+                Some(value) => value_to_string(value),
+            }
+        } else {
+            match &block.value {
+                Some(value) => value.clone(),
+                None => return Ok(()),
+            }
+        };
+
+        let formatted = if value.is_empty() {
+            value
+        } else {
+            self.format_text(&value, block.format.as_deref(), block.format_type)?
+        };
+
+        if let Some(font) = &block.font {
+            self.set_font(doc, font)?;
+            doc.set_text_color(convert_color(font.color.unwrap_or_default()));
+        } else {
+            doc.set_text_color(pdf_core::Color::black());
+        }
+
+        doc.set_field_value(&block.field, &formatted)?;
+        Ok(())
+    }
+
+    /// Create an interactive form field on each page of the block
+    ///
+    /// Widgets on several pages (or duplicated blocks) share one field.
+    fn render_field_block(
+        &self,
+        doc: &mut PdfDocument,
+        block: &FieldBlock,
+        data: &serde_json::Value,
+    ) -> Result<()> {
+        let value = match &block.bind {
+            Some(bind) => match (resolve_binding(bind, data), block.field_type) {
+                (Some(value), FieldType::Checkbox) => Some(if is_truthy(value) {
+                    "Yes".to_string()
+                } else {
+                    "Off".to_string()
+                }),
+                (None | Some(serde_json::Value::Null), _) => None,
+                (Some(value), _) => Some(value_to_string(value)),
+            },
+            None => block.value.clone(),
+        };
+
+        if let Some(font) = &block.font {
+            self.set_font(doc, font)?;
+            doc.set_text_color(convert_color(font.color.unwrap_or_default()));
+        } else {
+            doc.set_text_color(pdf_core::Color::black());
+        }
+
+        let rect = |dx: f64, dy: f64| {
+            pdf_core::Rect::new(
+                block.position.x + dx,
+                block.position.y + dy,
+                block.size.width,
+                block.size.height,
+            )
+        };
+
+        let pages = self.resolve_pages(block.pages.as_deref(), doc.page_count());
+        for page in pages {
+            let mut field = match block.field_type {
+                FieldType::Text => FieldDefinition::text(&block.name, page, rect(0.0, 0.0)),
+                FieldType::Checkbox => FieldDefinition::checkbox(&block.name, page, rect(0.0, 0.0)),
+                FieldType::Signature => {
+                    FieldDefinition::signature(&block.name, page, rect(0.0, 0.0))
+                }
+                FieldType::Radio => block.options.iter().fold(
+                    FieldDefinition::radio(&block.name),
+                    |field, option| {
+                        field.with_button(
+                            &option.value,
+                            page,
+                            rect(option.offset.x, option.offset.y),
+                        )
+                    },
+                ),
+            }
+            .with_align(convert_align(block.align));
+
+            if block.multiline {
+                field = field.multiline();
+            }
+            if let Some(max_length) = block.max_length {
+                field = if block.comb {
+                    field.comb(max_length)
+                } else {
+                    field.with_max_len(max_length)
+                };
+            }
+            if block.read_only {
+                field = field.read_only();
+            }
+            if block.required {
+                field = field.required();
+            }
+            if let Some(value) = &value {
+                field = field.with_value(value);
+            }
+
+            doc.add_form_field(&field)?;
+        }
+
+        Ok(())
+    }
+
+    /// Format text with optional format pattern or special format type
+    fn format_text(
+        &self,
+        text: &str,
+        format: Option<&str>,
+        format_type: Option<FormatType>,
     ) -> Result<String> {
         // Handle special format types first
         if let Some(ft) = format_type {
@@ -684,6 +1854,283 @@ impl TemplateRenderer {
     }
 }
 
+/// Document font saved while a table header uses its own font
+struct SavedFont {
+    family: String,
+    size: f32,
+    weight: FontWeight,
+    style: PdfFontStyle,
+}
+
+impl SavedFont {
+    /// The font in effect, if a family is set
+    fn save(doc: &PdfDocument) -> Option<Self> {
+        Some(Self {
+            family: doc.get_font_family()?.to_string(),
+            size: doc.get_font_size(),
+            weight: doc.get_font_weight(),
+            style: doc.get_font_style(),
+        })
+    }
+
+    /// Make a saved font current again (nothing to restore without one)
+    fn restore(saved: Option<Self>, doc: &mut PdfDocument) -> Result<()> {
+        if let Some(font) = saved {
+            doc.set_font(&font.family, font.size)?;
+            doc.set_font_weight(font.weight)?;
+            doc.set_font_style(font.style)?;
+        }
+        Ok(())
+    }
+}
+
+/// Resolved table geometry and colors shared by all rows
+#[derive(Debug)]
+struct TableGeometry {
+    /// Left edge and optional width of each column
+    columns: Vec<(f64, Option<f64>)>,
+    /// Left edge of the table
+    left: f64,
+    /// Right edge of the table
+    right: f64,
+    /// Line height
+    row_height: f64,
+    /// Cell padding
+    padding: f64,
+    /// Distance from a row's first baseline up to its top edge
+    top_offset: f64,
+    background: Option<pdf_core::Color>,
+    header_background: Option<pdf_core::Color>,
+    zebra: Option<pdf_core::Color>,
+    border: TableBorder,
+    border_style: pdf_core::ShapeStyle,
+}
+
+impl TableGeometry {
+    /// Resolve column edges and style (the table font must be set)
+    fn new(doc: &PdfDocument, block: &TableBlock) -> Result<Self> {
+        // Columns without `x` start where the previous column ends
+        let mut columns = Vec::with_capacity(block.columns.len());
+        let mut next_x = block.position.x;
+        for col in &block.columns {
+            let left = col.x.map_or(next_x, |x| block.position.x + x);
+            next_x = left + col.width.unwrap_or(0.0);
+            columns.push((left, col.width));
+        }
+
+        // Columns without a width span to the next column
+        let mut spans: Vec<(f64, f64)> = Vec::with_capacity(columns.len());
+        for (i, (left, width)) in columns.iter().enumerate() {
+            let width = width.unwrap_or_else(|| {
+                columns
+                    .get(i + 1)
+                    .map_or(0.0, |(next_left, _)| (next_left - left).max(0.0))
+            });
+            spans.push((*left, *left + width));
+        }
+        let left = spans
+            .iter()
+            .map(|s| s.0)
+            .reduce(f64::min)
+            .unwrap_or(block.position.x);
+        let right = spans.iter().map(|s| s.1).fold(left, f64::max);
+
+        let Some(style) = &block.style else {
+            return Ok(Self {
+                columns,
+                left,
+                right,
+                row_height: block.row_height,
+                padding: 0.0,
+                top_offset: 0.0,
+                background: None,
+                header_background: None,
+                zebra: None,
+                border: TableBorder::None,
+                border_style: pdf_core::ShapeStyle::default(),
+            });
+        };
+
+        // Center a single line vertically in its row box
+        let (ascent, descent) = doc.get_font_extents()?;
+        let leading = ((block.row_height - ascent - descent) / 2.0).max(0.0);
+        let border_color = style.border_color.unwrap_or_else(Color::black);
+
+        Ok(Self {
+            columns,
+            left,
+            right,
+            row_height: block.row_height,
+            padding: style.padding,
+            top_offset: ascent + leading + style.padding,
+            background: style.background.map(convert_color),
+            header_background: style.header_background.map(convert_color),
+            zebra: style.zebra.map(convert_color),
+            border: style.border,
+            border_style: pdf_core::ShapeStyle::stroke(
+                convert_color(border_color),
+                style.border_width,
+            ),
+        })
+    }
+
+    /// Height of a row box with the given cell lines
+    fn row_height(&self, cells: &[Vec<String>]) -> f64 {
+        let lines = cells.iter().map(Vec::len).max().unwrap_or(1).max(1);
+        self.row_height * lines as f64 + 2.0 * self.padding
+    }
+
+    /// Fill of data row `index` (0-based), alternating with the zebra color
+    fn body_fill(&self, index: usize) -> Option<pdf_core::Color> {
+        if index % 2 == 1 {
+            self.zebra.or(self.background)
+        } else {
+            self.background
+        }
+    }
+
+    /// Draw the borders around the rows rendered on the cursor's page
+    fn draw_borders(&self, doc: &mut PdfDocument, cursor: &TableCursor) -> Result<()> {
+        let (Some(first), Some(last)) = (cursor.rows.first(), cursor.rows.last()) else {
+            return Ok(());
+        };
+        let top = first.0;
+        let bottom = last.0 + last.1;
+        let page = cursor.page;
+        let style = &self.border_style;
+
+        match self.border {
+            TableBorder::None => {}
+            TableBorder::Outer => {
+                let rect =
+                    pdf_core::Rect::new(self.left, top, self.right - self.left, bottom - top);
+                doc.draw_rect(page, rect, style)?;
+            }
+            TableBorder::Horizontal | TableBorder::All => {
+                for y in cursor.rows.iter().map(|r| r.0).chain([bottom]) {
+                    doc.draw_line(page, self.left, y, self.right, y, style)?;
+                }
+
+                if self.border == TableBorder::All {
+                    let mut edges: Vec<f64> = self.columns.iter().map(|c| c.0).collect();
+                    edges.extend([self.left, self.right]);
+                    edges.sort_by(f64::total_cmp);
+                    edges.dedup();
+                    for x in edges {
+                        doc.draw_line(page, x, top, x, bottom, style)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Rows of a table rendered on one page
+#[derive(Debug)]
+struct TableCursor {
+    page: usize,
+    /// Baseline of the next row's first line
+    y: f64,
+    /// Top edge and height of each row box on this page
+    rows: Vec<(f64, f64)>,
+}
+
+impl TableCursor {
+    fn new(page: usize, y: f64) -> Self {
+        Self {
+            page,
+            y,
+            rows: Vec::new(),
+        }
+    }
+}
+
+/// Wrapped lines of a text box at one font size
+#[derive(Debug)]
+struct TextBoxLayout {
+    lines: Vec<String>,
+    line_height: f64,
+    ascent: f64,
+    descent: f64,
+    /// Box height in points
+    height: f64,
+    /// Whether every line fits the box
+    fits: bool,
+}
+
+impl TextBoxLayout {
+    /// Height from the top of the first line to the bottom of the last
+    fn content_height(&self, lines: usize) -> f64 {
+        if lines == 0 {
+            return 0.0;
+        }
+        (lines - 1) as f64 * self.line_height + self.ascent + self.descent
+    }
+
+    /// Number of lines that fit in the box height
+    fn max_lines(&self) -> usize {
+        let first_line = self.ascent + self.descent;
+        if first_line > self.height {
+            return 0;
+        }
+        if self.line_height <= 0.0 {
+            return usize::MAX;
+        }
+        1 + ((self.height - first_line) / self.line_height).floor() as usize
+    }
+}
+
+/// Font bytes for each variant of a font family
+#[derive(Debug, Clone, Default)]
+struct FontVariants {
+    regular: Option<Vec<u8>>,
+    bold: Option<Vec<u8>>,
+    italic: Option<Vec<u8>>,
+    bold_italic: Option<Vec<u8>>,
+}
+
+impl FontVariants {
+    /// Store bytes for the given style
+    fn set(&mut self, style: FontStyle, data: Vec<u8>) {
+        match style {
+            FontStyle::Regular => self.regular = Some(data),
+            FontStyle::Bold => self.bold = Some(data),
+            FontStyle::Italic => self.italic = Some(data),
+            FontStyle::BoldItalic => self.bold_italic = Some(data),
+        }
+    }
+
+    /// Check whether no variant has been loaded
+    #[cfg(not(target_arch = "wasm32"))]
+    fn is_empty(&self) -> bool {
+        self.regular.is_none()
+            && self.bold.is_none()
+            && self.italic.is_none()
+            && self.bold_italic.is_none()
+    }
+
+    /// Create a FontFamilyBuilder from the loaded variants
+    fn to_builder(&self, name: &str) -> Result<FontFamilyBuilder> {
+        let regular = self.regular.clone().ok_or_else(|| {
+            TemplateError::FontError(format!("Font {name} has no regular variant"))
+        })?;
+
+        let mut builder = FontFamilyBuilder::new().regular(regular);
+        if let Some(data) = &self.bold {
+            builder = builder.bold(data.clone());
+        }
+        if let Some(data) = &self.italic {
+            builder = builder.italic(data.clone());
+        }
+        if let Some(data) = &self.bold_italic {
+            builder = builder.bold_italic(data.clone());
+        }
+        Ok(builder)
+    }
+}
+
 /// Convert schema Align to pdf_core Align
 fn convert_align(align: Align) -> pdf_core::Align {
     match align {
@@ -693,6 +2140,95 @@ fn convert_align(align: Align) -> pdf_core::Align {
     }
 }
 
+/// Convert schema VerticalAlign to pdf_core position flags
+fn convert_vertical_align(align: VerticalAlign) -> i32 {
+    match align {
+        VerticalAlign::Top => pdf_core::position::TOP,
+        VerticalAlign::Middle => pdf_core::position::MIDDLE,
+        VerticalAlign::Bottom => pdf_core::position::BOTTOM,
+    }
+}
+
+/// Convert schema ScaleMode to pdf_core ImageScaleMode
+fn convert_scale_mode(mode: ScaleMode) -> pdf_core::ImageScaleMode {
+    match mode {
+        ScaleMode::Stretch => pdf_core::ImageScaleMode::Stretch,
+        ScaleMode::FitWidth => pdf_core::ImageScaleMode::FitWidth,
+        ScaleMode::FitHeight => pdf_core::ImageScaleMode::FitHeight,
+        ScaleMode::FitBox => pdf_core::ImageScaleMode::FitBox,
+    }
+}
+
+/// Convert a schema PageConfig (the `number`th declared page) to a pdf_core PageSize
+fn page_size(page: &PageConfig, number: usize) -> Result<pdf_core::PageSize> {
+    let size = match (page.width, page.height) {
+        (Some(width), Some(height)) => pdf_core::PageSize::custom(width, height),
+        (Some(_), None) | (None, Some(_)) => {
+            return Err(TemplateError::RenderError(format!(
+                "Declared page {number} needs both width and height for a custom size"
+            )))
+        }
+        (None, None) => match page.size {
+            PaperSize::A3 => pdf_core::PageSize::A3,
+            PaperSize::A4 => pdf_core::PageSize::A4,
+            PaperSize::A5 => pdf_core::PageSize::A5,
+            PaperSize::B5 => pdf_core::PageSize::B5,
+            PaperSize::Letter => pdf_core::PageSize::LETTER,
+            PaperSize::Legal => pdf_core::PageSize::LEGAL,
+        },
+    };
+    let orientation = match page.orientation {
+        Orientation::Portrait => pdf_core::Orientation::Portrait,
+        Orientation::Landscape => pdf_core::Orientation::Landscape,
+    };
+    let margins = page.margins.unwrap_or_default();
+    Ok(size
+        .with_orientation(orientation)
+        .with_margins(pdf_core::Margins::new(
+            margins.top,
+            margins.right,
+            margins.bottom,
+            margins.left,
+        )))
+}
+
+/// Convert schema BarcodeSymbology to pdf_core BarcodeSymbology
+fn convert_symbology(symbology: BarcodeSymbology) -> pdf_core::BarcodeSymbology {
+    match symbology {
+        BarcodeSymbology::Code128 => pdf_core::BarcodeSymbology::Code128,
+        BarcodeSymbology::Code39 => pdf_core::BarcodeSymbology::Code39,
+        BarcodeSymbology::Ean13 => pdf_core::BarcodeSymbology::Ean13,
+        BarcodeSymbology::Itf => pdf_core::BarcodeSymbology::Itf,
+    }
+}
+
+/// Decode image bytes from a base64 string or a `data:` URI
+fn decode_image_source(src: &str) -> Result<Vec<u8>> {
+    use base64::Engine;
+
+    let src = src.trim();
+    let payload = match src.strip_prefix("data:") {
+        Some(uri) => {
+            let (header, payload) = uri
+                .split_once(',')
+                .ok_or_else(|| TemplateError::ImageError("Invalid data URI".to_string()))?;
+            if !header.ends_with(";base64") {
+                return Err(TemplateError::ImageError(format!(
+                    "Unsupported data URI encoding: {header}"
+                )));
+            }
+            payload
+        }
+        None => src,
+    };
+
+    // Tolerate line breaks and spaces in long base64 strings
+    let payload: String = payload.chars().filter(|c| !c.is_whitespace()).collect();
+    base64::engine::general_purpose::STANDARD
+        .decode(payload)
+        .map_err(|e| TemplateError::ImageError(format!("Invalid base64 image data: {e}")))
+}
+
 /// Check if a JSON value is truthy
 fn is_truthy(value: &serde_json::Value) -> bool {
     match value {
@@ -705,9 +2241,165 @@ fn is_truthy(value: &serde_json::Value) -> bool {
     }
 }
 
-/// Generate QR code image as JPEG bytes
-fn generate_qr_image(data: &str, ec: ErrorCorrection) -> Result<Vec<u8>> {
-    use image::Luma;
+/// Build a PromptPay payload from bound data
+///
+/// Accepts a PromptPay ID string, `{ promptpayId, amount }` or
+/// `{ billerId, ref1, ref2, amount }`. Null or an empty ID renders nothing.
+fn build_promptpay_payload(value: &serde_json::Value) -> Result<String> {
+    let field = |name: &str| {
+        value
+            .get(name)
+            .map(value_to_string)
+            .filter(|s| !s.is_empty())
+    };
+
+    let payment = match value {
+        serde_json::Value::Null => return Ok(String::new()),
+        serde_json::Value::Object(_) => {
+            let payment = if let Some(biller_id) = field("billerId") {
+                let ref1 = field("ref1").unwrap_or_default();
+                thai_text::PromptPay::biller(&biller_id, &ref1, field("ref2").as_deref())
+            } else if let Some(id) = field("promptpayId") {
+                thai_text::PromptPay::from_id(&id)
+            } else {
+                return Ok(String::new());
+            };
+            let payment = payment.map_err(|e| TemplateError::RenderError(e.to_string()))?;
+
+            match field("amount") {
+                Some(amount) => {
+                    let amount: f64 = amount.parse().map_err(|_| {
+                        TemplateError::RenderError(format!("Invalid PromptPay amount: {amount}"))
+                    })?;
+                    payment.with_amount(amount)
+                }
+                None => payment,
+            }
+        }
+        _ => {
+            let id = value_to_string(value);
+            if id.is_empty() {
+                return Ok(String::new());
+            }
+            thai_text::PromptPay::from_id(&id)
+                .map_err(|e| TemplateError::RenderError(e.to_string()))?
+        }
+    };
+
+    payment
+        .payload()
+        .map_err(|e| TemplateError::RenderError(e.to_string()))
+}
+
+/// Sum the totals fields over rows
+///
+/// Numbers and numeric strings (with optional thousands separators) count;
+/// anything else is ignored.
+fn table_sums<'a>(totals: &'a TableTotals, rows: &[serde_json::Value]) -> HashMap<&'a str, f64> {
+    totals
+        .fields
+        .iter()
+        .map(|field| {
+            let sum = rows
+                .iter()
+                .filter_map(|row| match row.get(field)? {
+                    serde_json::Value::Number(n) => n.as_f64(),
+                    serde_json::Value::String(s) => s.replace(',', "").trim().parse().ok(),
+                    _ => None,
+                })
+                .sum();
+            (field.as_str(), sum)
+        })
+        .collect()
+}
+
+/// Convert schema Color to pdf_core Color
+fn convert_color(color: Color) -> pdf_core::Color {
+    pdf_core::Color::rgb(color.r as f32, color.g as f32, color.b as f32)
+}
+
+/// Parse a `#RRGGBB` hex color
+fn parse_hex_color(hex: &str) -> Result<Color> {
+    let digits = hex.trim().trim_start_matches('#');
+    let channel = |i: usize| {
+        digits
+            .get(i..i + 2)
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+    };
+
+    match (digits.len(), channel(0), channel(2), channel(4)) {
+        (6, Some(r), Some(g), Some(b)) => Ok(Color::from_rgb(r, g, b)),
+        _ => Err(TemplateError::RenderError(format!(
+            "Invalid color: {hex} (expected #RRGGBB)"
+        ))),
+    }
+}
+
+/// Resolve a color value against data
+///
+/// Returns None when a binding resolves to null or is missing, or when a
+/// mapped value has no entry and no default.
+fn resolve_color(color: &ColorValue, data: &serde_json::Value) -> Result<Option<Color>> {
+    match color {
+        ColorValue::Rgb(color) => Ok(Some(*color)),
+        ColorValue::Text(text) if text.starts_with("$.") => match resolve_binding(text, data) {
+            None | Some(serde_json::Value::Null) => Ok(None),
+            Some(serde_json::Value::String(hex)) if hex.is_empty() => Ok(None),
+            Some(serde_json::Value::String(hex)) => parse_hex_color(hex).map(Some),
+            Some(value) => serde_json::from_value::<Color>(value.clone())
+                .map(Some)
+                .map_err(|_| TemplateError::RenderError(format!("Invalid color bound at {text}"))),
+        },
+        ColorValue::Text(hex) => parse_hex_color(hex).map(Some),
+        ColorValue::Mapped { bind, map, default } => {
+            let key = resolve_binding(bind, data)
+                .map(value_to_string)
+                .unwrap_or_default();
+            match map.get(&key).or(default.as_deref()) {
+                Some(color) => resolve_color(color, data),
+                None => Ok(None),
+            }
+        }
+    }
+}
+
+/// Resolve a shape block's style, or None when there is nothing to draw
+fn resolve_shape_style(
+    style: &ShapeStyle,
+    data: &serde_json::Value,
+) -> Result<Option<pdf_core::ShapeStyle>> {
+    let (stroke, fill) = if style.stroke.is_none() && style.fill.is_none() {
+        (Some(Color::black()), None)
+    } else {
+        let resolve = |c: &Option<ColorValue>| match c {
+            Some(color) => resolve_color(color, data),
+            None => Ok(None),
+        };
+        (resolve(&style.stroke)?, resolve(&style.fill)?)
+    };
+
+    if stroke.is_none() && fill.is_none() {
+        return Ok(None);
+    }
+
+    let line_cap = match style.line_cap {
+        LineCap::Butt => pdf_core::LineCap::Butt,
+        LineCap::Round => pdf_core::LineCap::Round,
+        LineCap::Square => pdf_core::LineCap::Square,
+    };
+
+    Ok(Some(pdf_core::ShapeStyle {
+        stroke: stroke.map(convert_color),
+        fill: fill.map(convert_color),
+        line_width: style.stroke_width,
+        dash: style.dash.clone().unwrap_or_default(),
+        line_cap,
+        ..Default::default()
+    }))
+}
+
+/// Encode QR code data to a module matrix (without quiet zone)
+fn generate_qr_matrix(data: &str, ec: ErrorCorrection) -> Result<pdf_core::ModuleMatrix> {
     use qrcode::EcLevel;
     use qrcode::QrCode;
 
@@ -721,15 +2413,49 @@ fn generate_qr_image(data: &str, ec: ErrorCorrection) -> Result<Vec<u8>> {
     let code = QrCode::with_error_correction_level(data.as_bytes(), ec_level)
         .map_err(|e| TemplateError::ImageError(e.to_string()))?;
 
-    // Render QR code at larger size (200x200 pixels minimum)
-    let image = code.render::<Luma<u8>>().min_dimensions(200, 200).build();
+    let modules = code
+        .to_colors()
+        .into_iter()
+        .map(|c| c == qrcode::Color::Dark)
+        .collect();
+    pdf_core::ModuleMatrix::new(code.width(), modules).map_err(TemplateError::from)
+}
+
+/// Rasterize a QR module matrix as PNG bytes (lossless, so edges stay sharp)
+fn generate_qr_image(
+    matrix: &pdf_core::ModuleMatrix,
+    foreground: Color,
+    background: Color,
+) -> Result<Vec<u8>> {
+    // Integer pixels per module, at least 200x200 pixels overall
+    let scale = 200u32.div_ceil(matrix.columns as u32).max(1);
+    let to_rgb = |c: Color| {
+        image::Rgb([
+            (c.r.clamp(0.0, 1.0) * 255.0).round() as u8,
+            (c.g.clamp(0.0, 1.0) * 255.0).round() as u8,
+            (c.b.clamp(0.0, 1.0) * 255.0).round() as u8,
+        ])
+    };
+    let (dark, light) = (to_rgb(foreground), to_rgb(background));
+
+    let image = image::RgbImage::from_fn(
+        matrix.columns as u32 * scale,
+        matrix.rows() as u32 * scale,
+        |x, y| {
+            let index = (y / scale) as usize * matrix.columns + (x / scale) as usize;
+            if matrix.modules[index] {
+                dark
+            } else {
+                light
+            }
+        },
+    );
 
-    // Convert to JPEG
     let mut bytes: Vec<u8> = Vec::new();
     let mut cursor = std::io::Cursor::new(&mut bytes);
 
-    image::DynamicImage::ImageLuma8(image)
-        .write_to(&mut cursor, image::ImageFormat::Jpeg)
+    image::DynamicImage::ImageRgb8(image)
+        .write_to(&mut cursor, image::ImageFormat::Png)
         .map_err(|e| TemplateError::ImageError(e.to_string()))?;
 
     Ok(bytes)
@@ -764,6 +2490,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
 
     #[test]
     fn test_parse_and_format_date() {
@@ -791,4 +2518,111 @@ mod tests {
         assert!(!is_truthy(&serde_json::json!({})));
         assert!(is_truthy(&serde_json::json!({"key": "value"})));
     }
+
+    #[test]
+    fn test_table_sums() {
+        let totals = TableTotals {
+            fields: vec!["amount".to_string(), "qty".to_string()],
+            label_field: "name".to_string(),
+            page_subtotal: None,
+            carried_forward: None,
+            brought_forward: None,
+        };
+        let rows = vec![
+            json!({ "amount": 0.1, "qty": 1 }),
+            json!({ "amount": "1,000.20", "qty": "x" }),
+            json!({ "name": "no amount" }),
+        ];
+
+        let sums = table_sums(&totals, &rows);
+        assert!((sums["amount"] - 1000.3).abs() < 1e-9);
+        assert_eq!(sums["qty"], 1.0);
+        assert_eq!(table_sums(&totals, &[])["amount"], 0.0);
+    }
+
+    #[test]
+    fn test_build_promptpay_payload() {
+        let payload =
+            build_promptpay_payload(&json!({ "promptpayId": "0812345678", "amount": 250 }))
+                .unwrap();
+        let parsed = thai_text::PromptPay::parse(&payload).unwrap();
+        assert_eq!(parsed.amount, Some(250.0));
+
+        let payload = build_promptpay_payload(&json!({
+            "billerId": "010556012345601",
+            "ref1": "INV001",
+            "amount": "99.50"
+        }))
+        .unwrap();
+        assert!(payload.contains("0206INV001"));
+        assert!(payload.contains("540599.50"));
+
+        // Plain ID string, and nothing to render
+        assert!(build_promptpay_payload(&json!("1234567890123"))
+            .unwrap()
+            .contains("02131234567890123"));
+        assert_eq!(build_promptpay_payload(&json!(null)).unwrap(), "");
+        assert_eq!(build_promptpay_payload(&json!({})).unwrap(), "");
+
+        assert!(build_promptpay_payload(&json!({ "promptpayId": "123" })).is_err());
+        assert!(
+            build_promptpay_payload(&json!({ "promptpayId": "0812345678", "amount": "x" }))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_resolve_color() {
+        let data =
+            json!({ "status": "overdue", "hex": "#FF0000", "rgb": { "r": 0, "g": 0, "b": 1 } });
+        let mapped: ColorValue = serde_json::from_value(json!({
+            "bind": "$.status",
+            "map": { "paid": "#00FF00", "overdue": "$.hex" }
+        }))
+        .unwrap();
+
+        assert_eq!(resolve_color(&mapped, &data).unwrap(), Some(Color::red()));
+        assert_eq!(
+            resolve_color(&mapped, &json!({ "status": "draft" })).unwrap(),
+            None
+        );
+        assert_eq!(
+            resolve_color(&ColorValue::Text("$.rgb".to_string()), &data).unwrap(),
+            Some(Color::blue())
+        );
+        assert_eq!(
+            resolve_color(&ColorValue::Text("$.missing".to_string()), &data).unwrap(),
+            None
+        );
+        assert!(resolve_color(&ColorValue::Text("red".to_string()), &data).is_err());
+    }
+
+    #[test]
+    fn test_resolve_shape_style() {
+        let data = json!({});
+        let style = resolve_shape_style(&ShapeStyle::default(), &data)
+            .unwrap()
+            .unwrap();
+        assert_eq!(style.stroke, Some(pdf_core::Color::black()));
+        assert_eq!(style.fill, None);
+
+        let unset = ShapeStyle {
+            fill: Some(ColorValue::Text("$.color".to_string())),
+            ..Default::default()
+        };
+        assert!(resolve_shape_style(&unset, &data).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_decode_image_source() {
+        // "PNG" in base64
+        assert_eq!(decode_image_source("UE5H").unwrap(), b"PNG");
+        assert_eq!(
+            decode_image_source("data:image/png;base64,UE5H").unwrap(),
+            b"PNG"
+        );
+        assert_eq!(decode_image_source(" UE\n5H ").unwrap(), b"PNG");
+        assert!(decode_image_source("data:image/png,PNG").is_err());
+        assert!(decode_image_source("not base64!").is_err());
+    }
 }
@@ -1,6 +1,7 @@
 //! Template JSON schema types
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// RGB Color for text
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -46,6 +47,15 @@ impl Color {
         }
     }
 
+    /// White color
+    pub fn white() -> Self {
+        Self {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+        }
+    }
+
     /// Blue color
     pub fn blue() -> Self {
         Self {
@@ -91,6 +101,10 @@ pub struct Template {
     /// Content blocks
     pub blocks: Vec<Block>,
 
+    /// Watermark stamped after the blocks are rendered
+    #[serde(default)]
+    pub watermark: Option<WatermarkConfig>,
+
     // === Internal state for fluent API (not serialized) ===
     #[serde(skip)]
     current_font_family: Option<String>,
@@ -112,6 +126,7 @@ impl Default for Template {
             template: TemplateSource::default(),
             fonts: Vec::new(),
             blocks: Vec::new(),
+            watermark: None,
             current_font_family: None,
             current_font_size: 12,
             current_font_style: FontStyle::Regular,
@@ -165,8 +180,10 @@ impl Template {
             }),
             align,
             word_wrap: None,
+            text_box: None,
             format: None,
             format_type: None,
+            rotation: None,
             pages: Some(vec![page]),
             enable: None,
         });
@@ -196,8 +213,10 @@ impl Template {
             }),
             align,
             word_wrap: None,
+            text_box: None,
             format: None,
             format_type: None,
+            rotation: None,
             pages: Some(vec![page]),
             enable: None,
         });
@@ -210,14 +229,80 @@ impl Template {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TemplateSource {
     /// Path to base PDF or base64-encoded data
+    ///
+    /// May be omitted when `pages` are declared.
     #[serde(default)]
     pub source: String,
 
+    /// Pages created from scratch, added after the base PDF's pages
+    #[serde(default)]
+    pub pages: Vec<PageConfig>,
+
     /// Duplicate blocks with offset (for duplicate receipts)
     #[serde(default)]
     pub duplicate: Option<Duplicate>,
 }
 
+/// A page created from scratch
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PageConfig {
+    /// Paper size (ignored when width and height are set)
+    #[serde(default)]
+    pub size: PaperSize,
+
+    /// Custom width in points (requires `height`)
+    #[serde(default)]
+    pub width: Option<f64>,
+
+    /// Custom height in points (requires `width`)
+    #[serde(default)]
+    pub height: Option<f64>,
+
+    /// Portrait or landscape
+    #[serde(default)]
+    pub orientation: Orientation,
+
+    /// Margins; drawn content is positioned from their top-left corner
+    /// (field blocks and the watermark use the whole page)
+    #[serde(default)]
+    pub margins: Option<PageMargins>,
+}
+
+/// Standard paper sizes
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PaperSize {
+    A3,
+    #[default]
+    A4,
+    A5,
+    B5,
+    Letter,
+    Legal,
+}
+
+/// Page orientation
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    #[default]
+    Portrait,
+    Landscape,
+}
+
+/// Page margins in points
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct PageMargins {
+    #[serde(default)]
+    pub top: f64,
+    #[serde(default)]
+    pub right: f64,
+    #[serde(default)]
+    pub bottom: f64,
+    #[serde(default)]
+    pub left: f64,
+}
+
 /// Duplicate configuration
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Duplicate {
@@ -268,6 +353,89 @@ pub struct AdditionalItem {
     pub page: Option<usize>,
 }
 
+/// Watermark configuration (text or image stamped across pages)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatermarkConfig {
+    /// Static watermark text
+    #[serde(default)]
+    pub text: Option<String>,
+
+    /// Data binding path for the text
+    #[serde(default)]
+    pub bind: Option<String>,
+
+    /// Image data (base64 string or data URI), used instead of text
+    #[serde(default)]
+    pub src: Option<String>,
+
+    /// Box the image is fitted into (default 200 x 200)
+    #[serde(default)]
+    pub size: Option<Size>,
+
+    /// Font specification for text
+    #[serde(default)]
+    pub font: Option<Font>,
+
+    /// Text color (default gray, overrides the font color)
+    #[serde(default)]
+    pub color: Option<ColorValue>,
+
+    /// Rotation in degrees (counter-clockwise)
+    #[serde(default = "default_watermark_angle")]
+    pub angle: f64,
+
+    /// Opacity (0.0 - 1.0)
+    #[serde(default = "default_watermark_opacity")]
+    pub opacity: f64,
+
+    /// Over or under the page content
+    #[serde(default)]
+    pub layer: Layer,
+
+    /// Repeat across the page with these gaps (omit for one centered watermark)
+    #[serde(default)]
+    pub tile: Option<WatermarkTile>,
+
+    /// Pages to stamp (default all)
+    #[serde(default)]
+    pub pages: Option<Vec<usize>>,
+
+    /// Optional enable flag - if set, evaluates binding to determine if the watermark is rendered
+    #[serde(default)]
+    pub enable: Option<String>,
+}
+
+fn default_watermark_angle() -> f64 {
+    45.0
+}
+
+fn default_watermark_opacity() -> f64 {
+    0.3
+}
+
+/// Placement relative to the page content (watermarks and PDF pages)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Layer {
+    /// Over everything on the page
+    #[default]
+    Overlay,
+    /// Under the base PDF content
+    Underlay,
+}
+
+/// Gaps between tiled watermarks in points
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct WatermarkTile {
+    /// Horizontal gap
+    #[serde(default)]
+    pub x: f64,
+
+    /// Vertical gap
+    #[serde(default)]
+    pub y: f64,
+}
+
 /// Font family definition (new format with variants)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FontDef {
@@ -305,6 +473,8 @@ pub struct FontDef {
 }
 
 /// Content block (tagged union)
+// Blocks are parsed once per template, so variant size doesn't matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum Block {
@@ -321,6 +491,35 @@ pub enum Block {
     /// QR code block
     #[serde(rename = "qrcode")]
     QRCode(QRCodeBlock),
+
+    /// Image block
+    Image(ImageBlock),
+
+    /// 1D barcode block
+    Barcode(BarcodeBlock),
+
+    /// Straight line
+    Line(LineBlock),
+
+    /// Rectangle (optionally with rounded corners)
+    Rect(RectBlock),
+
+    /// Ellipse or circle
+    Ellipse(EllipseBlock),
+
+    /// AcroForm field of the base PDF, filled by name
+    #[serde(rename = "formfield")]
+    FormField(FormFieldBlock),
+
+    /// New interactive form field (text, checkbox, radio, signature)
+    Field(FieldBlock),
+
+    /// Vector check mark at the option matching a bound value
+    Checkbox(CheckboxBlock),
+
+    /// Page of another PDF (letterhead, approval page)
+    #[serde(rename = "pdfpage")]
+    PdfPage(PdfPageBlock),
 }
 
 /// Position in PDF coordinates
@@ -357,14 +556,14 @@ fn default_font_size() -> u8 {
 }
 
 /// Font style
-#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum FontStyle {
     #[default]
     Regular,
     Bold,
     Italic,
-    #[serde(rename = "bold-italic")]
+    #[serde(rename = "bold-italic", alias = "bolditalic")]
     BoldItalic,
 }
 
@@ -378,13 +577,76 @@ pub enum Align {
     Right,
 }
 
+/// Vertical alignment inside a text box
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum VerticalAlign {
+    #[default]
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// What to do when text does not fit its box
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Overflow {
+    /// Cut off text outside the box
+    #[default]
+    Clip,
+    /// Drop lines that don't fit and end the last line with "..."
+    Ellipsis,
+    /// Reduce the font size until the text fits (down to `minFontSize`)
+    Shrink,
+    /// Fail rendering
+    Error,
+}
+
+/// Bounded text box configuration
+///
+/// The block position is the top-left corner of the box. Text is wrapped
+/// to the box width and aligned horizontally with the block `align`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextBox {
+    /// Box width in points
+    pub width: f64,
+
+    /// Box height in points
+    pub height: f64,
+
+    /// Vertical alignment
+    #[serde(rename = "verticalAlign")]
+    #[serde(default)]
+    pub vertical_align: VerticalAlign,
+
+    /// Overflow policy
+    #[serde(default)]
+    pub overflow: Overflow,
+
+    /// Smallest font size for `shrink` overflow
+    #[serde(rename = "minFontSize")]
+    #[serde(default = "default_min_font_size")]
+    pub min_font_size: f64,
+}
+
+fn default_min_font_size() -> f64 {
+    6.0
+}
+
 /// Word wrap configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WordWrap {
-    /// Maximum characters per line
+    /// Maximum characters per line (0 = no character limit)
     #[serde(rename = "maxChars")]
+    #[serde(default)]
     pub max_chars: usize,
 
+    /// Maximum line width in points, measured with the block font
+    /// (takes precedence over `maxChars`)
+    #[serde(rename = "maxWidth")]
+    #[serde(default)]
+    pub max_width: Option<f64>,
+
     /// Line height in points
     #[serde(rename = "lineHeight")]
     pub line_height: f64,
@@ -432,6 +694,11 @@ pub struct TextBlock {
     #[serde(default)]
     pub word_wrap: Option<WordWrap>,
 
+    /// Bounded text box (wraps to its width, handles overflow)
+    #[serde(rename = "box")]
+    #[serde(default)]
+    pub text_box: Option<TextBox>,
+
     /// Number format pattern
     #[serde(default)]
     pub format: Option<String>,
@@ -441,6 +708,10 @@ pub struct TextBlock {
     #[serde(default)]
     pub format_type: Option<FormatType>,
 
+    /// Rotation in degrees around the block position (counter-clockwise)
+    #[serde(default)]
+    pub rotation: Option<f64>,
+
     /// Pages to render on (1-indexed)
     #[serde(default)]
     pub pages: Option<Vec<usize>>,
@@ -477,6 +748,10 @@ pub struct FieldFormBlock {
     #[serde(rename = "charSpacing")]
     pub char_spacing: Vec<f64>,
 
+    /// Rotation in degrees around the block position (counter-clockwise)
+    #[serde(default)]
+    pub rotation: Option<f64>,
+
     /// Pages to render on
     #[serde(default)]
     pub pages: Option<Vec<usize>>,
@@ -509,6 +784,16 @@ pub struct TableBlock {
     #[serde(default)]
     pub font: Option<Font>,
 
+    /// Header row font (the table `font`, or the font in effect before the
+    /// table, is restored after the header)
+    #[serde(rename = "headerFont")]
+    #[serde(default)]
+    pub header_font: Option<Font>,
+
+    /// Borders, padding and fills
+    #[serde(default)]
+    pub style: Option<TableStyle>,
+
     /// Row height in points
     #[serde(rename = "rowHeight")]
     #[serde(default = "default_row_height")]
@@ -517,7 +802,36 @@ pub struct TableBlock {
     /// Column definitions
     pub columns: Vec<TableColumn>,
 
-    /// Pages to render on
+    /// Maximum height of the table on a page in points, measured from
+    /// where the table starts on that page
+    #[serde(rename = "maxHeight")]
+    #[serde(default)]
+    pub max_height: Option<f64>,
+
+    /// Lowest Y coordinate rows may reach on a page (from top)
+    #[serde(default)]
+    pub bottom: Option<f64>,
+
+    /// Y coordinate where the table starts on continuation pages
+    /// (defaults to `position.y`)
+    #[serde(rename = "continuationY")]
+    #[serde(default)]
+    pub continuation_y: Option<f64>,
+
+    /// Repeat the header row on continuation pages
+    #[serde(rename = "repeatHeader")]
+    #[serde(default = "default_true")]
+    pub repeat_header: bool,
+
+    /// Subtotal and carried-forward rows
+    #[serde(default)]
+    pub totals: Option<TableTotals>,
+
+    /// Rotation in degrees around the block position (counter-clockwise)
+    #[serde(default)]
+    pub rotation: Option<f64>,
+
+    /// Pages to render on (a paginated table starts on the first listed page)
     #[serde(default)]
     pub pages: Option<Vec<usize>>,
 
@@ -527,14 +841,115 @@ pub struct TableBlock {
     pub enable: Option<String>,
 }
 
+impl TableBlock {
+    /// Whether rows flow onto continuation pages
+    pub fn is_paginated(&self) -> bool {
+        self.max_height.is_some() || self.bottom.is_some()
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Table borders, padding and fills
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableStyle {
+    /// Which borders to draw
+    #[serde(default)]
+    pub border: TableBorder,
+
+    /// Border color (default black)
+    #[serde(rename = "borderColor")]
+    #[serde(default)]
+    pub border_color: Option<Color>,
+
+    /// Border line width in points
+    #[serde(rename = "borderWidth")]
+    #[serde(default = "default_border_width")]
+    pub border_width: f64,
+
+    /// Cell padding in points (all sides)
+    #[serde(default)]
+    pub padding: f64,
+
+    /// Fill of every row
+    #[serde(default)]
+    pub background: Option<Color>,
+
+    /// Fill of the header row (defaults to `background`)
+    #[serde(rename = "headerBackground")]
+    #[serde(default)]
+    pub header_background: Option<Color>,
+
+    /// Fill of every second data row
+    #[serde(default)]
+    pub zebra: Option<Color>,
+}
+
+fn default_border_width() -> f64 {
+    0.5
+}
+
+/// Table border layout
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TableBorder {
+    /// No borders
+    #[default]
+    None,
+    /// Grid around every cell
+    All,
+    /// Box around the table
+    Outer,
+    /// Lines between rows
+    Horizontal,
+}
+
+/// Summary rows for numeric table columns
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableTotals {
+    /// Fields (column names) to sum
+    pub fields: Vec<String>,
+
+    /// Field (column) that shows the row label
+    #[serde(rename = "labelField")]
+    pub label_field: String,
+
+    /// Label of the per-page subtotal row, rendered after the rows of each page
+    #[serde(rename = "pageSubtotal")]
+    #[serde(default)]
+    pub page_subtotal: Option<String>,
+
+    /// Label of the running total row at the bottom of a page that continues
+    #[serde(rename = "carriedForward")]
+    #[serde(default)]
+    pub carried_forward: Option<String>,
+
+    /// Label of the running total row at the top of a continuation page
+    #[serde(rename = "broughtForward")]
+    #[serde(default)]
+    pub brought_forward: Option<String>,
+}
+
 /// Table column definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TableColumn {
     /// Field name in row object
     pub field: String,
 
-    /// X offset from table position
-    pub x: f64,
+    /// Header text
+    #[serde(default)]
+    pub header: Option<String>,
+
+    /// X offset from table position (defaults to the end of the previous column)
+    #[serde(default)]
+    pub x: Option<f64>,
+
+    /// Column width in points; aligns text within the cell and wraps it
+    /// unless `wordWrap` or `maxWidth` is set
+    #[serde(default)]
+    pub width: Option<f64>,
 
     /// Text alignment
     #[serde(default)]
@@ -545,6 +960,11 @@ pub struct TableColumn {
     #[serde(default)]
     pub word_wrap: Option<usize>,
 
+    /// Word wrap max width in points (takes precedence over `wordWrap`)
+    #[serde(rename = "maxWidth")]
+    #[serde(default)]
+    pub max_width: Option<f64>,
+
     /// Number format pattern
     #[serde(default)]
     pub format: Option<String>,
@@ -576,6 +996,32 @@ pub struct QRCodeBlock {
     #[serde(default)]
     pub error_correction: ErrorCorrection,
 
+    /// Light border around the code in modules
+    #[serde(rename = "quietZone")]
+    #[serde(default = "default_quiet_zone")]
+    pub quiet_zone: u32,
+
+    /// Color of dark modules (default black)
+    #[serde(default)]
+    pub foreground: Option<Color>,
+
+    /// Color of light modules and the quiet zone (default white)
+    #[serde(default)]
+    pub background: Option<Color>,
+
+    /// Vector paths or an embedded image
+    #[serde(rename = "renderMode")]
+    #[serde(default)]
+    pub render_mode: QRRenderMode,
+
+    /// How the bound value is turned into QR data
+    #[serde(default)]
+    pub payload: QRPayload,
+
+    /// Rotation in degrees around the block position (counter-clockwise)
+    #[serde(default)]
+    pub rotation: Option<f64>,
+
     /// Pages to render on
     #[serde(default)]
     pub pages: Option<Vec<usize>>,
@@ -586,6 +1032,33 @@ pub struct QRCodeBlock {
     pub enable: Option<String>,
 }
 
+fn default_quiet_zone() -> u32 {
+    4
+}
+
+/// How a QR code is drawn
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QRRenderMode {
+    /// Filled rectangles in the page content stream
+    #[default]
+    Vector,
+    /// Lossless PNG image
+    Raster,
+}
+
+/// QR payload mode
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QRPayload {
+    /// Encode the bound text as is
+    #[default]
+    Text,
+    /// Build a PromptPay payload from `{ promptpayId, amount }` or
+    /// `{ billerId, ref1, ref2, amount }` (a bound string is a PromptPay ID)
+    PromptPay,
+}
+
 /// Size specification
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Size {
@@ -593,54 +1066,678 @@ pub struct Size {
     pub height: f64,
 }
 
-/// QR code error correction level
-#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
-pub enum ErrorCorrection {
-    L,
-    #[default]
-    M,
-    Q,
-    H,
-}
+/// Image block (JPEG or PNG from base64 or a data URI)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageBlock {
+    /// Block identifier
+    #[serde(default)]
+    pub id: Option<String>,
 
-impl Block {
-    /// Get the block ID if present
-    pub fn id(&self) -> Option<&str> {
-        match self {
-            Block::Text(b) => b.id.as_deref(),
-            Block::FieldForm(b) => b.id.as_deref(),
-            Block::Table(b) => b.id.as_deref(),
-            Block::QRCode(b) => b.id.as_deref(),
-        }
-    }
+    /// Data binding path (value is a base64 string or data URI)
+    #[serde(default)]
+    pub bind: Option<String>,
 
-    /// Get the data binding path if present
-    pub fn bind(&self) -> Option<&str> {
-        match self {
-            Block::Text(b) => b.bind.as_deref(),
-            Block::FieldForm(b) => b.bind.as_deref(),
-            Block::Table(b) => b.bind.as_deref(),
-            Block::QRCode(b) => b.bind.as_deref(),
-        }
-    }
+    /// Static image data (base64 string or data URI)
+    #[serde(default)]
+    pub src: Option<String>,
 
-    /// Get the enable binding if present
-    pub fn enable(&self) -> Option<&str> {
-        match self {
-            Block::Text(b) => b.enable.as_deref(),
-            Block::FieldForm(b) => b.enable.as_deref(),
-            Block::Table(b) => b.enable.as_deref(),
-            Block::QRCode(b) => b.enable.as_deref(),
-        }
-    }
+    /// Position (top-left corner)
+    pub position: Position,
 
-    /// Get the position
+    /// Target size in points
+    pub size: Size,
+
+    /// How the image is scaled into `size`
+    #[serde(rename = "scaleMode")]
+    #[serde(default)]
+    pub scale_mode: ScaleMode,
+
+    /// Rotation in degrees around the block position (counter-clockwise)
+    #[serde(default)]
+    pub rotation: Option<f64>,
+
+    /// Pages to render on
+    #[serde(default)]
+    pub pages: Option<Vec<usize>>,
+
+    /// Optional enable flag - if set, evaluates binding to determine if block is rendered
+    /// If the bound value is falsy (null, false, 0, empty string), block is not rendered
+    #[serde(default)]
+    pub enable: Option<String>,
+}
+
+/// Image scaling mode
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ScaleMode {
+    /// Stretch to exact size
+    #[default]
+    Stretch,
+    /// Use the width, keep aspect ratio
+    FitWidth,
+    /// Use the height, keep aspect ratio
+    FitHeight,
+    /// Fit within the size, keep aspect ratio
+    FitBox,
+}
+
+/// 1D barcode block (drawn as vector bars)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BarcodeBlock {
+    /// Block identifier
+    #[serde(default)]
+    pub id: Option<String>,
+
+    /// Data binding path
+    #[serde(default)]
+    pub bind: Option<String>,
+
+    /// Static barcode data
+    #[serde(default)]
+    pub data: Option<String>,
+
+    /// Position (top-left corner of the bars)
+    pub position: Position,
+
+    /// Barcode symbology
+    #[serde(default)]
+    pub symbology: BarcodeSymbology,
+
+    /// Width of one module (narrow bar) in points
+    #[serde(rename = "moduleWidth")]
+    #[serde(default = "default_module_width")]
+    pub module_width: f64,
+
+    /// Bar height in points
+    #[serde(rename = "barHeight")]
+    #[serde(default = "default_bar_height")]
+    pub bar_height: f64,
+
+    /// Print the human-readable text below the bars
+    #[serde(rename = "showText")]
+    #[serde(default)]
+    pub show_text: bool,
+
+    /// Font for the human-readable text
+    #[serde(default)]
+    pub font: Option<Font>,
+
+    /// Rotation in degrees around the block position (counter-clockwise)
+    #[serde(default)]
+    pub rotation: Option<f64>,
+
+    /// Pages to render on
+    #[serde(default)]
+    pub pages: Option<Vec<usize>>,
+
+    /// Optional enable flag - if set, evaluates binding to determine if block is rendered
+    /// If the bound value is falsy (null, false, 0, empty string), block is not rendered
+    #[serde(default)]
+    pub enable: Option<String>,
+}
+
+fn default_module_width() -> f64 {
+    1.0
+}
+
+fn default_bar_height() -> f64 {
+    40.0
+}
+
+/// 1D barcode symbology
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BarcodeSymbology {
+    #[default]
+    Code128,
+    Code39,
+    Ean13,
+    Itf,
+}
+
+/// Color that is either fixed or driven by data
+///
+/// Accepts an RGB object, a hex string (`"#RRGGBB"`), a binding (`"$.path"`)
+/// resolving to either of those, or a value map:
+///
+/// ```json
+/// { "bind": "$.status", "map": { "paid": "#2E7D32", "overdue": "#C62828" }, "default": "#9E9E9E" }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum ColorValue {
+    /// Pick a color by the bound value
+    Mapped {
+        /// Data binding path
+        bind: String,
+        /// Bound value (as string) -> color
+        map: HashMap<String, ColorValue>,
+        /// Color used when the value is not in the map
+        #[serde(default)]
+        default: Option<Box<ColorValue>>,
+    },
+    /// Fixed RGB color
+    Rgb(Color),
+    /// Hex string or binding path
+    Text(String),
+}
+
+/// Stroke and fill properties shared by shape blocks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShapeStyle {
+    /// Outline color (black when neither stroke nor fill is set)
+    #[serde(default)]
+    pub stroke: Option<ColorValue>,
+
+    /// Fill color
+    #[serde(default)]
+    pub fill: Option<ColorValue>,
+
+    /// Outline width in points
+    #[serde(rename = "strokeWidth")]
+    #[serde(default = "default_stroke_width")]
+    pub stroke_width: f64,
+
+    /// Dash pattern: alternating dash and gap lengths in points
+    #[serde(default)]
+    pub dash: Option<Vec<f64>>,
+
+    /// Shape of line ends
+    #[serde(rename = "lineCap")]
+    #[serde(default)]
+    pub line_cap: LineCap,
+}
+
+impl Default for ShapeStyle {
+    fn default() -> Self {
+        Self {
+            stroke: None,
+            fill: None,
+            stroke_width: default_stroke_width(),
+            dash: None,
+            line_cap: LineCap::default(),
+        }
+    }
+}
+
+fn default_stroke_width() -> f64 {
+    1.0
+}
+
+/// Line end style
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LineCap {
+    #[default]
+    Butt,
+    Round,
+    Square,
+}
+
+/// Line block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineBlock {
+    /// Block identifier
+    #[serde(default)]
+    pub id: Option<String>,
+
+    /// Start point
+    pub position: Position,
+
+    /// End point
+    pub end: Position,
+
+    /// Stroke settings (fill is ignored)
+    #[serde(flatten)]
+    pub style: ShapeStyle,
+
+    /// Rotation in degrees around the block position (counter-clockwise)
+    #[serde(default)]
+    pub rotation: Option<f64>,
+
+    /// Pages to render on
+    #[serde(default)]
+    pub pages: Option<Vec<usize>>,
+
+    /// Optional enable flag - if set, evaluates binding to determine if block is rendered
+    /// If the bound value is falsy (null, false, 0, empty string), block is not rendered
+    #[serde(default)]
+    pub enable: Option<String>,
+}
+
+/// Rectangle block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RectBlock {
+    /// Block identifier
+    #[serde(default)]
+    pub id: Option<String>,
+
+    /// Position (top-left corner)
+    pub position: Position,
+
+    /// Rectangle size in points
+    pub size: Size,
+
+    /// Corner radius in points (0 = square corners)
+    #[serde(default)]
+    pub radius: f64,
+
+    /// Stroke and fill settings
+    #[serde(flatten)]
+    pub style: ShapeStyle,
+
+    /// Rotation in degrees around the block position (counter-clockwise)
+    #[serde(default)]
+    pub rotation: Option<f64>,
+
+    /// Pages to render on
+    #[serde(default)]
+    pub pages: Option<Vec<usize>>,
+
+    /// Optional enable flag - if set, evaluates binding to determine if block is rendered
+    /// If the bound value is falsy (null, false, 0, empty string), block is not rendered
+    #[serde(default)]
+    pub enable: Option<String>,
+}
+
+/// Ellipse block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EllipseBlock {
+    /// Block identifier
+    #[serde(default)]
+    pub id: Option<String>,
+
+    /// Position (top-left corner of the bounding box)
+    pub position: Position,
+
+    /// Bounding box size in points (equal sides draw a circle)
+    pub size: Size,
+
+    /// Stroke and fill settings
+    #[serde(flatten)]
+    pub style: ShapeStyle,
+
+    /// Rotation in degrees around the block position (counter-clockwise)
+    #[serde(default)]
+    pub rotation: Option<f64>,
+
+    /// Pages to render on
+    #[serde(default)]
+    pub pages: Option<Vec<usize>>,
+
+    /// Optional enable flag - if set, evaluates binding to determine if block is rendered
+    /// If the bound value is falsy (null, false, 0, empty string), block is not rendered
+    #[serde(default)]
+    pub enable: Option<String>,
+}
+
+/// AcroForm field block
+///
+/// Sets the value of a field that already exists in the base PDF. The field
+/// keeps its own position; text is drawn with the block font.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormFieldBlock {
+    /// Block identifier
+    #[serde(default)]
+    pub id: Option<String>,
+
+    /// Fully qualified field name in the base PDF (e.g. "applicant.name")
+    pub field: String,
+
+    /// Data binding path
+    #[serde(default)]
+    pub bind: Option<String>,
+
+    /// Static value (used if bind is not specified)
+    #[serde(default)]
+    pub value: Option<String>,
+
+    /// Font for text and choice fields
+    #[serde(default)]
+    pub font: Option<Font>,
+
+    /// Number format pattern
+    #[serde(default)]
+    pub format: Option<String>,
+
+    /// Special format type
+    #[serde(rename = "formatType")]
+    #[serde(default)]
+    pub format_type: Option<FormatType>,
+
+    /// Optional enable flag - if set, evaluates binding to determine if block is rendered
+    /// If the bound value is falsy (null, false, 0, empty string), block is not rendered
+    #[serde(default)]
+    pub enable: Option<String>,
+}
+
+/// Kind of field created by a field block
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    /// Editable text
+    #[default]
+    Text,
+    /// Checkbox, checked by a truthy value
+    Checkbox,
+    /// Radio group, one button per option
+    Radio,
+    /// Empty signature field
+    Signature,
+}
+
+/// A radio button of a field block, or a box of a checkbox block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldOption {
+    /// Value selecting this button or box
+    pub value: String,
+
+    /// Offset of the button or box from the block position
+    pub offset: Position,
+}
+
+/// Interactive form field block
+///
+/// Creates a field the recipient can still edit. Text fields use the block
+/// font for their default value and for typing in a viewer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldBlock {
+    /// Block identifier
+    #[serde(default)]
+    pub id: Option<String>,
+
+    /// Field name (unique in the output, without '.')
+    pub name: String,
+
+    /// Field kind
+    #[serde(rename = "fieldType")]
+    #[serde(default)]
+    pub field_type: FieldType,
+
+    /// Position (top-left corner of the field, or of a radio button at offset 0)
+    pub position: Position,
+
+    /// Field size in points (each button's size for radio groups)
+    pub size: Size,
+
+    /// Radio buttons
+    #[serde(default)]
+    pub options: Vec<FieldOption>,
+
+    /// Data binding path for the default value
+    #[serde(default)]
+    pub bind: Option<String>,
+
+    /// Static default value (used if bind is not specified)
+    #[serde(default)]
+    pub value: Option<String>,
+
+    /// Font for text fields
+    #[serde(default)]
+    pub font: Option<Font>,
+
+    /// Text alignment
+    #[serde(default)]
+    pub align: Align,
+
+    /// Allow several lines of text
+    #[serde(default)]
+    pub multiline: bool,
+
+    /// Maximum number of characters
+    #[serde(rename = "maxLength")]
+    #[serde(default)]
+    pub max_length: Option<usize>,
+
+    /// Spread `maxLength` characters evenly over the field
+    #[serde(default)]
+    pub comb: bool,
+
+    /// The recipient cannot change the value
+    #[serde(rename = "readOnly")]
+    #[serde(default)]
+    pub read_only: bool,
+
+    /// A value is required
+    #[serde(default)]
+    pub required: bool,
+
+    /// Pages to render on (one widget per page, sharing the value)
+    #[serde(default)]
+    pub pages: Option<Vec<usize>>,
+
+    /// Optional enable flag - if set, evaluates binding to determine if block is rendered
+    /// If the bound value is falsy (null, false, 0, empty string), block is not rendered
+    #[serde(default)]
+    pub enable: Option<String>,
+}
+
+/// Mark drawn by a checkbox block
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckMark {
+    /// Tick (✓)
+    #[default]
+    Tick,
+    /// Cross (✗)
+    Cross,
+    /// Filled square
+    Fill,
+}
+
+/// Checkbox block
+///
+/// Draws a vector mark in the box whose option matches the bound value
+/// (every matching box for an array). Without options, the box at
+/// `position` is marked when the bound value is truthy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckboxBlock {
+    /// Block identifier
+    #[serde(default)]
+    pub id: Option<String>,
+
+    /// Position (top-left corner of the box, or of an option box at offset 0)
+    pub position: Position,
+
+    /// Box side in points
+    #[serde(default = "default_check_size")]
+    pub size: f64,
+
+    /// Mark shape
+    #[serde(default)]
+    pub mark: CheckMark,
+
+    /// Mark color (default black)
+    #[serde(default)]
+    pub color: Option<ColorValue>,
+
+    /// Data binding path (without it the box is always marked)
+    #[serde(default)]
+    pub bind: Option<String>,
+
+    /// Boxes selected by value
+    #[serde(default)]
+    pub options: Vec<FieldOption>,
+
+    /// Rotation in degrees around the block position (counter-clockwise)
+    #[serde(default)]
+    pub rotation: Option<f64>,
+
+    /// Pages to render on
+    #[serde(default)]
+    pub pages: Option<Vec<usize>>,
+
+    /// Optional enable flag - if set, evaluates binding to determine if block is rendered
+    /// If the bound value is falsy (null, false, 0, empty string), block is not rendered
+    #[serde(default)]
+    pub enable: Option<String>,
+}
+
+fn default_check_size() -> f64 {
+    10.0
+}
+
+/// PDF page block
+///
+/// Draws a page of a PDF registered with `TemplateRenderer::add_pdf_source`,
+/// over or under the page content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdfPageBlock {
+    /// Block identifier
+    #[serde(default)]
+    pub id: Option<String>,
+
+    /// Registered PDF source name
+    #[serde(default)]
+    pub source: Option<String>,
+
+    /// Data binding path to a source name (overrides `source`)
+    #[serde(default)]
+    pub bind: Option<String>,
+
+    /// Page of the source PDF (1-indexed)
+    #[serde(rename = "sourcePage")]
+    #[serde(default = "default_source_page")]
+    pub source_page: usize,
+
+    /// Position (top-left corner)
+    pub position: Position,
+
+    /// Size factor (1.0 = the source page's size)
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+
+    /// Box the page is fitted into, keeping its aspect ratio (overrides `scale`)
+    #[serde(default)]
+    pub size: Option<Size>,
+
+    /// Over or under the page content
+    #[serde(default)]
+    pub layer: Layer,
+
+    /// Rotation in degrees around the block position (counter-clockwise)
+    #[serde(default)]
+    pub rotation: Option<f64>,
+
+    /// Pages to render on
+    #[serde(default)]
+    pub pages: Option<Vec<usize>>,
+
+    /// Optional enable flag - if set, evaluates binding to determine if block is rendered
+    /// If the bound value is falsy (null, false, 0, empty string), block is not rendered
+    #[serde(default)]
+    pub enable: Option<String>,
+}
+
+fn default_source_page() -> usize {
+    1
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+/// QR code error correction level
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ErrorCorrection {
+    L,
+    #[default]
+    M,
+    Q,
+    H,
+}
+
+impl Block {
+    /// Get the block ID if present
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            Block::Text(b) => b.id.as_deref(),
+            Block::FieldForm(b) => b.id.as_deref(),
+            Block::Table(b) => b.id.as_deref(),
+            Block::QRCode(b) => b.id.as_deref(),
+            Block::Image(b) => b.id.as_deref(),
+            Block::Barcode(b) => b.id.as_deref(),
+            Block::Line(b) => b.id.as_deref(),
+            Block::Rect(b) => b.id.as_deref(),
+            Block::Ellipse(b) => b.id.as_deref(),
+            Block::FormField(b) => b.id.as_deref(),
+            Block::Field(b) => b.id.as_deref(),
+            Block::Checkbox(b) => b.id.as_deref(),
+            Block::PdfPage(b) => b.id.as_deref(),
+        }
+    }
+
+    /// Get the data binding path if present
+    pub fn bind(&self) -> Option<&str> {
+        match self {
+            Block::Text(b) => b.bind.as_deref(),
+            Block::FieldForm(b) => b.bind.as_deref(),
+            Block::Table(b) => b.bind.as_deref(),
+            Block::QRCode(b) => b.bind.as_deref(),
+            Block::Image(b) => b.bind.as_deref(),
+            Block::Barcode(b) => b.bind.as_deref(),
+            Block::FormField(b) => b.bind.as_deref(),
+            Block::Field(b) => b.bind.as_deref(),
+            Block::Checkbox(b) => b.bind.as_deref(),
+            Block::PdfPage(b) => b.bind.as_deref(),
+            Block::Line(_) | Block::Rect(_) | Block::Ellipse(_) => None,
+        }
+    }
+
+    /// Get the enable binding if present
+    pub fn enable(&self) -> Option<&str> {
+        match self {
+            Block::Text(b) => b.enable.as_deref(),
+            Block::FieldForm(b) => b.enable.as_deref(),
+            Block::Table(b) => b.enable.as_deref(),
+            Block::QRCode(b) => b.enable.as_deref(),
+            Block::Image(b) => b.enable.as_deref(),
+            Block::Barcode(b) => b.enable.as_deref(),
+            Block::Line(b) => b.enable.as_deref(),
+            Block::Rect(b) => b.enable.as_deref(),
+            Block::Ellipse(b) => b.enable.as_deref(),
+            Block::FormField(b) => b.enable.as_deref(),
+            Block::Field(b) => b.enable.as_deref(),
+            Block::Checkbox(b) => b.enable.as_deref(),
+            Block::PdfPage(b) => b.enable.as_deref(),
+        }
+    }
+
+    /// Get the position
     pub fn position(&self) -> Position {
         match self {
             Block::Text(b) => b.position,
             Block::FieldForm(b) => b.position,
             Block::Table(b) => b.position,
             Block::QRCode(b) => b.position,
+            Block::Image(b) => b.position,
+            Block::Barcode(b) => b.position,
+            Block::Line(b) => b.position,
+            Block::Rect(b) => b.position,
+            Block::Ellipse(b) => b.position,
+            Block::Field(b) => b.position,
+            Block::Checkbox(b) => b.position,
+            Block::PdfPage(b) => b.position,
+            // Form fields are placed by the base PDF
+            Block::FormField(_) => Position { x: 0.0, y: 0.0 },
+        }
+    }
+
+    /// Get the rotation in degrees (form field blocks are never rotated)
+    pub fn rotation(&self) -> Option<f64> {
+        match self {
+            Block::Text(b) => b.rotation,
+            Block::FieldForm(b) => b.rotation,
+            Block::Table(b) => b.rotation,
+            Block::QRCode(b) => b.rotation,
+            Block::Image(b) => b.rotation,
+            Block::Barcode(b) => b.rotation,
+            Block::Line(b) => b.rotation,
+            Block::Rect(b) => b.rotation,
+            Block::Ellipse(b) => b.rotation,
+            Block::FormField(_) | Block::Field(_) => None,
+            Block::Checkbox(b) => b.rotation,
+            Block::PdfPage(b) => b.rotation,
         }
     }
 
@@ -658,11 +1755,48 @@ impl Block {
             Block::Table(b) => {
                 b.position.x += dx;
                 b.position.y += dy;
+                b.bottom = b.bottom.map(|y| y + dy);
+                b.continuation_y = b.continuation_y.map(|y| y + dy);
             }
             Block::QRCode(b) => {
                 b.position.x += dx;
                 b.position.y += dy;
             }
+            Block::Image(b) => {
+                b.position.x += dx;
+                b.position.y += dy;
+            }
+            Block::Barcode(b) => {
+                b.position.x += dx;
+                b.position.y += dy;
+            }
+            Block::Line(b) => {
+                b.position.x += dx;
+                b.position.y += dy;
+                b.end.x += dx;
+                b.end.y += dy;
+            }
+            Block::Rect(b) => {
+                b.position.x += dx;
+                b.position.y += dy;
+            }
+            Block::Ellipse(b) => {
+                b.position.x += dx;
+                b.position.y += dy;
+            }
+            Block::Field(b) => {
+                b.position.x += dx;
+                b.position.y += dy;
+            }
+            Block::Checkbox(b) => {
+                b.position.x += dx;
+                b.position.y += dy;
+            }
+            Block::PdfPage(b) => {
+                b.position.x += dx;
+                b.position.y += dy;
+            }
+            Block::FormField(_) => {}
         }
     }
 
@@ -674,6 +1808,15 @@ impl Block {
             Block::FieldForm(b) => b.pages = pages_opt,
             Block::Table(b) => b.pages = pages_opt,
             Block::QRCode(b) => b.pages = pages_opt,
+            Block::Image(b) => b.pages = pages_opt,
+            Block::Barcode(b) => b.pages = pages_opt,
+            Block::Line(b) => b.pages = pages_opt,
+            Block::Rect(b) => b.pages = pages_opt,
+            Block::Ellipse(b) => b.pages = pages_opt,
+            Block::Field(b) => b.pages = pages_opt,
+            Block::Checkbox(b) => b.pages = pages_opt,
+            Block::PdfPage(b) => b.pages = pages_opt,
+            Block::FormField(_) => {}
         }
     }
 }
@@ -720,6 +1863,122 @@ mod tests {
                 assert_eq!(b.bind, Some("$.qrData".to_string()));
                 assert_eq!(b.size.width, 50.0);
                 assert_eq!(b.error_correction, ErrorCorrection::M);
+                assert_eq!(b.quiet_zone, 4);
+                assert_eq!(b.render_mode, QRRenderMode::Vector);
+            }
+            _ => panic!("Expected QRCodeBlock"),
+        }
+    }
+
+    #[test]
+    fn test_parse_paginated_table() {
+        let json = r#"{
+            "type": "table",
+            "bind": "$.items",
+            "position": { "x": 50, "y": 300 },
+            "maxHeight": 400,
+            "continuationY": 120,
+            "columns": [{ "field": "amount", "x": 0, "header": "Amount" }],
+            "totals": { "fields": ["amount"], "labelField": "name", "pageSubtotal": "Subtotal" }
+        }"#;
+
+        let block: Block = serde_json::from_str(json).unwrap();
+
+        match block {
+            Block::Table(b) => {
+                assert!(b.is_paginated());
+                assert!(b.repeat_header);
+                assert_eq!(b.continuation_y, Some(120.0));
+                assert_eq!(b.columns[0].header.as_deref(), Some("Amount"));
+                let totals = b.totals.unwrap();
+                assert_eq!(totals.page_subtotal.as_deref(), Some("Subtotal"));
+                assert!(totals.carried_forward.is_none());
+            }
+            _ => panic!("Expected TableBlock"),
+        }
+    }
+
+    #[test]
+    fn test_parse_table_style() {
+        let json = r#"{
+            "type": "table",
+            "bind": "$.items",
+            "position": { "x": 50, "y": 300 },
+            "columns": [
+                { "field": "name", "width": 120 },
+                { "field": "amount", "x": 150, "width": 60, "align": "right" }
+            ],
+            "style": { "border": "horizontal", "padding": 3, "zebra": { "r": 0.9, "g": 0.9, "b": 0.9 } }
+        }"#;
+
+        let block: Block = serde_json::from_str(json).unwrap();
+
+        match block {
+            Block::Table(b) => {
+                assert_eq!(b.columns[0].x, None);
+                assert_eq!(b.columns[1].x, Some(150.0));
+                assert_eq!(b.columns[1].width, Some(60.0));
+                let style = b.style.unwrap();
+                assert_eq!(style.border, TableBorder::Horizontal);
+                assert_eq!(style.border_width, 0.5);
+                assert_eq!(style.padding, 3.0);
+                assert!(style.background.is_none());
+                assert!(style.zebra.is_some());
+            }
+            _ => panic!("Expected TableBlock"),
+        }
+    }
+
+    #[test]
+    fn test_parse_font_style() {
+        for json in [r#""bold-italic""#, r#""bolditalic""#] {
+            let style: FontStyle = serde_json::from_str(json).unwrap();
+            assert_eq!(style, FontStyle::BoldItalic);
+        }
+        assert_eq!(
+            serde_json::to_string(&FontStyle::BoldItalic).unwrap(),
+            r#""bold-italic""#
+        );
+    }
+
+    #[test]
+    fn test_parse_qrcode_block_promptpay() {
+        let json = r#"{
+            "type": "qrcode",
+            "bind": "$.payment",
+            "payload": "promptpay",
+            "position": { "x": 100, "y": 200 },
+            "size": { "width": 50, "height": 50 }
+        }"#;
+
+        let block: Block = serde_json::from_str(json).unwrap();
+
+        match block {
+            Block::QRCode(b) => assert_eq!(b.payload, QRPayload::PromptPay),
+            _ => panic!("Expected QRCodeBlock"),
+        }
+    }
+
+    #[test]
+    fn test_parse_qrcode_block_colors() {
+        let json = r#"{
+            "type": "qrcode",
+            "data": "hello",
+            "position": { "x": 100, "y": 200 },
+            "size": { "width": 50, "height": 50 },
+            "quietZone": 2,
+            "foreground": { "r": 0.0, "g": 0.0, "b": 0.5 },
+            "renderMode": "raster"
+        }"#;
+
+        let block: Block = serde_json::from_str(json).unwrap();
+
+        match block {
+            Block::QRCode(b) => {
+                assert_eq!(b.quiet_zone, 2);
+                assert_eq!(b.foreground.unwrap().b, 0.5);
+                assert!(b.background.is_none());
+                assert_eq!(b.render_mode, QRRenderMode::Raster);
             }
             _ => panic!("Expected QRCodeBlock"),
         }
@@ -737,4 +1996,338 @@ mod tests {
         let block: Block = serde_json::from_str(json).unwrap();
         assert_eq!(block.enable(), Some("$.showName"));
     }
+
+    #[test]
+    fn test_parse_word_wrap_max_width() {
+        let json = r#"{
+            "type": "text",
+            "bind": "$.address",
+            "position": { "x": 100, "y": 200 },
+            "wordWrap": { "maxWidth": 150.5, "lineHeight": 14 }
+        }"#;
+
+        let block: Block = serde_json::from_str(json).unwrap();
+        match block {
+            Block::Text(text) => {
+                let wrap = text.word_wrap.unwrap();
+                assert_eq!(wrap.max_width, Some(150.5));
+                assert_eq!(wrap.max_chars, 0);
+            }
+            _ => panic!("Expected TextBlock"),
+        }
+    }
+
+    #[test]
+    fn test_parse_text_box() {
+        let json = r#"{
+            "type": "text",
+            "bind": "$.name",
+            "position": { "x": 100, "y": 200 },
+            "box": { "width": 120, "height": 30, "verticalAlign": "middle", "overflow": "shrink" }
+        }"#;
+
+        let block: Block = serde_json::from_str(json).unwrap();
+        match block {
+            Block::Text(text) => {
+                let text_box = text.text_box.unwrap();
+                assert_eq!(text_box.width, 120.0);
+                assert_eq!(text_box.vertical_align, VerticalAlign::Middle);
+                assert_eq!(text_box.overflow, Overflow::Shrink);
+                assert_eq!(text_box.min_font_size, 6.0);
+            }
+            _ => panic!("Expected TextBlock"),
+        }
+    }
+
+    #[test]
+    fn test_parse_image_block() {
+        let json = r#"{
+            "type": "image",
+            "bind": "$.signature",
+            "position": { "x": 400, "y": 700 },
+            "size": { "width": 120, "height": 40 },
+            "scaleMode": "fitBox"
+        }"#;
+
+        let block: Block = serde_json::from_str(json).unwrap();
+        assert_eq!(block.bind(), Some("$.signature"));
+        match block {
+            Block::Image(image) => {
+                assert_eq!(image.scale_mode, ScaleMode::FitBox);
+                assert!(image.src.is_none());
+            }
+            _ => panic!("Expected ImageBlock"),
+        }
+    }
+
+    #[test]
+    fn test_parse_shape_blocks() {
+        let json = r##"[
+            { "type": "line", "position": { "x": 50, "y": 100 }, "end": { "x": 545, "y": 100 }, "dash": [4, 2] },
+            {
+                "type": "rect",
+                "position": { "x": 400, "y": 60 },
+                "size": { "width": 120, "height": 30 },
+                "radius": 4,
+                "stroke": "#333333",
+                "fill": { "bind": "$.status", "map": { "paid": { "r": 0.8, "g": 1, "b": 0.8 } }, "default": "#EEEEEE" },
+                "enable": "$.showStatus"
+            },
+            { "type": "ellipse", "position": { "x": 480, "y": 700 }, "size": { "width": 80, "height": 80 }, "strokeWidth": 2 }
+        ]"##;
+
+        let blocks: Vec<Block> = serde_json::from_str(json).unwrap();
+        match &blocks[0] {
+            Block::Line(line) => {
+                assert_eq!(line.end.x, 545.0);
+                assert_eq!(line.style.dash, Some(vec![4.0, 2.0]));
+                assert_eq!(line.style.stroke_width, 1.0);
+                assert!(line.style.stroke.is_none());
+            }
+            _ => panic!("Expected LineBlock"),
+        }
+        match &blocks[1] {
+            Block::Rect(rect) => {
+                assert_eq!(rect.radius, 4.0);
+                assert_eq!(
+                    rect.style.stroke,
+                    Some(ColorValue::Text("#333333".to_string()))
+                );
+                match &rect.style.fill {
+                    Some(ColorValue::Mapped { bind, map, default }) => {
+                        assert_eq!(bind, "$.status");
+                        assert_eq!(map["paid"], ColorValue::Rgb(Color::rgb(0.8, 1.0, 0.8)));
+                        assert!(default.is_some());
+                    }
+                    other => panic!("Expected mapped color, got {other:?}"),
+                }
+            }
+            _ => panic!("Expected RectBlock"),
+        }
+        assert_eq!(blocks[1].enable(), Some("$.showStatus"));
+        assert_eq!(blocks[1].bind(), None);
+        match &blocks[2] {
+            Block::Ellipse(ellipse) => assert_eq!(ellipse.style.stroke_width, 2.0),
+            _ => panic!("Expected EllipseBlock"),
+        }
+
+        let mut line = blocks[0].clone();
+        line.shift_position(0.0, 400.0);
+        match line {
+            Block::Line(line) => {
+                assert_eq!(line.position.y, 500.0);
+                assert_eq!(line.end.y, 500.0);
+            }
+            _ => panic!("Expected LineBlock"),
+        }
+    }
+
+    #[test]
+    fn test_parse_field_block() {
+        let json = r#"{
+            "type": "field",
+            "name": "gender",
+            "fieldType": "radio",
+            "position": { "x": 50, "y": 200 },
+            "size": { "width": 12, "height": 12 },
+            "options": [
+                { "value": "M", "offset": { "x": 0, "y": 0 } },
+                { "value": "F", "offset": { "x": 60, "y": 0 } }
+            ],
+            "bind": "$.gender",
+            "required": true
+        }"#;
+
+        let block: Block = serde_json::from_str(json).unwrap();
+        match block {
+            Block::Field(field) => {
+                assert_eq!(field.field_type, FieldType::Radio);
+                assert_eq!(field.options.len(), 2);
+                assert_eq!(field.options[1].offset.x, 60.0);
+                assert!(field.required);
+                assert!(!field.read_only);
+            }
+            _ => panic!("Expected FieldBlock"),
+        }
+
+        // Text is the default field type
+        let json = r#"{
+            "type": "field",
+            "name": "remarks",
+            "position": { "x": 50, "y": 600 },
+            "size": { "width": 300, "height": 60 },
+            "multiline": true,
+            "maxLength": 200
+        }"#;
+        match serde_json::from_str::<Block>(json).unwrap() {
+            Block::Field(field) => {
+                assert_eq!(field.field_type, FieldType::Text);
+                assert_eq!(field.max_length, Some(200));
+            }
+            _ => panic!("Expected FieldBlock"),
+        }
+    }
+
+    #[test]
+    fn test_parse_block_rotation() {
+        let json = r#"{
+            "type": "text",
+            "text": "สำเนา",
+            "position": { "x": 30, "y": 500 },
+            "rotation": 90
+        }"#;
+        let block: Block = serde_json::from_str(json).unwrap();
+        assert_eq!(block.rotation(), Some(90.0));
+
+        let json = r#"{ "type": "rect", "position": { "x": 0, "y": 0 }, "size": { "width": 10, "height": 10 } }"#;
+        let block: Block = serde_json::from_str(json).unwrap();
+        assert_eq!(block.rotation(), None);
+    }
+
+    #[test]
+    fn test_parse_checkbox_block() {
+        let json = r#"{
+            "type": "checkbox",
+            "bind": "$.gender",
+            "position": { "x": 100, "y": 200 },
+            "mark": "cross",
+            "options": [
+                { "value": "ชาย", "offset": { "x": 0, "y": 0 } },
+                { "value": "หญิง", "offset": { "x": 50, "y": 0 } }
+            ]
+        }"#;
+
+        let block: Block = serde_json::from_str(json).unwrap();
+        assert_eq!(block.bind(), Some("$.gender"));
+        match block {
+            Block::Checkbox(checkbox) => {
+                assert_eq!(checkbox.mark, CheckMark::Cross);
+                assert_eq!(checkbox.size, 10.0);
+                assert_eq!(checkbox.options[1].value, "หญิง");
+                assert!(checkbox.color.is_none());
+            }
+            _ => panic!("Expected CheckboxBlock"),
+        }
+    }
+
+    #[test]
+    fn test_parse_watermark() {
+        let json = r#"{
+            "version": "2.0",
+            "template": { "source": "base.pdf" },
+            "blocks": [],
+            "watermark": {
+                "text": "สำเนา",
+                "font": { "family": "sarabun", "size": 96 },
+                "layer": "underlay",
+                "tile": { "x": 40, "y": 60 }
+            }
+        }"#;
+
+        let template: Template = serde_json::from_str(json).unwrap();
+        let watermark = template.watermark.unwrap();
+        assert_eq!(watermark.text.as_deref(), Some("สำเนา"));
+        assert_eq!(watermark.angle, 45.0);
+        assert_eq!(watermark.opacity, 0.3);
+        assert_eq!(watermark.layer, Layer::Underlay);
+        assert_eq!(watermark.tile.unwrap().y, 60.0);
+        assert!(watermark.src.is_none());
+    }
+
+    #[test]
+    fn test_parse_pdfpage_block() {
+        let json = r#"{
+            "type": "pdfpage",
+            "source": "letterhead",
+            "position": { "x": 0, "y": 0 },
+            "layer": "underlay"
+        }"#;
+
+        let block: Block = serde_json::from_str(json).unwrap();
+        match block {
+            Block::PdfPage(page) => {
+                assert_eq!(page.source.as_deref(), Some("letterhead"));
+                assert_eq!(page.source_page, 1);
+                assert_eq!(page.scale, 1.0);
+                assert_eq!(page.layer, Layer::Underlay);
+                assert!(page.size.is_none());
+            }
+            _ => panic!("Expected PdfPageBlock"),
+        }
+    }
+
+    #[test]
+    fn test_parse_formfield_block() {
+        let json = r#"{
+            "type": "formfield",
+            "field": "applicant.birthDate",
+            "bind": "$.birthDate",
+            "formatType": "thai-date-long"
+        }"#;
+
+        let block: Block = serde_json::from_str(json).unwrap();
+        assert_eq!(block.bind(), Some("$.birthDate"));
+        match block {
+            Block::FormField(field) => {
+                assert_eq!(field.field, "applicant.birthDate");
+                assert_eq!(field.format_type, Some(FormatType::ThaiDateLong));
+                assert!(field.font.is_none());
+            }
+            _ => panic!("Expected FormFieldBlock"),
+        }
+    }
+
+    #[test]
+    fn test_parse_barcode_block() {
+        let json = r#"{
+            "type": "barcode",
+            "bind": "$.billPayment",
+            "position": { "x": 50, "y": 700 },
+            "symbology": "code128",
+            "barHeight": 30,
+            "showText": true
+        }"#;
+
+        let block: Block = serde_json::from_str(json).unwrap();
+        match block {
+            Block::Barcode(barcode) => {
+                assert_eq!(barcode.symbology, BarcodeSymbology::Code128);
+                assert_eq!(barcode.module_width, 1.0);
+                assert_eq!(barcode.bar_height, 30.0);
+                assert!(barcode.show_text);
+            }
+            _ => panic!("Expected BarcodeBlock"),
+        }
+    }
+
+    #[test]
+    fn test_parse_pages_without_source() {
+        let json = r#"{
+            "version": "2.0",
+            "template": {
+                "pages": [
+                    { "size": "a5", "orientation": "landscape", "margins": { "top": 36, "left": 36 } },
+                    { "width": 288, "height": 432 }
+                ]
+            },
+            "blocks": []
+        }"#;
+
+        let template: Template = serde_json::from_str(json).unwrap();
+        assert!(template.template.source.is_empty());
+        let pages = &template.template.pages;
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].size, PaperSize::A5);
+        assert_eq!(pages[0].orientation, Orientation::Landscape);
+        let margins = pages[0].margins.unwrap();
+        assert_eq!(
+            (margins.top, margins.right, margins.left),
+            (36.0, 0.0, 36.0)
+        );
+        assert_eq!(pages[1].size, PaperSize::A4);
+        assert_eq!(
+            (pages[1].width, pages[1].height),
+            (Some(288.0), Some(432.0))
+        );
+    }
 }
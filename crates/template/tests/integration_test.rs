@@ -1,7 +1,66 @@
 //! Integration tests for template rendering
 
+use lopdf::dictionary;
 use serde_json::json;
-use template::{parse_template, Block, FormatType};
+use template::{parse_template, Block, FlattenMode, FormatType, TemplateRenderer};
+
+/// Create a minimal one-page A4 PDF for render tests
+fn create_blank_pdf() -> Vec<u8> {
+    let mut doc = lopdf::Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let contents_id = doc.add_object(lopdf::Stream::new(lopdf::dictionary! {}, vec![]));
+    let page_id = doc.add_object(lopdf::dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "MediaBox" => vec![0.into(), 0.into(), 595.28.into(), 841.89.into()],
+        "Resources" => lopdf::dictionary! {},
+        "Contents" => contents_id,
+    });
+    doc.objects.insert(
+        pages_id,
+        lopdf::Object::Dictionary(lopdf::dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![page_id.into()],
+            "Count" => 1,
+        }),
+    );
+    let catalog_id = doc.add_object(lopdf::dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer).unwrap();
+    buffer
+}
+
+/// Collect the BaseFont names of all Type0 fonts in a rendered PDF
+fn embedded_font_names(pdf: &[u8]) -> Vec<String> {
+    let doc = lopdf::Document::load_mem(pdf).unwrap();
+    let mut names: Vec<String> = doc
+        .objects
+        .values()
+        .filter_map(|obj| obj.as_dict().ok())
+        .filter(|dict| {
+            dict.get(b"Subtype")
+                .and_then(|s| s.as_name())
+                .map(|n| n == b"Type0")
+                .unwrap_or(false)
+        })
+        .filter_map(|dict| dict.get(b"BaseFont").and_then(|n| n.as_name()).ok())
+        .map(|n| String::from_utf8_lossy(n).to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Concatenated content streams of the first page of a rendered PDF
+fn first_page_content(pdf: &[u8]) -> String {
+    let doc = lopdf::Document::load_mem(pdf).unwrap();
+    let page_id = doc.page_iter().next().unwrap();
+    String::from_utf8_lossy(&doc.get_page_content(page_id).unwrap()).to_string()
+}
 
 #[test]
 fn test_parse_simple_template() {
@@ -238,3 +297,1219 @@ fn test_data_binding_resolution() {
     // Missing field
     assert_eq!(resolve_binding("$.missing", &data), None);
 }
+
+#[test]
+fn test_render_uses_font_variants_and_fallback() {
+    let template_json = r#"{
+        "version": "2.0",
+        "template": { "source": "test.pdf" },
+        "fonts": [
+            {
+                "id": "sarabun",
+                "regular": "fonts/THSarabunNew.ttf",
+                "bold": "fonts/THSarabunNew Bold.ttf",
+                "fallback": ["symbols"]
+            },
+            {
+                "id": "symbols",
+                "regular": "fonts/NotoSansSymbols2-Regular.ttf"
+            }
+        ],
+        "blocks": [
+            {
+                "type": "text",
+                "text": "Regular",
+                "position": { "x": 100, "y": 50 },
+                "font": { "family": "sarabun", "size": 12 }
+            },
+            {
+                "type": "text",
+                "text": "Bold ✓",
+                "position": { "x": 100, "y": 100 },
+                "font": { "family": "sarabun", "size": 12, "style": "bold" }
+            }
+        ]
+    }"#;
+
+    let renderer = TemplateRenderer::new(
+        template_json,
+        create_blank_pdf(),
+        Some(std::path::Path::new("../..")),
+    )
+    .unwrap();
+    let output = renderer.render(&json!({})).unwrap();
+
+    assert_eq!(
+        embedded_font_names(&output),
+        vec!["sarabun-bold", "sarabun-regular", "symbols-regular"]
+    );
+}
+
+#[test]
+fn test_render_missing_variant_uses_regular() {
+    let template_json = r#"{
+        "version": "2.0",
+        "template": { "source": "test.pdf" },
+        "fonts": [
+            { "id": "sarabun", "regular": "fonts/THSarabunNew.ttf", "fallback": ["missing"] }
+        ],
+        "blocks": [
+            {
+                "type": "text",
+                "text": "Italic",
+                "position": { "x": 100, "y": 50 },
+                "font": { "family": "sarabun", "size": 12, "style": "italic" }
+            }
+        ]
+    }"#;
+
+    let renderer = TemplateRenderer::new(
+        template_json,
+        create_blank_pdf(),
+        Some(std::path::Path::new("../..")),
+    )
+    .unwrap();
+    let output = renderer.render(&json!({})).unwrap();
+
+    assert_eq!(embedded_font_names(&output), vec!["sarabun-regular"]);
+}
+
+#[test]
+fn test_render_word_wrap_max_width() {
+    let template_json = r#"{
+        "version": "2.0",
+        "template": { "source": "test.pdf" },
+        "fonts": [{ "id": "sarabun", "regular": "fonts/THSarabunNew.ttf" }],
+        "blocks": [
+            {
+                "type": "text",
+                "bind": "$.text",
+                "position": { "x": 50, "y": 50 },
+                "font": { "family": "sarabun", "size": 16 },
+                "wordWrap": { "maxWidth": 120, "lineHeight": 18 }
+            },
+            {
+                "type": "table",
+                "bind": "$.rows",
+                "position": { "x": 50, "y": 300 },
+                "font": { "family": "sarabun", "size": 16 },
+                "rowHeight": 18,
+                "columns": [{ "field": "name", "x": 0, "maxWidth": 60 }]
+            }
+        ]
+    }"#;
+
+    let renderer = TemplateRenderer::new(
+        template_json,
+        create_blank_pdf(),
+        Some(std::path::Path::new("../..")),
+    )
+    .unwrap();
+    // 30 characters, but far narrower than 30 average glyphs at 16pt:
+    // a character count limit would split this, a width limit must not
+    let narrow = "iiiiiiiiiiiiiiiiiiiiiiiiiiiiii";
+    let output = renderer
+        .render(&json!({ "text": narrow, "rows": [{ "name": "one two three four" }] }))
+        .unwrap();
+    let content = first_page_content(&output);
+    // One line for the text block, two for the wrapped table cell
+    assert_eq!(content.matches("BT").count(), 3);
+
+    let wide = "Wide Wide Wide Wide Wide Wide";
+    let output = renderer
+        .render(&json!({ "text": wide, "rows": [] }))
+        .unwrap();
+    let content = first_page_content(&output);
+    assert!(content.matches("BT").count() > 1);
+}
+
+/// Render a single text box block with the given box JSON
+fn render_text_box(box_json: &str, text: &str) -> template::Result<Vec<u8>> {
+    let template_json = format!(
+        r#"{{
+            "version": "2.0",
+            "template": {{ "source": "test.pdf" }},
+            "fonts": [{{ "id": "sarabun", "regular": "fonts/THSarabunNew.ttf" }}],
+            "blocks": [
+                {{
+                    "type": "text",
+                    "id": "remark",
+                    "bind": "$.text",
+                    "position": {{ "x": 50, "y": 50 }},
+                    "font": {{ "family": "sarabun", "size": 16 }},
+                    "wordWrap": {{ "lineHeight": 18 }},
+                    "box": {box_json}
+                }}
+            ]
+        }}"#
+    );
+
+    let renderer = TemplateRenderer::new(
+        &template_json,
+        create_blank_pdf(),
+        Some(std::path::Path::new("../..")),
+    )?;
+    renderer.render(&json!({ "text": text }))
+}
+
+const LONG_TEXT: &str = "The quick brown fox jumps over the lazy dog again and again and again";
+
+#[test]
+fn test_text_box_fits() {
+    let output = render_text_box(r#"{ "width": 400, "height": 40 }"#, "Short text").unwrap();
+    let content = first_page_content(&output);
+
+    assert_eq!(content.matches("BT").count(), 1);
+    assert!(!content.contains("re W n"));
+}
+
+#[test]
+fn test_text_box_clip() {
+    let output = render_text_box(r#"{ "width": 100, "height": 40 }"#, LONG_TEXT).unwrap();
+    let content = first_page_content(&output);
+
+    assert!(content.contains("100 40 re W n"));
+    // Lines starting below the box are dropped
+    assert_eq!(content.matches("BT").count(), 3);
+}
+
+#[test]
+fn test_text_box_ellipsis() {
+    let output = render_text_box(
+        r#"{ "width": 100, "height": 40, "overflow": "ellipsis" }"#,
+        LONG_TEXT,
+    )
+    .unwrap();
+    let content = first_page_content(&output);
+
+    // Only the lines that fit completely are kept
+    assert_eq!(content.matches("BT").count(), 2);
+    assert!(!content.contains("re W n"));
+}
+
+#[test]
+fn test_text_box_shrink() {
+    let output = render_text_box(
+        r#"{ "width": 100, "height": 40, "overflow": "shrink", "minFontSize": 4 }"#,
+        LONG_TEXT,
+    )
+    .unwrap();
+    let content = first_page_content(&output);
+
+    assert!(!content.contains(" 16 Tf"));
+    assert!(!content.contains("re W n"));
+}
+
+#[test]
+fn test_text_box_overflow_error() {
+    let result = render_text_box(
+        r#"{ "width": 100, "height": 40, "overflow": "error" }"#,
+        LONG_TEXT,
+    );
+
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("'remark'"));
+}
+
+#[test]
+fn test_text_box_vertical_align() {
+    let top = render_text_box(r#"{ "width": 400, "height": 100 }"#, "Text").unwrap();
+    let bottom = render_text_box(
+        r#"{ "width": 400, "height": 100, "verticalAlign": "bottom" }"#,
+        "Text",
+    )
+    .unwrap();
+
+    let baseline = |pdf: &[u8]| -> f64 {
+        let content = first_page_content(pdf);
+        let td = content.lines().find(|l| l.ends_with(" Td")).unwrap();
+        td.split_whitespace().nth(1).unwrap().parse().unwrap()
+    };
+    // Bottom-aligned text sits lower on the page (smaller PDF y)
+    assert!(baseline(&bottom) < baseline(&top) - 50.0);
+}
+
+/// Count image XObjects in a rendered PDF
+fn image_xobject_count(pdf: &[u8]) -> usize {
+    let doc = lopdf::Document::load_mem(pdf).unwrap();
+    doc.objects
+        .values()
+        .filter_map(|obj| obj.as_stream().ok())
+        .filter(|stream| {
+            stream
+                .dict
+                .get(b"Subtype")
+                .and_then(|s| s.as_name())
+                .map(|n| n == b"Image")
+                .unwrap_or(false)
+        })
+        .count()
+}
+
+/// Encode a small PNG as a data URI
+fn png_data_uri() -> String {
+    use base64::Engine;
+
+    let img = image::RgbImage::from_pixel(4, 2, image::Rgb([200, 0, 0]));
+    let mut bytes = Vec::new();
+    image::DynamicImage::ImageRgb8(img)
+        .write_to(
+            &mut std::io::Cursor::new(&mut bytes),
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+    format!(
+        "data:image/png;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    )
+}
+
+#[test]
+fn test_render_image_block() {
+    let template_json = r#"{
+        "version": "2.0",
+        "template": { "source": "test.pdf" },
+        "blocks": [
+            {
+                "type": "image",
+                "bind": "$.signature",
+                "position": { "x": 400, "y": 700 },
+                "size": { "width": 120, "height": 40 },
+                "scaleMode": "fitBox"
+            }
+        ]
+    }"#;
+
+    let renderer = TemplateRenderer::new(template_json, create_blank_pdf(), None).unwrap();
+
+    let output = renderer
+        .render(&json!({ "signature": png_data_uri() }))
+        .unwrap();
+    assert_eq!(image_xobject_count(&output), 1);
+    // 4x2 image fit into 120x40 keeps its 2:1 aspect ratio
+    assert!(first_page_content(&output).contains("80 0 0 40 "));
+
+    // Missing data renders nothing
+    let output = renderer.render(&json!({})).unwrap();
+    assert_eq!(image_xobject_count(&output), 0);
+
+    // Invalid data is an error
+    assert!(renderer.render(&json!({ "signature": "%%%" })).is_err());
+}
+
+#[test]
+fn test_render_barcode_block() {
+    let template_json = r#"{
+        "version": "2.0",
+        "template": { "source": "test.pdf" },
+        "fonts": [{ "id": "sarabun", "regular": "fonts/THSarabunNew.ttf" }],
+        "blocks": [
+            {
+                "type": "barcode",
+                "bind": "$.ean",
+                "symbology": "ean13",
+                "position": { "x": 50, "y": 700 },
+                "moduleWidth": 1,
+                "barHeight": 30,
+                "showText": true,
+                "font": { "family": "sarabun", "size": 10 }
+            }
+        ]
+    }"#;
+
+    let renderer = TemplateRenderer::new(
+        template_json,
+        create_blank_pdf(),
+        Some(std::path::Path::new("../..")),
+    )
+    .unwrap();
+
+    let output = renderer.render(&json!({ "ean": "885000000001" })).unwrap();
+    let content = first_page_content(&output);
+
+    // Bars are vector rectangles, not an image
+    assert_eq!(image_xobject_count(&output), 0);
+    // First bar starts at the block position
+    assert!(content.contains("\n50 "));
+    assert!(content.contains(" 1 30 re\n"));
+    assert!(content.contains("f\nQ\n"));
+    // Human-readable text
+    assert_eq!(content.matches("BT").count(), 1);
+
+    // Wrong check digit is reported
+    assert!(renderer.render(&json!({ "ean": "8850000000013" })).is_err());
+}
+
+#[test]
+fn test_render_qrcode_vector_and_raster() {
+    let template = |mode: &str| {
+        format!(
+            r#"{{
+            "version": "2.0",
+            "template": {{ "source": "test.pdf" }},
+            "blocks": [
+                {{
+                    "type": "qrcode",
+                    "bind": "$.qr",
+                    "position": {{ "x": 400, "y": 50 }},
+                    "size": {{ "width": 58, "height": 58 }},
+                    "foreground": {{ "r": 0.0, "g": 0.0, "b": 0.5 }},
+                    "renderMode": "{mode}"
+                }}
+            ]
+        }}"#
+        )
+    };
+    let data = json!({ "qr": "hello" });
+
+    // Vector: 21 modules + 4 quiet zone modules on each side = 29 x 2pt
+    let renderer = TemplateRenderer::new(&template("vector"), create_blank_pdf(), None).unwrap();
+    let output = renderer.render(&data).unwrap();
+    let content = first_page_content(&output);
+
+    assert_eq!(image_xobject_count(&output), 0);
+    assert!(content.contains("1 1 1 rg\n400 "));
+    assert!(content.contains(" 58 58 re\nf\n"));
+    assert!(content.contains("0 0 0.5 rg\n"));
+    // Dark modules start after the quiet zone; only the background starts at x = 400
+    assert!(content.contains("\n408 "));
+    assert_eq!(content.matches("\n400 ").count(), 1);
+    assert!(content.contains(" 2 re\n"));
+
+    // Raster: one lossless image, no module rectangles
+    let renderer = TemplateRenderer::new(&template("raster"), create_blank_pdf(), None).unwrap();
+    let output = renderer.render(&data).unwrap();
+
+    assert_eq!(image_xobject_count(&output), 1);
+    assert!(!first_page_content(&output).contains(" 2 re\n"));
+}
+
+#[test]
+fn test_render_qrcode_promptpay_payload() {
+    let template_json = r#"{
+        "version": "2.0",
+        "template": { "source": "test.pdf" },
+        "blocks": [
+            {
+                "type": "qrcode",
+                "bind": "$.payment",
+                "payload": "promptpay",
+                "position": { "x": 400, "y": 50 },
+                "size": { "width": 80, "height": 80 }
+            }
+        ]
+    }"#;
+
+    let renderer = TemplateRenderer::new(template_json, create_blank_pdf(), None).unwrap();
+
+    let output = renderer
+        .render(&json!({ "payment": { "promptpayId": "0812345678", "amount": 120.5 } }))
+        .unwrap();
+    assert!(first_page_content(&output).contains(" re\n"));
+
+    // No payment data renders nothing; an invalid ID is an error
+    let output = renderer.render(&json!({})).unwrap();
+    assert!(!first_page_content(&output).contains(" re\n"));
+    assert!(renderer
+        .render(&json!({ "payment": { "promptpayId": "12345" } }))
+        .is_err());
+}
+
+/// Concatenated content streams of a page (1-indexed) of a rendered PDF
+fn page_content(pdf: &[u8], page: u32) -> String {
+    let doc = lopdf::Document::load_mem(pdf).unwrap();
+    let page_id = doc.get_pages()[&page];
+    String::from_utf8_lossy(&doc.get_page_content(page_id).unwrap()).to_string()
+}
+
+#[test]
+fn test_render_paginated_table() {
+    let template_json = r#"{
+        "version": "2.0",
+        "template": { "source": "test.pdf" },
+        "fonts": [{ "id": "sarabun", "regular": "fonts/THSarabunNew.ttf" }],
+        "blocks": [
+            {
+                "type": "table",
+                "bind": "$.items",
+                "position": { "x": 50, "y": 100 },
+                "font": { "family": "sarabun", "size": 12 },
+                "rowHeight": 20,
+                "bottom": 300,
+                "columns": [
+                    { "field": "name", "x": 0, "header": "Item" },
+                    { "field": "amount", "x": 200, "align": "right", "header": "Amount" }
+                ],
+                "totals": {
+                    "fields": ["amount"],
+                    "labelField": "name",
+                    "pageSubtotal": "Subtotal",
+                    "carriedForward": "Carried forward",
+                    "broughtForward": "Brought forward"
+                }
+            },
+            {
+                "type": "text",
+                "text": "Invoice",
+                "position": { "x": 50, "y": 50 },
+                "font": { "family": "sarabun", "size": 12 }
+            }
+        ]
+    }"#;
+
+    let renderer = TemplateRenderer::new(
+        template_json,
+        create_blank_pdf(),
+        Some(std::path::Path::new("../..")),
+    )
+    .unwrap();
+    let items: Vec<_> = (1..=10)
+        .map(|i| json!({ "name": format!("Item {i}"), "amount": i * 10 }))
+        .collect();
+    let output = renderer.render(&json!({ "items": items })).unwrap();
+
+    let doc = lopdf::Document::load_mem(&output).unwrap();
+    assert_eq!(doc.get_pages().len(), 2);
+
+    // Page 1: title, header, 7 rows, subtotal and carried-forward rows
+    // (two cells each); the other blocks stay on the template page only
+    assert_eq!(
+        page_content(&output, 1).matches("BT").count(),
+        1 + 2 + 14 + 2 + 2
+    );
+    // Page 2: repeated header, brought-forward row, 3 rows and subtotal
+    assert_eq!(
+        page_content(&output, 2).matches("BT").count(),
+        2 + 2 + 6 + 2
+    );
+
+    // Short tables stay on one page
+    let output = renderer
+        .render(&json!({ "items": [{ "name": "One", "amount": 1 }] }))
+        .unwrap();
+    let doc = lopdf::Document::load_mem(&output).unwrap();
+    assert_eq!(doc.get_pages().len(), 1);
+}
+
+#[test]
+fn test_render_styled_table() {
+    let template_json = r#"{
+        "version": "2.0",
+        "template": { "source": "test.pdf" },
+        "fonts": [{ "id": "sarabun", "regular": "fonts/THSarabunNew.ttf", "bold": "fonts/THSarabunNew Bold.ttf" }],
+        "blocks": [
+            {
+                "type": "table",
+                "bind": "$.items",
+                "position": { "x": 50, "y": 100 },
+                "font": { "family": "sarabun", "size": 12 },
+                "headerFont": { "family": "sarabun", "size": 12, "style": "bold" },
+                "rowHeight": 16,
+                "columns": [
+                    { "field": "name", "width": 100, "header": "Item" },
+                    { "field": "amount", "width": 60, "align": "right", "header": "Amount" }
+                ],
+                "style": {
+                    "border": "all",
+                    "padding": 4,
+                    "headerBackground": { "r": 0.8, "g": 0.8, "b": 0.8 },
+                    "zebra": { "r": 0.95, "g": 0.95, "b": 0.95 }
+                }
+            }
+        ]
+    }"#;
+
+    let renderer = TemplateRenderer::new(
+        template_json,
+        create_blank_pdf(),
+        Some(std::path::Path::new("../..")),
+    )
+    .unwrap();
+    let data = json!({ "items": [
+        { "name": "A", "amount": 1 },
+        { "name": "B", "amount": 2 },
+        { "name": "C", "amount": 3 }
+    ]});
+    let output = renderer.render(&data).unwrap();
+    let content = first_page_content(&output);
+
+    // Header fill and one zebra row
+    assert_eq!(content.matches(" re\nf\n").count(), 2);
+    assert!(content.contains("0.8 0.8 0.8 rg\n50 "));
+    assert!(content.contains(" 160 24 re\n"));
+    // Five horizontal rules (4 rows) and three column edges
+    assert_eq!(content.matches(" l\nS\n").count(), 8);
+    assert!(content.contains("\n210 "));
+    // Header in bold plus the regular body font
+    assert_eq!(content.matches("BT").count(), 8);
+    assert_eq!(embedded_font_names(&output).len(), 2);
+}
+
+#[test]
+fn test_render_table_header_font_only() {
+    let template_json = r#"{
+        "version": "2.0",
+        "template": { "source": "test.pdf" },
+        "fonts": [{ "id": "sarabun", "regular": "fonts/THSarabunNew.ttf", "bold": "fonts/THSarabunNew Bold.ttf" }],
+        "blocks": [
+            {
+                "type": "text",
+                "text": "Before",
+                "position": { "x": 50, "y": 50 },
+                "font": { "family": "sarabun", "size": 14 }
+            },
+            {
+                "type": "table",
+                "bind": "$.items",
+                "position": { "x": 50, "y": 100 },
+                "headerFont": { "family": "sarabun", "size": 12, "style": "bold" },
+                "columns": [
+                    { "field": "name", "width": 100, "header": "Item" },
+                    { "field": "amount", "width": 60, "header": "Amount" }
+                ]
+            },
+            {
+                "type": "text",
+                "text": "After",
+                "position": { "x": 50, "y": 300 }
+            }
+        ]
+    }"#;
+
+    let renderer = TemplateRenderer::new(
+        template_json,
+        create_blank_pdf(),
+        Some(std::path::Path::new("../..")),
+    )
+    .unwrap();
+    let output = renderer
+        .render(&json!({ "items": [{ "name": "A", "amount": 1 }] }))
+        .unwrap();
+    let content = first_page_content(&output);
+
+    // Only the header is bold; the body rows and the next block go back to
+    // the regular 14pt font in effect before the table
+    let fonts: Vec<&str> = content.lines().filter(|l| l.ends_with(" Tf")).collect();
+    assert_eq!(fonts.len(), 6);
+    let regular = fonts[0];
+    assert!(regular.ends_with(" 14 Tf"));
+    assert!(fonts[1] != regular && fonts[1].ends_with(" 12 Tf"));
+    assert_eq!(fonts[2], fonts[1]);
+    assert!(fonts[3..].iter().all(|font| *font == regular));
+}
+
+#[test]
+fn test_render_shape_blocks() {
+    let template_json = r##"{
+        "version": "2.0",
+        "template": {
+            "source": "test.pdf",
+            "duplicate": { "x": 0, "y": 400 }
+        },
+        "blocks": [
+            {
+                "type": "line",
+                "position": { "x": 50, "y": 100 },
+                "end": { "x": 545, "y": 100 },
+                "strokeWidth": 0.5,
+                "dash": [3, 2]
+            },
+            {
+                "type": "rect",
+                "position": { "x": 400, "y": 40 },
+                "size": { "width": 120, "height": 30 },
+                "fill": { "bind": "$.status", "map": { "paid": "#00FF00", "overdue": "#FF0000" } },
+                "enable": "$.showStatus"
+            },
+            {
+                "type": "ellipse",
+                "position": { "x": 100, "y": 200 },
+                "size": { "width": 40, "height": 20 },
+                "stroke": { "r": 0, "g": 0, "b": 1 }
+            }
+        ]
+    }"##;
+
+    let renderer = TemplateRenderer::new(template_json, create_blank_pdf(), None).unwrap();
+
+    let output = renderer
+        .render(&json!({ "status": "overdue", "showStatus": true }))
+        .unwrap();
+    let content = first_page_content(&output);
+    // Every shape is drawn twice: in place and shifted down by the duplicate offset
+    assert_eq!(content.matches("[3 2] 0 d\n").count(), 2);
+    assert!(content.contains("50 741.89"));
+    assert!(content.contains("50 341.89"));
+    assert_eq!(content.matches("1 0 0 rg\n").count(), 2);
+    assert!(content.contains("400 771.89"));
+    assert_eq!(content.matches(" 120 30 re\nf\n").count(), 2);
+    assert_eq!(content.matches("0 0 1 RG\n").count(), 2);
+    assert_eq!(content.matches(" c\n").count(), 8);
+
+    // Unmapped status draws no box; a disabled block is skipped
+    let output = renderer
+        .render(&json!({ "status": "draft", "showStatus": true }))
+        .unwrap();
+    assert!(!first_page_content(&output).contains(" rg\n"));
+    let output = renderer
+        .render(&json!({ "status": "paid", "showStatus": false }))
+        .unwrap();
+    assert!(!first_page_content(&output).contains(" re\n"));
+}
+
+/// Create a one-page PDF with a text field `name` and a checkbox `agree`
+fn create_form_pdf() -> Vec<u8> {
+    let mut doc = lopdf::Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let page_id = doc.new_object_id();
+
+    let name_id = doc.add_object(lopdf::dictionary! {
+        "Type" => "Annot",
+        "Subtype" => "Widget",
+        "T" => lopdf::Object::string_literal("name"),
+        "FT" => "Tx",
+        "P" => page_id,
+        "Rect" => vec![100.into(), 700.into(), 300.into(), 720.into()],
+    });
+    let agree_id = doc.add_object(lopdf::dictionary! {
+        "Type" => "Annot",
+        "Subtype" => "Widget",
+        "T" => lopdf::Object::string_literal("agree"),
+        "FT" => "Btn",
+        "P" => page_id,
+        "Rect" => vec![100.into(), 650.into(), 112.into(), 662.into()],
+    });
+    let contents_id = doc.add_object(lopdf::Stream::new(lopdf::dictionary! {}, vec![]));
+    doc.objects.insert(
+        page_id,
+        lopdf::dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), 595.28.into(), 841.89.into()],
+            "Resources" => lopdf::dictionary! {},
+            "Contents" => contents_id,
+            "Annots" => vec![name_id.into(), agree_id.into()],
+        }
+        .into(),
+    );
+    doc.objects.insert(
+        pages_id,
+        lopdf::dictionary! { "Type" => "Pages", "Kids" => vec![page_id.into()], "Count" => 1 }
+            .into(),
+    );
+    let catalog_id = doc.add_object(lopdf::dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+        "AcroForm" => lopdf::dictionary! { "Fields" => vec![name_id.into(), agree_id.into()] },
+    });
+    doc.trailer.set("Root", catalog_id);
+
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer).unwrap();
+    buffer
+}
+
+#[test]
+fn test_render_formfield_block() {
+    let template_json = r#"{
+        "version": "2.0",
+        "template": { "source": "form.pdf" },
+        "fonts": [{ "id": "sarabun", "regular": "fonts/THSarabunNew.ttf" }],
+        "blocks": [
+            {
+                "type": "formfield",
+                "field": "name",
+                "bind": "$.name",
+                "font": { "family": "sarabun", "size": 14 }
+            },
+            { "type": "formfield", "field": "agree", "bind": "$.agree" }
+        ]
+    }"#;
+
+    let renderer = TemplateRenderer::new(
+        template_json,
+        create_form_pdf(),
+        Some(std::path::Path::new("../..")),
+    )
+    .unwrap();
+    let output = renderer
+        .render(&json!({ "name": "สมหญิง", "agree": true }))
+        .unwrap();
+
+    let doc = template::PdfDocument::open_from_bytes(&output).unwrap();
+    let fields = doc.form_fields().unwrap();
+    assert_eq!(fields[0].value.as_deref(), Some("สมหญิง"));
+    assert_eq!(fields[1].value.as_deref(), Some("Yes"));
+    assert_eq!(embedded_font_names(&output).len(), 1);
+
+    // Flattening draws the filled values into the page and drops the form
+    let mut renderer = renderer;
+    renderer.set_flatten(Some(FlattenMode::Forms));
+    let output = renderer
+        .render(&json!({ "name": "สมหญิง", "agree": true }))
+        .unwrap();
+    let doc = template::PdfDocument::open_from_bytes(&output).unwrap();
+    assert!(doc.form_fields().unwrap().is_empty());
+    assert_eq!(first_page_content(&output).matches(" Do\n").count(), 2);
+
+    // Unknown field names are reported
+    let template_json = template_json.replace("\"field\": \"agree\"", "\"field\": \"consent\"");
+    let renderer = TemplateRenderer::new(
+        &template_json,
+        create_form_pdf(),
+        Some(std::path::Path::new("../..")),
+    )
+    .unwrap();
+    assert!(renderer.render(&json!({ "agree": true })).is_err());
+}
+
+#[test]
+fn test_render_field_block() {
+    let template_json = r#"{
+        "version": "2.0",
+        "template": { "source": "blank.pdf" },
+        "fonts": [{ "id": "sarabun", "regular": "fonts/THSarabunNew.ttf" }],
+        "blocks": [
+            {
+                "type": "field",
+                "name": "remarks",
+                "position": { "x": 50, "y": 100 },
+                "size": { "width": 300, "height": 60 },
+                "multiline": true,
+                "bind": "$.remarks",
+                "font": { "family": "sarabun", "size": 14 }
+            },
+            {
+                "type": "field",
+                "name": "agree",
+                "fieldType": "checkbox",
+                "position": { "x": 50, "y": 200 },
+                "size": { "width": 12, "height": 12 },
+                "bind": "$.agree"
+            },
+            {
+                "type": "field",
+                "name": "gender",
+                "fieldType": "radio",
+                "position": { "x": 50, "y": 250 },
+                "size": { "width": 12, "height": 12 },
+                "options": [
+                    { "value": "M", "offset": { "x": 0, "y": 0 } },
+                    { "value": "F", "offset": { "x": 60, "y": 0 } }
+                ],
+                "bind": "$.gender"
+            },
+            {
+                "type": "field",
+                "name": "signature",
+                "fieldType": "signature",
+                "position": { "x": 300, "y": 700 },
+                "size": { "width": 200, "height": 50 },
+                "readOnly": true
+            }
+        ]
+    }"#;
+
+    let renderer = TemplateRenderer::new(
+        template_json,
+        create_blank_pdf(),
+        Some(std::path::Path::new("../..")),
+    )
+    .unwrap();
+    let output = renderer
+        .render(&json!({ "remarks": "ส่งภายในวันที่ 5", "agree": true, "gender": "F" }))
+        .unwrap();
+
+    let doc = template::PdfDocument::open_from_bytes(&output).unwrap();
+    let fields: Vec<(String, Option<String>, usize)> = doc
+        .form_fields()
+        .unwrap()
+        .into_iter()
+        .map(|f| (f.name, f.value, f.widgets.len()))
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            ("remarks".to_string(), Some("ส่งภายในวันที่ 5".to_string()), 1),
+            ("agree".to_string(), Some("Yes".to_string()), 1),
+            ("gender".to_string(), Some("F".to_string()), 2),
+            ("signature".to_string(), None, 1),
+        ]
+    );
+    // The content subset and the whole font for editing in /DR
+    let fonts = embedded_font_names(&output);
+    assert_eq!(fonts.len(), 2);
+    assert_eq!(fonts[0], fonts[1]);
+
+    // Without data the fields stay empty and editable
+    let output = renderer.render(&json!({})).unwrap();
+    let doc = template::PdfDocument::open_from_bytes(&output).unwrap();
+    let fields = doc.form_fields().unwrap();
+    assert_eq!(fields[0].value, None);
+    assert_eq!(fields[1].value.as_deref(), Some("Off"));
+}
+
+#[test]
+fn test_render_checkbox_block() {
+    let template_json = r##"{
+        "version": "2.0",
+        "template": { "source": "blank.pdf" },
+        "blocks": [
+            {
+                "type": "checkbox",
+                "bind": "$.gender",
+                "position": { "x": 100, "y": 200 },
+                "mark": "cross",
+                "options": [
+                    { "value": "M", "offset": { "x": 0, "y": 0 } },
+                    { "value": "F", "offset": { "x": 50, "y": 0 } }
+                ]
+            },
+            {
+                "type": "checkbox",
+                "bind": "$.services",
+                "position": { "x": 100, "y": 300 },
+                "mark": "fill",
+                "options": [
+                    { "value": "a", "offset": { "x": 0, "y": 0 } },
+                    { "value": "b", "offset": { "x": 0, "y": 20 } },
+                    { "value": "c", "offset": { "x": 0, "y": 40 } }
+                ]
+            },
+            {
+                "type": "checkbox",
+                "bind": "$.agree",
+                "position": { "x": 300, "y": 400 },
+                "size": 20,
+                "color": "#FF0000"
+            }
+        ]
+    }"##;
+
+    let renderer = TemplateRenderer::new(template_json, create_blank_pdf(), None).unwrap();
+
+    let output = renderer
+        .render(&json!({ "gender": "F", "services": ["a", "c"], "agree": true }))
+        .unwrap();
+    let content = page_content(&output, 1);
+    let move_x: Vec<&str> = content
+        .lines()
+        .filter(|line| line.ends_with(" m"))
+        .filter_map(|line| line.split(' ').next())
+        .collect();
+    // Cross in the second box only, then the tick of the boolean box
+    assert_eq!(move_x, vec!["152", "152", "304"]);
+    assert!(content.contains("1 0 0 RG"));
+    // Two filled squares
+    assert_eq!(content.matches(" re\n").count(), 2);
+
+    let output = renderer
+        .render(&json!({ "gender": "X", "services": [], "agree": false }))
+        .unwrap();
+    let content = page_content(&output, 1);
+    assert!(!content.contains(" m\n"));
+    assert!(!content.contains(" re\n"));
+}
+
+#[test]
+fn test_render_rotated_blocks() {
+    let template_json = r#"{
+        "version": "2.0",
+        "template": { "source": "blank.pdf" },
+        "fonts": [{ "id": "sarabun", "regular": "fonts/THSarabunNew.ttf" }],
+        "blocks": [
+            {
+                "type": "text",
+                "text": "สำเนา",
+                "position": { "x": 30, "y": 500 },
+                "font": { "family": "sarabun", "size": 14 },
+                "rotation": 90
+            },
+            {
+                "type": "rect",
+                "position": { "x": 100, "y": 100 },
+                "size": { "width": 50, "height": 20 },
+                "rotation": 0
+            },
+            {
+                "type": "rect",
+                "position": { "x": 100, "y": 200 },
+                "size": { "width": 50, "height": 20 }
+            }
+        ]
+    }"#;
+
+    let renderer = TemplateRenderer::new(
+        template_json,
+        create_blank_pdf(),
+        Some(std::path::Path::new("../..")),
+    )
+    .unwrap();
+    let output = renderer.render(&json!({})).unwrap();
+    let content = page_content(&output, 1);
+
+    // Only the rotated text gets a matrix, turning around its position
+    assert_eq!(content.matches(" cm\n").count(), 1);
+    assert!(content.contains("q\n0 1 -1 0 371.89"));
+    let rotated = content.find(" cm\n").unwrap();
+    assert!(content[rotated..].contains("TJ"));
+    assert_eq!(content.matches(" re\n").count(), 2);
+}
+
+#[test]
+fn test_render_watermark() {
+    let template_json = r##"{
+        "version": "2.0",
+        "template": { "source": "blank.pdf" },
+        "fonts": [{ "id": "sarabun", "regular": "fonts/THSarabunNew.ttf" }],
+        "blocks": [
+            {
+                "type": "rect",
+                "position": { "x": 100, "y": 100 },
+                "size": { "width": 50, "height": 20 }
+            }
+        ],
+        "watermark": {
+            "bind": "$.stamp",
+            "color": "#FF0000",
+            "opacity": 0.2,
+            "layer": "underlay",
+            "enable": "$.draft"
+        }
+    }"##;
+
+    let renderer = TemplateRenderer::new(
+        template_json,
+        create_blank_pdf(),
+        Some(std::path::Path::new("../..")),
+    )
+    .unwrap();
+
+    let output = renderer
+        .render(&json!({ "stamp": "ร่าง", "draft": true }))
+        .unwrap();
+    let content = page_content(&output, 1);
+    // Red text at 45°, painted before the rect
+    let text = content.find("1 0 0 rg").expect("No watermark");
+    assert!(text < content.find(" re\n").unwrap());
+    assert!(content.contains("0.707107 0.707107 -0.707107 0.707107 "));
+    assert!(content.contains("/F1 72 Tf"));
+
+    let output = renderer
+        .render(&json!({ "stamp": "ร่าง", "draft": false }))
+        .unwrap();
+    let content = page_content(&output, 1);
+    assert!(!content.contains(" cm\n"));
+
+    // The fallback font must have been loaded
+    let renderer = TemplateRenderer::new(template_json, create_blank_pdf(), None).unwrap();
+    let err = renderer
+        .render(&json!({ "stamp": "ร่าง", "draft": true }))
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Watermark font not loaded: sarabun"));
+}
+
+#[test]
+fn test_render_batch() {
+    let template_json = r#"{
+        "version": "2.0",
+        "template": { "source": "blank.pdf" },
+        "fonts": [{ "id": "sarabun", "regular": "fonts/THSarabunNew.ttf" }],
+        "blocks": [
+            {
+                "type": "text",
+                "bind": "$.name",
+                "position": { "x": 100, "y": 100 },
+                "font": { "family": "sarabun", "size": 14 }
+            }
+        ]
+    }"#;
+
+    let renderer = TemplateRenderer::new(
+        template_json,
+        create_blank_pdf(),
+        Some(std::path::Path::new("../..")),
+    )
+    .unwrap();
+
+    let records = vec![
+        json!({ "name": "สมชาย" }),
+        json!({ "name": "สมหญิง" }),
+        json!({ "name": "Alice" }),
+    ];
+    let mut doc = renderer.render_batch(&records).unwrap();
+    assert_eq!(doc.page_count(), 3);
+
+    let output = doc.to_bytes().unwrap();
+    let saved = lopdf::Document::load_mem(&output).unwrap();
+    let font_files = saved
+        .objects
+        .values()
+        .filter_map(|object| object.as_dict().ok())
+        .filter(|dict| dict.has(b"FontFile2"))
+        .count();
+    assert_eq!(font_files, 1);
+    for page in 1..=3 {
+        assert!(page_content(&output, page).contains("TJ"));
+    }
+
+    assert!(renderer.render_batch(&[]).is_err());
+}
+
+#[test]
+fn test_render_pdfpage_block() {
+    let template_json = r#"{
+        "version": "2.0",
+        "template": { "source": "blank.pdf" },
+        "blocks": [
+            {
+                "type": "rect",
+                "position": { "x": 100, "y": 100 },
+                "size": { "width": 50, "height": 20 }
+            },
+            {
+                "type": "pdfpage",
+                "source": "letterhead",
+                "position": { "x": 0, "y": 0 },
+                "layer": "underlay"
+            },
+            {
+                "type": "pdfpage",
+                "bind": "$.approval",
+                "position": { "x": 300, "y": 500 },
+                "size": { "width": 100, "height": 100 }
+            }
+        ]
+    }"#;
+
+    let mut renderer = TemplateRenderer::new(template_json, create_blank_pdf(), None).unwrap();
+    renderer.add_pdf_source("letterhead", create_blank_pdf());
+    renderer.add_pdf_source("signed", create_blank_pdf());
+
+    let output = renderer.render(&json!({ "approval": "signed" })).unwrap();
+    let content = page_content(&output, 1);
+    // Both blocks draw the same imported page: full size under the rect,
+    // and fitted into 100 x 100 over it
+    assert_eq!(content.matches(" Do\n").count(), 2);
+    let underlay = content.find("1 0 0 1 0 0 cm").expect("No underlay");
+    let rect = content.find(" re\n").unwrap();
+    assert!(underlay < rect);
+    assert!(content[rect..].contains("0.118780"));
+
+    let output = renderer.render(&json!({})).unwrap();
+    assert_eq!(page_content(&output, 1).matches(" Do\n").count(), 1);
+
+    let output = renderer.render(&json!({ "approval": "missing" }));
+    assert!(output.is_err());
+
+    // A page without size cannot be fitted
+    let mut empty = lopdf::Document::load_mem(&create_blank_pdf()).unwrap();
+    let page_id = empty.page_iter().next().unwrap();
+    empty
+        .get_dictionary_mut(page_id)
+        .unwrap()
+        .set("MediaBox", vec![0.into(), 0.into(), 0.into(), 0.into()]);
+    let mut empty_data = Vec::new();
+    empty.save_to(&mut empty_data).unwrap();
+    renderer.add_pdf_source("signed", empty_data);
+    let err = renderer
+        .render(&json!({ "approval": "signed" }))
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("PDF source signed page 1 has no size"));
+}
+
+#[test]
+fn test_render_without_base_pdf() {
+    let template_json = r#"{
+        "version": "2.0",
+        "template": {
+            "pages": [
+                {
+                    "size": "a5",
+                    "orientation": "landscape",
+                    "margins": { "top": 30, "right": 30, "bottom": 20, "left": 40 }
+                },
+                { "width": 288, "height": 432 }
+            ]
+        },
+        "blocks": [
+            {
+                "type": "rect",
+                "position": { "x": 10, "y": 10 },
+                "size": { "width": 50, "height": 20 },
+                "pages": [1, 2]
+            }
+        ]
+    }"#;
+
+    let renderer = TemplateRenderer::new(template_json, Vec::new(), None).unwrap();
+    let output = renderer.render(&json!({})).unwrap();
+
+    let doc = lopdf::Document::load_mem(&output).unwrap();
+    let media_boxes: Vec<Vec<f32>> = doc
+        .page_iter()
+        .map(|id| {
+            doc.get_dictionary(id)
+                .unwrap()
+                .get(b"MediaBox")
+                .unwrap()
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|v| v.as_float().unwrap())
+                .collect()
+        })
+        .collect();
+    assert_eq!(
+        media_boxes,
+        vec![vec![0.0, 0.0, 595.28, 419.53], vec![0.0, 0.0, 288.0, 432.0]]
+    );
+
+    // Positions on the first page start inside the margins
+    assert!(page_content(&output, 1).starts_with("q\n1 0 0 1 40 20 cm\n"));
+    assert!(page_content(&output, 2).contains("10 402 50 20 re"));
+
+    // Without a base PDF, pages must be declared
+    let template_json = r#"{ "version": "2.0", "template": {}, "blocks": [] }"#;
+    let renderer = TemplateRenderer::new(template_json, Vec::new(), None).unwrap();
+    assert!(renderer.render(&json!({})).is_err());
+
+    // A custom size needs both dimensions
+    let template_json =
+        r#"{ "version": "2.0", "template": { "pages": [{ "width": 288 }] }, "blocks": [] }"#;
+    let renderer = TemplateRenderer::new(template_json, Vec::new(), None).unwrap();
+    let error = renderer.render(&json!({})).unwrap_err().to_string();
+    assert!(error.contains("needs both width and height"), "{error}");
+}
+
+#[test]
+fn test_render_declared_page_duplicate() {
+    let template_json = r#"{
+        "version": "2.0",
+        "template": {
+            "pages": [{ "size": "a5", "margins": { "top": 30, "left": 40, "bottom": 20 } }],
+            "duplicate": { "page": 2 }
+        },
+        "blocks": [
+            {
+                "type": "rect",
+                "position": { "x": 10, "y": 10 },
+                "size": { "width": 50, "height": 20 },
+                "pages": [1]
+            }
+        ]
+    }"#;
+
+    let renderer = TemplateRenderer::new(template_json, Vec::new(), None).unwrap();
+    let output = renderer.render(&json!({})).unwrap();
+
+    // The duplicated copy sits inside the same margins as the original
+    let original = page_content(&output, 1);
+    let copy = page_content(&output, 2);
+    assert!(original.starts_with("q\n1 0 0 1 40 20 cm\n"), "{original}");
+    assert_eq!(original, copy);
+}
@@ -6,11 +6,12 @@
 //! - Thai number formatting (หนึ่ง, สอง, สาม...)
 //! - Thai currency formatting (บาท, สตางค์)
 //! - Thai date formatting (Buddhist calendar)
+//! - PromptPay QR payloads (EMVCo TLV with CRC16)
 //!
 //! # Example
 //!
 //! ```ignore
-//! use thai_text::{ThaiWordcut, ThaiFormatter};
+//! use thai_text::{PromptPay, ThaiWordcut, ThaiFormatter};
 //!
 //! // Word segmentation
 //! let wordcut = ThaiWordcut::from_file("dict.txt")?;
@@ -22,15 +23,21 @@
 //! // Number formatting
 //! let text = ThaiFormatter::format_number(42);  // "สี่สิบสอง"
 //! let baht = ThaiFormatter::format_baht(100.50); // "หนึ่งร้อยบาทห้าสิบสตางค์"
+//!
+//! // PromptPay QR payload
+//! let payload = PromptPay::from_id("0812345678")?.with_amount(100.0).payload()?;
 //! ```
 
 mod dictionary;
 mod formatter;
 mod linebreak;
+mod promptpay;
 mod wordcut;
 
 pub use dictionary::{Dictionary, EMBEDDED_DICT};
 pub use formatter::ThaiFormatter;
+pub use linebreak::{is_above_below_mark, is_thai_char};
+pub use promptpay::{crc16, parse_tlv, PromptPay, PromptPayTarget};
 pub use wordcut::ThaiWordcut;
 
 // Re-export commonly used formatting functions
@@ -50,6 +57,9 @@ pub enum ThaiTextError {
     #[error("Invalid date: {0}")]
     InvalidDate(String),
 
+    #[error("Invalid PromptPay data: {0}")]
+    InvalidPromptPay(String),
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 }
@@ -0,0 +1,488 @@
+//! Thai PromptPay QR payloads (EMVCo merchant-presented mode)
+//!
+//! Builds and parses the TLV payload encoded in PromptPay QR codes:
+//! credit transfer to a phone number, national ID / tax ID or e-wallet,
+//! and bill payment to a biller ID with references, with an optional amount.
+
+use crate::{Result, ThaiTextError};
+
+/// Application ID for PromptPay credit transfer
+const AID_CREDIT_TRANSFER: &str = "A000000677010111";
+
+/// Application ID for PromptPay bill payment
+const AID_BILL_PAYMENT: &str = "A000000677010112";
+
+/// ISO 4217 numeric code for Thai Baht
+const CURRENCY_THB: &str = "764";
+
+/// Payload tags used by PromptPay
+mod tag {
+    pub const PAYLOAD_FORMAT: &str = "00";
+    pub const POINT_OF_INITIATION: &str = "01";
+    pub const CREDIT_TRANSFER: &str = "29";
+    pub const BILL_PAYMENT: &str = "30";
+    pub const CURRENCY: &str = "53";
+    pub const AMOUNT: &str = "54";
+    pub const COUNTRY: &str = "58";
+    pub const CRC: &str = "63";
+}
+
+/// Who receives a PromptPay payment
+#[derive(Debug, Clone, PartialEq)]
+pub enum PromptPayTarget {
+    /// Mobile phone number in local format (e.g. "0812345678")
+    Phone(String),
+    /// 13-digit national ID or tax ID
+    NationalId(String),
+    /// 15-digit e-wallet ID
+    EWallet(String),
+    /// Bill payment to a 15-digit biller ID with references
+    Biller {
+        biller_id: String,
+        ref1: String,
+        ref2: Option<String>,
+    },
+}
+
+/// PromptPay QR payload
+///
+/// # Example
+/// ```ignore
+/// let payload = PromptPay::from_id("081-234-5678")?
+///     .with_amount(150.0)
+///     .payload()?;
+///
+/// let parsed = PromptPay::parse(&payload)?;
+/// assert_eq!(parsed.amount, Some(150.0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptPay {
+    /// Payment target
+    pub target: PromptPayTarget,
+    /// Amount in Baht (None lets the payer enter it)
+    pub amount: Option<f64>,
+}
+
+impl PromptPay {
+    /// Create a payload for a target without amount
+    pub fn new(target: PromptPayTarget) -> Self {
+        Self {
+            target,
+            amount: None,
+        }
+    }
+
+    /// Create a credit transfer payload from a PromptPay ID
+    ///
+    /// The ID type is detected from its digits: 10 digits (or 11 starting with
+    /// "66") is a phone number, 13 a national ID / tax ID and 15 an e-wallet.
+    /// Spaces, dashes and a leading '+' are ignored.
+    ///
+    /// # Arguments
+    /// * `id` - Phone number, national ID or e-wallet ID
+    pub fn from_id(id: &str) -> Result<Self> {
+        let digits: String = id
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '-' | '+'))
+            .collect();
+        if !is_digits(&digits) {
+            return Err(invalid(format!("PromptPay ID must be digits: {id}")));
+        }
+
+        let target = match digits.len() {
+            10 if digits.starts_with('0') => PromptPayTarget::Phone(digits),
+            11 if digits.starts_with("66") => PromptPayTarget::Phone(format!("0{}", &digits[2..])),
+            13 => PromptPayTarget::NationalId(digits),
+            15 => PromptPayTarget::EWallet(digits),
+            _ => {
+                return Err(invalid(format!(
+                    "Unrecognized PromptPay ID: {id} (expected phone, national ID or e-wallet)"
+                )))
+            }
+        };
+
+        Ok(Self::new(target))
+    }
+
+    /// Create a bill payment payload
+    ///
+    /// # Arguments
+    /// * `biller_id` - 15-digit biller ID (tax ID + suffix)
+    /// * `ref1` - Reference 1 (required by the biller)
+    /// * `ref2` - Optional reference 2
+    pub fn biller(biller_id: &str, ref1: &str, ref2: Option<&str>) -> Result<Self> {
+        let payment = Self::new(PromptPayTarget::Biller {
+            biller_id: biller_id.to_string(),
+            ref1: ref1.to_uppercase(),
+            ref2: ref2.filter(|r| !r.is_empty()).map(str::to_uppercase),
+        });
+        payment.validate()?;
+        Ok(payment)
+    }
+
+    /// Set the amount in Baht
+    pub fn with_amount(mut self, amount: f64) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    /// Build the EMVCo payload string, including CRC
+    pub fn payload(&self) -> Result<String> {
+        self.validate()?;
+
+        let mut out = String::new();
+        push_tlv(&mut out, tag::PAYLOAD_FORMAT, "01");
+        // 11 = static (reusable), 12 = dynamic (one payment with amount)
+        let initiation = if self.amount.is_some() { "12" } else { "11" };
+        push_tlv(&mut out, tag::POINT_OF_INITIATION, initiation);
+
+        let mut account = String::new();
+        let account_tag = match &self.target {
+            PromptPayTarget::Phone(phone) => {
+                push_tlv(&mut account, "00", AID_CREDIT_TRANSFER);
+                push_tlv(&mut account, "01", &format!("0066{}", &phone[1..]));
+                tag::CREDIT_TRANSFER
+            }
+            PromptPayTarget::NationalId(id) => {
+                push_tlv(&mut account, "00", AID_CREDIT_TRANSFER);
+                push_tlv(&mut account, "02", id);
+                tag::CREDIT_TRANSFER
+            }
+            PromptPayTarget::EWallet(id) => {
+                push_tlv(&mut account, "00", AID_CREDIT_TRANSFER);
+                push_tlv(&mut account, "03", id);
+                tag::CREDIT_TRANSFER
+            }
+            PromptPayTarget::Biller {
+                biller_id,
+                ref1,
+                ref2,
+            } => {
+                push_tlv(&mut account, "00", AID_BILL_PAYMENT);
+                push_tlv(&mut account, "01", biller_id);
+                push_tlv(&mut account, "02", ref1);
+                if let Some(ref2) = ref2 {
+                    push_tlv(&mut account, "03", ref2);
+                }
+                tag::BILL_PAYMENT
+            }
+        };
+        push_tlv(&mut out, account_tag, &account);
+
+        push_tlv(&mut out, tag::CURRENCY, CURRENCY_THB);
+        if let Some(amount) = self.amount {
+            push_tlv(&mut out, tag::AMOUNT, &format!("{amount:.2}"));
+        }
+        push_tlv(&mut out, tag::COUNTRY, "TH");
+
+        out.push_str(tag::CRC);
+        out.push_str("04");
+        let crc = crc16(out.as_bytes());
+        out.push_str(&format!("{crc:04X}"));
+
+        Ok(out)
+    }
+
+    /// Parse and validate a PromptPay payload
+    ///
+    /// Checks the CRC, payload format, currency and account information.
+    ///
+    /// # Arguments
+    /// * `payload` - Payload string read from a QR code
+    pub fn parse(payload: &str) -> Result<Self> {
+        let payload = payload.trim();
+        let (body, crc) = payload
+            .len()
+            .checked_sub(4)
+            .filter(|&i| payload.is_char_boundary(i))
+            .map(|i| payload.split_at(i))
+            .ok_or_else(|| invalid("Payload is too short".to_string()))?;
+        if !body.ends_with("6304") {
+            return Err(invalid("Payload must end with a CRC field".to_string()));
+        }
+        let expected = format!("{:04X}", crc16(body.as_bytes()));
+        if !crc.eq_ignore_ascii_case(&expected) {
+            return Err(invalid(format!(
+                "CRC mismatch: payload has {crc}, expected {expected}"
+            )));
+        }
+
+        let fields = parse_tlv(payload)?;
+        let get = |t: &str| fields.iter().find(|(k, _)| *k == t).map(|(_, v)| *v);
+
+        if get(tag::PAYLOAD_FORMAT) != Some("01") {
+            return Err(invalid("Unsupported payload format".to_string()));
+        }
+        if let Some(currency) = get(tag::CURRENCY) {
+            if currency != CURRENCY_THB {
+                return Err(invalid(format!("Unsupported currency: {currency}")));
+            }
+        }
+
+        let target = if let Some(account) = get(tag::CREDIT_TRANSFER) {
+            let account = parse_tlv(account)?;
+            let sub = |t: &str| account.iter().find(|(k, _)| *k == t).map(|(_, v)| *v);
+            if sub("00") != Some(AID_CREDIT_TRANSFER) {
+                return Err(invalid("Unknown credit transfer AID".to_string()));
+            }
+            if let Some(phone) = sub("01") {
+                let local = phone
+                    .strip_prefix("0066")
+                    .ok_or_else(|| invalid(format!("Invalid phone number: {phone}")))?;
+                PromptPayTarget::Phone(format!("0{local}"))
+            } else if let Some(id) = sub("02") {
+                PromptPayTarget::NationalId(id.to_string())
+            } else if let Some(id) = sub("03") {
+                PromptPayTarget::EWallet(id.to_string())
+            } else {
+                return Err(invalid("Credit transfer has no PromptPay ID".to_string()));
+            }
+        } else if let Some(account) = get(tag::BILL_PAYMENT) {
+            let account = parse_tlv(account)?;
+            let sub = |t: &str| account.iter().find(|(k, _)| *k == t).map(|(_, v)| *v);
+            if sub("00") != Some(AID_BILL_PAYMENT) {
+                return Err(invalid("Unknown bill payment AID".to_string()));
+            }
+            PromptPayTarget::Biller {
+                biller_id: sub("01").unwrap_or_default().to_string(),
+                ref1: sub("02").unwrap_or_default().to_string(),
+                ref2: sub("03").map(str::to_string),
+            }
+        } else {
+            return Err(invalid("Payload has no PromptPay account".to_string()));
+        };
+
+        let amount = get(tag::AMOUNT)
+            .map(|a| {
+                a.parse::<f64>()
+                    .map_err(|_| invalid(format!("Invalid amount: {a}")))
+            })
+            .transpose()?;
+
+        let payment = Self { target, amount };
+        payment.validate()?;
+        Ok(payment)
+    }
+
+    /// Check field formats and lengths
+    fn validate(&self) -> Result<()> {
+        match &self.target {
+            PromptPayTarget::Phone(phone) => {
+                if phone.len() != 10 || !phone.starts_with('0') || !is_digits(phone) {
+                    return Err(invalid(format!("Invalid phone number: {phone}")));
+                }
+            }
+            PromptPayTarget::NationalId(id) => {
+                if id.len() != 13 || !is_digits(id) {
+                    return Err(invalid(format!("National ID must be 13 digits: {id}")));
+                }
+            }
+            PromptPayTarget::EWallet(id) => {
+                if id.len() != 15 || !is_digits(id) {
+                    return Err(invalid(format!("E-wallet ID must be 15 digits: {id}")));
+                }
+            }
+            PromptPayTarget::Biller {
+                biller_id,
+                ref1,
+                ref2,
+            } => {
+                if biller_id.len() != 15 || !is_digits(biller_id) {
+                    return Err(invalid(format!("Biller ID must be 15 digits: {biller_id}")));
+                }
+                if ref1.is_empty() {
+                    return Err(invalid("Bill payment requires ref1".to_string()));
+                }
+                for reference in std::iter::once(ref1).chain(ref2) {
+                    if reference.len() > 20
+                        || !reference
+                            .chars()
+                            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+                    {
+                        return Err(invalid(format!(
+                            "Reference must be up to 20 letters or digits: {reference}"
+                        )));
+                    }
+                }
+            }
+        }
+
+        if let Some(amount) = self.amount {
+            // Amount field holds at most 13 characters ("9999999999.99")
+            if !amount.is_finite() || amount <= 0.0 || amount >= 1e10 {
+                return Err(invalid(format!("Invalid amount: {amount}")));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Calculate CRC-16/CCITT-FALSE (polynomial 0x1021, initial 0xFFFF)
+///
+/// This is the checksum used by EMVCo QR payloads (tag 63).
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Split an EMVCo TLV string into (tag, value) pairs
+///
+/// # Arguments
+/// * `data` - Concatenated fields of 2-digit tag, 2-digit length and value
+pub fn parse_tlv(data: &str) -> Result<Vec<(&str, &str)>> {
+    let mut fields = Vec::new();
+    let mut rest = data;
+
+    while !rest.is_empty() {
+        let header = rest
+            .get(..4)
+            .ok_or_else(|| invalid(format!("Truncated field: {rest}")))?;
+        // Byte slices within the header may split a non-ASCII character
+        let (tag, len) = header
+            .get(..2)
+            .zip(header.get(2..))
+            .ok_or_else(|| invalid(format!("Invalid field header: {header}")))?;
+        let len: usize = len
+            .parse()
+            .map_err(|_| invalid(format!("Invalid field length: {header}")))?;
+        let value = rest
+            .get(4..4 + len)
+            .ok_or_else(|| invalid(format!("Field {tag} is truncated")))?;
+        fields.push((tag, value));
+        rest = &rest[4 + len..];
+    }
+
+    Ok(fields)
+}
+
+/// Append a TLV field
+fn push_tlv(out: &mut String, tag: &str, value: &str) {
+    out.push_str(tag);
+    out.push_str(&format!("{:02}", value.len()));
+    out.push_str(value);
+}
+
+fn is_digits(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_digit())
+}
+
+fn invalid(message: String) -> ThaiTextError {
+    ThaiTextError::InvalidPromptPay(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc16() {
+        assert_eq!(crc16(b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn test_phone_payload() {
+        let payload = PromptPay::from_id("081-234-5678")
+            .unwrap()
+            .payload()
+            .unwrap();
+
+        assert_eq!(
+            &payload[..payload.len() - 4],
+            "00020101021129370016A000000677010111011300668123456785303764\
+             5802TH6304"
+        );
+        let parsed = PromptPay::parse(&payload).unwrap();
+        assert_eq!(
+            parsed.target,
+            PromptPayTarget::Phone("0812345678".to_string())
+        );
+        assert_eq!(parsed.amount, None);
+    }
+
+    #[test]
+    fn test_national_id_with_amount() {
+        let payment = PromptPay::from_id("1234567890123")
+            .unwrap()
+            .with_amount(1500.5);
+        let payload = payment.payload().unwrap();
+
+        assert!(payload.starts_with("000201010212"));
+        assert!(payload.contains("02131234567890123"));
+        assert!(payload.contains("54071500.50"));
+        assert_eq!(PromptPay::parse(&payload).unwrap(), payment);
+    }
+
+    #[test]
+    fn test_ewallet_and_country_code_phone() {
+        assert_eq!(
+            PromptPay::from_id("123456789012345").unwrap().target,
+            PromptPayTarget::EWallet("123456789012345".to_string())
+        );
+        assert_eq!(
+            PromptPay::from_id("+66812345678").unwrap().target,
+            PromptPayTarget::Phone("0812345678".to_string())
+        );
+        assert!(PromptPay::from_id("12345").is_err());
+        assert!(PromptPay::from_id("08123456ab").is_err());
+    }
+
+    #[test]
+    fn test_biller_payload() {
+        let payment = PromptPay::biller("010556012345601", "inv001", Some("A1"))
+            .unwrap()
+            .with_amount(99.0);
+        let payload = payment.payload().unwrap();
+
+        assert!(payload.contains("0016A000000677010112"));
+        assert!(payload.contains("0115010556012345601"));
+        assert!(payload.contains("0206INV001"));
+        assert!(payload.contains("0302A1"));
+        assert_eq!(PromptPay::parse(&payload).unwrap(), payment);
+
+        assert!(PromptPay::biller("123", "INV001", None).is_err());
+        assert!(PromptPay::biller("010556012345601", "", None).is_err());
+        assert!(PromptPay::biller("010556012345601", "INV-001", None).is_err());
+    }
+
+    #[test]
+    fn test_invalid_amount() {
+        let payment = PromptPay::from_id("0812345678").unwrap();
+        assert!(payment.clone().with_amount(0.0).payload().is_err());
+        assert!(payment.with_amount(1e12).payload().is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_crc() {
+        let payload = PromptPay::from_id("0812345678").unwrap().payload().unwrap();
+        let mut tampered = payload.clone();
+        tampered.replace_range(payload.len() - 4.., "0000");
+
+        assert!(PromptPay::parse(&tampered).is_err());
+        assert!(PromptPay::parse("0002").is_err());
+        assert!(PromptPay::parse("").is_err());
+    }
+
+    #[test]
+    fn test_parse_tlv() {
+        let fields = parse_tlv("000201010211").unwrap();
+        assert_eq!(fields, vec![("00", "01"), ("01", "11")]);
+        assert!(parse_tlv("0005ab").is_err());
+
+        // Non-ASCII in the header is an error, not a panic
+        assert!(parse_tlv("0é0102").is_err());
+        assert!(parse_tlv("00é1").is_err());
+        assert!(parse_tlv("0004ไทย").is_err());
+        assert_eq!(parse_tlv("0009ไทย").unwrap(), vec![("00", "ไทย")]);
+    }
+}
@@ -1,5 +1,6 @@
 //! Thai word segmentation using longest matching algorithm
 
+use crate::linebreak::is_thai_char;
 use crate::{Dictionary, Result, ThaiTextError};
 use std::path::Path;
 use std::str::FromStr;
@@ -132,6 +133,88 @@ impl ThaiWordcut {
         lines
     }
 
+    /// Word wrap text so each line fits a measured width
+    ///
+    /// Break points come from `segment()`; runs of non-Thai characters
+    /// (e.g. Latin words, numbers) are kept together. Each candidate line is
+    /// measured as a whole, so kerning and zero-width marks are accounted
+    /// for. A single word wider than `max_width` is placed on its own line.
+    ///
+    /// # Arguments
+    /// * `text` - Text to wrap
+    /// * `max_width` - Maximum line width (same unit as `measure`)
+    /// * `measure` - Returns the rendered width of a string
+    ///
+    /// # Returns
+    /// Vector of lines
+    ///
+    /// # Example
+    /// ```ignore
+    /// let lines = wordcut.word_wrap_width(text, 200.0, |s| doc.get_text_width(s).unwrap_or(0.0));
+    /// ```
+    pub fn word_wrap_width<F>(&self, text: &str, max_width: f64, mut measure: F) -> Vec<String>
+    where
+        F: FnMut(&str) -> f64,
+    {
+        if max_width <= 0.0 {
+            return vec![text.to_string()];
+        }
+
+        let mut lines = Vec::new();
+        let mut current_line = String::new();
+
+        for word in self.break_units(text) {
+            if current_line.is_empty() {
+                // Don't start a line with whitespace
+                if word.trim().is_empty() {
+                    continue;
+                }
+                current_line = word;
+                continue;
+            }
+
+            let candidate = format!("{current_line}{word}");
+            if measure(candidate.trim_end()) <= max_width {
+                current_line = candidate;
+            } else {
+                lines.push(current_line.trim_end().to_string());
+                current_line = if word.trim().is_empty() {
+                    String::new()
+                } else {
+                    word
+                };
+            }
+        }
+
+        let last = current_line.trim_end();
+        if !last.is_empty() {
+            lines.push(last.to_string());
+        }
+
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+
+        lines
+    }
+
+    /// Segment text into unbreakable units for line wrapping
+    ///
+    /// Merges adjacent non-Thai, non-whitespace segments so Latin words and
+    /// numbers are not split character by character.
+    fn break_units(&self, text: &str) -> Vec<String> {
+        let is_run = |word: &str| word.chars().all(|c| !is_thai_char(c) && !c.is_whitespace());
+
+        let mut units: Vec<String> = Vec::new();
+        for word in self.segment(text) {
+            match units.last_mut() {
+                Some(last) if is_run(last) && is_run(&word) => last.push_str(&word),
+                _ => units.push(word),
+            }
+        }
+        units
+    }
+
     /// Get reference to the dictionary
     pub fn dictionary(&self) -> &Dictionary {
         &self.dict
@@ -200,6 +283,38 @@ mod tests {
         assert_eq!(lines[0], "สวัสดี");
     }
 
+    #[test]
+    fn test_word_wrap_width() {
+        let wordcut = create_test_wordcut();
+        // Measure with zero-width combining marks, like a real font
+        let measure = |s: &str| {
+            s.chars()
+                .filter(|&c| !crate::linebreak::is_above_below_mark(c))
+                .count() as f64
+        };
+        let lines = wordcut.word_wrap_width("สวัสดีครับประเทศไทย", 9.0, measure);
+
+        // สวัสดี (4 wide) + ครับ (3 wide) fits in 9 even though it is 10 chars
+        assert_eq!(lines, vec!["สวัสดีครับ", "ประเทศไทย"]);
+    }
+
+    #[test]
+    fn test_word_wrap_width_keeps_latin_words() {
+        let wordcut = create_test_wordcut();
+        let measure = |s: &str| s.chars().count() as f64;
+        let lines = wordcut.word_wrap_width("Hello world ประเทศไทย", 12.0, measure);
+
+        assert_eq!(lines, vec!["Hello world", "ประเทศไทย"]);
+    }
+
+    #[test]
+    fn test_word_wrap_width_zero_max() {
+        let wordcut = create_test_wordcut();
+        let lines = wordcut.word_wrap_width("สวัสดี", 0.0, |s| s.len() as f64);
+
+        assert_eq!(lines, vec!["สวัสดี"]);
+    }
+
     #[test]
     fn test_word_wrap_empty() {
         let wordcut = create_test_wordcut();
@@ -158,6 +158,23 @@ impl ThaiFormatter {
     pub fn render_float(format: &str, n: f64) -> String {
         thai_text::render_float(format, n)
     }
+
+    /// Build a PromptPay QR payload (EMVCo with CRC)
+    ///
+    /// @param id - Phone number, national ID or e-wallet ID
+    /// @param amount - Optional amount in Baht
+    /// @returns Payload string to encode in a QR code
+    #[wasm_bindgen(js_name = promptpayPayload)]
+    pub fn promptpay_payload(id: &str, amount: Option<f64>) -> Result<String, JsValue> {
+        let mut payment =
+            thai_text::PromptPay::from_id(id).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        if let Some(amount) = amount {
+            payment = payment.with_amount(amount);
+        }
+        payment
+            .payload()
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
 }
 
 /// PDF Template renderer
@@ -166,8 +183,10 @@ pub struct PdfTemplate {
     renderer: Option<template::TemplateRenderer>,
     template_json: Option<String>,
     pdf_bytes: Option<Vec<u8>>,
-    fonts: std::collections::HashMap<String, Vec<u8>>,
+    fonts: std::collections::HashMap<(String, template::FontStyle), Vec<u8>>,
+    pdf_sources: std::collections::HashMap<String, Vec<u8>>,
     wordcut: Option<thai_text::ThaiWordcut>,
+    flatten: Option<template::FlattenMode>,
 }
 
 #[wasm_bindgen]
@@ -180,7 +199,9 @@ impl PdfTemplate {
             template_json: None,
             pdf_bytes: None,
             fonts: std::collections::HashMap::new(),
+            pdf_sources: std::collections::HashMap::new(),
             wordcut: None,
+            flatten: None,
         }
     }
 
@@ -195,7 +216,9 @@ impl PdfTemplate {
             template_json: Some(json.to_string()),
             pdf_bytes: None,
             fonts: std::collections::HashMap::new(),
+            pdf_sources: std::collections::HashMap::new(),
             wordcut: None,
+            flatten: None,
         })
     }
 
@@ -215,10 +238,42 @@ impl PdfTemplate {
     /// @param data - TTF file bytes (Uint8Array)
     #[wasm_bindgen(js_name = loadFont)]
     pub fn load_font(&mut self, name: &str, data: &[u8]) -> Result<(), JsValue> {
-        self.fonts.insert(name.to_string(), data.to_vec());
+        self.load_font_variant(name, "regular", data)
+    }
+
+    /// Load a font variant for a font family
+    ///
+    /// @param name - Font identifier
+    /// @param style - Style: "regular", "bold", "italic", "bold-italic" (or "bolditalic")
+    /// @param data - TTF file bytes (Uint8Array)
+    #[wasm_bindgen(js_name = loadFontVariant)]
+    pub fn load_font_variant(
+        &mut self,
+        name: &str,
+        style: &str,
+        data: &[u8],
+    ) -> Result<(), JsValue> {
+        let style_enum = parse_font_style(style)?;
+
+        self.fonts
+            .insert((name.to_string(), style_enum), data.to_vec());
+        // Update renderer if it exists
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.add_font_variant(name, style_enum, data.to_vec());
+        }
+        Ok(())
+    }
+
+    /// Load a PDF used by `pdfpage` blocks
+    ///
+    /// @param name - Source name referenced by the block `source` or bound value
+    /// @param data - PDF file bytes (Uint8Array)
+    #[wasm_bindgen(js_name = loadPdfSource)]
+    pub fn load_pdf_source(&mut self, name: &str, data: &[u8]) -> Result<(), JsValue> {
+        self.pdf_sources.insert(name.to_string(), data.to_vec());
         // Update renderer if it exists
         if let Some(ref mut renderer) = self.renderer {
-            renderer.add_font(name, data.to_vec());
+            renderer.add_pdf_source(name, data.to_vec());
         }
         Ok(())
     }
@@ -239,20 +294,48 @@ impl PdfTemplate {
         Ok(())
     }
 
+    /// Flatten form fields when rendering
+    ///
+    /// @param mode - "none" (keep forms editable), "forms" (widgets only) or "all" (every annotation)
+    #[wasm_bindgen(js_name = setFlatten)]
+    pub fn set_flatten(&mut self, mode: &str) -> Result<(), JsValue> {
+        self.flatten = parse_flatten_mode(mode)?;
+        // Update renderer if it exists
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_flatten(self.flatten);
+        }
+        Ok(())
+    }
+
     /// Rebuild the internal renderer when template_json and pdf_bytes are both available
+    ///
+    /// Templates that declare `template.pages` need no base PDF.
     fn rebuild_renderer(&mut self) -> Result<(), JsValue> {
-        if let (Some(json), Some(pdf)) = (&self.template_json, &self.pdf_bytes) {
+        let pdf = match (&self.template_json, &self.pdf_bytes) {
+            (Some(_), Some(pdf)) => Some(pdf.clone()),
+            (Some(json), None) => {
+                let template = template::parse_template(json)
+                    .map_err(|e| JsValue::from_str(&e.to_string()))?;
+                (!template.template.pages.is_empty()).then(Vec::new)
+            }
+            _ => None,
+        };
+        if let (Some(json), Some(pdf)) = (&self.template_json, pdf) {
             #[cfg(target_arch = "wasm32")]
-            let mut renderer = template::TemplateRenderer::new(json, pdf.clone())
+            let mut renderer = template::TemplateRenderer::new(json, pdf)
                 .map_err(|e| JsValue::from_str(&e.to_string()))?;
 
             #[cfg(not(target_arch = "wasm32"))]
-            let mut renderer = template::TemplateRenderer::new(json, pdf.clone(), None)
+            let mut renderer = template::TemplateRenderer::new(json, pdf, None)
                 .map_err(|e| JsValue::from_str(&e.to_string()))?;
 
             // Add all fonts
-            for (name, data) in &self.fonts {
-                renderer.add_font(name, data.clone());
+            for ((name, style), data) in &self.fonts {
+                renderer.add_font_variant(name, *style, data.clone());
+            }
+
+            for (name, data) in &self.pdf_sources {
+                renderer.add_pdf_source(name, data.clone());
             }
 
             // Set wordcut if available
@@ -260,6 +343,8 @@ impl PdfTemplate {
                 renderer.set_wordcut(wc.clone());
             }
 
+            renderer.set_flatten(self.flatten);
+
             self.renderer = Some(renderer);
         }
         Ok(())
@@ -272,7 +357,7 @@ impl PdfTemplate {
     pub fn render(&self, data: JsValue) -> Result<Vec<u8>, JsValue> {
         let renderer = self.renderer.as_ref().ok_or_else(|| {
             JsValue::from_str(
-                "Template or PDF not loaded. Call fromJson() and loadBasePdf() first.",
+                "Template or PDF not loaded. Call fromJson() and loadBasePdf() first, or declare template.pages.",
             )
         })?;
 
@@ -294,7 +379,7 @@ impl PdfTemplate {
     pub fn render_to_document(&self, data: JsValue) -> Result<WasmPdfDocument, JsValue> {
         let renderer = self.renderer.as_ref().ok_or_else(|| {
             JsValue::from_str(
-                "Template or PDF not loaded. Call fromJson() and loadBasePdf() first.",
+                "Template or PDF not loaded. Call fromJson() and loadBasePdf() first, or declare template.pages.",
             )
         })?;
 
@@ -307,6 +392,30 @@ impl PdfTemplate {
         Ok(WasmPdfDocument { inner: doc })
     }
 
+    /// Render an array of data records into one document
+    ///
+    /// Records are appended in order with a bookmark each; fonts are
+    /// embedded once.
+    ///
+    /// @param records - Array of data objects
+    /// @returns WasmPdfDocument instance for further modification
+    #[wasm_bindgen(js_name = renderBatch)]
+    pub fn render_batch(&self, records: JsValue) -> Result<WasmPdfDocument, JsValue> {
+        let renderer = self.renderer.as_ref().ok_or_else(|| {
+            JsValue::from_str(
+                "Template or PDF not loaded. Call fromJson() and loadBasePdf() first, or declare template.pages.",
+            )
+        })?;
+
+        let records: Vec<serde_json::Value> = serde_wasm_bindgen::from_value(records)?;
+
+        let doc = renderer
+            .render_batch(&records)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+        Ok(WasmPdfDocument { inner: doc })
+    }
+
     /// Set font for subsequent text insertions (pre-render template modification)
     ///
     /// @param fontId - Font identifier (must be loaded via loadFont)
@@ -322,7 +431,7 @@ impl PdfTemplate {
 
     /// Set font style for subsequent text insertions
     ///
-    /// @param style - Style: "regular", "bold", "italic", "bold-italic"
+    /// @param style - Style: "regular", "bold", "italic", "bold-italic" (or "bolditalic")
     #[wasm_bindgen(js_name = setFontStyle)]
     pub fn set_font_style(&mut self, style: &str) -> Result<(), JsValue> {
         let renderer = self
@@ -332,7 +441,7 @@ impl PdfTemplate {
         let style_enum = match style {
             "bold" => template::FontStyle::Bold,
             "italic" => template::FontStyle::Italic,
-            "bold-italic" => template::FontStyle::BoldItalic,
+            "bold-italic" | "bolditalic" => template::FontStyle::BoldItalic,
             _ => template::FontStyle::Regular,
         };
         renderer.template_mut().set_font_style(style_enum);
@@ -456,6 +565,69 @@ impl WasmPdfDocument {
             .set_text_color(pdf_core::Color::from_rgb(r, g, b));
     }
 
+    /// Rotate, scale and skew subsequent text insertions
+    ///
+    /// Without an anchor, text turns around its insertion point.
+    ///
+    /// @param rotation - Rotation in degrees (counter-clockwise)
+    /// @param scaleX - Horizontal scale factor
+    /// @param scaleY - Vertical scale factor
+    /// @param skewX - Horizontal skew in degrees
+    /// @param skewY - Vertical skew in degrees
+    /// @param anchorX - Optional anchor X in points
+    /// @param anchorY - Optional anchor Y in points (from top)
+    #[allow(clippy::too_many_arguments)]
+    #[wasm_bindgen(js_name = setTransform)]
+    pub fn set_transform(
+        &mut self,
+        rotation: f64,
+        scale_x: f64,
+        scale_y: f64,
+        skew_x: f64,
+        skew_y: f64,
+        anchor_x: Option<f64>,
+        anchor_y: Option<f64>,
+    ) {
+        let mut transform = pdf_core::Transform::rotate(rotation)
+            .with_scale(scale_x, scale_y)
+            .with_skew(skew_x, skew_y);
+        if let (Some(x), Some(y)) = (anchor_x, anchor_y) {
+            transform = transform.with_anchor(x, y);
+        }
+        self.inner.set_transform(Some(transform));
+    }
+
+    /// Stop transforming subsequent text insertions
+    #[wasm_bindgen(js_name = resetTransform)]
+    pub fn reset_transform(&mut self) {
+        self.inner.set_transform(None);
+    }
+
+    /// Paint subsequent text with an opacity and blend mode
+    ///
+    /// @param fillAlpha - Opacity of text and fills (0-1)
+    /// @param strokeAlpha - Opacity of strokes (0-1)
+    /// @param blendMode - "normal", "multiply", "screen", "overlay", "darken", "lighten", ...
+    #[wasm_bindgen(js_name = setTransparency)]
+    pub fn set_transparency(
+        &mut self,
+        fill_alpha: f64,
+        stroke_alpha: f64,
+        blend_mode: &str,
+    ) -> Result<(), JsValue> {
+        let transparency = pdf_core::Transparency::alpha(fill_alpha)
+            .with_stroke_alpha(stroke_alpha)
+            .with_blend_mode(parse_blend_mode(blend_mode)?);
+        self.inner.set_transparency(Some(transparency));
+        Ok(())
+    }
+
+    /// Paint subsequent text opaque again
+    #[wasm_bindgen(js_name = resetTransparency)]
+    pub fn reset_transparency(&mut self) {
+        self.inner.set_transparency(None);
+    }
+
     /// Insert text at a specific position
     ///
     /// @param text - Text to insert
@@ -483,6 +655,33 @@ impl WasmPdfDocument {
             .map_err(|e| JsValue::from_str(&e.to_string()))
     }
 
+    /// Stamp a gray text watermark on every page in the current font
+    ///
+    /// @param text - Watermark text
+    /// @param fontSize - Font size in points
+    /// @param angle - Rotation in degrees (counter-clockwise)
+    /// @param opacity - Opacity (0-1)
+    /// @param layer - "overlay" (over the page content) or "underlay"
+    #[wasm_bindgen(js_name = addWatermark)]
+    pub fn add_watermark(
+        &mut self,
+        text: &str,
+        font_size: f32,
+        angle: f64,
+        opacity: f64,
+        layer: &str,
+    ) -> Result<(), JsValue> {
+        let mut watermark = pdf_core::Watermark::text(text)
+            .with_angle(angle)
+            .with_opacity(opacity)
+            .with_layer(parse_watermark_layer(layer)?);
+        watermark.font_size = font_size;
+
+        self.inner
+            .add_watermark(&watermark)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
     /// Get page count
     ///
     /// @returns Number of pages in the document
@@ -491,6 +690,62 @@ impl WasmPdfDocument {
         self.inner.page_count()
     }
 
+    /// Delete a page
+    ///
+    /// @param page - Page number (1-indexed)
+    #[wasm_bindgen(js_name = deletePage)]
+    pub fn delete_page(&mut self, page: usize) -> Result<(), JsValue> {
+        self.inner
+            .delete_page(page)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Move a page to a new position
+    ///
+    /// @param from - Page number to move (1-indexed)
+    /// @param to - Page number after the move (1-indexed)
+    #[wasm_bindgen(js_name = movePage)]
+    pub fn move_page(&mut self, from: usize, to: usize) -> Result<(), JsValue> {
+        self.inner
+            .move_page(from, to)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Set the page rotation
+    ///
+    /// @param page - Page number (1-indexed)
+    /// @param degrees - Clockwise rotation, a multiple of 90
+    #[wasm_bindgen(js_name = setRotation)]
+    pub fn set_rotation(&mut self, page: usize, degrees: i32) -> Result<(), JsValue> {
+        self.inner
+            .set_rotation(page, degrees)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Copy pages into a new document
+    ///
+    /// @param first - First page number (1-indexed)
+    /// @param last - Last page number (inclusive)
+    /// @returns WasmPdfDocument with the pages
+    #[wasm_bindgen(js_name = extractPages)]
+    pub fn extract_pages(&self, first: usize, last: usize) -> Result<WasmPdfDocument, JsValue> {
+        let doc = self
+            .inner
+            .extract_pages(first..=last)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(WasmPdfDocument { inner: doc })
+    }
+
+    /// Flatten form fields into page content when the document is saved
+    ///
+    /// @param mode - "forms" (widgets only) or "all" (every annotation)
+    pub fn flatten(&mut self, mode: &str) -> Result<(), JsValue> {
+        if let Some(mode) = parse_flatten_mode(mode)? {
+            self.inner.flatten(mode);
+        }
+        Ok(())
+    }
+
     /// Convert document to PDF bytes
     ///
     /// @returns PDF bytes (Uint8Array)
@@ -502,6 +757,62 @@ impl WasmPdfDocument {
     }
 }
 
+/// Parse a flatten mode name; "none" disables flattening
+fn parse_flatten_mode(mode: &str) -> Result<Option<template::FlattenMode>, JsValue> {
+    match mode {
+        "none" => Ok(None),
+        "forms" => Ok(Some(template::FlattenMode::Forms)),
+        "all" => Ok(Some(template::FlattenMode::All)),
+        _ => Err(JsValue::from_str(&format!("Unknown flatten mode: {mode}"))),
+    }
+}
+
+/// Parse a font style name (the template JSON spellings)
+fn parse_font_style(style: &str) -> Result<template::FontStyle, JsValue> {
+    match style {
+        "regular" => Ok(template::FontStyle::Regular),
+        "bold" => Ok(template::FontStyle::Bold),
+        "italic" => Ok(template::FontStyle::Italic),
+        "bold-italic" | "bolditalic" => Ok(template::FontStyle::BoldItalic),
+        _ => Err(JsValue::from_str(&format!("Unknown font style: {style}"))),
+    }
+}
+
+/// Parse a watermark layer name
+fn parse_watermark_layer(layer: &str) -> Result<pdf_core::WatermarkLayer, JsValue> {
+    match layer {
+        "overlay" => Ok(pdf_core::WatermarkLayer::Overlay),
+        "underlay" => Ok(pdf_core::WatermarkLayer::Underlay),
+        _ => Err(JsValue::from_str(&format!(
+            "Unknown watermark layer: {layer}"
+        ))),
+    }
+}
+
+/// Parse a blend mode name (PDF names in lowercase or kebab-case)
+fn parse_blend_mode(mode: &str) -> Result<pdf_core::BlendMode, JsValue> {
+    use pdf_core::BlendMode;
+    Ok(match mode {
+        "normal" => BlendMode::Normal,
+        "multiply" => BlendMode::Multiply,
+        "screen" => BlendMode::Screen,
+        "overlay" => BlendMode::Overlay,
+        "darken" => BlendMode::Darken,
+        "lighten" => BlendMode::Lighten,
+        "color-dodge" | "colordodge" => BlendMode::ColorDodge,
+        "color-burn" | "colorburn" => BlendMode::ColorBurn,
+        "hard-light" | "hardlight" => BlendMode::HardLight,
+        "soft-light" | "softlight" => BlendMode::SoftLight,
+        "difference" => BlendMode::Difference,
+        "exclusion" => BlendMode::Exclusion,
+        "hue" => BlendMode::Hue,
+        "saturation" => BlendMode::Saturation,
+        "color" => BlendMode::Color,
+        "luminosity" => BlendMode::Luminosity,
+        _ => return Err(JsValue::from_str(&format!("Unknown blend mode: {mode}"))),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -53,13 +53,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let template_json = std::fs::read_to_string(template_path)
         .map_err(|e| format!("Failed to read template '{}': {}", template_path, e))?;
 
-    // Parse template to get the PDF source path
+    // Parse template to get the PDF source path (templates that declare
+    // their pages may omit it)
     let temp_template: serde_json::Value = serde_json::from_str(&template_json)?;
-    let pdf_source = temp_template["template"]["source"]
-        .as_str()
-        .ok_or("Missing template.source in JSON")?;
-    let pdf_bytes = std::fs::read(pdf_source)
-        .map_err(|e| format!("Failed to read PDF '{}': {}", pdf_source, e))?;
+    let pdf_bytes = match temp_template["template"]["source"].as_str() {
+        Some(pdf_source) if !pdf_source.is_empty() => std::fs::read(pdf_source)
+            .map_err(|e| format!("Failed to read PDF '{}': {}", pdf_source, e))?,
+        _ => Vec::new(),
+    };
 
     // Create renderer - fonts auto-loaded from template paths
     let renderer = TemplateRenderer::new(&template_json, pdf_bytes, Some(Path::new(".")))?;